@@ -1,5 +1,7 @@
+use std::fmt;
+
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
@@ -32,6 +34,8 @@ impl NodeKind {
 #[serde(rename_all = "camelCase")]
 pub struct SecretRef {
     pub id: String,
+    /// The vault holding the secret; also encoded in `id` for vaults other than the default.
+    pub vault_id: String,
     pub kind: SecretKind,
     pub created_at: DateTime<Utc>,
 }
@@ -44,6 +48,102 @@ pub enum SecretKind {
     RdpPassword,
 }
 
+/// How the SSH client treats the server's host key.
+#[derive(Debug, Clone, Copy, Default, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum HostKeyMode {
+    /// Accept any host key without pinning it.
+    Off,
+    /// Pin the first key seen and refuse changed keys until confirmed.
+    #[default]
+    Tofu,
+    /// Like `Tofu`, but unknown hosts are refused until explicitly approved.
+    Strict,
+}
+
+impl HostKeyMode {
+    pub fn as_db_str(&self) -> &'static str {
+        match self {
+            Self::Off => "off",
+            Self::Tofu => "tofu",
+            Self::Strict => "strict",
+        }
+    }
+
+    pub fn from_db_str(value: &str) -> Option<Self> {
+        match value {
+            "off" => Some(Self::Off),
+            "tofu" => Some(Self::Tofu),
+            "strict" => Some(Self::Strict),
+            _ => None,
+        }
+    }
+
+    /// Maps the legacy `strict_host_key` flag, which pinned on first use when set.
+    pub fn from_strict_flag(strict_host_key: bool) -> Self {
+        if strict_host_key {
+            Self::Tofu
+        } else {
+            Self::Off
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for HostKeyMode {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Mode(String),
+            LegacyStrictFlag(bool),
+        }
+
+        match Repr::deserialize(deserializer)? {
+            Repr::Mode(value) => Self::from_db_str(&value).ok_or_else(|| {
+                serde::de::Error::unknown_variant(&value, &["off", "tofu", "strict"])
+            }),
+            Repr::LegacyStrictFlag(strict_host_key) => Ok(Self::from_strict_flag(strict_host_key)),
+        }
+    }
+}
+
+/// An SSH authentication method, named as in the protocol.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SshAuthMethod {
+    /// The configured private key, with its certificate if there is one.
+    PublicKey,
+    Password,
+    /// Asks to be let in without credentials. Some servers count it as a failed attempt.
+    None,
+}
+
+impl SshAuthMethod {
+    /// Key, then password, then none.
+    pub const DEFAULT_ORDER: [Self; 3] = [Self::PublicKey, Self::Password, Self::None];
+
+    pub fn as_db_str(&self) -> &'static str {
+        match self {
+            Self::PublicKey => "publickey",
+            Self::Password => "password",
+            Self::None => "none",
+        }
+    }
+
+    pub fn from_db_str(value: &str) -> Option<Self> {
+        match value {
+            "publickey" => Some(Self::PublicKey),
+            "password" => Some(Self::Password),
+            "none" => Some(Self::None),
+            _ => None,
+        }
+    }
+}
+
+fn default_auth_methods() -> Vec<SshAuthMethod> {
+    SshAuthMethod::DEFAULT_ORDER.to_vec()
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ConnectionNode {
@@ -52,18 +152,53 @@ pub struct ConnectionNode {
     pub kind: NodeKind,
     pub name: String,
     pub order_index: i64,
+    /// Accent color as `#rrggbb`.
+    #[serde(default)]
+    pub color: Option<String>,
+    /// Icon key understood by the sidebar.
+    #[serde(default)]
+    pub icon: Option<String>,
     pub ssh: Option<SshConfig>,
     pub rdp: Option<RdpConfig>,
 }
 
+fn default_true() -> bool {
+    true
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SshConfig {
     pub host: String,
     pub port: i64,
     pub username: String,
-    pub strict_host_key: bool,
+    #[serde(default, alias = "strictHostKey")]
+    pub host_key_mode: HostKeyMode,
     pub key_path: Option<String>,
+    #[serde(default)]
+    pub cert_path: Option<String>,
+    /// Keep recent terminal output in memory so a reconnect can replay it.
+    #[serde(default)]
+    pub retain_scrollback: bool,
+    /// Terminal size used when a session is opened without one.
+    #[serde(default)]
+    pub default_cols: Option<i64>,
+    #[serde(default)]
+    pub default_rows: Option<i64>,
+    /// Command whose stdin/stdout carry the connection instead of TCP (`%h`, `%p`, `%r`).
+    #[serde(default)]
+    pub proxy_command: Option<String>,
+    /// Local IP address to connect from, like `ssh -b`; unused with a proxy command.
+    #[serde(default)]
+    pub bind_address: Option<String>,
+    /// Open SFTP in the directory last listed on this connection instead of the home directory.
+    #[serde(default = "default_true")]
+    pub remember_sftp_path: bool,
+    #[serde(default)]
+    pub last_sftp_path: Option<String>,
+    /// The methods tried when connecting, in order; methods not listed are never tried.
+    #[serde(default = "default_auth_methods")]
+    pub auth_methods: Vec<SshAuthMethod>,
     pub auth_ref: Option<String>,
     pub key_passphrase_ref: Option<String>,
 }
@@ -78,9 +213,79 @@ pub struct RdpConfig {
     pub screen_mode: i64,
     pub width: Option<i64>,
     pub height: Option<i64>,
+    /// Seconds to wait for the server before an attempt counts as failed; the RDP
+    /// client's own default when unset.
+    #[serde(default)]
+    pub connect_timeout_secs: Option<i64>,
+    /// Extra attempts made when the server refuses or doesn't answer, e.g. while it's
+    /// still booting.
+    #[serde(default)]
+    pub connect_retries: i64,
+    /// Makes local smart cards available to the remote session, e.g. for smart-card logon.
+    #[serde(default)]
+    pub redirect_smartcards: bool,
+    /// Keeps the bitmap cache on disk between sessions. Off by default, since the cache
+    /// holds pieces of the remote screen.
+    #[serde(default)]
+    pub bitmap_cache: bool,
+    #[serde(default)]
+    pub experience: RdpExperience,
     pub credential_ref: Option<String>,
 }
 
+/// The visual effects an RDP session asks the server for, as in mstsc's Experience tab.
+/// Turning them off saves bandwidth on slow links.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RdpExperience {
+    pub wallpaper: bool,
+    pub font_smoothing: bool,
+    pub desktop_composition: bool,
+    pub menu_animations: bool,
+    pub themes: bool,
+}
+
+impl RdpExperience {
+    /// Everything on, for fast local networks.
+    pub const LAN: Self = Self {
+        wallpaper: true,
+        font_smoothing: true,
+        desktop_composition: true,
+        menu_animations: true,
+        themes: true,
+    };
+    /// Keeps what makes text and windows readable, drops the wallpaper and animations.
+    pub const BROADBAND: Self = Self {
+        wallpaper: false,
+        font_smoothing: true,
+        desktop_composition: true,
+        menu_animations: false,
+        themes: true,
+    };
+    /// Everything off, for the slowest links.
+    pub const MODEM: Self = Self {
+        wallpaper: false,
+        font_smoothing: false,
+        desktop_composition: false,
+        menu_animations: false,
+        themes: false,
+    };
+}
+
+impl Default for RdpExperience {
+    /// What the RDP client asks for when told nothing: no font smoothing or desktop
+    /// composition, everything else on.
+    fn default() -> Self {
+        Self {
+            wallpaper: true,
+            font_smoothing: false,
+            desktop_composition: false,
+            menu_animations: true,
+            themes: true,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct FolderUpsert {
@@ -88,21 +293,46 @@ pub struct FolderUpsert {
     pub parent_id: Option<String>,
     pub name: String,
     pub order_index: i64,
+    /// Accent color as `#rrggbb`.
+    #[serde(default)]
+    pub color: Option<String>,
+    /// Icon key understood by the sidebar.
+    #[serde(default)]
+    pub icon: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// `Debug` redacts `password` and `key_passphrase`, like [`Secret`].
+#[derive(Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SshConfigInput {
     pub host: String,
     pub port: i64,
     pub username: String,
-    pub strict_host_key: bool,
+    #[serde(default, alias = "strictHostKey")]
+    pub host_key_mode: HostKeyMode,
     pub key_path: Option<String>,
+    #[serde(default)]
+    pub cert_path: Option<String>,
+    #[serde(default)]
+    pub retain_scrollback: bool,
+    #[serde(default)]
+    pub default_cols: Option<i64>,
+    #[serde(default)]
+    pub default_rows: Option<i64>,
+    #[serde(default)]
+    pub proxy_command: Option<String>,
+    #[serde(default)]
+    pub bind_address: Option<String>,
+    #[serde(default = "default_true")]
+    pub remember_sftp_path: bool,
+    #[serde(default = "default_auth_methods")]
+    pub auth_methods: Vec<SshAuthMethod>,
     pub password: Option<String>,
     pub key_passphrase: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// `Debug` redacts `password`, like [`Secret`].
+#[derive(Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RdpConfigInput {
     pub host: String,
@@ -112,9 +342,64 @@ pub struct RdpConfigInput {
     pub screen_mode: i64,
     pub width: Option<i64>,
     pub height: Option<i64>,
+    #[serde(default)]
+    pub connect_timeout_secs: Option<i64>,
+    #[serde(default)]
+    pub connect_retries: i64,
+    #[serde(default)]
+    pub redirect_smartcards: bool,
+    #[serde(default)]
+    pub bitmap_cache: bool,
+    #[serde(default)]
+    pub experience: RdpExperience,
     pub password: Option<String>,
 }
 
+impl fmt::Debug for SshConfigInput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SshConfigInput")
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("username", &self.username)
+            .field("host_key_mode", &self.host_key_mode)
+            .field("key_path", &self.key_path)
+            .field("cert_path", &self.cert_path)
+            .field("retain_scrollback", &self.retain_scrollback)
+            .field("default_cols", &self.default_cols)
+            .field("default_rows", &self.default_rows)
+            .field("proxy_command", &self.proxy_command)
+            .field("bind_address", &self.bind_address)
+            .field("remember_sftp_path", &self.remember_sftp_path)
+            .field("auth_methods", &self.auth_methods)
+            .field("password", &self.password.as_ref().map(Secret::new))
+            .field(
+                "key_passphrase",
+                &self.key_passphrase.as_ref().map(Secret::new),
+            )
+            .finish()
+    }
+}
+
+impl fmt::Debug for RdpConfigInput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RdpConfigInput")
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("username", &self.username)
+            .field("domain", &self.domain)
+            .field("screen_mode", &self.screen_mode)
+            .field("width", &self.width)
+            .field("height", &self.height)
+            .field("connect_timeout_secs", &self.connect_timeout_secs)
+            .field("connect_retries", &self.connect_retries)
+            .field("redirect_smartcards", &self.redirect_smartcards)
+            .field("bitmap_cache", &self.bitmap_cache)
+            .field("experience", &self.experience)
+            .field("password", &self.password.as_ref().map(Secret::new))
+            .finish()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ConnectionUpsert {
@@ -123,6 +408,12 @@ pub struct ConnectionUpsert {
     pub kind: NodeKind,
     pub name: String,
     pub order_index: i64,
+    /// Accent color as `#rrggbb`.
+    #[serde(default)]
+    pub color: Option<String>,
+    /// Icon key understood by the sidebar.
+    #[serde(default)]
+    pub icon: Option<String>,
     pub ssh: Option<SshConfigInput>,
     pub rdp: Option<RdpConfigInput>,
 }
@@ -149,6 +440,9 @@ pub struct ImportReport {
     pub updated: usize,
     pub skipped: usize,
     pub warnings: Vec<String>,
+    /// The import was cancelled before every node was saved.
+    #[serde(default)]
+    pub cancelled: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -163,6 +457,11 @@ pub struct SessionOptions {
     pub cols: Option<u16>,
     pub rows: Option<u16>,
     pub session_id: Option<String>,
+    pub probe_system: Option<bool>,
+    pub cache_private_key: Option<bool>,
+    /// One-off passphrase for the connection's private key, used instead of the saved one.
+    #[serde(default, skip_serializing)]
+    pub key_passphrase: Option<Secret<String>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -170,3 +469,186 @@ pub struct SessionOptions {
 pub struct RdpLaunchOptions {
     pub full_screen: Option<bool>,
 }
+
+/// Saved text to send to SSH sessions. `${name}` placeholders in `body` are filled in
+/// when it is sent.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Snippet {
+    pub id: String,
+    pub name: String,
+    pub body: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FileSortKey {
+    #[default]
+    Name,
+    Size,
+    Modified,
+    Kind,
+}
+
+/// Order of a file listing. The default is directories first, then by name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FileSort {
+    pub key: FileSortKey,
+    pub descending: bool,
+    pub dirs_first: bool,
+}
+
+impl Default for FileSort {
+    fn default() -> Self {
+        Self {
+            key: FileSortKey::Name,
+            descending: false,
+            dirs_first: true,
+        }
+    }
+}
+
+/// The parts of a file entry `FileSort` looks at.
+pub struct FileSortFields<'a> {
+    pub name: &'a str,
+    pub is_dir: bool,
+    pub size: Option<u64>,
+    pub modified: Option<u64>,
+    /// `dir`, `file`, `symlink` or `other`.
+    pub kind: &'a str,
+}
+
+impl FileSort {
+    /// Sorts `entries`, breaking ties by case-insensitive name. Entries without a size or
+    /// modification time sort before those with one.
+    pub fn sort<T>(&self, entries: &mut [T], fields: fn(&T) -> FileSortFields<'_>) {
+        entries.sort_by(|a, b| self.compare(&fields(a), &fields(b)));
+    }
+
+    fn compare(&self, a: &FileSortFields<'_>, b: &FileSortFields<'_>) -> std::cmp::Ordering {
+        let by_name = || a.name.to_lowercase().cmp(&b.name.to_lowercase());
+        let by_key = match self.key {
+            FileSortKey::Name => by_name(),
+            FileSortKey::Size => a.size.cmp(&b.size).then_with(by_name),
+            FileSortKey::Modified => a.modified.cmp(&b.modified).then_with(by_name),
+            FileSortKey::Kind => a.kind.cmp(b.kind).then_with(by_name),
+        };
+        let by_key = if self.descending {
+            by_key.reverse()
+        } else {
+            by_key
+        };
+
+        if self.dirs_first {
+            b.is_dir.cmp(&a.is_dir).then(by_key)
+        } else {
+            by_key
+        }
+    }
+}
+
+pub const REDACTED: &str = "***";
+
+/// A value derived from a vault secret. `Debug` and `Display` print `***`, so a secret
+/// can't end up in logs through a derived `Debug` on its containing struct.
+///
+/// It can be deserialized from UI input but deliberately isn't `Serialize`.
+#[derive(Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(transparent)]
+pub struct Secret<T>(T);
+
+impl<T> Secret<T> {
+    pub fn new(value: T) -> Self {
+        Self(value)
+    }
+
+    pub fn expose(&self) -> &T {
+        &self.0
+    }
+
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> From<T> for Secret<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T> fmt::Debug for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl<T> fmt::Display for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+/// Replaces every occurrence of the given secrets in `text`, for messages that come back
+/// from third-party code which may echo its input.
+pub fn redact<'a>(text: &str, secrets: impl IntoIterator<Item = &'a str>) -> String {
+    secrets
+        .into_iter()
+        .filter(|secret| !secret.is_empty())
+        .fold(text.to_string(), |text, secret| {
+            text.replace(secret, REDACTED)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_input_debug_redacts_credentials() {
+        let ssh = SshConfigInput {
+            host: "db1.example.com".to_string(),
+            port: 22,
+            username: "deploy".to_string(),
+            host_key_mode: HostKeyMode::Tofu,
+            key_path: Some("~/.ssh/id_ed25519".to_string()),
+            cert_path: None,
+            retain_scrollback: false,
+            default_cols: None,
+            default_rows: None,
+            proxy_command: None,
+            bind_address: None,
+            remember_sftp_path: true,
+            auth_methods: default_auth_methods(),
+            password: Some("correct horse battery staple".to_string()),
+            key_passphrase: Some("Tr0ub4dor&3-key".to_string()),
+        };
+        let rdp = RdpConfigInput {
+            host: "rdp.example.com".to_string(),
+            port: 3389,
+            username: Some("alice".to_string()),
+            domain: Some("CORP".to_string()),
+            screen_mode: 0,
+            width: None,
+            height: None,
+            connect_timeout_secs: None,
+            connect_retries: 0,
+            redirect_smartcards: false,
+            bitmap_cache: false,
+            experience: RdpExperience::default(),
+            password: Some("Winter2026!".to_string()),
+        };
+
+        let debug = format!("{ssh:?} {rdp:?}");
+        for secret in [
+            "correct horse battery staple",
+            "Tr0ub4dor&3-key",
+            "Winter2026!",
+        ] {
+            assert!(!debug.contains(secret), "{secret} leaked into {debug}");
+        }
+        assert!(debug.contains("db1.example.com"));
+        assert!(debug.contains("key_passphrase: Some(***)"));
+    }
+}
@@ -3,13 +3,66 @@ use std::path::Path;
 
 use anyhow::{Context, Result};
 use janus_domain::{
-    ConnectionNode, ConnectionUpsert, FolderUpsert, ImportReport, NodeKind, RdpConfigInput, SshConfigInput,
+    ConnectionNode, ConnectionUpsert, FolderUpsert, HostKeyMode, ImportReport, NodeKind,
+    RdpConfigInput, RdpExperience, SshAuthMethod, SshConfigInput,
 };
 use quick_xml::events::{BytesDecl, BytesEnd, BytesStart, Event};
 use quick_xml::Writer;
 use roxmltree::Document;
 use uuid::Uuid;
 
+/// Connection attributes the import reads or reports on its own.
+const MAPPED_ATTRIBUTES: &[&str] = &[
+    "Name",
+    "Id",
+    "ConfId",
+    "Type",
+    "Protocol",
+    "ConnectionType",
+    "Hostname",
+    "Host",
+    "Port",
+    "Username",
+    "Password",
+    "Domain",
+    "Resolution",
+    "RedirectSmartCards",
+    "CacheBitmaps",
+    "DisplayWallpaper",
+    "EnableFontSmoothing",
+    "EnableDesktopComposition",
+    "DisableMenuAnimations",
+    "DisplayThemes",
+];
+
+/// mRemoteNG's own tree state, which has no meaning for a connection.
+const IGNORED_ATTRIBUTES: &[&str] = &["Expanded", "Favorite", "Icon", "Panel"];
+
+/// Values mRemoteNG writes on every node unless changed, so they carry no setting.
+/// `false`, `0` and empty values are treated the same way.
+const MREMOTENG_DEFAULTS: &[(&str, &str)] = &[
+    ("AutomaticResize", "True"),
+    ("Colors", "Colors16Bit"),
+    ("ExternalAddressProvider", "None"),
+    ("ExternalCredentialProvider", "None"),
+    ("ICAEncryptionStrength", "EncrBasic"),
+    ("PuttySession", "Default Settings"),
+    ("RDGatewayUsageMethod", "Never"),
+    ("RDGatewayUseConnectionCredentials", "Yes"),
+    ("RDPAuthenticationLevel", "NoAuth"),
+    ("RdpVersion", "Highest"),
+    ("RedirectSound", "DoNotPlay"),
+    ("RenderingEngine", "IE"),
+    ("SoundQuality", "Dynamic"),
+    ("UseCredSsp", "True"),
+    ("VNCAuthMode", "AuthVNC"),
+    ("VNCColors", "ColNormal"),
+    ("VNCCompression", "CompNone"),
+    ("VNCEncoding", "EncHextile"),
+    ("VNCProxyType", "ProxyNone"),
+    ("VNCSmartSizeMode", "SmartSAspect"),
+];
+
 #[derive(Debug, Default)]
 pub struct ParsedImport {
     pub folders: Vec<FolderUpsert>,
@@ -17,35 +70,91 @@ pub struct ParsedImport {
     pub warnings: Vec<String>,
 }
 
+/// Element names that are imported as a folder or connection.
+const NODE_TAGS: [&str; 3] = ["Node", "Connection", "Container"];
+
+/// Counts node elements as they are parsed and hands `(done, total)` to a callback.
+struct ParseProgress<'a> {
+    done: usize,
+    total: usize,
+    report: &'a mut dyn FnMut(usize, usize),
+}
+
+impl ParseProgress<'_> {
+    fn advance(&mut self) {
+        self.done += 1;
+        (self.report)(self.done, self.total);
+    }
+}
+
 pub fn parse_mremoteng(path: &Path) -> Result<ParsedImport> {
+    parse_mremoteng_with_progress(path, |_, _| {})
+}
+
+/// Like [`parse_mremoteng`], calling `on_node(done, total)` after each node element is
+/// parsed. `total` counts every node element in the file.
+pub fn parse_mremoteng_with_progress(
+    path: &Path,
+    mut on_node: impl FnMut(usize, usize),
+) -> Result<ParsedImport> {
     let xml = std::fs::read_to_string(path)
         .with_context(|| format!("reading import XML {}", path.display()))?;
     let doc = Document::parse(&xml).context("parsing mRemoteNG XML")?;
 
     let root = doc.root_element();
     let mut parsed = ParsedImport::default();
+    let mut progress = ParseProgress {
+        done: 0,
+        total: root
+            .descendants()
+            .filter(|node| node.is_element() && NODE_TAGS.contains(&node.tag_name().name()))
+            .count(),
+        report: &mut on_node,
+    };
 
     for child in root.children().filter(|node| node.is_element()) {
-        parse_node(child, None, &mut parsed);
+        parse_node(child, None, &mut parsed, &mut progress);
     }
 
     Ok(parsed)
 }
 
-fn parse_node(node: roxmltree::Node<'_, '_>, parent_id: Option<String>, parsed: &mut ParsedImport) {
+fn parse_node(
+    node: roxmltree::Node<'_, '_>,
+    parent_id: Option<String>,
+    parsed: &mut ParsedImport,
+    progress: &mut ParseProgress<'_>,
+) {
     fn trimmed_attr<'a>(node: roxmltree::Node<'a, 'a>, key: &str) -> Option<&'a str> {
         node.attribute(key).map(str::trim).filter(|value| !value.is_empty())
     }
 
+    fn flag_attr(node: roxmltree::Node<'_, '_>, key: &str, default: bool) -> bool {
+        trimmed_attr(node, key).map_or(default, |value| value.eq_ignore_ascii_case("true"))
+    }
+
     let tag = node.tag_name().name();
-    if tag != "Node" && tag != "Connection" && tag != "Container" {
+    if !NODE_TAGS.contains(&tag) {
         for child in node.children().filter(|child| child.is_element()) {
-            parse_node(child, parent_id.clone(), parsed);
+            parse_node(child, parent_id.clone(), parsed, progress);
         }
         return;
     }
+    progress.advance();
 
-    let node_id = Uuid::new_v4().to_string();
+    // Reuse the id from a previous export so re-importing updates nodes instead of
+    // duplicating them. Files from other tools, or repeated ids, get a fresh one.
+    let node_id = trimmed_attr(node, "Id")
+        .or_else(|| trimmed_attr(node, "ConfId"))
+        .filter(|id| {
+            !parsed.folders.iter().any(|folder| folder.id == *id)
+                && !parsed
+                    .connections
+                    .iter()
+                    .any(|connection| connection.id == *id)
+        })
+        .map(ToOwned::to_owned)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
     let name = node.attribute("Name").unwrap_or("Imported Node").to_string();
     let type_attr = trimmed_attr(node, "Type").unwrap_or_default();
     let protocol = trimmed_attr(node, "Protocol")
@@ -62,10 +171,12 @@ fn parse_node(node: roxmltree::Node<'_, '_>, parent_id: Option<String>, parsed:
             parent_id: parent_id.clone(),
             name,
             order_index: parsed.folders.len() as i64,
+            color: None,
+            icon: None,
         });
 
         for child in node.children().filter(|child| child.is_element()) {
-            parse_node(child, Some(node_id.clone()), parsed);
+            parse_node(child, Some(node_id.clone()), parsed, progress);
         }
         return;
     }
@@ -81,23 +192,65 @@ fn parse_node(node: roxmltree::Node<'_, '_>, parent_id: Option<String>, parsed:
         .and_then(|value| value.parse::<i64>().ok())
         .unwrap_or_else(|| if protocol.eq_ignore_ascii_case("RDP") { 3389 } else { 22 });
     let username = trimmed_attr(node, "Username").unwrap_or_default().to_string();
+    let mut unmapped = unmapped_attributes(node);
 
     if protocol.eq_ignore_ascii_case("RDP") {
+        let resolution = trimmed_attr(node, "Resolution");
+        let (screen_mode, size) = match resolution.map(rdp_resolution) {
+            Some(Some(mapped)) => mapped,
+            Some(None) => {
+                unmapped.push("Resolution");
+                (2, None)
+            }
+            None => (2, None),
+        };
         parsed.connections.push(ConnectionUpsert {
             id: node_id,
             parent_id,
             kind: NodeKind::Rdp,
             name: name.clone(),
             order_index: parsed.connections.len() as i64,
+            color: None,
+            icon: None,
             ssh: None,
             rdp: Some(RdpConfigInput {
                 host,
                 port,
                 username: if username.is_empty() { None } else { Some(username) },
                 domain: node.attribute("Domain").map(ToOwned::to_owned),
-                screen_mode: 2,
-                width: None,
-                height: None,
+                screen_mode,
+                width: size.map(|(width, _)| width),
+                height: size.map(|(_, height)| height),
+                connect_timeout_secs: None,
+                connect_retries: 0,
+                redirect_smartcards: node
+                    .attribute("RedirectSmartCards")
+                    .is_some_and(|value| value.trim().eq_ignore_ascii_case("true")),
+                bitmap_cache: node
+                    .attribute("CacheBitmaps")
+                    .is_some_and(|value| value.trim().eq_ignore_ascii_case("true")),
+                experience: {
+                    let defaults = RdpExperience::default();
+                    RdpExperience {
+                        wallpaper: flag_attr(node, "DisplayWallpaper", defaults.wallpaper),
+                        font_smoothing: flag_attr(
+                            node,
+                            "EnableFontSmoothing",
+                            defaults.font_smoothing,
+                        ),
+                        desktop_composition: flag_attr(
+                            node,
+                            "EnableDesktopComposition",
+                            defaults.desktop_composition,
+                        ),
+                        menu_animations: !flag_attr(
+                            node,
+                            "DisableMenuAnimations",
+                            !defaults.menu_animations,
+                        ),
+                        themes: flag_attr(node, "DisplayThemes", defaults.themes),
+                    }
+                },
                 password: None,
             }),
         });
@@ -114,12 +267,22 @@ fn parse_node(node: roxmltree::Node<'_, '_>, parent_id: Option<String>, parsed:
             kind: NodeKind::Ssh,
             name: name.clone(),
             order_index: parsed.connections.len() as i64,
+            color: None,
+            icon: None,
             ssh: Some(SshConfigInput {
                 host,
                 port,
                 username,
-                strict_host_key: true,
+                host_key_mode: HostKeyMode::Tofu,
                 key_path: None,
+                cert_path: None,
+                retain_scrollback: false,
+                default_cols: None,
+                default_rows: None,
+                proxy_command: None,
+                bind_address: None,
+                remember_sftp_path: true,
+                auth_methods: SshAuthMethod::DEFAULT_ORDER.to_vec(),
                 password: None,
                 key_passphrase: None,
             }),
@@ -135,19 +298,130 @@ fn parse_node(node: roxmltree::Node<'_, '_>, parent_id: Option<String>, parsed:
         parsed
             .warnings
             .push(format!("Unsupported protocol '{protocol}' on node '{name}', skipped."));
+        return;
+    }
+
+    if !unmapped.is_empty() {
+        parsed.warnings.push(format!(
+            "Skipped unsupported settings for node '{name}': {}.",
+            unmapped.join(", ")
+        ));
     }
 }
 
+/// Attributes of a connection that hold a setting the import doesn't carry over.
+fn unmapped_attributes<'a>(node: roxmltree::Node<'a, '_>) -> Vec<&'a str> {
+    node.attributes()
+        .filter(|attribute| {
+            let (name, value) = (attribute.name(), attribute.value().trim());
+            let is_default = value.is_empty()
+                || value.eq_ignore_ascii_case("false")
+                || value == "0"
+                || MREMOTENG_DEFAULTS.iter().any(|(default_name, default)| {
+                    *default_name == name && default.eq_ignore_ascii_case(value)
+                });
+            !is_default
+                && !name.starts_with("Inherit")
+                && !MAPPED_ATTRIBUTES.contains(&name)
+                && !IGNORED_ATTRIBUTES.contains(&name)
+        })
+        .map(|attribute| attribute.name())
+        .collect()
+}
+
+/// Maps an mRemoteNG `Resolution` to a screen mode and, for fixed sizes such as
+/// `Res1280x1024`, a windowed desktop of that size; `FitToWindow` and `SmartSize` keep
+/// the default fullscreen mode. `None` for values with no equivalent.
+fn rdp_resolution(value: &str) -> Option<(i64, Option<(i64, i64)>)> {
+    if ["Fullscreen", "FitToWindow", "SmartSize"]
+        .iter()
+        .any(|mode| mode.eq_ignore_ascii_case(value))
+    {
+        return Some((2, None));
+    }
+    let (width, height) = value.strip_prefix("Res")?.split_once('x')?;
+    Some((1, Some((width.parse().ok()?, height.parse().ok()?))))
+}
+
+/// Moves the import's top-level nodes under `parent_id`, numbering them from
+/// `first_order_index` so they follow the folder's existing children.
+pub fn nest_under(parsed: &mut ParsedImport, parent_id: &str, first_order_index: i64) {
+    for folder in parsed
+        .folders
+        .iter_mut()
+        .filter(|folder| folder.parent_id.is_none())
+    {
+        folder.parent_id = Some(parent_id.to_string());
+        folder.order_index += first_order_index;
+    }
+    for connection in parsed
+        .connections
+        .iter_mut()
+        .filter(|connection| connection.parent_id.is_none())
+    {
+        connection.parent_id = Some(parent_id.to_string());
+        connection.order_index += first_order_index;
+    }
+}
+
+/// Whether `target_id` is one of the imported nodes or, going by `existing`, inside one.
+/// Nesting the import there would make a node its own ancestor.
+pub fn nests_inside_itself(
+    parsed: &ParsedImport,
+    existing: &[ConnectionNode],
+    target_id: &str,
+) -> bool {
+    let parsed_ids: Vec<&str> = parsed
+        .folders
+        .iter()
+        .map(|folder| folder.id.as_str())
+        .chain(parsed.connections.iter().map(|node| node.id.as_str()))
+        .collect();
+    let parents: HashMap<&str, Option<&str>> = existing
+        .iter()
+        .map(|node| (node.id.as_str(), node.parent_id.as_deref()))
+        .collect();
+
+    let mut current = Some(target_id);
+    // Bounded by the tree size in case the existing tree already has a cycle.
+    for _ in 0..=existing.len() {
+        let Some(id) = current else {
+            return false;
+        };
+        if parsed_ids.contains(&id) {
+            return true;
+        }
+        current = parents.get(id).copied().flatten();
+    }
+    false
+}
+
 pub fn apply_report(parsed: &ParsedImport, created: usize, updated: usize, skipped: usize) -> ImportReport {
     ImportReport {
         created,
         updated,
         skipped,
         warnings: parsed.warnings.clone(),
+        cancelled: false,
     }
 }
 
-pub fn export_mremoteng(path: &Path, nodes: &[ConnectionNode]) -> Result<()> {
+/// Writes `nodes` as mRemoteNG XML. With `root_node_id`, only that node and its
+/// descendants are written, the node itself at the top level.
+pub fn export_mremoteng(
+    path: &Path,
+    nodes: &[ConnectionNode],
+    root_node_id: Option<&str>,
+) -> Result<()> {
+    let subtree;
+    let nodes = match root_node_id {
+        Some(root_id) => {
+            subtree = subtree_of(nodes, root_id)?;
+            subtree.as_slice()
+        }
+        None => nodes,
+    };
+
     let mut writer = Writer::new_with_indent(Vec::new(), b' ', 2);
     writer
         .write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))
@@ -180,15 +454,19 @@ pub fn export_mremoteng(path: &Path, nodes: &[ConnectionNode]) -> Result<()> {
             for node in ordered {
                 let mut element = BytesStart::new("Node");
                 element.push_attribute(("Name", node.name.as_str()));
+                element.push_attribute(("Id", node.id.as_str()));
 
                 match node.kind {
                     NodeKind::Folder => {
+                        // Always written as a start/end pair so a folder without
+                        // children still comes back as a container on import.
                         element.push_attribute(("Type", "Container"));
                         writer.write_event(Event::Start(element.to_owned()))?;
                         write_branch(writer, by_parent, Some(node.id.as_str()))?;
                         writer.write_event(Event::End(BytesEnd::new("Node")))?;
                     }
                     NodeKind::Ssh => {
+                        element.push_attribute(("Type", "Connection"));
                         if let Some(ssh) = &node.ssh {
                             element.push_attribute(("Protocol", "SSH2"));
                             element.push_attribute(("Hostname", ssh.host.as_str()));
@@ -199,6 +477,7 @@ pub fn export_mremoteng(path: &Path, nodes: &[ConnectionNode]) -> Result<()> {
                         writer.write_event(Event::Empty(element))?;
                     }
                     NodeKind::Rdp => {
+                        element.push_attribute(("Type", "Connection"));
                         if let Some(rdp) = &node.rdp {
                             element.push_attribute(("Protocol", "RDP"));
                             element.push_attribute(("Hostname", rdp.host.as_str()));
@@ -210,6 +489,25 @@ pub fn export_mremoteng(path: &Path, nodes: &[ConnectionNode]) -> Result<()> {
                             if let Some(domain) = &rdp.domain {
                                 element.push_attribute(("Domain", domain.as_str()));
                             }
+                            if rdp.redirect_smartcards {
+                                element.push_attribute(("RedirectSmartCards", "True"));
+                            }
+                            if rdp.bitmap_cache {
+                                element.push_attribute(("CacheBitmaps", "True"));
+                            }
+                            let experience = &rdp.experience;
+                            for (attribute, enabled) in [
+                                ("DisplayWallpaper", experience.wallpaper),
+                                ("DisplayThemes", experience.themes),
+                                ("EnableFontSmoothing", experience.font_smoothing),
+                                ("EnableDesktopComposition", experience.desktop_composition),
+                                ("DisableMenuAnimations", !experience.menu_animations),
+                            ] {
+                                element.push_attribute((
+                                    attribute,
+                                    if enabled { "True" } else { "False" },
+                                ));
+                            }
                         }
                         writer.write_event(Event::Empty(element))?;
                     }
@@ -230,3 +528,28 @@ pub fn export_mremoteng(path: &Path, nodes: &[ConnectionNode]) -> Result<()> {
     std::fs::write(path, bytes).with_context(|| format!("writing export XML {}", path.display()))?;
     Ok(())
 }
+
+/// Copies the node `root_id` and its descendants, rebasing the node to the top level.
+fn subtree_of(nodes: &[ConnectionNode], root_id: &str) -> Result<Vec<ConnectionNode>> {
+    let root = nodes
+        .iter()
+        .find(|node| node.id == root_id)
+        .with_context(|| format!("node not found: {root_id}"))?;
+
+    let mut subtree = vec![ConnectionNode {
+        parent_id: None,
+        ..root.clone()
+    }];
+    let mut next = 0;
+    while next < subtree.len() {
+        let parent_id = subtree[next].id.clone();
+        subtree.extend(
+            nodes
+                .iter()
+                .filter(|node| node.parent_id.as_deref() == Some(parent_id.as_str()))
+                .cloned(),
+        );
+        next += 1;
+    }
+    Ok(subtree)
+}
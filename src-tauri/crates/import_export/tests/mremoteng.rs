@@ -1,6 +1,11 @@
-use janus_import_export::parse_mremoteng;
+use janus_import_export::{
+    export_mremoteng, nest_under, nests_inside_itself, parse_mremoteng,
+    parse_mremoteng_with_progress,
+};
 use std::path::Path;
-use janus_domain::NodeKind;
+use janus_domain::{
+    ConnectionNode, HostKeyMode, NodeKind, RdpExperience, SshAuthMethod, SshConfig,
+};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 #[test]
@@ -112,3 +117,333 @@ fn parses_ssh2_connection_and_skips_password_warning() {
             .all(|warning| !warning.contains("Unsupported protocol"))
     );
 }
+
+fn folder(id: &str, parent_id: Option<&str>, name: &str, order_index: i64) -> ConnectionNode {
+    ConnectionNode {
+        id: id.to_string(),
+        parent_id: parent_id.map(ToOwned::to_owned),
+        kind: NodeKind::Folder,
+        name: name.to_string(),
+        order_index,
+        color: None,
+        icon: None,
+        ssh: None,
+        rdp: None,
+    }
+}
+
+#[test]
+fn export_round_trip_keeps_empty_folders() {
+    let nodes = vec![
+        folder("servers", None, "Servers", 0),
+        folder("empty", None, "Empty", 1),
+        folder("nested-empty", Some("servers"), "Nested Empty", 1),
+        ConnectionNode {
+            id: "web".to_string(),
+            parent_id: Some("servers".to_string()),
+            kind: NodeKind::Ssh,
+            name: "Web".to_string(),
+            order_index: 0,
+            color: None,
+            icon: None,
+            ssh: Some(SshConfig {
+                host: "web.example.com".to_string(),
+                port: 22,
+                username: "deploy".to_string(),
+                host_key_mode: HostKeyMode::Tofu,
+                key_path: None,
+                cert_path: None,
+                retain_scrollback: false,
+                default_cols: None,
+                default_rows: None,
+                proxy_command: None,
+                bind_address: None,
+                remember_sftp_path: true,
+                last_sftp_path: None,
+                auth_methods: SshAuthMethod::DEFAULT_ORDER.to_vec(),
+                auth_ref: None,
+                key_passphrase_ref: None,
+            }),
+            rdp: None,
+        },
+    ];
+
+    let unique = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("current time should be after unix epoch")
+        .as_nanos();
+    let path = std::env::temp_dir().join(format!("janus-mremoteng-export-{unique}.xml"));
+
+    export_mremoteng(&path, &nodes, None).expect("export should be written");
+    let parsed = parse_mremoteng(&path).expect("exported XML should parse");
+    std::fs::remove_file(&path).expect("exported XML should be removed");
+
+    assert!(parsed.warnings.is_empty());
+    assert_eq!(parsed.folders.len(), 3);
+    assert_eq!(parsed.connections.len(), 1);
+
+    let find_folder = |name: &str| {
+        parsed
+            .folders
+            .iter()
+            .find(|folder| folder.name == name)
+            .unwrap_or_else(|| panic!("folder '{name}' should survive the round trip"))
+    };
+    let servers = find_folder("Servers").id.as_str();
+    assert_eq!(servers, "servers");
+    assert_eq!(find_folder("Empty").id, "empty");
+    assert_eq!(find_folder("Empty").parent_id, None);
+    assert_eq!(
+        find_folder("Nested Empty").parent_id.as_deref(),
+        Some(servers)
+    );
+
+    let web = &parsed.connections[0];
+    assert_eq!(web.id, "web");
+    assert_eq!(web.kind, NodeKind::Ssh);
+    assert_eq!(web.parent_id.as_deref(), Some(servers));
+}
+
+#[test]
+fn exports_only_the_selected_subtree() {
+    let nodes = vec![
+        folder("teams", None, "Teams", 0),
+        folder("ops", Some("teams"), "Ops", 0),
+        folder("ops-db", Some("ops"), "Databases", 0),
+        folder("dev", Some("teams"), "Dev", 1),
+        folder("other", None, "Other", 1),
+    ];
+    let path = std::env::temp_dir().join(format!(
+        "janus-mremoteng-subtree-{}.xml",
+        std::process::id()
+    ));
+
+    export_mremoteng(&path, &nodes, Some("ops")).expect("export should be written");
+    let parsed = parse_mremoteng(&path).expect("exported XML should parse");
+    std::fs::remove_file(&path).expect("exported XML should be removed");
+
+    let mut exported = parsed
+        .folders
+        .iter()
+        .map(|folder| (folder.id.as_str(), folder.parent_id.as_deref()))
+        .collect::<Vec<_>>();
+    exported.sort();
+    assert_eq!(exported, vec![("ops", None), ("ops-db", Some("ops"))]);
+
+    assert!(export_mremoteng(&path, &nodes, Some("missing")).is_err());
+}
+
+#[test]
+fn reuses_ids_only_when_present_and_unique() {
+    let xml = r#"
+<Connections>
+  <Node Name="Exported" Id=" 7f0c6d52-5b0e-4a4b-9a57-2f1f6f1d0c11 " Protocol="SSH2" Hostname="a.example.com" />
+  <Node Name="Legacy" ConfId="legacy-id" Protocol="SSH2" Hostname="b.example.com" />
+  <Node Name="Duplicate" Id="legacy-id" Protocol="SSH2" Hostname="c.example.com" />
+  <Node Name="Other tool" Protocol="SSH2" Hostname="d.example.com" />
+</Connections>
+"#;
+
+    let unique = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("current time should be after unix epoch")
+        .as_nanos();
+    let path = std::env::temp_dir().join(format!("janus-mremoteng-ids-{unique}.xml"));
+
+    std::fs::write(&path, xml).expect("temporary XML fixture should be written");
+    let parsed = parse_mremoteng(&path).expect("inline fixture should parse");
+    std::fs::remove_file(&path).expect("temporary XML fixture should be removed");
+
+    let ids: Vec<&str> = parsed
+        .connections
+        .iter()
+        .map(|connection| connection.id.as_str())
+        .collect();
+    assert_eq!(ids[0], "7f0c6d52-5b0e-4a4b-9a57-2f1f6f1d0c11");
+    assert_eq!(ids[1], "legacy-id");
+    assert_ne!(ids[2], "legacy-id");
+    assert!(uuid_like(ids[2]));
+    assert!(uuid_like(ids[3]));
+}
+
+#[test]
+fn nests_top_level_nodes_under_a_target_folder() {
+    let xml = r#"
+<Connections>
+  <Node Name="Servers" Id="servers" Type="Container">
+    <Node Name="web" Id="web" Protocol="SSH2" Hostname="web.example.com" />
+  </Node>
+  <Node Name="db" Id="db" Protocol="SSH2" Hostname="db.example.com" />
+</Connections>
+"#;
+
+    let unique = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("current time should be after unix epoch")
+        .as_nanos();
+    let path = std::env::temp_dir().join(format!("janus-mremoteng-nest-{unique}.xml"));
+
+    std::fs::write(&path, xml).expect("temporary XML fixture should be written");
+    let mut parsed = parse_mremoteng(&path).expect("inline fixture should parse");
+    std::fs::remove_file(&path).expect("temporary XML fixture should be removed");
+
+    let db_order = parsed.connections[1].order_index;
+    nest_under(&mut parsed, "imports", 5);
+
+    assert_eq!(parsed.folders[0].parent_id.as_deref(), Some("imports"));
+    assert_eq!(parsed.folders[0].order_index, 5);
+    let web = &parsed.connections[0];
+    assert_eq!(web.parent_id.as_deref(), Some("servers"));
+    let db = &parsed.connections[1];
+    assert_eq!(db.parent_id.as_deref(), Some("imports"));
+    assert_eq!(db.order_index, db_order + 5);
+}
+
+#[test]
+fn refuses_to_nest_an_import_inside_its_own_nodes() {
+    let xml = r#"
+<Connections>
+  <Node Name="Servers" Id="servers" Type="Container">
+    <Node Name="web" Id="web" Protocol="SSH2" Hostname="web.example.com" />
+  </Node>
+</Connections>
+"#;
+
+    let unique = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("current time should be after unix epoch")
+        .as_nanos();
+    let path = std::env::temp_dir().join(format!("janus-mremoteng-cycle-{unique}.xml"));
+
+    std::fs::write(&path, xml).expect("temporary XML fixture should be written");
+    let parsed = parse_mremoteng(&path).expect("inline fixture should parse");
+    std::fs::remove_file(&path).expect("temporary XML fixture should be removed");
+
+    // An earlier import of the same file, with a folder added inside it since.
+    let folder = |id: &str, parent_id: Option<&str>| ConnectionNode {
+        id: id.to_string(),
+        parent_id: parent_id.map(str::to_string),
+        kind: NodeKind::Folder,
+        name: id.to_string(),
+        order_index: 0,
+        color: None,
+        icon: None,
+        ssh: None,
+        rdp: None,
+    };
+    let existing = vec![
+        folder("imports", None),
+        folder("servers", Some("imports")),
+        folder("staging", Some("servers")),
+    ];
+
+    assert!(nests_inside_itself(&parsed, &existing, "servers"));
+    assert!(nests_inside_itself(&parsed, &existing, "staging"));
+    assert!(!nests_inside_itself(&parsed, &existing, "imports"));
+    assert!(!nests_inside_itself(&parsed, &existing, "elsewhere"));
+}
+
+#[test]
+fn maps_fixed_resolutions_and_warns_about_unsupported_settings() {
+    let xml = r#"
+<Connections>
+  <Node Name="jump" Protocol="RDP" Hostname="jump.example.com" Resolution="Res1280x1024"
+    Colors="Colors32Bit" RDGatewayHostname="gw.example.com" RedirectDiskDrives="false"
+    RenderingEngine="IE" InheritColors="true" Icon="mRemoteNG" Descr="" />
+  <Node Name="router" Protocol="SSH2" Hostname="10.0.0.1" SSHOptions="-C"
+    PuttySession="Default Settings" Resolution="FitToWindow" />
+</Connections>
+"#;
+
+    let unique = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("current time should be after unix epoch")
+        .as_nanos();
+    let path = std::env::temp_dir().join(format!("janus-mremoteng-unmapped-{unique}.xml"));
+
+    std::fs::write(&path, xml).expect("temporary XML fixture should be written");
+    let parsed = parse_mremoteng(&path).expect("inline fixture should parse");
+    std::fs::remove_file(&path).expect("temporary XML fixture should be removed");
+
+    let rdp = parsed.connections[0]
+        .rdp
+        .as_ref()
+        .expect("rdp node should be parsed");
+    assert_eq!(rdp.screen_mode, 1);
+    assert_eq!((rdp.width, rdp.height), (Some(1280), Some(1024)));
+    assert_eq!(
+        parsed.warnings,
+        [
+            "Skipped unsupported settings for node 'jump': Colors, RDGatewayHostname.",
+            "Skipped unsupported settings for node 'router': SSHOptions.",
+        ]
+    );
+}
+
+fn uuid_like(id: &str) -> bool {
+    id.len() == 36 && id.chars().filter(|c| *c == '-').count() == 4
+}
+
+#[test]
+fn reports_progress_for_each_parsed_node() {
+    let xml = r#"
+<Connections>
+  <Node Name="Servers" Id="servers" Type="Container">
+    <Node Name="web" Id="web" Protocol="SSH2" Hostname="web.example.com" />
+    <Node Name="db" Id="db" Protocol="SSH2" Hostname="db.example.com" />
+  </Node>
+  <Node Name="desk" Id="desk" Protocol="RDP" Hostname="desk.example.com" />
+</Connections>
+"#;
+
+    let unique = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("current time should be after unix epoch")
+        .as_nanos();
+    let path = std::env::temp_dir().join(format!("janus-mremoteng-progress-{unique}.xml"));
+
+    std::fs::write(&path, xml).expect("temporary XML fixture should be written");
+    let mut reported = Vec::new();
+    let parsed = parse_mremoteng_with_progress(&path, |done, total| reported.push((done, total)))
+        .expect("inline fixture should parse");
+    std::fs::remove_file(&path).expect("temporary XML fixture should be removed");
+
+    assert_eq!(parsed.folders.len() + parsed.connections.len(), 4);
+    assert_eq!(reported, vec![(1, 4), (2, 4), (3, 4), (4, 4)]);
+}
+
+#[test]
+fn maps_rdp_redirection_and_experience_flags() {
+    let xml = r#"
+<Connections>
+  <Node Name="desk" ConnectionType="RDP" Host="rdp.example.com" RedirectSmartCards=" True " CacheBitmaps="True" DisplayWallpaper="False" EnableFontSmoothing="True" DisableMenuAnimations="True" />
+</Connections>
+"#;
+
+    let unique = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("current time should be after unix epoch")
+        .as_nanos();
+    let path = std::env::temp_dir().join(format!("janus-mremoteng-rdp-flags-{unique}.xml"));
+
+    std::fs::write(&path, xml).expect("temporary XML fixture should be written");
+    let parsed = parse_mremoteng(&path).expect("inline fixture should parse");
+    std::fs::remove_file(&path).expect("temporary XML fixture should be removed");
+
+    let rdp = parsed.connections[0]
+        .rdp
+        .as_ref()
+        .expect("RDP node should map to RDP config");
+    assert!(rdp.redirect_smartcards);
+    assert!(rdp.bitmap_cache);
+    assert_eq!(
+        rdp.experience,
+        RdpExperience {
+            wallpaper: false,
+            font_smoothing: true,
+            desktop_composition: false,
+            menu_animations: false,
+            themes: true,
+        }
+    );
+}
@@ -0,0 +1,178 @@
+//! Human-readable text for the codes the RDP control reports when a session ends.
+//!
+//! `OnDisconnected` carries a `discReason` describing the transport-level cause, and
+//! `ExtendedDisconnectReason` says why the server ended the session, which is the more
+//! useful of the two when it is set (idle timeout, logoff, licensing).
+
+/// Why the control couldn't connect, for the `discReason` codes that mean the connection
+/// itself failed. The codes match the SSH crate's `SshConnectFailure`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RdpConnectFailure {
+    /// The host answered, but nothing is listening on the port.
+    Refused,
+    /// The connection failed or closed while data was being exchanged.
+    Reset,
+    /// Nothing answered in time.
+    TimedOut,
+    /// The host name didn't resolve.
+    DnsFailed,
+}
+
+impl RdpConnectFailure {
+    /// `None` for reasons that aren't connection failures, such as a server-side logoff.
+    pub fn from_disconnect_reason(reason: i32) -> Option<Self> {
+        let failure = match reason {
+            0x204 => Self::Refused,
+            0x304 | 0x404 | 0x904 => Self::Reset,
+            0x108 | 0x704 => Self::TimedOut,
+            0x104 | 0x208 | 0x508 | 0x604 => Self::DnsFailed,
+            _ => return None,
+        };
+        Some(failure)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Refused => "refused",
+            Self::Reset => "reset",
+            Self::TimedOut => "timedOut",
+            Self::DnsFailed => "dnsFailed",
+        }
+    }
+}
+
+/// Describes a `discReason` code from `OnDisconnected`.
+pub fn disconnect_reason_message(reason: i32) -> Option<&'static str> {
+    let message = match reason {
+        0x0 => "no information is available",
+        0x1 => "the session was disconnected locally",
+        0x2 => "the session was disconnected by the remote user",
+        0x3 => "the session was disconnected by the server",
+        0x104 => "the host name could not be resolved",
+        0x106 | 0x206 | 0x306 => "the client ran out of memory",
+        0x108 => "the connection timed out",
+        0x204 => "the server refused the connection",
+        0x208 => "the host was not found",
+        0x304 => "sending data to the server failed",
+        0x308 | 0x804 => "the IP address is not valid",
+        0x404 => "receiving data from the server failed",
+        0x406 => "the server sent invalid security data",
+        0x408 => "an internal error occurred",
+        0x506 => "the encryption method is not valid",
+        0x508 => "the DNS lookup failed",
+        0x604 => "the server could not be found",
+        0x606 => "the server security data is not valid",
+        0x608 => "an internal timer error occurred",
+        0x704 => "the connection timed out",
+        0x706 => "the server certificate could not be read",
+        0x807 => "the credentials were rejected",
+        0x808 | 0x906 | 0xA06 => "an internal security error occurred",
+        0x904 => "the connection was closed",
+        0xB06 => "an encryption error occurred",
+        0xC06 => "a decryption error occurred",
+        0xC08 => "a decompression error occurred",
+        _ => return None,
+    };
+    Some(message)
+}
+
+/// Describes an `ExtendedDisconnectReason` code. Zero means the server gave no reason.
+pub fn extended_disconnect_reason_message(extended: i32) -> Option<&'static str> {
+    let message = match extended {
+        1 => "the session was disconnected by an administrative tool",
+        2 => "the session was logged off by an administrative tool",
+        3 => "the server ended the session after its idle timeout",
+        4 => "the server ended the session after its logon timeout",
+        5 => "another user connected to the session",
+        6 => "the server ran out of memory",
+        7 => "the server denied the connection",
+        8 => "the server denied the connection for FIPS security policy",
+        9 => "the user is not allowed to log on remotely",
+        10 => "the server requires fresh credentials",
+        11 => "the session was disconnected by the user",
+        12 => "the user logged off",
+        0x100 => "an internal licensing error occurred",
+        0x101 => "no license server was available",
+        0x102 => "no client access license was available",
+        0x103 => "the license server received an invalid client message",
+        0x104 => "the client license does not match this computer",
+        0x105 => "the client license is not valid",
+        0x106 => "the licensing protocol could not finish",
+        0x107 => "the client ended the licensing protocol",
+        0x108 => "a licensing message was not encrypted correctly",
+        0x109 => "the client license could not be upgraded",
+        0x10A => "the server is not licensed for remote connections",
+        _ => return None,
+    };
+    Some(message)
+}
+
+/// Describes why a session ended, preferring the server's extended reason when it
+/// gave one and naming the raw codes when neither is known.
+pub fn describe_disconnect(reason: i32, extended: i32) -> String {
+    if extended != 0 {
+        if let Some(message) = extended_disconnect_reason_message(extended) {
+            return message.to_string();
+        }
+    }
+    match disconnect_reason_message(reason) {
+        Some(message) if extended == 0 => message.to_string(),
+        Some(message) => format!("{message} (extended reason {extended:#x})"),
+        None => format!("disconnected for an unknown reason ({reason:#x})"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefers_the_extended_reason() {
+        assert_eq!(
+            describe_disconnect(0x3, 3),
+            "the server ended the session after its idle timeout"
+        );
+        assert_eq!(
+            describe_disconnect(0x3, 0x103),
+            "the license server received an invalid client message"
+        );
+        assert_eq!(
+            describe_disconnect(0x807, 0),
+            "the credentials were rejected"
+        );
+    }
+
+    #[test]
+    fn classifies_connection_failures() {
+        for (reason, failure) in [
+            (516, RdpConnectFailure::Refused),
+            (264, RdpConnectFailure::TimedOut),
+            (1796, RdpConnectFailure::TimedOut),
+            (260, RdpConnectFailure::DnsFailed),
+            (520, RdpConnectFailure::DnsFailed),
+            (2308, RdpConnectFailure::Reset),
+        ] {
+            assert_eq!(
+                RdpConnectFailure::from_disconnect_reason(reason),
+                Some(failure),
+                "{reason}"
+            );
+        }
+        for reason in [0x1, 0x2, 0x3, 0x807, 0x9999] {
+            assert_eq!(RdpConnectFailure::from_disconnect_reason(reason), None);
+        }
+    }
+
+    #[test]
+    fn names_unknown_codes() {
+        assert_eq!(
+            describe_disconnect(0x3, 0x4242),
+            "the session was disconnected by the server (extended reason 0x4242)"
+        );
+        assert_eq!(
+            describe_disconnect(0x9999, 0),
+            "disconnected for an unknown reason (0x9999)"
+        );
+        assert_eq!(disconnect_reason_message(0x9999), None);
+    }
+}
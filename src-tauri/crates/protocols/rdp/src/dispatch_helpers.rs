@@ -134,6 +134,34 @@ pub unsafe fn put_bool_property(
     Ok(())
 }
 
+/// Get an i32 property via IDispatch.
+pub unsafe fn get_i32_property(dispatch: &IDispatch, name: &str) -> windows::core::Result<i32> {
+    let dispid = get_dispid(dispatch, name)?;
+    let params = DISPPARAMS::default();
+    let mut result = VARIANT::default();
+
+    dispatch.Invoke(
+        dispid,
+        &windows::core::GUID::zeroed(),
+        0,
+        windows::Win32::System::Com::DISPATCH_PROPERTYGET,
+        &params,
+        Some(&mut result),
+        None,
+        None,
+    )?;
+
+    let inner = &*result.Anonymous.Anonymous;
+    if inner.vt == windows::Win32::System::Variant::VT_I4 {
+        Ok(inner.Anonymous.lVal)
+    } else {
+        Err(windows::core::Error::new(
+            HRESULT(-1),
+            "expected i32 property value",
+        ))
+    }
+}
+
 /// Get an IDispatch property that returns another IDispatch (e.g., AdvancedSettings).
 pub unsafe fn get_dispatch_property(
     dispatch: &IDispatch,
@@ -7,11 +7,12 @@ use std::panic::{catch_unwind, AssertUnwindSafe};
 use tokio::sync::mpsc;
 use windows::core::{implement, Error, GUID, HRESULT};
 use windows::Win32::System::Com::{
-    DISPATCH_FLAGS, DISPPARAMS, IDispatch_Impl, ITypeInfo,
+    IDispatch, IDispatch_Impl, ITypeInfo, DISPATCH_FLAGS, DISPPARAMS,
 };
 use windows::Win32::System::Variant::{VARIANT, VT_I4};
 
 use crate::com_interfaces::*;
+use crate::dispatch_helpers;
 use crate::manager::RdpActiveXEvent;
 
 const E_FAIL_HR: HRESULT = HRESULT(0x80004005u32 as i32);
@@ -22,16 +23,21 @@ const E_NOTIMPL_HR: HRESULT = HRESULT(0x80004001u32 as i32);
 pub struct RdpEventSink {
     session_id: String,
     event_tx: mpsc::UnboundedSender<RdpActiveXEvent>,
+    /// The control, to read `ExtendedDisconnectReason` when a session ends. The control
+    /// holds the sink too, so the session must `Unadvise` to release both.
+    rdp_dispatch: IDispatch,
 }
 
 impl RdpEventSink {
     pub fn new(
         session_id: String,
         event_tx: mpsc::UnboundedSender<RdpActiveXEvent>,
+        rdp_dispatch: IDispatch,
     ) -> Self {
         Self {
             session_id,
             event_tx,
+            rdp_dispatch,
         }
     }
 
@@ -91,10 +97,23 @@ impl IDispatch_Impl for RdpEventSink_Impl {
                 }
                 CYCLIC_DISPID_DISCONNECTED => {
                     let reason = unsafe { extract_i32_arg(pdispparams, 0) }.unwrap_or(0);
-                    tracing::debug!(session_id = %sid, reason, "RDP event: OnDisconnected");
+                    let extended = unsafe {
+                        dispatch_helpers::get_i32_property(
+                            &self.rdp_dispatch,
+                            "ExtendedDisconnectReason",
+                        )
+                    }
+                    .unwrap_or(0);
+                    tracing::debug!(
+                        session_id = %sid,
+                        reason,
+                        extended,
+                        "RDP event: OnDisconnected"
+                    );
                     self.send(RdpActiveXEvent::Disconnected {
                         session_id: sid,
                         reason,
+                        extended,
                     });
                 }
                 CYCLIC_DISPID_FATAL_ERROR => {
@@ -0,0 +1,68 @@
+/// How recently ended sessions ended, for a UI that missed the events.
+///
+/// The last error a session reported (a logon error, or the disconnect, fatal error or
+/// failed host setup that ended it) is kept for `EXIT_TTL`.
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::RdpActiveXEvent;
+
+const EXIT_TTL: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Default)]
+pub struct SessionExits {
+    exits: Mutex<HashMap<String, (Instant, RdpActiveXEvent)>>,
+}
+
+impl SessionExits {
+    /// Keeps `event` if it is an error; other events are ignored.
+    pub fn observe(&self, event: &RdpActiveXEvent) {
+        let session_id = match event {
+            RdpActiveXEvent::Disconnected { session_id, .. }
+            | RdpActiveXEvent::FatalError { session_id, .. }
+            | RdpActiveXEvent::HostInitFailed { session_id, .. } => session_id,
+            #[cfg(windows)]
+            RdpActiveXEvent::LogonError { session_id, .. } => session_id,
+            _ => return,
+        };
+        let mut exits = self.exits.lock().expect("session exits lock poisoned");
+        exits.retain(|_, (ended_at, _)| ended_at.elapsed() < EXIT_TTL);
+        exits.insert(session_id.clone(), (Instant::now(), event.clone()));
+    }
+
+    pub fn last(&self, session_id: &str) -> Option<RdpActiveXEvent> {
+        let mut exits = self.exits.lock().expect("session exits lock poisoned");
+        exits.retain(|_, (ended_at, _)| ended_at.elapsed() < EXIT_TTL);
+        exits.get(session_id).map(|(_, event)| event.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_the_latest_error_per_session() {
+        let exits = SessionExits::default();
+        exits.observe(&RdpActiveXEvent::Connected {
+            session_id: "a".to_string(),
+        });
+        assert!(exits.last("a").is_none());
+
+        exits.observe(&RdpActiveXEvent::FatalError {
+            session_id: "a".to_string(),
+            error_code: -2,
+        });
+        exits.observe(&RdpActiveXEvent::Disconnected {
+            session_id: "a".to_string(),
+            reason: 2308,
+            extended: 0,
+        });
+        assert!(matches!(
+            exits.last("a"),
+            Some(RdpActiveXEvent::Disconnected { reason: 2308, .. })
+        ));
+        assert!(exits.last("b").is_none());
+    }
+}
@@ -0,0 +1,44 @@
+/// Maps an `RdpExperience` to the control's `AdvancedSettings.PerformanceFlags`.
+///
+/// Wallpaper, menu animations and themes are on unless a `TS_PERF_DISABLE_*` bit is set;
+/// font smoothing and desktop composition are off unless a `TS_PERF_ENABLE_*` bit is.
+use janus_domain::RdpExperience;
+
+const TS_PERF_DISABLE_WALLPAPER: i32 = 0x01;
+const TS_PERF_DISABLE_MENUANIMATIONS: i32 = 0x04;
+const TS_PERF_DISABLE_THEMING: i32 = 0x08;
+const TS_PERF_ENABLE_FONT_SMOOTHING: i32 = 0x80;
+const TS_PERF_ENABLE_DESKTOP_COMPOSITION: i32 = 0x100;
+
+pub fn performance_flags(experience: &RdpExperience) -> i32 {
+    let mut flags = 0;
+    if !experience.wallpaper {
+        flags |= TS_PERF_DISABLE_WALLPAPER;
+    }
+    if !experience.menu_animations {
+        flags |= TS_PERF_DISABLE_MENUANIMATIONS;
+    }
+    if !experience.themes {
+        flags |= TS_PERF_DISABLE_THEMING;
+    }
+    if experience.font_smoothing {
+        flags |= TS_PERF_ENABLE_FONT_SMOOTHING;
+    }
+    if experience.desktop_composition {
+        flags |= TS_PERF_ENABLE_DESKTOP_COMPOSITION;
+    }
+    flags
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_presets_to_performance_flags() {
+        assert_eq!(performance_flags(&RdpExperience::default()), 0);
+        assert_eq!(performance_flags(&RdpExperience::LAN), 0x180);
+        assert_eq!(performance_flags(&RdpExperience::BROADBAND), 0x185);
+        assert_eq!(performance_flags(&RdpExperience::MODEM), 0x0D);
+    }
+}
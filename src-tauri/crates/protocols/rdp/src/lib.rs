@@ -1,13 +1,24 @@
 #[cfg(windows)]
 mod com_interfaces;
+mod disconnect_reason;
+#[cfg(any(windows, test))]
+mod exits;
+#[cfg(any(windows, test))]
+mod experience;
 #[cfg(windows)]
 mod dispatch_helpers;
 #[cfg(windows)]
 mod event_sink;
 #[cfg(windows)]
 mod manager;
+mod metrics;
+mod negotiation;
 #[cfg(windows)]
 mod ole_container;
+#[cfg(any(windows, test))]
+mod resize;
+#[cfg(any(windows, test))]
+mod retry;
 #[cfg(windows)]
 mod session;
 #[cfg(windows)]
@@ -20,6 +31,38 @@ pub use manager::{RdpActiveXEvent, RdpActiveXManager, RdpSessionConfig};
 #[cfg(not(windows))]
 pub use manager_stub::{RdpActiveXEvent, RdpActiveXManager, RdpSessionConfig};
 
+pub use disconnect_reason::{
+    describe_disconnect, disconnect_reason_message, extended_disconnect_reason_message,
+    RdpConnectFailure,
+};
+pub use metrics::RdpMetrics;
+pub use negotiation::{probe_security, RdpNegotiationFailure, RdpSecurity};
+
+/// Splits a `DOMAIN\user` username into its parts when no domain is configured. UPNs
+/// (`user@realm`) carry their own realm, so they are passed on without a domain.
+/// Returns the `(username, domain)` to log on with.
+pub fn normalize_rdp_logon(
+    username: Option<String>,
+    domain: Option<String>,
+) -> (Option<String>, Option<String>) {
+    let domain = domain.filter(|domain| !domain.trim().is_empty());
+    let Some(username) = username else {
+        return (None, domain);
+    };
+
+    if username.contains('@') {
+        return (Some(username), None);
+    }
+    if domain.is_none() {
+        if let Some((domain, user)) = username.split_once('\\') {
+            if !domain.is_empty() && !user.is_empty() {
+                return (Some(user.to_string()), Some(domain.to_string()));
+            }
+        }
+    }
+    (Some(username), domain)
+}
+
 #[cfg(any(windows, test))]
 fn should_suppress_rdp_credential_prompt(
     username: Option<&str>,
@@ -34,7 +77,41 @@ fn should_suppress_rdp_credential_prompt(
 
 #[cfg(test)]
 mod tests {
-    use super::should_suppress_rdp_credential_prompt;
+    use super::{normalize_rdp_logon, should_suppress_rdp_credential_prompt, RdpSessionConfig};
+
+    fn logon(username: &str, domain: Option<&str>) -> (Option<String>, Option<String>) {
+        normalize_rdp_logon(Some(username.to_string()), domain.map(str::to_string))
+    }
+
+    #[test]
+    fn normalizes_the_three_username_shapes() {
+        assert_eq!(
+            logon("alice", Some("CORP")),
+            (Some("alice".to_string()), Some("CORP".to_string()))
+        );
+        assert_eq!(
+            logon("CORP\\alice", None),
+            (Some("alice".to_string()), Some("CORP".to_string()))
+        );
+        assert_eq!(
+            logon("alice@corp.example.com", Some("CORP")),
+            (Some("alice@corp.example.com".to_string()), None)
+        );
+    }
+
+    #[test]
+    fn splits_down_level_names_only_without_a_domain() {
+        assert_eq!(
+            logon("CORP\\alice", Some("OTHER")),
+            (Some("CORP\\alice".to_string()), Some("OTHER".to_string()))
+        );
+        assert_eq!(
+            logon("CORP\\alice", Some(" ")),
+            (Some("alice".to_string()), Some("CORP".to_string()))
+        );
+        assert_eq!(logon("\\alice", None), (Some("\\alice".to_string()), None));
+        assert_eq!(normalize_rdp_logon(None, None), (None, None));
+    }
 
     #[test]
     fn suppresses_prompt_only_with_non_empty_username_and_password() {
@@ -48,4 +125,26 @@ mod tests {
             Some("secret")
         ));
     }
+
+    #[test]
+    fn debug_output_redacts_password() {
+        let config = RdpSessionConfig {
+            host: "rdp.example.com".to_string(),
+            port: 3389,
+            username: Some("alice".to_string()),
+            password: Some("hunter2".to_string().into()),
+            domain: None,
+            width: None,
+            height: None,
+            connect_timeout_secs: None,
+            connect_retries: 0,
+            redirect_smartcards: false,
+            bitmap_cache: false,
+            experience: Default::default(),
+        };
+
+        let debug = format!("{config:?}");
+        assert!(!debug.contains("hunter2"));
+        assert!(debug.contains("password: Some(***)"));
+    }
 }
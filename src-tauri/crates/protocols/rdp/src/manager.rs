@@ -3,12 +3,18 @@
 /// Provides async methods that dispatch to the STA thread via channels.
 /// This is the only type exposed to the rest of the application.
 use std::sync::mpsc as std_mpsc;
+use std::sync::Arc;
 use std::thread;
 
 use anyhow::Result;
+use janus_domain::{RdpExperience, Secret};
 use tokio::sync::{mpsc, oneshot};
 
+use crate::exits::SessionExits;
+use crate::metrics::SessionCounters;
+use crate::retry::ConnectRetry;
 use crate::sta_thread::{self, StaCommand};
+use crate::RdpMetrics;
 
 /// Configuration for an RDP session.
 #[derive(Debug, Clone)]
@@ -16,10 +22,20 @@ pub struct RdpSessionConfig {
     pub host: String,
     pub port: u16,
     pub username: Option<String>,
-    pub password: Option<String>,
+    pub password: Option<Secret<String>>,
     pub domain: Option<String>,
     pub width: Option<u16>,
     pub height: Option<u16>,
+    /// Seconds the control waits for the server per attempt; its own default when unset.
+    pub connect_timeout_secs: Option<u32>,
+    /// Extra attempts when the server refuses or doesn't answer before connecting once.
+    pub connect_retries: u32,
+    /// Sets the control's `RedirectSmartCards`, for smart-card logon and use in the session.
+    pub redirect_smartcards: bool,
+    /// Sets the control's persistent bitmap caching, which writes the cache to disk.
+    pub bitmap_cache: bool,
+    /// Sets the control's `PerformanceFlags`.
+    pub experience: RdpExperience,
 }
 
 /// Events emitted by the RDP ActiveX control.
@@ -34,9 +50,19 @@ pub enum RdpActiveXEvent {
     LoginComplete {
         session_id: String,
     },
+    /// A connect attempt failed with `reason` and attempt `attempt` of the configured
+    /// retries is about to start; the session stays open.
+    Retrying {
+        session_id: String,
+        attempt: u32,
+        reason: i32,
+    },
+    /// `reason` is the control's `discReason`; `extended` its `ExtendedDisconnectReason`,
+    /// zero when the server gave none.
     Disconnected {
         session_id: String,
         reason: i32,
+        extended: i32,
     },
     FatalError {
         session_id: String,
@@ -61,6 +87,8 @@ pub enum RdpActiveXEvent {
 #[derive(Clone)]
 pub struct RdpActiveXManager {
     cmd_tx: std_mpsc::Sender<StaCommand>,
+    exits: Arc<SessionExits>,
+    counters: Arc<SessionCounters>,
 }
 
 impl RdpActiveXManager {
@@ -75,7 +103,11 @@ impl RdpActiveXManager {
             })
             .expect("failed to spawn RDP STA thread");
 
-        Self { cmd_tx }
+        Self {
+            cmd_tx,
+            exits: Arc::new(SessionExits::default()),
+            counters: Arc::new(SessionCounters::default()),
+        }
     }
 
     /// Create a new RDP session.
@@ -88,6 +120,12 @@ impl RdpActiveXManager {
         config: &RdpSessionConfig,
         event_tx: mpsc::UnboundedSender<RdpActiveXEvent>,
     ) -> Result<()> {
+        let event_tx = self.record_errors(event_tx);
+        let event_tx = if config.connect_retries > 0 {
+            self.retry_connects(session_id, config.connect_retries, event_tx)
+        } else {
+            event_tx
+        };
         let (reply_tx, reply_rx) = oneshot::channel();
 
         self.cmd_tx
@@ -106,6 +144,98 @@ impl RdpActiveXManager {
             .map_err(|e| anyhow::anyhow!("{e}"))
     }
 
+    /// The last error `session_id` reported (disconnect, fatal or logon error, failed
+    /// host setup) in the last few minutes; for a UI that missed the event.
+    pub fn last_error(&self, session_id: &str) -> Option<RdpActiveXEvent> {
+        self.exits.last(session_id)
+    }
+
+    /// Totals since the manager was created, for the diagnostics view.
+    pub fn metrics(&self) -> RdpMetrics {
+        self.counters.snapshot()
+    }
+
+    /// Forwards a session's events, noting errors for `last_error` and counting
+    /// connects for `metrics` on the way.
+    fn record_errors(
+        &self,
+        events: mpsc::UnboundedSender<RdpActiveXEvent>,
+    ) -> mpsc::UnboundedSender<RdpActiveXEvent> {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let exits = Arc::clone(&self.exits);
+        let counters = Arc::clone(&self.counters);
+
+        tokio::spawn(async move {
+            while let Some(event) = rx.recv().await {
+                exits.observe(&event);
+                counters.observe(&event);
+                if events.send(event).is_err() {
+                    break;
+                }
+            }
+        });
+        tx
+    }
+
+    /// Forwards a session's events, turning retryable connect failures into `Retrying`
+    /// and calling `Connect()` again instead of passing the disconnect on.
+    fn retry_connects(
+        &self,
+        session_id: &str,
+        retries: u32,
+        events: mpsc::UnboundedSender<RdpActiveXEvent>,
+    ) -> mpsc::UnboundedSender<RdpActiveXEvent> {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let cmd_tx = self.cmd_tx.clone();
+        let session_id = session_id.to_string();
+
+        tokio::spawn(async move {
+            let mut retry = ConnectRetry::new(retries);
+            while let Some(event) = rx.recv().await {
+                match &event {
+                    RdpActiveXEvent::Connected { .. } => retry.note_connected(),
+                    RdpActiveXEvent::Disconnected { reason, .. } => {
+                        if let Some((attempt, delay)) = retry.on_disconnect(*reason) {
+                            tracing::info!(
+                                session_id = %session_id,
+                                attempt,
+                                reason,
+                                "RDP connect failed, retrying"
+                            );
+                            let retrying = RdpActiveXEvent::Retrying {
+                                session_id: session_id.clone(),
+                                attempt,
+                                reason: *reason,
+                            };
+                            if events.send(retrying).is_err() {
+                                break;
+                            }
+                            tokio::time::sleep(delay).await;
+
+                            let (reply_tx, reply_rx) = oneshot::channel();
+                            let reconnect = StaCommand::Reconnect {
+                                session_id: session_id.clone(),
+                                reply: reply_tx,
+                            };
+                            let reconnected = cmd_tx.send(reconnect).is_ok()
+                                && matches!(reply_rx.await, Ok(Ok(())));
+                            if reconnected {
+                                continue;
+                            }
+                            // The session was closed meanwhile or can't connect again;
+                            // end it with the failure that prompted the retry.
+                        }
+                    }
+                    _ => {}
+                }
+                if events.send(event).is_err() {
+                    break;
+                }
+            }
+        });
+        tx
+    }
+
     /// Reposition the session's host window.
     pub fn reposition(
         &self,
@@ -146,6 +276,7 @@ impl RdpActiveXManager {
 
     /// Close and destroy an RDP session.
     pub async fn close(&self, session_id: &str) -> Result<()> {
+        self.counters.closed(session_id);
         let (reply_tx, reply_rx) = oneshot::channel();
 
         self.cmd_tx
@@ -160,6 +291,19 @@ impl RdpActiveXManager {
             .map_err(|_| anyhow::anyhow!("RDP STA thread dropped reply channel"))?
             .map_err(|e| anyhow::anyhow!("{e}"))
     }
+
+    /// Close every RDP session, leaving the STA thread running.
+    pub async fn close_all(&self) -> Result<()> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+
+        self.cmd_tx
+            .send(StaCommand::CloseAll { reply: reply_tx })
+            .map_err(|_| anyhow::anyhow!("RDP STA thread is not running"))?;
+
+        reply_rx
+            .await
+            .map_err(|_| anyhow::anyhow!("RDP STA thread dropped reply channel"))
+    }
 }
 
 impl Default for RdpActiveXManager {
@@ -1,15 +1,28 @@
 use anyhow::{bail, Result};
+use janus_domain::{RdpExperience, Secret};
 use tokio::sync::mpsc;
 
+use crate::RdpMetrics;
+
 #[derive(Debug, Clone)]
 pub struct RdpSessionConfig {
     pub host: String,
     pub port: u16,
     pub username: Option<String>,
-    pub password: Option<String>,
+    pub password: Option<Secret<String>>,
     pub domain: Option<String>,
     pub width: Option<u16>,
     pub height: Option<u16>,
+    /// Seconds the control waits for the server per attempt; its own default when unset.
+    pub connect_timeout_secs: Option<u32>,
+    /// Extra attempts when the server refuses or doesn't answer before connecting once.
+    pub connect_retries: u32,
+    /// Sets the control's `RedirectSmartCards`, for smart-card logon and use in the session.
+    pub redirect_smartcards: bool,
+    /// Sets the control's persistent bitmap caching, which writes the cache to disk.
+    pub bitmap_cache: bool,
+    /// Sets the control's `PerformanceFlags`.
+    pub experience: RdpExperience,
 }
 
 #[derive(Debug, Clone)]
@@ -23,9 +36,19 @@ pub enum RdpActiveXEvent {
     LoginComplete {
         session_id: String,
     },
+    /// A connect attempt failed with `reason` and attempt `attempt` of the configured
+    /// retries is about to start; the session stays open.
+    Retrying {
+        session_id: String,
+        attempt: u32,
+        reason: i32,
+    },
+    /// `reason` is the control's `discReason`; `extended` its `ExtendedDisconnectReason`,
+    /// zero when the server gave none.
     Disconnected {
         session_id: String,
         reason: i32,
+        extended: i32,
     },
     FatalError {
         session_id: String,
@@ -79,4 +102,17 @@ impl RdpActiveXManager {
     pub async fn close(&self, _session_id: &str) -> Result<()> {
         bail!("RDP ActiveX is only supported on Windows")
     }
+
+    pub fn last_error(&self, _session_id: &str) -> Option<RdpActiveXEvent> {
+        None
+    }
+
+    pub fn metrics(&self) -> RdpMetrics {
+        RdpMetrics::default()
+    }
+
+    /// There are never any sessions to close.
+    pub async fn close_all(&self) -> Result<()> {
+        Ok(())
+    }
 }
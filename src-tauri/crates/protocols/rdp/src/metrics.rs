@@ -0,0 +1,115 @@
+//! Running totals for the app's diagnostics view, counted from session events.
+#[cfg(any(windows, test))]
+use std::collections::HashSet;
+#[cfg(any(windows, test))]
+use std::sync::Mutex;
+
+#[cfg(any(windows, test))]
+use crate::RdpActiveXEvent;
+
+/// Totals since the manager was created.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RdpMetrics {
+    /// Sessions connected now.
+    pub active_sessions: u64,
+    /// Sessions that reached `Connected`.
+    pub sessions_opened: u64,
+    /// Connected sessions that have since disconnected or been closed.
+    pub sessions_closed: u64,
+    /// Sessions that disconnected, after any retries, or failed to set up the control
+    /// before ever connecting.
+    pub failed_connects: u64,
+}
+
+#[cfg(any(windows, test))]
+#[derive(Default)]
+pub struct SessionCounters {
+    inner: Mutex<Inner>,
+}
+
+#[cfg(any(windows, test))]
+#[derive(Default)]
+struct Inner {
+    totals: RdpMetrics,
+    connected: HashSet<String>,
+}
+
+#[cfg(any(windows, test))]
+impl SessionCounters {
+    pub fn observe(&self, event: &RdpActiveXEvent) {
+        let mut inner = self.inner.lock().expect("rdp counters lock poisoned");
+        match event {
+            RdpActiveXEvent::Connected { session_id }
+                if inner.connected.insert(session_id.clone()) =>
+            {
+                inner.totals.sessions_opened += 1;
+            }
+            RdpActiveXEvent::Disconnected { session_id, .. } => {
+                if inner.connected.remove(session_id) {
+                    inner.totals.sessions_closed += 1;
+                } else {
+                    inner.totals.failed_connects += 1;
+                }
+            }
+            RdpActiveXEvent::HostInitFailed { .. } => inner.totals.failed_connects += 1,
+            _ => {}
+        }
+    }
+
+    /// Counts a session the app closed, unless it had already disconnected.
+    pub fn closed(&self, session_id: &str) {
+        let mut inner = self.inner.lock().expect("rdp counters lock poisoned");
+        if inner.connected.remove(session_id) {
+            inner.totals.sessions_closed += 1;
+        }
+    }
+
+    pub fn snapshot(&self) -> RdpMetrics {
+        let inner = self.inner.lock().expect("rdp counters lock poisoned");
+        RdpMetrics {
+            active_sessions: inner.connected.len() as u64,
+            ..inner.totals
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn connected(session_id: &str) -> RdpActiveXEvent {
+        RdpActiveXEvent::Connected {
+            session_id: session_id.to_string(),
+        }
+    }
+
+    fn disconnected(session_id: &str) -> RdpActiveXEvent {
+        RdpActiveXEvent::Disconnected {
+            session_id: session_id.to_string(),
+            reason: 2308,
+            extended: 0,
+        }
+    }
+
+    #[test]
+    fn tells_closed_sessions_from_failed_connects() {
+        let counters = SessionCounters::default();
+        counters.observe(&connected("a"));
+        counters.observe(&disconnected("a"));
+        counters.observe(&disconnected("b"));
+        counters.observe(&connected("c"));
+        counters.closed("c");
+        counters.closed("c");
+        counters.closed("d");
+
+        assert_eq!(
+            counters.snapshot(),
+            RdpMetrics {
+                active_sessions: 0,
+                sessions_opened: 2,
+                sessions_closed: 2,
+                failed_connects: 1,
+            }
+        );
+    }
+}
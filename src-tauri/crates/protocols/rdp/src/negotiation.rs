@@ -0,0 +1,197 @@
+//! Security negotiation probe.
+//!
+//! Sends the X.224 Connection Request that opens every RDP connection, offering TLS and
+//! NLA (CredSSP), and reads which one the server picks. Nothing past the negotiation is
+//! sent, so the server never starts a logon and credentials are not checked; that would
+//! take a CredSSP exchange the ActiveX control normally does for us.
+use std::fmt;
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+/// `PROTOCOL_SSL | PROTOCOL_HYBRID` from [MS-RDPBCGR] 2.2.1.1.1.
+const REQUESTED_PROTOCOLS: u32 = 0x1 | 0x2;
+const TYPE_RDP_NEG_RSP: u8 = 0x02;
+const TYPE_RDP_NEG_FAILURE: u8 = 0x03;
+const X224_CONNECTION_CONFIRM: u8 = 0xd0;
+/// Longest TPKT a Connection Confirm is expected to fit in.
+const MAX_CONFIRM_LEN: usize = 64;
+
+/// Security protocol the server chose for the connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RdpSecurity {
+    /// Legacy RDP encryption; the server didn't take part in negotiation.
+    Standard,
+    Tls,
+    /// Network Level Authentication: credentials are checked before a session starts.
+    Nla,
+}
+
+impl RdpSecurity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Standard => "standard",
+            Self::Tls => "tls",
+            Self::Nla => "nla",
+        }
+    }
+}
+
+/// The server answered but refused every protocol offered, e.g. because it only allows
+/// legacy RDP security.
+///
+/// Returned inside `anyhow::Error`; recover it with
+/// `error.downcast_ref::<RdpNegotiationFailure>()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RdpNegotiationFailure {
+    /// `failureCode` from the server's RDP_NEG_FAILURE.
+    pub code: u32,
+}
+
+impl fmt::Display for RdpNegotiationFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self.code {
+            0x1 => "the server requires TLS",
+            0x2 => "the server only allows legacy RDP security",
+            0x3 => "the server has no TLS certificate",
+            0x4 => "the server rejected the negotiation flags",
+            0x5 => "the server requires NLA",
+            0x6 => "the server requires TLS with client certificates",
+            _ => return write!(f, "RDP negotiation failed (code {})", self.code),
+        };
+        write!(f, "RDP negotiation failed: {reason}")
+    }
+}
+
+impl std::error::Error for RdpNegotiationFailure {}
+
+/// Connects to `host:port` and reports the security protocol the server picks when
+/// offered TLS and NLA, then hangs up. Socket errors and timeouts come back as
+/// `std::io::Error`.
+pub async fn probe_security(host: &str, port: u16, timeout: Duration) -> Result<RdpSecurity> {
+    tokio::time::timeout(timeout, async {
+        let mut stream = TcpStream::connect((host, port)).await?;
+        stream.write_all(&connection_request()).await?;
+
+        let mut header = [0u8; 4];
+        stream.read_exact(&mut header).await?;
+        let len = usize::from(u16::from_be_bytes([header[2], header[3]]));
+        if header[0] != 0x03 || !(header.len()..=MAX_CONFIRM_LEN).contains(&len) {
+            return Err(anyhow!("{host}:{port} did not answer as an RDP server"));
+        }
+        let mut body = vec![0u8; len - header.len()];
+        stream.read_exact(&mut body).await?;
+        let _ = stream.shutdown().await;
+
+        parse_connection_confirm(&body)
+            .with_context(|| format!("{host}:{port} did not answer as an RDP server"))?
+    })
+    .await
+    .map_err(|_| {
+        std::io::Error::new(
+            std::io::ErrorKind::TimedOut,
+            format!("no response within {}s", timeout.as_secs()),
+        )
+    })?
+}
+
+/// TPKT-framed X.224 Connection Request carrying an RDP_NEG_REQ: the TPKT header
+/// (version 3, length 19), the CR header (length indicator, code, dst-ref, src-ref,
+/// class), then the RDP_NEG_REQ (type, flags, length 8, requested protocols).
+fn connection_request() -> Vec<u8> {
+    let mut request = vec![3, 0, 0, 19, 14, 0xe0, 0, 0, 0, 0, 0, 1, 0, 8, 0];
+    request.extend_from_slice(&REQUESTED_PROTOCOLS.to_le_bytes());
+    request
+}
+
+/// Reads the X.224 Connection Confirm that follows the TPKT header. The outer `Result`
+/// is a malformed reply; the inner one is the server's answer to the negotiation.
+fn parse_connection_confirm(body: &[u8]) -> Result<Result<RdpSecurity>> {
+    if body.len() < 7 || body[1] & 0xf0 != X224_CONNECTION_CONFIRM {
+        return Err(anyhow!("expected an X.224 Connection Confirm"));
+    }
+    let Some(negotiation) = body.get(7..15) else {
+        return Ok(Ok(RdpSecurity::Standard));
+    };
+    let value = u32::from_le_bytes([
+        negotiation[4],
+        negotiation[5],
+        negotiation[6],
+        negotiation[7],
+    ]);
+    match negotiation[0] {
+        TYPE_RDP_NEG_RSP => match value {
+            0x0 => Ok(Ok(RdpSecurity::Standard)),
+            0x1 => Ok(Ok(RdpSecurity::Tls)),
+            0x2 => Ok(Ok(RdpSecurity::Nla)),
+            other => Err(anyhow!(
+                "server selected protocol {other:#x}, which wasn't offered"
+            )),
+        },
+        TYPE_RDP_NEG_FAILURE => Ok(Err(RdpNegotiationFailure { code: value }.into())),
+        other => Err(anyhow!("unknown negotiation message type {other:#x}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn confirm(negotiation: &[u8]) -> Vec<u8> {
+        let mut body = vec![0x06 + negotiation.len() as u8, 0xd0, 0, 0, 0x12, 0x34, 0];
+        body.extend_from_slice(negotiation);
+        body
+    }
+
+    #[test]
+    fn reads_the_selected_protocol() {
+        let nla = confirm(&[0x02, 0x1f, 0x08, 0x00, 0x02, 0, 0, 0]);
+        assert_eq!(
+            parse_connection_confirm(&nla).unwrap().unwrap(),
+            RdpSecurity::Nla
+        );
+        let tls = confirm(&[0x02, 0x00, 0x08, 0x00, 0x01, 0, 0, 0]);
+        assert_eq!(
+            parse_connection_confirm(&tls).unwrap().unwrap(),
+            RdpSecurity::Tls
+        );
+        assert_eq!(
+            parse_connection_confirm(&confirm(&[])).unwrap().unwrap(),
+            RdpSecurity::Standard
+        );
+
+        let failure = confirm(&[0x03, 0x00, 0x08, 0x00, 0x02, 0, 0, 0]);
+        let error = parse_connection_confirm(&failure).unwrap().unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<RdpNegotiationFailure>(),
+            Some(&RdpNegotiationFailure { code: 2 })
+        );
+
+        assert!(parse_connection_confirm(&[0x02, 0xf0, 0x80]).is_err());
+    }
+
+    #[tokio::test]
+    async fn probes_a_listening_server() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind");
+        let port = listener.local_addr().expect("local addr").port();
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.expect("accept");
+            let mut request = [0u8; 19];
+            stream.read_exact(&mut request).await.expect("read request");
+            assert_eq!(request.to_vec(), connection_request());
+            let mut reply = vec![0x03, 0x00, 0x00, 0x13];
+            reply.extend(confirm(&[0x02, 0x00, 0x08, 0x00, 0x02, 0, 0, 0]));
+            stream.write_all(&reply).await.expect("write confirm");
+        });
+
+        let security = probe_security("127.0.0.1", port, Duration::from_secs(2))
+            .await
+            .expect("probe");
+        assert_eq!(security, RdpSecurity::Nla);
+        server.await.expect("server task");
+    }
+}
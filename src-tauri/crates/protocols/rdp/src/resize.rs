@@ -0,0 +1,131 @@
+/// Resize coalescing for the STA thread.
+///
+/// A window drag produces a burst of `Reposition` commands; applying each one means a
+/// `SetWindowPos` + `SetObjectRects` round trip and visible flicker. The STA loop keeps
+/// only the last reposition per session from each drained batch, and waits for the size
+/// to stay put for `RESIZE_SETTLE_DELAY` before treating a resize as finished.
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::time::{Duration, Instant};
+
+pub const RESIZE_SETTLE_DELAY: Duration = Duration::from_millis(200);
+
+/// Drops every item whose key is superseded by a later item with the same key.
+///
+/// Items without a key are always kept; relative order is preserved.
+pub fn retain_last_by_key<T, K, F>(items: Vec<T>, key: F) -> Vec<T>
+where
+    K: Eq + Hash,
+    F: Fn(&T) -> Option<K>,
+{
+    let mut seen = HashSet::new();
+    let mut kept: Vec<T> = items
+        .into_iter()
+        .rev()
+        .filter(|item| key(item).is_none_or(|key| seen.insert(key)))
+        .collect();
+    kept.reverse();
+    kept
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct PendingResize {
+    width: i32,
+    height: i32,
+    changed_at: Instant,
+}
+
+/// Tracks per-session host sizes and reports a size once it has been stable long enough.
+#[derive(Default)]
+pub struct ResizeDebouncer {
+    pending: HashMap<String, PendingResize>,
+    settled: HashMap<String, (i32, i32)>,
+}
+
+impl ResizeDebouncer {
+    /// Records the size applied by a reposition. Pure moves don't restart the timer.
+    pub fn note(&mut self, session_id: &str, width: i32, height: i32, now: Instant) {
+        let unchanged = match self.pending.get(session_id) {
+            Some(pending) => (pending.width, pending.height) == (width, height),
+            None => self.settled.get(session_id) == Some(&(width, height)),
+        };
+        if unchanged {
+            return;
+        }
+
+        self.pending.insert(
+            session_id.to_string(),
+            PendingResize {
+                width,
+                height,
+                changed_at: now,
+            },
+        );
+    }
+
+    /// Returns sessions whose size hasn't changed for `RESIZE_SETTLE_DELAY`.
+    pub fn take_settled(&mut self, now: Instant) -> Vec<(String, i32, i32)> {
+        let ready: Vec<String> = self
+            .pending
+            .iter()
+            .filter(|(_, pending)| now.duration_since(pending.changed_at) >= RESIZE_SETTLE_DELAY)
+            .map(|(session_id, _)| session_id.clone())
+            .collect();
+
+        ready
+            .into_iter()
+            .filter_map(|session_id| {
+                let pending = self.pending.remove(&session_id)?;
+                self.settled
+                    .insert(session_id.clone(), (pending.width, pending.height));
+                Some((session_id, pending.width, pending.height))
+            })
+            .collect()
+    }
+
+    pub fn forget(&mut self, session_id: &str) {
+        self.pending.remove(session_id);
+        self.settled.remove(session_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_only_the_last_item_per_key() {
+        let items = vec![("a", 1), ("b", 1), ("-", 0), ("a", 2), ("a", 3), ("b", 2)];
+        let kept = retain_last_by_key(items, |(key, _)| (*key != "-").then_some(*key));
+        assert_eq!(kept, vec![("-", 0), ("a", 3), ("b", 2)]);
+    }
+
+    #[test]
+    fn settles_only_after_the_size_stops_changing() {
+        let start = Instant::now();
+        let mut debouncer = ResizeDebouncer::default();
+
+        debouncer.note("s1", 800, 600, start);
+        debouncer.note("s1", 820, 610, start + Duration::from_millis(150));
+        assert!(debouncer
+            .take_settled(start + Duration::from_millis(300))
+            .is_empty());
+
+        let settled = debouncer.take_settled(start + Duration::from_millis(350));
+        assert_eq!(settled, vec![("s1".to_string(), 820, 610)]);
+
+        // Moving the window without resizing it doesn't start another settle.
+        debouncer.note("s1", 820, 610, start + Duration::from_millis(400));
+        assert!(debouncer
+            .take_settled(start + Duration::from_secs(1))
+            .is_empty());
+
+        // A closed and recreated session starts from scratch.
+        debouncer.forget("s1");
+        debouncer.note("s1", 820, 610, start + Duration::from_secs(1));
+        assert_eq!(
+            debouncer.take_settled(start + Duration::from_secs(2)),
+            vec![("s1".to_string(), 820, 610)]
+        );
+    }
+}
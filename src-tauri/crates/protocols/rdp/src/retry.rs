@@ -0,0 +1,84 @@
+/// Connect retries for servers that aren't up yet.
+///
+/// The control reports a failed connect as `OnDisconnected` with a reason code. Until a
+/// session has connected once, refusals and timeouts are retried after a short backoff
+/// (a VM that is still booting usually refuses for a while); any other disconnect, or one
+/// after the session was up, ends the session as before.
+use std::time::Duration;
+
+/// `discReason` for a connect refused or unanswered at the socket level (0x204).
+const DISC_REASON_SOCKET_CONNECT_FAILED: i32 = 516;
+/// `discReason` for a connect that timed out (0x108).
+const DISC_REASON_TIMEOUT: i32 = 264;
+const RETRY_BASE_DELAY: Duration = Duration::from_secs(2);
+const RETRY_MAX_DELAY: Duration = Duration::from_secs(10);
+
+pub fn is_retryable_disconnect(reason: i32) -> bool {
+    matches!(
+        reason,
+        DISC_REASON_SOCKET_CONNECT_FAILED | DISC_REASON_TIMEOUT
+    )
+}
+
+/// Tracks one session's connect attempts.
+pub struct ConnectRetry {
+    retries: u32,
+    attempts: u32,
+    connected: bool,
+}
+
+impl ConnectRetry {
+    pub fn new(retries: u32) -> Self {
+        Self {
+            retries,
+            attempts: 0,
+            connected: false,
+        }
+    }
+
+    pub fn note_connected(&mut self) {
+        self.connected = true;
+    }
+
+    /// Returns the retry number (from 1) and how long to wait before it, or `None` when
+    /// the disconnect should end the session.
+    pub fn on_disconnect(&mut self, reason: i32) -> Option<(u32, Duration)> {
+        if self.connected || self.attempts >= self.retries || !is_retryable_disconnect(reason) {
+            return None;
+        }
+        self.attempts += 1;
+        let delay = RETRY_BASE_DELAY
+            .saturating_mul(self.attempts)
+            .min(RETRY_MAX_DELAY);
+        Some((self.attempts, delay))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retries_refused_connects_with_growing_backoff() {
+        let mut retry = ConnectRetry::new(7);
+        let delays: Vec<_> = std::iter::from_fn(|| retry.on_disconnect(516)).collect();
+        assert_eq!(delays.len(), 7);
+        assert_eq!(delays[0], (1, Duration::from_secs(2)));
+        assert_eq!(delays[1], (2, Duration::from_secs(4)));
+        assert_eq!(delays[6], (7, RETRY_MAX_DELAY));
+    }
+
+    #[test]
+    fn ends_the_session_on_other_disconnects() {
+        let mut retry = ConnectRetry::new(3);
+        assert_eq!(retry.on_disconnect(264), Some((1, Duration::from_secs(2))));
+        // A security or protocol failure won't go away by retrying.
+        assert_eq!(retry.on_disconnect(2055), None);
+
+        // Neither does a drop once the session was up.
+        retry.note_connected();
+        assert_eq!(retry.on_disconnect(516), None);
+
+        assert_eq!(ConnectRetry::new(0).on_disconnect(516), None);
+    }
+}
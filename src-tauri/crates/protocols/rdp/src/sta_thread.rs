@@ -7,10 +7,11 @@
 use std::collections::HashMap;
 use std::fmt;
 use std::sync::mpsc as std_mpsc;
+use std::time::Instant;
 
 use tokio::sync::{mpsc, oneshot};
 use windows::core::{Interface, IUnknown};
-use windows::Win32::Foundation::{HWND, LPARAM, LRESULT, RECT, WPARAM};
+use windows::Win32::Foundation::{HWND, LPARAM, LRESULT, RECT, REGDB_E_CLASSNOTREG, WPARAM};
 use windows::Win32::Graphics::Gdi::{GetStockObject, HBRUSH, BLACK_BRUSH};
 use windows::Win32::System::Com::{
     CoCreateInstance, IConnectionPointContainer, IDispatch, CLSCTX_INPROC_SERVER,
@@ -24,9 +25,11 @@ use windows::Win32::UI::WindowsAndMessaging::*;
 
 use crate::com_interfaces::*;
 use crate::dispatch_helpers;
+use crate::experience;
 use crate::event_sink::RdpEventSink;
 use crate::manager::{RdpActiveXEvent, RdpSessionConfig};
 use crate::ole_container::OleContainer;
+use crate::resize::{retain_last_by_key, ResizeDebouncer};
 use crate::session::ActiveXSession;
 
 /// Commands sent from the async runtime to the STA thread.
@@ -51,10 +54,19 @@ pub enum StaCommand {
     Hide {
         session_id: String,
     },
+    /// Calls `Connect()` again on a session whose connect failed.
+    Reconnect {
+        session_id: String,
+        reply: oneshot::Sender<Result<(), String>>,
+    },
     CloseSession {
         session_id: String,
         reply: oneshot::Sender<Result<(), String>>,
     },
+    /// Closes every session but keeps the thread running.
+    CloseAll {
+        reply: oneshot::Sender<()>,
+    },
     Shutdown,
 }
 
@@ -179,6 +191,7 @@ pub fn run_sta_thread(cmd_rx: std_mpsc::Receiver<StaCommand>) {
         register_host_window_class();
 
         let mut sessions: HashMap<String, ActiveXSession> = HashMap::new();
+        let mut resizes = ResizeDebouncer::default();
 
         loop {
             // Wait for either Win32 messages or channel commands
@@ -203,30 +216,50 @@ pub fn run_sta_thread(cmd_rx: std_mpsc::Receiver<StaCommand>) {
             }
 
             // Drain all pending commands from the channel
-            loop {
+            let mut commands = Vec::new();
+            let disconnected = loop {
                 match cmd_rx.try_recv() {
-                    Ok(cmd) => {
-                        if matches!(cmd, StaCommand::Shutdown) {
-                            cleanup_all_sessions(&mut sessions);
-                            OleUninitialize();
-                            return;
-                        }
-                        handle_command(cmd, &mut sessions);
-                    }
-                    Err(std_mpsc::TryRecvError::Empty) => break,
-                    Err(std_mpsc::TryRecvError::Disconnected) => {
-                        tracing::info!("STA command channel disconnected, shutting down");
+                    Ok(StaCommand::Shutdown) => {
                         cleanup_all_sessions(&mut sessions);
                         OleUninitialize();
                         return;
                     }
+                    Ok(cmd) => commands.push(cmd),
+                    Err(std_mpsc::TryRecvError::Empty) => break false,
+                    Err(std_mpsc::TryRecvError::Disconnected) => break true,
+                }
+            };
+
+            // During a window drag only the last reposition of each session matters.
+            let commands = retain_last_by_key(commands, |cmd| match cmd {
+                StaCommand::Reposition { session_id, .. } => Some(session_id.clone()),
+                _ => None,
+            });
+            for cmd in commands {
+                handle_command(cmd, &mut sessions, &mut resizes);
+            }
+
+            if disconnected {
+                tracing::info!("STA command channel disconnected, shutting down");
+                cleanup_all_sessions(&mut sessions);
+                OleUninitialize();
+                return;
+            }
+
+            for (session_id, width, height) in resizes.take_settled(Instant::now()) {
+                if sessions.contains_key(&session_id) {
+                    on_resize_settled(&session_id, width, height);
                 }
             }
         }
     }
 }
 
-unsafe fn handle_command(cmd: StaCommand, sessions: &mut HashMap<String, ActiveXSession>) {
+unsafe fn handle_command(
+    cmd: StaCommand,
+    sessions: &mut HashMap<String, ActiveXSession>,
+    resizes: &mut ResizeDebouncer,
+) {
     match cmd {
         StaCommand::CreateSession {
             session_id,
@@ -280,6 +313,7 @@ unsafe fn handle_command(cmd: StaCommand, sessions: &mut HashMap<String, ActiveX
 
                 // Also resize the ActiveX control's in-place window
                 resize_activex_control(session, width, height);
+                resizes.note(&session_id, width, height, Instant::now());
             }
         }
         StaCommand::Show { session_id } => {
@@ -301,10 +335,29 @@ unsafe fn handle_command(cmd: StaCommand, sessions: &mut HashMap<String, ActiveX
                 let _ = ShowWindow(session.host_hwnd, SW_HIDE);
             }
         }
+        StaCommand::Reconnect { session_id, reply } => {
+            let result = match sessions.get(&session_id) {
+                Some(session) => dispatch_helpers::invoke_method(&session.rdp_dispatch, "Connect")
+                    .map_err(|e| e.to_string()),
+                None => Err(format!("unknown RDP session: {session_id}")),
+            };
+            if let Err(error) = &result {
+                tracing::warn!(session_id = %session_id, %error, "RDP reconnect failed");
+            }
+            let _ = reply.send(result);
+        }
         StaCommand::CloseSession { session_id, reply } => {
+            resizes.forget(&session_id);
             let result = close_session(&session_id, sessions);
             let _ = reply.send(result.map_err(|e| format!("{e}")));
         }
+        StaCommand::CloseAll { reply } => {
+            for session_id in sessions.keys() {
+                resizes.forget(session_id);
+            }
+            cleanup_all_sessions(sessions);
+            let _ = reply.send(());
+        }
         StaCommand::Shutdown => unreachable!("handled in caller"),
     }
 }
@@ -405,9 +458,9 @@ unsafe fn create_session(
 
     // 6. Set password via IMsTscNonScriptable
     if let Some(password) = &config.password {
-        if !password.is_empty() {
+        if !password.expose().is_empty() {
             tracing::debug!(session_id, stage = "set_password", "RDP host init stage start");
-            set_clear_text_password(&rdp_unknown, password)
+            set_clear_text_password(&rdp_unknown, password.expose())
                 .map_err(|e| HostInitError::from_win("set_password", e))?;
             tracing::debug!(session_id, stage = "set_password", "RDP host init stage complete");
             tracing::debug!(session_id, "password injected via IMsTscNonScriptable");
@@ -417,7 +470,10 @@ unsafe fn create_session(
     // 6.5. Configure credential/dialog suppression via NonScriptable3
     let suppress_credential_prompt = crate::should_suppress_rdp_credential_prompt(
         config.username.as_deref(),
-        config.password.as_deref(),
+        config
+            .password
+            .as_ref()
+            .map(|password| password.expose().as_str()),
     );
     tracing::debug!(session_id, stage = "configure_non_scriptable3", "RDP host init stage start");
     configure_non_scriptable3(&rdp_unknown, host_hwnd, suppress_credential_prompt);
@@ -437,8 +493,13 @@ unsafe fn create_session(
 
     // 8. Call Connect()
     tracing::debug!(session_id, stage = "connect_call", "RDP host init stage start");
-    dispatch_helpers::invoke_method(&rdp_dispatch, "Connect")
-        .map_err(|e| HostInitError::from_win("connect_call", e))?;
+    if let Err(e) = dispatch_helpers::invoke_method(&rdp_dispatch, "Connect") {
+        // The sink holds the control, so it must be released for the guard's cleanup.
+        if let Some(cp) = &session.connection_point {
+            let _ = cp.Unadvise(session.advise_cookie);
+        }
+        return Err(HostInitError::from_win("connect_call", e));
+    }
     tracing::debug!(session_id, stage = "connect_call", "RDP host init stage complete");
     tracing::info!(session_id, "RDP Connect() called");
 
@@ -470,14 +531,18 @@ unsafe fn try_create_rdp_control() -> Result<IUnknown, HostInitError> {
         }
     }
 
-    if let Some(error) = last_error {
-        return Err(HostInitError::from_win("co_create_activex", error));
-    }
+    const MISSING_CONTROL: &str = "RDP ActiveX control (MsTscAx) is not available on this system. Ensure Remote Desktop Connection is installed.";
 
-    Err(HostInitError::message(
-        "co_create_activex",
-        "RDP ActiveX control (MsTscAx) is not available on this system. Ensure Remote Desktop Connection is installed.",
-    ))
+    match last_error {
+        // Neither class is registered, as on Server Core or stripped-down images.
+        Some(error) if error.code() == REGDB_E_CLASSNOTREG => Err(HostInitError {
+            stage: "co_create_activex",
+            hresult: Some(error.code().0),
+            message: MISSING_CONTROL.to_string(),
+        }),
+        Some(error) => Err(HostInitError::from_win("co_create_activex", error)),
+        None => Err(HostInitError::message("co_create_activex", MISSING_CONTROL)),
+    }
 }
 
 unsafe fn configure_rdp_properties(
@@ -526,9 +591,36 @@ unsafe fn configure_rdp_properties(
         if let Err(e) = dispatch_helpers::put_i32_property(&adv, "Compress", 1) {
             tracing::warn!("failed to set Compress: {e}");
         }
-        // Bitmap caching
-        if let Err(e) = dispatch_helpers::put_i32_property(&adv, "BitmapPeristence", 1) {
-            tracing::warn!("failed to set BitmapPeristence: {e}");
+        // Persistent bitmap caching. `BitmapPeristence` is the legacy misspelling from the
+        // control's first interface; IMsRdpClientAdvancedSettings adds `BitmapPersistence`.
+        let bitmap_cache = i32::from(config.bitmap_cache);
+        for property in ["BitmapPeristence", "BitmapPersistence"] {
+            match dispatch_helpers::put_i32_property(&adv, property, bitmap_cache) {
+                Ok(()) => tracing::debug!("set {property} to {bitmap_cache}"),
+                Err(e) => tracing::warn!("failed to set {property}: {e}"),
+            }
+        }
+        // Wallpaper, themes, font smoothing and other visual effects
+        let performance_flags = experience::performance_flags(&config.experience);
+        if let Err(e) =
+            dispatch_helpers::put_i32_property(&adv, "PerformanceFlags", performance_flags)
+        {
+            tracing::warn!("failed to set PerformanceFlags: {e}");
+        }
+        // Connect timeout, per attempt and for the whole connect
+        if let Some(timeout) = config.connect_timeout_secs {
+            let timeout = timeout as i32;
+            for property in ["singleConnectionTimeout", "overallConnectionTimeout"] {
+                if let Err(e) = dispatch_helpers::put_i32_property(&adv, property, timeout) {
+                    tracing::warn!("failed to set {property}: {e}");
+                }
+            }
+        }
+        // Smart card redirection, which smart-card logon needs
+        if config.redirect_smartcards {
+            if let Err(e) = dispatch_helpers::put_bool_property(&adv, "RedirectSmartCards", true) {
+                tracing::warn!("failed to set RedirectSmartCards: {e}");
+            }
         }
     }
 
@@ -544,7 +636,7 @@ unsafe fn connect_event_sink(
     let cpc: IConnectionPointContainer = rdp_unknown.cast()?;
     let cp = cpc.FindConnectionPoint(&DIID_IMSTSC_AX_EVENTS)?;
 
-    let sink = RdpEventSink::new(session_id.to_string(), event_tx);
+    let sink = RdpEventSink::new(session_id.to_string(), event_tx, rdp_unknown.cast()?);
     let events: IMsTscAxEvents = sink.into();
     let unknown = IUnknown::from(events);
     let cookie = cp.Advise(&unknown)?;
@@ -594,6 +686,14 @@ unsafe fn cleanup_all_sessions(sessions: &mut HashMap<String, ActiveXSession>) {
     }
 }
 
+/// Called once a session's host size has been stable for `RESIZE_SETTLE_DELAY`.
+///
+/// This is the point to renegotiate the remote desktop size; doing it on every
+/// reposition would reconnect continuously while the user drags.
+fn on_resize_settled(session_id: &str, width: i32, height: i32) {
+    tracing::debug!(session_id, width, height, "RDP host resize settled");
+}
+
 unsafe fn resize_activex_control(session: &ActiveXSession, width: i32, height: i32) {
     // Tell the OLE object about the new size
     if let Ok(ole_inplace) = session
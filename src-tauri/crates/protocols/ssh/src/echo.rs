@@ -0,0 +1,56 @@
+//! Local echo, for remote shells that don't echo input back.
+//!
+//! It is only ever turned on by the user for a session. SSH has no message reporting
+//! whether the remote terminal echoes, and guessing from the output would repeat
+//! passwords typed at prompts that turn echo off on purpose.
+
+const ESC: u8 = 0x1b;
+
+/// Renders typed input the way a line-mode terminal echoes it: Enter starts a new line,
+/// Backspace erases a character, and other control bytes and escape sequences (arrow
+/// keys, paste markers) are dropped.
+pub(crate) fn local_echo(input: &[u8]) -> String {
+    let mut echoed = Vec::with_capacity(input.len());
+    let mut bytes = input.iter().copied();
+    while let Some(byte) = bytes.next() {
+        match byte {
+            b'\r' | b'\n' => echoed.extend_from_slice(b"\r\n"),
+            b'\t' => echoed.push(byte),
+            0x08 | 0x7f => echoed.extend_from_slice(b"\x08 \x08"),
+            ESC => match bytes.next() {
+                // CSI: parameters until a final byte in 0x40..=0x7e.
+                Some(b'[') => {
+                    for byte in bytes.by_ref() {
+                        if (0x40..=0x7e).contains(&byte) {
+                            break;
+                        }
+                    }
+                }
+                // SS3 (application cursor keys) carries one more byte.
+                Some(b'O') => {
+                    bytes.next();
+                }
+                _ => {}
+            },
+            0x00..=0x1f => {}
+            _ => echoed.push(byte),
+        }
+    }
+    String::from_utf8_lossy(&echoed).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn echoes_typing_without_control_sequences() {
+        assert_eq!(local_echo(b"ls -la\r"), "ls -la\r\n");
+        assert_eq!(local_echo(b"cd\x7f\x7fpwd"), "cd\x08 \x08\x08 \x08pwd");
+        assert_eq!(local_echo(b"\x1b[A\x1bOB\x03x"), "x");
+        assert_eq!(
+            local_echo("\x1b[200~caf\u{e9}\rdone\x1b[201~".as_bytes()),
+            "caf\u{e9}\r\ndone"
+        );
+    }
+}
@@ -0,0 +1,117 @@
+//! Running totals for the app's diagnostics view.
+//!
+//! Counted since the manager was created and never sent anywhere; the UI asks for a
+//! snapshot with `SshSessionManager::metrics`.
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+use crate::SftpTransferProgress;
+
+/// A snapshot of the counters.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SshMetrics {
+    /// Sessions whose shell is still running.
+    pub active_sessions: u64,
+    pub sessions_opened: u64,
+    pub sessions_closed: u64,
+    /// Opens that ended without a session, including ones stopped for a host key or
+    /// passphrase prompt.
+    pub failed_connects: u64,
+    /// SFTP bytes sent, counting bytes a retried transfer sent twice.
+    pub bytes_uploaded: u64,
+    pub bytes_downloaded: u64,
+}
+
+#[derive(Default)]
+pub(crate) struct Counters {
+    pub(crate) sessions_opened: AtomicU64,
+    pub(crate) sessions_closed: AtomicU64,
+    pub(crate) failed_connects: AtomicU64,
+    pub(crate) bytes_uploaded: AtomicU64,
+    pub(crate) bytes_downloaded: AtomicU64,
+}
+
+impl Counters {
+    pub(crate) fn snapshot(&self, active_sessions: u64) -> SshMetrics {
+        SshMetrics {
+            active_sessions,
+            sessions_opened: self.sessions_opened.load(Ordering::Relaxed),
+            sessions_closed: self.sessions_closed.load(Ordering::Relaxed),
+            failed_connects: self.failed_connects.load(Ordering::Relaxed),
+            bytes_uploaded: self.bytes_uploaded.load(Ordering::Relaxed),
+            bytes_downloaded: self.bytes_downloaded.load(Ordering::Relaxed),
+        }
+    }
+}
+
+pub(crate) fn increment(counter: &AtomicU64) {
+    counter.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Adds one to `counter` unless `counted` is already set, for an end both the app and
+/// the server can report.
+pub(crate) fn increment_once(counter: &AtomicU64, counted: &AtomicBool) {
+    if !counted.swap(true, Ordering::Relaxed) {
+        increment(counter);
+    }
+}
+
+/// Wraps `on_progress`, adding the bytes each report moves on by to `counter`.
+///
+/// The first report is taken as the starting point, so a resumed upload doesn't count
+/// the bytes that were already there. A report below the previous one (a parallel
+/// transfer starting over sequentially) becomes the new starting point.
+pub(crate) fn counting_progress<'a, F>(
+    counter: &'a AtomicU64,
+    mut on_progress: F,
+) -> impl FnMut(SftpTransferProgress) + Send + 'a
+where
+    F: FnMut(SftpTransferProgress) + Send + 'a,
+{
+    let mut last: Option<u64> = None;
+    move |progress| {
+        let transferred = progress.bytes_transferred;
+        if let Some(last) = last.filter(|&last| transferred > last) {
+            counter.fetch_add(transferred - last, Ordering::Relaxed);
+        }
+        last = Some(transferred);
+        on_progress(progress);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(on_progress: &mut impl FnMut(SftpTransferProgress), bytes_transferred: u64) {
+        on_progress(SftpTransferProgress {
+            bytes_transferred,
+            total_bytes: None,
+        });
+    }
+
+    #[test]
+    fn counts_progress_from_the_first_report() {
+        let counter = AtomicU64::new(0);
+        let mut reports = 0;
+        {
+            let mut on_progress = counting_progress(&counter, |_| reports += 1);
+            // A resume from byte 1000, then a restart after a failed parallel copy.
+            for bytes in [1000, 1500, 2000, 0, 700] {
+                report(&mut on_progress, bytes);
+            }
+        }
+        assert_eq!(counter.load(Ordering::Relaxed), 1000 + 700);
+        assert_eq!(reports, 5);
+    }
+
+    #[test]
+    fn counts_a_session_end_once() {
+        let counter = AtomicU64::new(0);
+        let counted = AtomicBool::new(false);
+        // The server closes the session, then the app closes it too.
+        increment_once(&counter, &counted);
+        increment_once(&counter, &counted);
+        assert_eq!(counter.load(Ordering::Relaxed), 1);
+    }
+}
@@ -0,0 +1,298 @@
+//! Reconstructs the algorithms a connection negotiated.
+//!
+//! russh keeps its negotiation result private, so the server's KEXINIT is teed off the
+//! socket as it is read (it is the first binary packet and never encrypted) and the same
+//! "first client preference the server also offers" rule is applied to our own lists.
+
+use std::io;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex as StdMutex};
+use std::task::{Context, Poll};
+
+use russh::{cipher, Preferred};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+/// Enough for the version banner plus the largest packet russh accepts.
+const MAX_CAPTURE: usize = 64 * 1024;
+const SSH_MSG_KEXINIT: u8 = 20;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SshNegotiatedAlgorithms {
+    pub kex: String,
+    pub host_key_alg: String,
+    pub cipher: String,
+    /// `None` for AEAD ciphers, which authenticate without a separate MAC.
+    pub mac: Option<String>,
+}
+
+type Capture = Arc<StdMutex<Option<Vec<u8>>>>;
+
+/// Copies the first bytes read from `inner` until `take_negotiated` is called.
+pub(crate) struct KexInitTap<S> {
+    inner: S,
+    capture: Capture,
+}
+
+impl<S> KexInitTap<S> {
+    pub(crate) fn new(inner: S) -> (Self, KexInitCapture) {
+        let capture = Arc::new(StdMutex::new(Some(Vec::new())));
+        let tap = Self {
+            inner,
+            capture: Arc::clone(&capture),
+        };
+        (tap, KexInitCapture(capture))
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct KexInitCapture(Capture);
+
+impl KexInitCapture {
+    /// Works out what is being negotiated without stopping the capture; usable once the
+    /// server's KEXINIT has arrived, e.g. while its host key is being checked.
+    pub(crate) fn peek_negotiated(&self, preferred: &Preferred) -> Option<SshNegotiatedAlgorithms> {
+        let captured = self
+            .0
+            .lock()
+            .expect("kexinit capture lock poisoned")
+            .clone()?;
+        negotiate(preferred, &parse_server_kexinit(&captured)?)
+    }
+
+    /// Stops capturing and works out what was negotiated. Only meaningful once the key
+    /// exchange has completed.
+    pub(crate) fn take_negotiated(&self, preferred: &Preferred) -> Option<SshNegotiatedAlgorithms> {
+        let captured = self
+            .0
+            .lock()
+            .expect("kexinit capture lock poisoned")
+            .take()?;
+        negotiate(preferred, &parse_server_kexinit(&captured)?)
+    }
+}
+
+impl<S: AsyncRead + Unpin> AsyncRead for KexInitTap<S> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let before = buf.filled().len();
+        let poll = Pin::new(&mut self.inner).poll_read(cx, buf);
+        if let Poll::Ready(Ok(())) = poll {
+            let mut capture = self.capture.lock().expect("kexinit capture lock poisoned");
+            if let Some(captured) = capture.as_mut() {
+                let read = &buf.filled()[before..];
+                let room = MAX_CAPTURE.saturating_sub(captured.len());
+                captured.extend_from_slice(&read[..read.len().min(room)]);
+            }
+        }
+        poll
+    }
+}
+
+impl<S: AsyncWrite + Unpin> AsyncWrite for KexInitTap<S> {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.inner).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+struct ServerKexInit {
+    kex: Vec<String>,
+    host_key: Vec<String>,
+    cipher_client_to_server: Vec<String>,
+    mac_client_to_server: Vec<String>,
+}
+
+/// Finds the KEXINIT packet that follows the server's version banner.
+fn parse_server_kexinit(captured: &[u8]) -> Option<ServerKexInit> {
+    // Servers may send other lines before the `SSH-` identification line.
+    let mut offset = 0;
+    loop {
+        let line_end = offset + captured[offset..].iter().position(|&b| b == b'\n')?;
+        let is_banner = captured[offset..].starts_with(b"SSH-");
+        offset = line_end + 1;
+        if is_banner {
+            break;
+        }
+    }
+
+    let packet = &captured[offset..];
+    let packet_len = u32::from_be_bytes(packet.get(..4)?.try_into().ok()?) as usize;
+    let padding_len = *packet.get(4)? as usize;
+    let payload = packet.get(5..(4 + packet_len).checked_sub(padding_len)?)?;
+    if payload.first() != Some(&SSH_MSG_KEXINIT) {
+        return None;
+    }
+
+    // Message type and 16-byte cookie, then the name-lists in a fixed order.
+    let mut rest = payload.get(17..)?;
+    let mut lists = Vec::with_capacity(10);
+    for _ in 0..10 {
+        let len = u32::from_be_bytes(rest.get(..4)?.try_into().ok()?) as usize;
+        let names = std::str::from_utf8(rest.get(4..4 + len)?).ok()?;
+        lists.push(
+            names
+                .split(',')
+                .filter(|name| !name.is_empty())
+                .map(str::to_string)
+                .collect::<Vec<_>>(),
+        );
+        rest = &rest[4 + len..];
+    }
+
+    let mut lists = lists.into_iter();
+    let kex = lists.next()?;
+    let host_key = lists.next()?;
+    let cipher_client_to_server = lists.next()?;
+    let _cipher_server_to_client = lists.next()?;
+    let mac_client_to_server = lists.next()?;
+    Some(ServerKexInit {
+        kex,
+        host_key,
+        cipher_client_to_server,
+        mac_client_to_server,
+    })
+}
+
+fn first_shared<'a>(ours: impl IntoIterator<Item = &'a str>, theirs: &[String]) -> Option<String> {
+    ours.into_iter()
+        .find(|name| theirs.iter().any(|theirs| theirs == name))
+        .map(str::to_string)
+}
+
+fn negotiate(preferred: &Preferred, server: &ServerKexInit) -> Option<SshNegotiatedAlgorithms> {
+    // `ext-info-*` and `kex-strict-*` are capability markers, not key exchanges.
+    let kex = first_shared(
+        preferred
+            .kex
+            .iter()
+            .map(AsRef::as_ref)
+            .filter(|name| !name.starts_with("ext-info-") && !name.starts_with("kex-strict-")),
+        &server.kex,
+    )?;
+    let host_key_alg = first_shared(
+        preferred.key.iter().map(|algorithm| algorithm.as_str()),
+        &server.host_key,
+    )?;
+    let cipher = first_shared(
+        preferred.cipher.iter().map(AsRef::as_ref),
+        &server.cipher_client_to_server,
+    )?;
+
+    let aead = [
+        cipher::CHACHA20_POLY1305,
+        cipher::AES_128_GCM,
+        cipher::AES_256_GCM,
+    ]
+    .iter()
+    .any(|name| name.as_ref() == cipher);
+    let mac = if aead {
+        None
+    } else {
+        Some(first_shared(
+            preferred.mac.iter().map(AsRef::as_ref),
+            &server.mac_client_to_server,
+        )?)
+    };
+
+    Some(SshNegotiatedAlgorithms {
+        kex,
+        host_key_alg,
+        cipher,
+        mac,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn name_list(names: &str) -> Vec<u8> {
+        let mut out = (names.len() as u32).to_be_bytes().to_vec();
+        out.extend_from_slice(names.as_bytes());
+        out
+    }
+
+    fn server_hello(kex: &str, host_key: &str, cipher: &str, mac: &str) -> Vec<u8> {
+        let mut payload = vec![SSH_MSG_KEXINIT];
+        payload.extend_from_slice(&[7; 16]);
+        for list in [
+            kex, host_key, cipher, cipher, mac, mac, "none", "none", "", "",
+        ] {
+            payload.extend(name_list(list));
+        }
+        payload.extend_from_slice(&[0, 0, 0, 0, 0]);
+
+        let padding = 4;
+        let mut captured = b"Welcome\r\nSSH-2.0-OpenSSH_9.6\r\n".to_vec();
+        captured.extend(((payload.len() + padding + 1) as u32).to_be_bytes());
+        captured.push(padding as u8);
+        captured.extend(payload);
+        captured.extend(vec![0; padding]);
+        captured
+    }
+
+    #[test]
+    fn applies_client_preference_to_the_server_kexinit() {
+        let preferred = Preferred::default();
+        let captured = server_hello(
+            "diffie-hellman-group14-sha256,curve25519-sha256,ext-info-s",
+            "rsa-sha2-256,ssh-ed25519",
+            "aes128-ctr,aes256-ctr",
+            "hmac-sha2-256,hmac-sha2-256-etm@openssh.com",
+        );
+
+        let server = parse_server_kexinit(&captured).expect("kexinit parses");
+        assert_eq!(server.host_key, vec!["rsa-sha2-256", "ssh-ed25519"]);
+
+        assert_eq!(
+            negotiate(&preferred, &server),
+            Some(SshNegotiatedAlgorithms {
+                kex: "curve25519-sha256".to_string(),
+                host_key_alg: "ssh-ed25519".to_string(),
+                cipher: "aes256-ctr".to_string(),
+                mac: Some("hmac-sha2-256-etm@openssh.com".to_string()),
+            })
+        );
+    }
+
+    #[test]
+    fn aead_ciphers_report_no_mac() {
+        let captured = server_hello(
+            "curve25519-sha256",
+            "ssh-ed25519",
+            "chacha20-poly1305@openssh.com",
+            "hmac-sha2-512",
+        );
+        let server = parse_server_kexinit(&captured).expect("kexinit parses");
+        let negotiated = negotiate(&Preferred::default(), &server).expect("negotiates");
+        assert_eq!(negotiated.cipher, "chacha20-poly1305@openssh.com");
+        assert_eq!(negotiated.mac, None);
+    }
+
+    #[test]
+    fn ignores_truncated_captures() {
+        let captured = server_hello(
+            "curve25519-sha256",
+            "ssh-ed25519",
+            "aes256-ctr",
+            "hmac-sha2-256",
+        );
+        assert_eq!(parse_server_kexinit(&captured[..captured.len() - 20]), None);
+        assert_eq!(parse_server_kexinit(b"SSH-2.0-OpenSSH_9.6"), None);
+    }
+}
@@ -0,0 +1,92 @@
+//! Bracketed paste (DEC private mode 2004).
+//!
+//! Shells and editors that enable the mode treat text between the paste markers as typed
+//! input rather than commands, so a pasted newline doesn't run anything. The remote
+//! toggles the mode in its output, which is watched as it streams past.
+
+const MODE_ON: &[u8] = b"\x1b[?2004h";
+const MODE_OFF: &[u8] = b"\x1b[?2004l";
+const PASTE_START: &str = "\x1b[200~";
+const PASTE_END: &str = "\x1b[201~";
+
+/// Follows mode 2004 toggles across output chunks.
+#[derive(Default)]
+pub(crate) struct BracketedPasteTracker {
+    /// End of the previous chunk, in case a toggle was split between chunks.
+    tail: Vec<u8>,
+}
+
+impl BracketedPasteTracker {
+    /// Returns the mode set by the last toggle seen in `chunk`, if any.
+    pub(crate) fn observe(&mut self, chunk: &[u8]) -> Option<bool> {
+        let mut window = std::mem::take(&mut self.tail);
+        window.extend_from_slice(chunk);
+
+        let toggled = window
+            .windows(MODE_ON.len())
+            .rposition(|bytes| bytes == MODE_ON || bytes == MODE_OFF)
+            .map(|start| window[start + MODE_ON.len() - 1] == b'h');
+
+        let keep_from = window.len().saturating_sub(MODE_ON.len() - 1);
+        self.tail = window.split_off(keep_from);
+        toggled
+    }
+}
+
+/// Prepares pasted text the way a terminal would: line breaks become carriage returns and,
+/// when the remote asked for it, the text is wrapped in paste markers. Markers inside the
+/// text are dropped so it can't end the paste early, including ones that only form once
+/// an inner marker is removed.
+pub(crate) fn paste_payload(text: &str, bracketed: bool) -> String {
+    let mut text = text.replace("\r\n", "\r").replace('\n', "\r");
+    if !bracketed {
+        return text;
+    }
+    while text.contains(PASTE_START) || text.contains(PASTE_END) {
+        text = text.replace(PASTE_START, "").replace(PASTE_END, "");
+    }
+    format!("{PASTE_START}{text}{PASTE_END}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn follows_mode_toggles_split_across_chunks() {
+        let mut tracker = BracketedPasteTracker::default();
+        assert_eq!(tracker.observe(b"login banner\r\n"), None);
+        assert_eq!(tracker.observe(b"$ \x1b[?20"), None);
+        assert_eq!(tracker.observe(b"04h"), Some(true));
+        assert_eq!(tracker.observe(b"ls\r\n"), None);
+        assert_eq!(
+            tracker.observe(b"\x1b[?2004l\r\nout\x1b[?2004h$ "),
+            Some(true)
+        );
+        assert_eq!(tracker.observe(b"\x1b[?2004l"), Some(false));
+    }
+
+    #[test]
+    fn wraps_pastes_only_when_the_remote_enabled_it() {
+        assert_eq!(
+            paste_payload("echo a\necho b\r\n", false),
+            "echo a\recho b\r"
+        );
+        assert_eq!(
+            paste_payload("echo a\n\x1b[201~rm -rf ~\n", true),
+            "\x1b[200~echo a\rrm -rf ~\r\x1b[201~"
+        );
+    }
+
+    #[test]
+    fn strips_markers_nested_inside_markers() {
+        assert_eq!(
+            paste_payload("\x1b[20\x1b[201~1~rm -rf ~\n", true),
+            "\x1b[200~rm -rf ~\r\x1b[201~"
+        );
+        assert_eq!(
+            paste_payload("a\x1b[20\x1b[200~0~b", true),
+            "\x1b[200~ab\x1b[201~"
+        );
+    }
+}
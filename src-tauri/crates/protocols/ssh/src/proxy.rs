@@ -0,0 +1,289 @@
+//! `ProxyCommand`: the SSH transport runs over a helper program's stdin and stdout
+//! instead of a TCP connection, as with the OpenSSH option of the same name.
+//!
+//! The helper is killed when the transport is dropped, which happens once the russh
+//! session ends, so closing a session also ends its proxy.
+
+use std::io;
+use std::pin::Pin;
+use std::process::Stdio;
+use std::sync::{Arc, Mutex as StdMutex};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use anyhow::{bail, Context as _, Result};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, ReadBuf};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+
+/// Stderr kept from the proxy to explain why it exited.
+const MAX_STDERR: usize = 4 * 1024;
+/// How long a failed handshake waits for the proxy to finish exiting.
+const EXIT_GRACE: Duration = Duration::from_millis(500);
+/// Characters `sh -c` or `cmd /C` would treat as more than text.
+const SHELL_METACHARACTERS: &[char] = &[
+    ';', '&', '|', '`', '$', '(', ')', '<', '>', '\'', '"', '\\', '*', '?', '[', ']', '{', '}',
+    '!', '^', '%', '~', '#',
+];
+
+/// Expands `%h` (host), `%p` (port), `%r` (remote user) and `%%` in `template`. Other
+/// `%` sequences are kept as written.
+///
+/// The command runs through a shell, so like OpenSSH this refuses to substitute a host
+/// or user containing shell metacharacters, whitespace or a leading `-`.
+pub(crate) fn expand_proxy_command(
+    template: &str,
+    host: &str,
+    port: u16,
+    user: &str,
+) -> Result<String> {
+    let mut expanded = String::with_capacity(template.len());
+    let mut chars = template.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            expanded.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('h') => expanded.push_str(shell_safe("host", host)?),
+            Some('p') => expanded.push_str(&port.to_string()),
+            Some('r') => expanded.push_str(shell_safe("user", user)?),
+            Some('%') => expanded.push('%'),
+            Some(other) => {
+                expanded.push('%');
+                expanded.push(other);
+            }
+            None => expanded.push('%'),
+        }
+    }
+    Ok(expanded)
+}
+
+fn shell_safe<'a>(what: &str, value: &'a str) -> Result<&'a str> {
+    let unsafe_char = value
+        .chars()
+        .any(|c| c.is_whitespace() || c.is_control() || SHELL_METACHARACTERS.contains(&c));
+    if unsafe_char || value.starts_with('-') {
+        bail!("the {what} `{value}` can't be passed to a proxy command; it contains shell syntax");
+    }
+    Ok(value)
+}
+
+/// The proxy's stdout and stdin as one duplex stream.
+pub(crate) struct ProxyStream {
+    stdout: ChildStdout,
+    stdin: ChildStdin,
+    // Keeps the process alive for as long as the transport is in use.
+    _process: ProxyProcess,
+}
+
+/// Shared handle on a running proxy; the process is killed when the last one drops.
+#[derive(Clone)]
+pub(crate) struct ProxyProcess {
+    command: String,
+    child: Arc<Mutex<Child>>,
+    stderr: Arc<StdMutex<Vec<u8>>>,
+    stderr_reader: Arc<StdMutex<Option<JoinHandle<()>>>>,
+}
+
+/// Runs `command` through the platform shell.
+pub(crate) fn spawn_proxy(command: &str) -> Result<(ProxyStream, ProxyProcess)> {
+    let mut child = shell_command(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .with_context(|| format!("failed to start proxy command `{command}`"))?;
+
+    let stdin = child.stdin.take().expect("proxy stdin is piped");
+    let stdout = child.stdout.take().expect("proxy stdout is piped");
+    let stderr = Arc::new(StdMutex::new(Vec::new()));
+    let stderr_reader = child.stderr.take().map(|mut pipe| {
+        let stderr = Arc::clone(&stderr);
+        tokio::spawn(async move {
+            let mut chunk = [0_u8; 1024];
+            while let Ok(read @ 1..) = pipe.read(&mut chunk).await {
+                let mut kept = stderr.lock().expect("proxy stderr lock poisoned");
+                kept.extend_from_slice(&chunk[..read]);
+                let excess = kept.len().saturating_sub(MAX_STDERR);
+                kept.drain(..excess);
+            }
+        })
+    });
+
+    let process = ProxyProcess {
+        command: command.to_string(),
+        child: Arc::new(Mutex::new(child)),
+        stderr,
+        stderr_reader: Arc::new(StdMutex::new(stderr_reader)),
+    };
+    let stream = ProxyStream {
+        stdout,
+        stdin,
+        _process: process.clone(),
+    };
+    Ok((stream, process))
+}
+
+#[cfg(unix)]
+fn shell_command(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.arg("-c").arg(command);
+    shell
+}
+
+#[cfg(windows)]
+fn shell_command(command: &str) -> Command {
+    const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+    let mut shell = Command::new("cmd");
+    shell
+        .arg("/C")
+        .arg(command)
+        .creation_flags(CREATE_NO_WINDOW);
+    shell
+}
+
+impl ProxyProcess {
+    /// Replaces a handshake error with one naming the proxy if it has exited, since its
+    /// exit status and stderr say more than the closed stream does.
+    pub(crate) async fn explain_failure(&self, error: anyhow::Error) -> anyhow::Error {
+        let status = {
+            let mut child = self.child.lock().await;
+            match tokio::time::timeout(EXIT_GRACE, child.wait()).await {
+                Ok(Ok(status)) => status,
+                _ => return error,
+            }
+        };
+        // The pipe closes once the proxy has exited, so this only waits for the last read.
+        let reader = self
+            .stderr_reader
+            .lock()
+            .expect("proxy stderr lock poisoned")
+            .take();
+        if let Some(reader) = reader {
+            let _ = tokio::time::timeout(EXIT_GRACE, reader).await;
+        }
+        let stderr = self.stderr.lock().expect("proxy stderr lock poisoned");
+        let stderr = String::from_utf8_lossy(&stderr);
+        let stderr = stderr.trim();
+
+        let mut message = format!(
+            "proxy command `{}` exited before the SSH handshake finished ({status})",
+            self.command
+        );
+        if !stderr.is_empty() {
+            message.push_str(": ");
+            message.push_str(stderr);
+        }
+        error.context(message)
+    }
+}
+
+impl AsyncRead for ProxyStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stdout).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for ProxyStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.stdin).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stdin).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stdin).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expands_openssh_tokens() {
+        assert_eq!(
+            expand_proxy_command(
+                "cloudflared access ssh --hostname %h:%p",
+                "db.internal",
+                22,
+                "me"
+            )
+            .expect("expand"),
+            "cloudflared access ssh --hostname db.internal:22"
+        );
+        assert_eq!(
+            expand_proxy_command("ssh -W %h:%p %r@bastion 100%% %x%", "h", 2222, "ops")
+                .expect("expand"),
+            "ssh -W h:2222 ops@bastion 100% %x%"
+        );
+    }
+
+    #[test]
+    fn refuses_hosts_with_shell_syntax() {
+        for host in [
+            "db.internal; rm -rf ~",
+            "$(reboot)",
+            "`id`",
+            "a&b",
+            "a|b",
+            "db internal",
+            "-oProxyCommand=x",
+        ] {
+            assert!(
+                expand_proxy_command("nc %h %p", host, 22, "me").is_err(),
+                "{host}"
+            );
+        }
+        assert_eq!(
+            expand_proxy_command("nc %h %p", "fe80::1", 22, "me").expect("ipv6 host"),
+            "nc fe80::1 22"
+        );
+    }
+
+    #[test]
+    fn refuses_users_with_shell_syntax() {
+        for user in ["me;id", "$(id)", "me`id`", "me&id", "me id", "me\nid"] {
+            assert!(
+                expand_proxy_command("ssh -W %h:%p %r@bastion", "db", 22, user).is_err(),
+                "{user}"
+            );
+        }
+        assert_eq!(
+            expand_proxy_command("ssh -W %h:%p %r@bastion", "db", 22, "deploy.bot_1")
+                .expect("plain user"),
+            "ssh -W db:22 deploy.bot_1@bastion"
+        );
+        // Only values that are substituted are checked.
+        assert!(expand_proxy_command("nc %h %p", "db", 22, "me;id").is_ok());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn reports_a_proxy_that_exits_early() {
+        let (stream, process) =
+            spawn_proxy("echo 'no route to db.internal' >&2; exit 3").expect("spawn proxy");
+        drop(stream);
+
+        let error = process
+            .explain_failure(anyhow::anyhow!("SSH connection failed"))
+            .await
+            .to_string();
+        assert!(error.contains("exited before the SSH handshake"), "{error}");
+        assert!(error.contains("3"), "{error}");
+        assert!(error.contains("no route to db.internal"), "{error}");
+    }
+}
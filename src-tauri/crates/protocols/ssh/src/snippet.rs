@@ -0,0 +1,133 @@
+//! Snippets: saved text typed into a session, with `${name}` placeholders filled in
+//! when it is sent.
+//!
+//! `$${` is a literal `${`, for shell parameter expansions like `$${HOME}`. Anything
+//! between `${` and `}` that isn't a plain name of letters, digits and `_`
+//! (`${HOME:-/root}`, `${USER-me}`) is kept as written.
+
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+
+/// Placeholder names in `body`, once each, in the order they first appear.
+pub fn snippet_placeholders(body: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for_each_part(body, |part| {
+        if let Part::Placeholder(name) = part {
+            if !names.iter().any(|known| known == name) {
+                names.push(name.to_string());
+            }
+        }
+    });
+    names
+}
+
+/// Fills in every placeholder in `body` from `values`, failing with the names that
+/// have no value.
+pub fn expand_snippet(body: &str, values: &HashMap<String, String>) -> Result<String> {
+    let mut expanded = String::with_capacity(body.len());
+    let mut missing: Vec<&str> = Vec::new();
+    for_each_part(body, |part| match part {
+        Part::Text(text) => expanded.push_str(text),
+        Part::Placeholder(name) => match values.get(name) {
+            Some(value) => expanded.push_str(value),
+            None if !missing.contains(&name) => missing.push(name),
+            None => {}
+        },
+    });
+    if !missing.is_empty() {
+        return Err(anyhow!(
+            "missing values for snippet placeholders: {}",
+            missing.join(", ")
+        ));
+    }
+    Ok(expanded)
+}
+
+/// Splits text into the lines to type, each ending in a carriage return as Enter
+/// sends. Without `trailing_newline` the last line is left unsubmitted.
+pub(crate) fn snippet_lines(text: &str, trailing_newline: bool) -> Vec<String> {
+    let text = text.replace("\r\n", "\n").replace('\r', "\n");
+    let text = text.strip_suffix('\n').unwrap_or(&text);
+    if text.is_empty() {
+        return Vec::new();
+    }
+    let mut lines: Vec<String> = text.split('\n').map(|line| format!("{line}\r")).collect();
+    if !trailing_newline {
+        if let Some(last) = lines.last_mut() {
+            last.pop();
+        }
+    }
+    lines.retain(|line| !line.is_empty());
+    lines
+}
+
+enum Part<'a> {
+    Text(&'a str),
+    Placeholder(&'a str),
+}
+
+fn for_each_part<'a>(body: &'a str, mut visit: impl FnMut(Part<'a>)) {
+    let mut rest = body;
+    while let Some(start) = rest.find("${") {
+        // `$${` escapes the placeholder: keep `${` and carry on after it.
+        if rest[..start].ends_with('$') {
+            visit(Part::Text(&rest[..start - 1]));
+            visit(Part::Text("${"));
+            rest = &rest[start + 2..];
+            continue;
+        }
+        let after = &rest[start + 2..];
+        let name = after.find('}').map(|end| &after[..end]).filter(|name| {
+            !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        });
+        match name {
+            Some(name) => {
+                visit(Part::Text(&rest[..start]));
+                visit(Part::Placeholder(name));
+                rest = &after[name.len() + 1..];
+            }
+            None => {
+                visit(Part::Text(&rest[..start + 2]));
+                rest = after;
+            }
+        }
+    }
+    visit(Part::Text(rest));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fills_placeholders_and_keeps_shell_expansions() {
+        let body = "cd ${dir} && ls ${dir}/${sub}\necho $${HOME} ${USER:-me} ${USER-me}";
+        assert_eq!(snippet_placeholders(body), ["dir", "sub"]);
+
+        let values = HashMap::from([
+            ("dir".to_string(), "/srv/app".to_string()),
+            ("sub".to_string(), "logs".to_string()),
+        ]);
+        assert_eq!(
+            expand_snippet(body, &values).expect("expand"),
+            "cd /srv/app && ls /srv/app/logs\necho ${HOME} ${USER:-me} ${USER-me}"
+        );
+
+        let error = expand_snippet(body, &HashMap::new()).expect_err("values missing");
+        assert_eq!(
+            error.to_string(),
+            "missing values for snippet placeholders: dir, sub"
+        );
+    }
+
+    #[test]
+    fn splits_lines_for_typing() {
+        assert_eq!(
+            snippet_lines("cd /srv\r\nls\n", true),
+            ["cd /srv\r", "ls\r"]
+        );
+        assert_eq!(snippet_lines("cd /srv\nls", false), ["cd /srv\r", "ls"]);
+        assert_eq!(snippet_lines("a\n\nb", true), ["a\r", "\r", "b\r"]);
+    }
+}
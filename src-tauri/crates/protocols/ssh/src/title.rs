@@ -0,0 +1,136 @@
+//! Window title sequences (OSC 0 and OSC 2).
+//!
+//! Shells set the title with `ESC ] 0 ; title BEL` (or ST, `ESC \`, instead of BEL). The
+//! sequences are taken out of the output stream and reported separately so the tab can
+//! show the title. Other OSC sequences pass through untouched.
+
+const ESC: u8 = 0x1b;
+const BEL: u8 = 0x07;
+/// A sequence still unterminated after this many bytes isn't a title anyone wants; it is
+/// passed through as output rather than held back indefinitely.
+const MAX_SEQUENCE_LEN: usize = 4096;
+
+/// Strips title sequences from output chunks, including ones split between chunks.
+#[derive(Default)]
+pub(crate) struct TitleTracker {
+    /// An OSC sequence (or a lone trailing `ESC`) whose end hasn't arrived yet.
+    pending: Vec<u8>,
+    title: Option<String>,
+}
+
+impl TitleTracker {
+    /// Returns `chunk` without title sequences, and the new title if one of them
+    /// changed it.
+    pub(crate) fn filter(&mut self, chunk: &[u8]) -> (Vec<u8>, Option<String>) {
+        let mut input = std::mem::take(&mut self.pending);
+        input.extend_from_slice(chunk);
+
+        let mut output = Vec::with_capacity(input.len());
+        let mut changed = None;
+        let mut pos = 0;
+        while pos < input.len() {
+            let Some(offset) = input[pos..].iter().position(|&byte| byte == ESC) else {
+                output.extend_from_slice(&input[pos..]);
+                break;
+            };
+            let start = pos + offset;
+            output.extend_from_slice(&input[pos..start]);
+
+            match input.get(start + 1) {
+                None => {
+                    self.pending = input[start..].to_vec();
+                    break;
+                }
+                Some(b']') => {}
+                Some(_) => {
+                    output.push(ESC);
+                    pos = start + 1;
+                    continue;
+                }
+            }
+
+            let Some((body_end, end)) = osc_end(&input, start + 2) else {
+                if input.len() - start > MAX_SEQUENCE_LEN {
+                    output.extend_from_slice(&input[start..]);
+                } else {
+                    self.pending = input[start..].to_vec();
+                }
+                break;
+            };
+
+            match title_text(&input[start + 2..body_end]) {
+                Some(title) => {
+                    if self.title.as_deref() != Some(title.as_str()) {
+                        self.title = Some(title.clone());
+                        changed = Some(title);
+                    }
+                }
+                None => output.extend_from_slice(&input[start..end]),
+            }
+            pos = end;
+        }
+
+        (output, changed)
+    }
+}
+
+/// Finds the terminator of an OSC body starting at `from`: the end of the body and the
+/// end of the whole sequence.
+fn osc_end(input: &[u8], from: usize) -> Option<(usize, usize)> {
+    let mut index = from;
+    while index < input.len() {
+        match input[index] {
+            BEL => return Some((index, index + 1)),
+            ESC if input.get(index + 1) == Some(&b'\\') => return Some((index, index + 2)),
+            _ => index += 1,
+        }
+    }
+    None
+}
+
+fn title_text(body: &[u8]) -> Option<String> {
+    let text = body
+        .strip_prefix(b"0;")
+        .or_else(|| body.strip_prefix(b"2;"))?;
+    Some(String::from_utf8_lossy(text).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_title_sequences_and_reports_changes() {
+        let mut tracker = TitleTracker::default();
+        let (output, title) = tracker.filter(b"\x1b]0;user@host: ~\x07$ ls\r\n");
+        assert_eq!(output, b"$ ls\r\n");
+        assert_eq!(title.as_deref(), Some("user@host: ~"));
+
+        let (output, title) = tracker.filter(b"\x1b]2;user@host: ~\x1b\\\x1b[1mbold\x1b[0m");
+        assert_eq!(output, b"\x1b[1mbold\x1b[0m");
+        assert_eq!(title, None);
+
+        let link = b"\x1b]8;;https://example.com\x07link\x1b]8;;\x07";
+        assert_eq!(tracker.filter(link), (link.to_vec(), None));
+    }
+
+    #[test]
+    fn holds_sequences_split_across_chunks() {
+        let mut tracker = TitleTracker::default();
+        assert_eq!(tracker.filter(b"out\x1b"), (b"out".to_vec(), None));
+        assert_eq!(tracker.filter(b"]0;vim REA"), (Vec::new(), None));
+        let (output, title) = tracker.filter(b"DME.md\x07\x1b[H");
+        assert_eq!(output, b"\x1b[H");
+        assert_eq!(title.as_deref(), Some("vim README.md"));
+    }
+
+    #[test]
+    fn passes_through_unterminated_sequences_past_the_limit() {
+        let mut tracker = TitleTracker::default();
+        let mut chunk = b"\x1b]0;".to_vec();
+        chunk.resize(MAX_SEQUENCE_LEN + 8, b'x');
+        let (output, title) = tracker.filter(&chunk);
+        assert_eq!(output, chunk);
+        assert_eq!(title, None);
+    }
+}
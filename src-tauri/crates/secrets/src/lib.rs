@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 use std::mem::MaybeUninit;
 use std::path::{Path, PathBuf};
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 
 use anyhow::{anyhow, Context, Result};
 use argon2::Argon2;
@@ -16,13 +16,24 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use zeroize::Zeroize;
 
+mod ssh_keys;
+
+pub use ssh_keys::{generate_ssh_keypair, GeneratedSshKey, SshKeyType};
+
 const SALT_LEN: usize = 16;
 const NONCE_LEN: usize = 24;
+const VAULT_FILE_SUFFIX: &str = ".enc.json";
+const MAX_VAULT_ID_LEN: usize = 64;
+
+/// Id of the vault at the path given to `Vaults::new`. Its secret ids are bare UUIDs;
+/// every other vault prefixes its ids with `<vault id>:`.
+pub const DEFAULT_VAULT_ID: &str = "default";
 
 #[derive(Clone)]
 pub struct VaultManager {
+    id: String,
     file_path: PathBuf,
-    state: std::sync::Arc<Mutex<VaultState>>,
+    state: Arc<Mutex<VaultState>>,
 }
 
 struct VaultState {
@@ -52,12 +63,21 @@ struct VaultEnvelope {
 
 impl VaultManager {
     pub fn new(file_path: &Path) -> Self {
+        Self::named(DEFAULT_VAULT_ID, file_path)
+    }
+
+    fn named(id: &str, file_path: &Path) -> Self {
         Self {
+            id: id.to_string(),
             file_path: file_path.to_path_buf(),
-            state: std::sync::Arc::new(Mutex::new(VaultState { unlocked: None })),
+            state: Arc::new(Mutex::new(VaultState { unlocked: None })),
         }
     }
 
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
     pub async fn initialize(&self, passphrase: &str) -> Result<()> {
         if passphrase.is_empty() {
             return Err(anyhow!("passphrase cannot be empty"));
@@ -147,12 +167,12 @@ impl VaultManager {
                 .lock()
                 .map_err(|_| anyhow!("vault mutex poisoned"))?;
 
-            let unlocked = guard
-                .unlocked
-                .as_mut()
-                .ok_or_else(|| anyhow!("vault is locked"))?;
+            let unlocked = guard.unlocked.as_mut().ok_or_else(|| self.locked_error())?;
 
-            let id = Uuid::new_v4().to_string();
+            let id = match self.id.as_str() {
+                DEFAULT_VAULT_ID => Uuid::new_v4().to_string(),
+                vault_id => format!("{vault_id}:{}", Uuid::new_v4()),
+            };
             let created_at = Utc::now();
 
             unlocked.data.insert(
@@ -170,35 +190,140 @@ impl VaultManager {
                 unlocked.salt,
                 SecretRef {
                     id,
+                    vault_id: self.id.clone(),
                     kind,
                     created_at,
                 },
             )
         };
 
-        let envelope = encrypt_payload(&key, &salt, &serialized)?;
-        tokio::fs::write(&self.file_path, serde_json::to_vec_pretty(&envelope)?)
-            .await
-            .with_context(|| format!("writing vault file {}", self.file_path.display()))?;
+        self.write_payload(&key, &salt, &serialized).await?;
         key.zeroize();
 
         Ok(secret_ref)
     }
 
+    /// Stores `value` as a new secret of the same kind as `id`, which is kept. Returns
+    /// `None` if `id` is unknown.
+    pub async fn put_secret_like(&self, id: &str, value: &str) -> Result<Option<SecretRef>> {
+        let kind = {
+            let guard = self
+                .state
+                .lock()
+                .map_err(|_| anyhow!("vault mutex poisoned"))?;
+
+            let unlocked = guard.unlocked.as_ref().ok_or_else(|| self.locked_error())?;
+            unlocked.data.get(id).map(|record| record.kind.clone())
+        };
+
+        match kind {
+            Some(kind) => self.put_secret(kind, value).await.map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Remove a secret and persist the vault. Returns `false` if the id was unknown.
+    pub async fn delete_secret(&self, id: &str) -> Result<bool> {
+        Ok(self.delete_secrets([id]).await? == 1)
+    }
+
+    /// Remove several secrets with a single vault write and return how many existed.
+    ///
+    /// Either all of them are removed or, if persisting fails, none are.
+    pub async fn delete_secrets<'a>(
+        &self,
+        ids: impl IntoIterator<Item = &'a str>,
+    ) -> Result<usize> {
+        let (removed, serialized, mut key, salt) = {
+            let mut guard = self
+                .state
+                .lock()
+                .map_err(|_| anyhow!("vault mutex poisoned"))?;
+
+            let unlocked = guard.unlocked.as_mut().ok_or_else(|| self.locked_error())?;
+
+            let removed: Vec<(String, StoredSecret)> = ids
+                .into_iter()
+                .filter_map(|id| unlocked.data.remove_entry(id))
+                .collect();
+            if removed.is_empty() {
+                return Ok(0);
+            }
+
+            (
+                removed,
+                serde_json::to_vec(&unlocked.data).context("serializing vault map")?,
+                unlocked.key,
+                unlocked.salt,
+            )
+        };
+
+        let written = self.write_payload(&key, &salt, &serialized).await;
+        key.zeroize();
+
+        if let Err(error) = written {
+            let mut guard = self
+                .state
+                .lock()
+                .map_err(|_| anyhow!("vault mutex poisoned"))?;
+            if let Some(unlocked) = guard.unlocked.as_mut() {
+                unlocked.data.extend(removed);
+            }
+            return Err(error);
+        }
+
+        Ok(removed.len())
+    }
+
     pub fn get_secret(&self, id: &str) -> Result<Option<String>> {
         let guard = self
             .state
             .lock()
             .map_err(|_| anyhow!("vault mutex poisoned"))?;
 
-        let unlocked = guard
-            .unlocked
-            .as_ref()
-            .ok_or_else(|| anyhow!("vault is locked"))?;
+        let unlocked = guard.unlocked.as_ref().ok_or_else(|| self.locked_error())?;
 
         Ok(unlocked.data.get(id).map(|record| record.value.clone()))
     }
 
+    /// Describe every stored secret, oldest first, without exposing any value.
+    pub fn list_secrets_meta(&self) -> Result<Vec<SecretRef>> {
+        let guard = self
+            .state
+            .lock()
+            .map_err(|_| anyhow!("vault mutex poisoned"))?;
+
+        let unlocked = guard.unlocked.as_ref().ok_or_else(|| self.locked_error())?;
+
+        let mut secrets: Vec<SecretRef> = unlocked
+            .data
+            .iter()
+            .map(|(id, record)| SecretRef {
+                id: id.clone(),
+                vault_id: self.id.clone(),
+                kind: record.kind.clone(),
+                created_at: record.created_at,
+            })
+            .collect();
+        secrets.sort_by(|a, b| {
+            a.created_at
+                .cmp(&b.created_at)
+                .then_with(|| a.id.cmp(&b.id))
+        });
+        Ok(secrets)
+    }
+
+    pub fn secret_count(&self) -> Result<usize> {
+        let guard = self
+            .state
+            .lock()
+            .map_err(|_| anyhow!("vault mutex poisoned"))?;
+
+        let unlocked = guard.unlocked.as_ref().ok_or_else(|| self.locked_error())?;
+
+        Ok(unlocked.data.len())
+    }
+
     pub fn is_unlocked(&self) -> bool {
         self.state
             .lock()
@@ -211,6 +336,228 @@ impl VaultManager {
             .await
             .context("checking vault file existence")
     }
+
+    fn locked_error(&self) -> anyhow::Error {
+        match self.id.as_str() {
+            DEFAULT_VAULT_ID => anyhow!("vault is locked"),
+            id => anyhow!("vault \"{id}\" is locked"),
+        }
+    }
+
+    async fn write_payload(
+        &self,
+        key: &[u8; 32],
+        salt: &[u8; SALT_LEN],
+        payload: &[u8],
+    ) -> Result<()> {
+        let envelope = encrypt_payload(key, salt, payload)?;
+        tokio::fs::write(&self.file_path, serde_json::to_vec_pretty(&envelope)?)
+            .await
+            .with_context(|| format!("writing vault file {}", self.file_path.display()))
+    }
+}
+
+/// A vault as reported by `Vaults::list_vaults`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VaultInfo {
+    pub id: String,
+    pub initialized: bool,
+    pub unlocked: bool,
+}
+
+/// The default vault plus any named vaults kept in one directory, each with its own
+/// passphrase and locked or unlocked independently of the others.
+///
+/// Secret ids carry the id of the vault that issued them, so reads and deletes are
+/// routed without the caller keeping track.
+#[derive(Clone)]
+pub struct Vaults {
+    default: VaultManager,
+    dir: PathBuf,
+    named: Arc<Mutex<HashMap<String, VaultManager>>>,
+}
+
+impl Vaults {
+    /// `default_path` is the default vault's file; named vaults live in `dir`.
+    pub fn new(default_path: &Path, dir: &Path) -> Self {
+        Self {
+            default: VaultManager::new(default_path),
+            dir: dir.to_path_buf(),
+            named: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn default_vault(&self) -> &VaultManager {
+        &self.default
+    }
+
+    /// Returns the vault with `vault_id`, which need not have been created yet.
+    pub fn vault(&self, vault_id: &str) -> Result<VaultManager> {
+        if vault_id == DEFAULT_VAULT_ID {
+            return Ok(self.default.clone());
+        }
+        validate_vault_id(vault_id)?;
+
+        let mut named = self
+            .named
+            .lock()
+            .map_err(|_| anyhow!("vault mutex poisoned"))?;
+        let vault = named.entry(vault_id.to_string()).or_insert_with(|| {
+            let file_path = self.dir.join(format!("{vault_id}{VAULT_FILE_SUFFIX}"));
+            VaultManager::named(vault_id, &file_path)
+        });
+        Ok(vault.clone())
+    }
+
+    /// Creates a named vault. The name becomes its id, so it is limited to lowercase
+    /// letters, digits, `-` and `_`.
+    pub async fn create_vault(&self, name: &str, passphrase: &str) -> Result<()> {
+        if name == DEFAULT_VAULT_ID {
+            return Err(anyhow!(
+                "\"{DEFAULT_VAULT_ID}\" is the name of the main vault"
+            ));
+        }
+        self.vault(name)?.initialize(passphrase).await
+    }
+
+    pub async fn unlock_vault(&self, vault_id: &str, passphrase: &str) -> Result<()> {
+        let vault = self.vault(vault_id)?;
+        if vault_id != DEFAULT_VAULT_ID && !vault.is_initialized().await? {
+            return Err(anyhow!("no vault named \"{vault_id}\""));
+        }
+        vault.unlock(passphrase).await
+    }
+
+    /// Locks one vault; the others keep their state.
+    pub fn lock_vault(&self, vault_id: &str) -> Result<()> {
+        self.vault(vault_id)?.lock()
+    }
+
+    /// The default vault first, then named vaults by id.
+    pub async fn list_vaults(&self) -> Result<Vec<VaultInfo>> {
+        let mut ids = Vec::new();
+        match tokio::fs::read_dir(&self.dir).await {
+            Ok(mut entries) => {
+                while let Some(entry) = entries
+                    .next_entry()
+                    .await
+                    .with_context(|| format!("reading vault directory {}", self.dir.display()))?
+                {
+                    let file_name = entry.file_name();
+                    let id = file_name
+                        .to_str()
+                        .and_then(|name| name.strip_suffix(VAULT_FILE_SUFFIX))
+                        .filter(|id| *id != DEFAULT_VAULT_ID && validate_vault_id(id).is_ok());
+                    if let Some(id) = id {
+                        ids.push(id.to_string());
+                    }
+                }
+            }
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {}
+            Err(error) => {
+                return Err(error)
+                    .with_context(|| format!("reading vault directory {}", self.dir.display()))
+            }
+        }
+        ids.sort();
+
+        let mut vaults = vec![VaultInfo {
+            id: DEFAULT_VAULT_ID.to_string(),
+            initialized: self.default.is_initialized().await?,
+            unlocked: self.default.is_unlocked(),
+        }];
+        for id in ids {
+            let unlocked = self.vault(&id)?.is_unlocked();
+            vaults.push(VaultInfo {
+                id,
+                initialized: true,
+                unlocked,
+            });
+        }
+        Ok(vaults)
+    }
+
+    pub async fn put_secret(
+        &self,
+        vault_id: &str,
+        kind: SecretKind,
+        value: &str,
+    ) -> Result<SecretRef> {
+        self.vault(vault_id)?.put_secret(kind, value).await
+    }
+
+    pub fn get_secret(&self, id: &str) -> Result<Option<String>> {
+        self.vault(secret_vault_id(id))?.get_secret(id)
+    }
+
+    pub async fn delete_secret(&self, id: &str) -> Result<bool> {
+        self.vault(secret_vault_id(id))?.delete_secret(id).await
+    }
+
+    /// Stores `value` beside `id`, in the vault that holds it and with its kind.
+    pub async fn put_secret_like(&self, id: &str, value: &str) -> Result<Option<SecretRef>> {
+        self.vault(secret_vault_id(id))?
+            .put_secret_like(id, value)
+            .await
+    }
+
+    /// Removes secrets from whichever vaults hold them and returns how many existed.
+    ///
+    /// Each vault is written once and keeps `VaultManager::delete_secrets`' all-or-nothing
+    /// guarantee. Every vault involved must be unlocked, which is checked before any of
+    /// them is written; a failed write still leaves removals from earlier vaults in place.
+    pub async fn delete_secrets<'a>(
+        &self,
+        ids: impl IntoIterator<Item = &'a str>,
+    ) -> Result<usize> {
+        let mut by_vault: Vec<(&str, Vec<&str>)> = Vec::new();
+        for id in ids {
+            let vault_id = secret_vault_id(id);
+            match by_vault
+                .iter_mut()
+                .find(|(existing, _)| *existing == vault_id)
+            {
+                Some((_, vault_ids)) => vault_ids.push(id),
+                None => by_vault.push((vault_id, vec![id])),
+            }
+        }
+
+        let mut vaults = Vec::with_capacity(by_vault.len());
+        for (vault_id, ids) in by_vault {
+            let vault = self.vault(vault_id)?;
+            if !vault.is_unlocked() {
+                return Err(vault.locked_error());
+            }
+            vaults.push((vault, ids));
+        }
+
+        let mut removed = 0;
+        for (vault, ids) in vaults {
+            removed += vault.delete_secrets(ids).await?;
+        }
+        Ok(removed)
+    }
+}
+
+/// The vault that issued a secret id.
+fn secret_vault_id(id: &str) -> &str {
+    id.split_once(':')
+        .map_or(DEFAULT_VAULT_ID, |(vault_id, _)| vault_id)
+}
+
+fn validate_vault_id(id: &str) -> Result<()> {
+    let valid = !id.is_empty()
+        && id.len() <= MAX_VAULT_ID_LEN
+        && id.bytes().all(|byte| {
+            byte.is_ascii_lowercase() || byte.is_ascii_digit() || byte == b'-' || byte == b'_'
+        });
+    if valid {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "vault names are 1 to {MAX_VAULT_ID_LEN} lowercase letters, digits, '-' or '_'"
+        ))
+    }
 }
 
 fn derive_key(passphrase: &str, salt: &[u8; SALT_LEN]) -> Result<[u8; 32]> {
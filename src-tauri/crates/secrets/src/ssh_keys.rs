@@ -0,0 +1,81 @@
+use anyhow::{anyhow, Context, Result};
+use russh::keys::ssh_key::rand_core::OsRng;
+use russh::keys::ssh_key::LineEnding;
+use russh::keys::{Algorithm, EcdsaCurve, HashAlg, PrivateKey};
+use serde::Deserialize;
+use zeroize::Zeroizing;
+
+/// Key types `generate_ssh_keypair` can create.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SshKeyType {
+    #[default]
+    Ed25519,
+    EcdsaP256,
+    EcdsaP384,
+    EcdsaP521,
+    /// 4096 bits; slow to generate, so only for servers that take nothing else.
+    Rsa,
+}
+
+impl SshKeyType {
+    fn algorithm(self) -> Algorithm {
+        match self {
+            Self::Ed25519 => Algorithm::Ed25519,
+            Self::EcdsaP256 => Algorithm::Ecdsa {
+                curve: EcdsaCurve::NistP256,
+            },
+            Self::EcdsaP384 => Algorithm::Ecdsa {
+                curve: EcdsaCurve::NistP384,
+            },
+            Self::EcdsaP521 => Algorithm::Ecdsa {
+                curve: EcdsaCurve::NistP521,
+            },
+            Self::Rsa => Algorithm::Rsa { hash: None },
+        }
+    }
+}
+
+pub struct GeneratedSshKey {
+    /// OpenSSH private key file contents, encrypted when a passphrase was given.
+    pub private_openssh: Zeroizing<String>,
+    /// A single `authorized_keys` line: `<type> <base64> <comment>`.
+    pub public_openssh: String,
+    /// `SHA256:...`, as `ssh-keygen -l` prints it.
+    pub fingerprint: String,
+}
+
+/// Generates a new keypair in OpenSSH format, for users without a key of their own.
+///
+/// An empty passphrase leaves the private key unencrypted, as `ssh-keygen` does.
+pub fn generate_ssh_keypair(
+    key_type: SshKeyType,
+    comment: &str,
+    passphrase: Option<&str>,
+) -> Result<GeneratedSshKey> {
+    let mut key = PrivateKey::random(&mut OsRng, key_type.algorithm())
+        .map_err(|error| anyhow!("failed to generate {key_type:?} key: {error}"))?;
+    key.set_comment(comment);
+
+    let public_openssh = key
+        .public_key()
+        .to_openssh()
+        .context("failed to encode public key")?;
+    let fingerprint = key.public_key().fingerprint(HashAlg::Sha256).to_string();
+
+    let key = match passphrase.filter(|passphrase| !passphrase.is_empty()) {
+        Some(passphrase) => key
+            .encrypt(&mut OsRng, passphrase)
+            .map_err(|error| anyhow!("failed to encrypt private key: {error}"))?,
+        None => key,
+    };
+    let private_openssh = key
+        .to_openssh(LineEnding::LF)
+        .context("failed to encode private key")?;
+
+    Ok(GeneratedSshKey {
+        private_openssh,
+        public_openssh,
+        fingerprint,
+    })
+}
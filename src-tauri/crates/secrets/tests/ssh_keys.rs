@@ -0,0 +1,50 @@
+use janus_secrets::{generate_ssh_keypair, SshKeyType};
+use russh::keys::{load_secret_key, PublicKey};
+
+fn temp_key_path() -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("janus-key-{}", uuid::Uuid::new_v4()))
+}
+
+#[test]
+fn generated_keys_load_back() {
+    for key_type in [
+        SshKeyType::Ed25519,
+        SshKeyType::EcdsaP256,
+        SshKeyType::EcdsaP384,
+        SshKeyType::EcdsaP521,
+    ] {
+        let generated = generate_ssh_keypair(key_type, "me@laptop", None).expect("generate key");
+        let path = temp_key_path();
+        std::fs::write(&path, generated.private_openssh.as_bytes()).expect("write key");
+
+        let loaded = load_secret_key(&path, None).expect("load generated key");
+        let public = PublicKey::from_openssh(&generated.public_openssh).expect("parse public key");
+        assert_eq!(
+            loaded.public_key().key_data(),
+            public.key_data(),
+            "{key_type:?}"
+        );
+        assert_eq!(public.comment(), "me@laptop");
+        assert!(generated.fingerprint.starts_with("SHA256:"));
+
+        let _ = std::fs::remove_file(path);
+    }
+}
+
+#[test]
+fn passphrase_protects_generated_keys() {
+    let generated =
+        generate_ssh_keypair(SshKeyType::Ed25519, "", Some("hunter2")).expect("generate key");
+    let path = temp_key_path();
+    std::fs::write(&path, generated.private_openssh.as_bytes()).expect("write key");
+
+    assert!(load_secret_key(&path, None).is_err());
+    assert!(load_secret_key(&path, Some("wrong")).is_err());
+    let loaded = load_secret_key(&path, Some("hunter2")).expect("load with passphrase");
+    assert_eq!(
+        loaded.public_key().to_openssh().expect("encode"),
+        generated.public_openssh
+    );
+
+    let _ = std::fs::remove_file(path);
+}
@@ -1,5 +1,5 @@
 use janus_domain::SecretKind;
-use janus_secrets::VaultManager;
+use janus_secrets::{VaultInfo, VaultManager, Vaults, DEFAULT_VAULT_ID};
 
 #[tokio::test]
 async fn reports_initialization_status() {
@@ -47,8 +47,201 @@ async fn unlock_rejects_invalid_salt_length() {
 }"#;
     std::fs::write(&file_path, malformed).expect("write malformed vault");
 
-    let err = vault.unlock("passphrase").await.expect_err("unlock should fail");
+    let err = vault
+        .unlock("passphrase")
+        .await
+        .expect_err("unlock should fail");
     assert!(err.to_string().contains("invalid salt length in vault"));
 
     let _ = std::fs::remove_file(file_path);
 }
+
+#[tokio::test]
+async fn delete_secret_removes_it_across_unlocks() {
+    let file_path = std::env::temp_dir().join(format!("janus-vault-{}.json", uuid::Uuid::new_v4()));
+    let vault = VaultManager::new(&file_path);
+
+    vault.initialize("passphrase").await.expect("init");
+    vault.unlock("passphrase").await.expect("unlock");
+
+    let secret = vault
+        .put_secret(SecretKind::Password, "super-secret")
+        .await
+        .expect("store secret");
+    assert!(vault
+        .delete_secret(&secret.id)
+        .await
+        .expect("delete secret"));
+    assert!(!vault.delete_secret(&secret.id).await.expect("delete again"));
+
+    vault.lock().expect("lock");
+    vault.unlock("passphrase").await.expect("re-unlock");
+    assert_eq!(vault.get_secret(&secret.id).expect("get secret"), None);
+    assert_eq!(vault.secret_count().expect("count"), 0);
+
+    let _ = std::fs::remove_file(file_path);
+}
+
+#[tokio::test]
+async fn delete_secrets_removes_several_in_one_write() {
+    let file_path = std::env::temp_dir().join(format!("janus-vault-{}.json", uuid::Uuid::new_v4()));
+    let vault = VaultManager::new(&file_path);
+
+    vault.initialize("passphrase").await.expect("init");
+    vault.unlock("passphrase").await.expect("unlock");
+
+    let first = vault
+        .put_secret(SecretKind::Password, "one")
+        .await
+        .expect("store first");
+    let second = vault
+        .put_secret(SecretKind::Password, "two")
+        .await
+        .expect("store second");
+    let kept = vault
+        .put_secret(SecretKind::Password, "three")
+        .await
+        .expect("store kept");
+
+    let removed = vault
+        .delete_secrets([first.id.as_str(), second.id.as_str(), "unknown"])
+        .await
+        .expect("delete secrets");
+    assert_eq!(removed, 2);
+
+    vault.lock().expect("lock");
+    vault.unlock("passphrase").await.expect("re-unlock");
+    assert_eq!(vault.secret_count().expect("count"), 1);
+    assert_eq!(
+        vault.get_secret(&kept.id).expect("get kept").as_deref(),
+        Some("three")
+    );
+
+    let _ = std::fs::remove_file(file_path);
+}
+
+#[tokio::test]
+async fn lists_secret_metadata_without_values() {
+    let file_path = std::env::temp_dir().join(format!("janus-vault-{}.json", uuid::Uuid::new_v4()));
+    let vault = VaultManager::new(&file_path);
+
+    vault.initialize("passphrase").await.expect("init");
+    assert!(vault.list_secrets_meta().is_err());
+    vault.unlock("passphrase").await.expect("unlock");
+    assert!(vault
+        .list_secrets_meta()
+        .expect("list empty vault")
+        .is_empty());
+
+    let password = vault
+        .put_secret(SecretKind::Password, "super-secret")
+        .await
+        .expect("store password");
+    let passphrase = vault
+        .put_secret(SecretKind::KeyPassphrase, "key-secret")
+        .await
+        .expect("store passphrase");
+
+    vault.lock().expect("lock");
+    vault.unlock("passphrase").await.expect("re-unlock");
+
+    let listed = vault.list_secrets_meta().expect("list secrets");
+    let ids: Vec<&str> = listed.iter().map(|secret| secret.id.as_str()).collect();
+    assert_eq!(ids, [password.id.as_str(), passphrase.id.as_str()]);
+    assert!(matches!(listed[1].kind, SecretKind::KeyPassphrase));
+    assert_eq!(listed[0].created_at, password.created_at);
+
+    let _ = std::fs::remove_file(file_path);
+}
+
+#[tokio::test]
+async fn named_vaults_lock_independently_and_route_secrets() {
+    let dir = std::env::temp_dir().join(format!("janus-vaults-{}", uuid::Uuid::new_v4()));
+    let vaults = Vaults::new(&dir.join("vault.enc.json"), &dir.join("vaults"));
+
+    vaults
+        .default_vault()
+        .initialize("personal")
+        .await
+        .expect("init default");
+    vaults
+        .create_vault("work", "work-pass")
+        .await
+        .expect("create");
+    assert!(vaults.create_vault("work", "again").await.is_err());
+    assert!(vaults.create_vault(DEFAULT_VAULT_ID, "x").await.is_err());
+    assert!(vaults.create_vault("Work Stuff", "x").await.is_err());
+    assert!(vaults.unlock_vault("missing", "x").await.is_err());
+
+    vaults
+        .unlock_vault(DEFAULT_VAULT_ID, "personal")
+        .await
+        .expect("unlock default");
+    vaults
+        .unlock_vault("work", "work-pass")
+        .await
+        .expect("unlock work");
+
+    let home = vaults
+        .put_secret(DEFAULT_VAULT_ID, SecretKind::Password, "home-secret")
+        .await
+        .expect("store home");
+    let office = vaults
+        .put_secret("work", SecretKind::Password, "office-secret")
+        .await
+        .expect("store office");
+    assert_eq!(office.vault_id, "work");
+    assert!(office.id.starts_with("work:"));
+    assert_eq!(home.vault_id, DEFAULT_VAULT_ID);
+
+    vaults.lock_vault("work").expect("lock work");
+    assert_eq!(
+        vaults.get_secret(&home.id).expect("home").as_deref(),
+        Some("home-secret")
+    );
+    let locked = vaults.get_secret(&office.id).expect_err("work is locked");
+    assert!(locked.to_string().contains("\"work\" is locked"));
+    vaults
+        .delete_secrets([home.id.as_str(), office.id.as_str()])
+        .await
+        .expect_err("work is locked");
+    assert_eq!(
+        vaults.get_secret(&home.id).expect("home kept").as_deref(),
+        Some("home-secret")
+    );
+    assert_eq!(
+        vaults.list_vaults().await.expect("list"),
+        [
+            VaultInfo {
+                id: DEFAULT_VAULT_ID.to_string(),
+                initialized: true,
+                unlocked: true,
+            },
+            VaultInfo {
+                id: "work".to_string(),
+                initialized: true,
+                unlocked: false,
+            },
+        ]
+    );
+
+    assert!(vaults.unlock_vault("work", "personal").await.is_err());
+    vaults
+        .unlock_vault("work", "work-pass")
+        .await
+        .expect("re-unlock work");
+    assert_eq!(
+        vaults.get_secret(&office.id).expect("office").as_deref(),
+        Some("office-secret")
+    );
+    assert_eq!(
+        vaults
+            .delete_secrets([home.id.as_str(), office.id.as_str()])
+            .await
+            .expect("delete both"),
+        2
+    );
+    assert_eq!(vaults.get_secret(&office.id).expect("gone"), None);
+
+    let _ = std::fs::remove_dir_all(dir);
+}
@@ -0,0 +1,5 @@
+-- strict_host_key is kept (and still written) so older builds can read the table.
+ALTER TABLE ssh_configs ADD COLUMN host_key_mode TEXT NOT NULL DEFAULT 'tofu';
+
+UPDATE ssh_configs
+SET host_key_mode = CASE WHEN strict_host_key = 0 THEN 'off' ELSE 'tofu' END;
@@ -0,0 +1 @@
+ALTER TABLE ssh_configs ADD COLUMN cert_path TEXT NULL;
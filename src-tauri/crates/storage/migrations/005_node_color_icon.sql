@@ -0,0 +1,2 @@
+ALTER TABLE nodes ADD COLUMN color TEXT NULL;
+ALTER TABLE nodes ADD COLUMN icon TEXT NULL;
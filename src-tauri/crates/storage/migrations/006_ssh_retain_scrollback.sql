@@ -0,0 +1 @@
+ALTER TABLE ssh_configs ADD COLUMN retain_scrollback INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,2 @@
+ALTER TABLE ssh_configs ADD COLUMN default_cols INTEGER;
+ALTER TABLE ssh_configs ADD COLUMN default_rows INTEGER;
@@ -0,0 +1 @@
+ALTER TABLE ssh_configs ADD COLUMN proxy_command TEXT;
@@ -0,0 +1,2 @@
+ALTER TABLE ssh_configs ADD COLUMN remember_sftp_path INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE ssh_configs ADD COLUMN last_sftp_path TEXT;
@@ -0,0 +1,2 @@
+ALTER TABLE rdp_configs ADD COLUMN connect_timeout_secs INTEGER;
+ALTER TABLE rdp_configs ADD COLUMN connect_retries INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1 @@
+ALTER TABLE rdp_configs ADD COLUMN redirect_smartcards INTEGER NOT NULL DEFAULT 0;
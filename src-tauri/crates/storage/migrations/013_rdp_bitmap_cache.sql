@@ -0,0 +1 @@
+ALTER TABLE rdp_configs ADD COLUMN bitmap_cache INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,5 @@
+ALTER TABLE rdp_configs ADD COLUMN wallpaper INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE rdp_configs ADD COLUMN font_smoothing INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE rdp_configs ADD COLUMN desktop_composition INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE rdp_configs ADD COLUMN menu_animations INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE rdp_configs ADD COLUMN themes INTEGER NOT NULL DEFAULT 1;
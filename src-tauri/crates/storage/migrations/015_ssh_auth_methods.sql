@@ -0,0 +1 @@
+ALTER TABLE ssh_configs ADD COLUMN auth_methods TEXT NOT NULL DEFAULT 'publickey,password,none';
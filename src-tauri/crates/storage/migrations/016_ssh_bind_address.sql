@@ -0,0 +1 @@
+ALTER TABLE ssh_configs ADD COLUMN bind_address TEXT;
@@ -0,0 +1 @@
+ALTER TABLE ssh_known_hosts ADD COLUMN weak_advisory TEXT;
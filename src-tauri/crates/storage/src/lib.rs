@@ -1,13 +1,16 @@
 use std::collections::HashMap;
+use std::future::Future;
+use std::net::IpAddr;
 use std::path::Path;
 
 use anyhow::{anyhow, Context, Result};
 use janus_domain::{
-    ConnectionNode, ConnectionUpsert, FolderUpsert, NodeKind, NodeMoveRequest, RdpConfig,
-    SshConfig,
+    ConnectionNode, ConnectionUpsert, FolderUpsert, HostKeyMode, NodeKind, NodeMoveRequest,
+    RdpConfig, RdpExperience, Snippet, SshAuthMethod, SshConfig,
 };
-use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
-use sqlx::{Row, SqlitePool};
+use sqlx::migrate::Migrator;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions, SqliteRow};
+use sqlx::{Row, SqliteConnection, SqlitePool};
 
 #[derive(Debug, Default, Clone)]
 pub struct ResolvedSecretRefs {
@@ -16,17 +19,176 @@ pub struct ResolvedSecretRefs {
     pub rdp_password_ref: Option<String>,
 }
 
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SubtreeDeleteReport {
+    pub nodes_removed: usize,
+    /// As counted by `delete_subtree`'s `delete_secrets`.
+    pub secrets_removed: usize,
+}
+
+/// A saved SSH or RDP password and the connection that references it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PasswordRef {
+    pub node_id: String,
+    pub secret_ref: String,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SshKnownHost {
     pub host: String,
     pub port: i64,
     pub key_type: String,
     pub public_key: String,
+    /// Why the pinned key is weak or deprecated, when it is.
+    pub weak_advisory: Option<String>,
     pub created_at: String,
     pub updated_at: String,
     pub last_seen_at: String,
 }
 
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+/// Highest schema version this build can migrate a database to.
+pub fn supported_schema_version() -> i64 {
+    MIGRATOR
+        .iter()
+        .map(|migration| migration.version)
+        .max()
+        .unwrap_or(0)
+}
+
+async fn read_schema_version(pool: &SqlitePool) -> Result<i64> {
+    let has_migrations_table = sqlx::query(
+        "SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = '_sqlx_migrations'",
+    )
+    .fetch_optional(pool)
+    .await
+    .context("checking for migrations table")?
+    .is_some();
+    if !has_migrations_table {
+        return Ok(0);
+    }
+
+    let row = sqlx::query("SELECT MAX(version) AS version FROM _sqlx_migrations WHERE success = 1")
+        .fetch_one(pool)
+        .await
+        .context("reading schema version")?;
+    Ok(row.try_get::<Option<i64>, _>("version")?.unwrap_or(0))
+}
+
+/// Checks that `request` is a legal move: the node exists, the destination is a folder
+/// and a folder isn't moved into itself or a descendant. Returns the node's current parent.
+async fn validate_move(
+    conn: &mut SqliteConnection,
+    request: &NodeMoveRequest,
+) -> Result<Option<String>> {
+    let rows = sqlx::query("SELECT id, parent_id, kind FROM nodes")
+        .fetch_all(&mut *conn)
+        .await
+        .context("loading nodes for move validation")?;
+
+    let mut parents = HashMap::<String, Option<String>>::with_capacity(rows.len());
+    let mut kinds = HashMap::<String, NodeKind>::with_capacity(rows.len());
+    for row in rows {
+        let id: String = row.try_get("id")?;
+        let kind_raw: String = row.try_get("kind")?;
+        let kind = NodeKind::from_db_str(&kind_raw)
+            .ok_or_else(|| anyhow!("invalid node kind in db: {kind_raw}"))?;
+        let parent_id: Option<String> = row.try_get("parent_id")?;
+        parents.insert(id.clone(), parent_id);
+        kinds.insert(id, kind);
+    }
+
+    let Some(old_parent_id) = parents.get(&request.node_id).cloned() else {
+        return Err(anyhow!("node not found"));
+    };
+    let Some(moving_kind) = kinds.get(&request.node_id).copied() else {
+        return Err(anyhow!("node kind not found"));
+    };
+
+    if let Some(new_parent_id) = request.new_parent_id.as_deref() {
+        if new_parent_id == request.node_id {
+            return Err(anyhow!("cannot move a node into itself"));
+        }
+
+        let Some(parent_kind) = kinds.get(new_parent_id).copied() else {
+            return Err(anyhow!("destination folder not found"));
+        };
+        if parent_kind != NodeKind::Folder {
+            return Err(anyhow!("destination parent must be a folder"));
+        }
+    }
+
+    if moving_kind == NodeKind::Folder {
+        let mut cursor = request.new_parent_id.clone();
+        while let Some(parent_id) = cursor {
+            if parent_id == request.node_id {
+                return Err(anyhow!("cannot move a folder into its descendant"));
+            }
+            cursor = parents.get(&parent_id).cloned().flatten();
+        }
+    }
+
+    Ok(old_parent_id)
+}
+
+/// Accepts `#rrggbb` or `rrggbb` and stores it as lowercase `#rrggbb`.
+fn normalize_color(color: Option<&str>) -> Result<Option<String>> {
+    let Some(color) = color else {
+        return Ok(None);
+    };
+    let hex = color.strip_prefix('#').unwrap_or(color);
+    if hex.len() != 6 || !hex.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return Err(anyhow!(
+            "invalid color {color:?}: expected a 6-digit hex value like #1e90ff"
+        ));
+    }
+    Ok(Some(format!("#{}", hex.to_ascii_lowercase())))
+}
+
+/// Stores the methods comma-separated, e.g. `publickey,password`.
+fn auth_methods_to_db(methods: &[SshAuthMethod]) -> Result<String> {
+    if methods.is_empty() {
+        return Err(anyhow!(
+            "at least one SSH authentication method is required"
+        ));
+    }
+    for (index, method) in methods.iter().enumerate() {
+        if methods[..index].contains(method) {
+            return Err(anyhow!(
+                "SSH authentication method {} is listed twice",
+                method.as_db_str()
+            ));
+        }
+    }
+    Ok(methods
+        .iter()
+        .map(SshAuthMethod::as_db_str)
+        .collect::<Vec<_>>()
+        .join(","))
+}
+
+/// Blank means no bind address; anything else must be an IP address.
+fn bind_address_to_db(value: Option<&str>) -> Result<Option<String>> {
+    let Some(value) = value.map(str::trim).filter(|value| !value.is_empty()) else {
+        return Ok(None);
+    };
+    let address: IpAddr = value
+        .parse()
+        .map_err(|_| anyhow!("SSH bind address {value} is not an IP address"))?;
+    Ok(Some(address.to_string()))
+}
+
+fn auth_methods_from_db(value: &str) -> Result<Vec<SshAuthMethod>> {
+    value
+        .split(',')
+        .map(|method| {
+            SshAuthMethod::from_db_str(method)
+                .ok_or_else(|| anyhow!("invalid ssh auth method in db: {method}"))
+        })
+        .collect()
+}
+
 #[derive(Clone)]
 pub struct Storage {
     pool: SqlitePool,
@@ -51,7 +213,19 @@ impl Storage {
             .await
             .context("connecting to sqlite")?;
 
-        sqlx::migrate!("./migrations")
+        // sqlx refuses to run when the database has migrations it doesn't know about,
+        // which is what a downgrade looks like; report that in plain terms instead.
+        let db_version = read_schema_version(&pool).await?;
+        let supported_version = supported_schema_version();
+        if db_version > supported_version {
+            return Err(anyhow!(
+                "this database was created by a newer version of Janus (schema version \
+                 {db_version}, this version supports up to {supported_version}); update Janus \
+                 to open it"
+            ));
+        }
+
+        MIGRATOR
             .run(&pool)
             .await
             .context("running sqlite migrations")?;
@@ -59,9 +233,14 @@ impl Storage {
         Ok(Self { pool })
     }
 
+    /// Highest migration version applied to this database.
+    pub async fn schema_version(&self) -> Result<i64> {
+        read_schema_version(&self.pool).await
+    }
+
     pub async fn list_tree(&self) -> Result<Vec<ConnectionNode>> {
         let rows = sqlx::query(
-            "SELECT id, parent_id, kind, name, order_index
+            "SELECT id, parent_id, kind, name, order_index, color, icon
              FROM nodes
              ORDER BY COALESCE(parent_id, ''), order_index, name",
         )
@@ -69,42 +248,84 @@ impl Storage {
         .await
         .context("listing nodes")?;
 
-        let mut nodes = Vec::with_capacity(rows.len());
-        for row in rows {
-            let id: String = row.try_get("id")?;
-            let kind_raw: String = row.try_get("kind")?;
-            let kind = NodeKind::from_db_str(&kind_raw)
-                .ok_or_else(|| anyhow!("invalid node kind in db: {kind_raw}"))?;
-
-            let ssh = if kind == NodeKind::Ssh {
-                Some(self.get_ssh_config(&id).await?)
-            } else {
-                None
-            };
-
-            let rdp = if kind == NodeKind::Rdp {
-                Some(self.get_rdp_config(&id).await?)
-            } else {
-                None
-            };
-
-            nodes.push(ConnectionNode {
-                id,
-                parent_id: row.try_get("parent_id")?,
-                kind,
-                name: row.try_get("name")?,
-                order_index: row.try_get("order_index")?,
-                ssh,
-                rdp,
-            });
-        }
+        let configs = self.node_configs(None).await?;
+        nodes_from_rows(rows, configs)
+    }
+
+    /// Lists `node_id` and all of its descendants, ordered like `list_tree`.
+    ///
+    /// Empty if the node doesn't exist.
+    pub async fn get_subtree(&self, node_id: &str) -> Result<Vec<ConnectionNode>> {
+        let rows = sqlx::query(
+            "WITH RECURSIVE subtree(id) AS (
+                 SELECT id FROM nodes WHERE id = ?1
+                 UNION
+                 SELECT nodes.id FROM nodes JOIN subtree ON nodes.parent_id = subtree.id
+             )
+             SELECT nodes.id, nodes.parent_id, nodes.kind, nodes.name, nodes.order_index,
+                    nodes.color, nodes.icon
+             FROM nodes JOIN subtree ON nodes.id = subtree.id
+             ORDER BY COALESCE(nodes.parent_id, ''), nodes.order_index, nodes.name",
+        )
+        .bind(node_id)
+        .fetch_all(&self.pool)
+        .await
+        .context("listing subtree")?;
 
-        Ok(nodes)
+        let configs = self.node_configs(Some(node_id)).await?;
+        nodes_from_rows(rows, configs)
+    }
+
+    /// Loads the SSH and RDP configs of every node, or with `subtree_of` of that node and
+    /// its descendants, in one query per table rather than one per node.
+    async fn node_configs(&self, subtree_of: Option<&str>) -> Result<NodeConfigs> {
+        let ssh_rows = sqlx::query(
+            "WITH RECURSIVE subtree(id) AS (
+                 SELECT id FROM nodes WHERE id = ?1
+                 UNION
+                 SELECT nodes.id FROM nodes JOIN subtree ON nodes.parent_id = subtree.id
+             )
+             SELECT node_id, host, port, username, host_key_mode, key_path, cert_path, retain_scrollback, default_cols, default_rows, proxy_command, bind_address, remember_sftp_path, last_sftp_path, auth_methods, auth_ref, key_passphrase_ref
+             FROM ssh_configs
+             WHERE ?1 IS NULL OR node_id IN (SELECT id FROM subtree)",
+        )
+        .bind(subtree_of)
+        .fetch_all(&self.pool)
+        .await
+        .context("listing ssh configs")?;
+
+        let rdp_rows = sqlx::query(
+            "WITH RECURSIVE subtree(id) AS (
+                 SELECT id FROM nodes WHERE id = ?1
+                 UNION
+                 SELECT nodes.id FROM nodes JOIN subtree ON nodes.parent_id = subtree.id
+             )
+             SELECT node_id, host, port, username, domain, screen_mode, width, height, connect_timeout_secs, connect_retries, redirect_smartcards, bitmap_cache, wallpaper, font_smoothing, desktop_composition, menu_animations, themes, credential_ref
+             FROM rdp_configs
+             WHERE ?1 IS NULL OR node_id IN (SELECT id FROM subtree)",
+        )
+        .bind(subtree_of)
+        .fetch_all(&self.pool)
+        .await
+        .context("listing rdp configs")?;
+
+        let mut configs = NodeConfigs::default();
+        for row in &ssh_rows {
+            configs
+                .ssh
+                .insert(row.try_get("node_id")?, ssh_config_from_row(row)?);
+        }
+        for row in &rdp_rows {
+            configs
+                .rdp
+                .insert(row.try_get("node_id")?, rdp_config_from_row(row)?);
+        }
+        Ok(configs)
     }
 
     pub async fn get_node(&self, node_id: &str) -> Result<Option<ConnectionNode>> {
         let row = sqlx::query(
-            "SELECT id, parent_id, kind, name, order_index
+            "SELECT id, parent_id, kind, name, order_index, color, icon
              FROM nodes WHERE id = ?1",
         )
         .bind(node_id)
@@ -138,26 +359,34 @@ impl Storage {
             kind,
             name: row.try_get("name")?,
             order_index: row.try_get("order_index")?,
+            color: row.try_get("color")?,
+            icon: row.try_get("icon")?,
             ssh,
             rdp,
         }))
     }
 
     pub async fn upsert_folder(&self, folder: &FolderUpsert) -> Result<()> {
+        let color = normalize_color(folder.color.as_deref())?;
+
         sqlx::query(
-            "INSERT INTO nodes (id, parent_id, kind, name, order_index, created_at, updated_at)
-             VALUES (?1, ?2, 'folder', ?3, ?4, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)
+            "INSERT INTO nodes (id, parent_id, kind, name, order_index, color, icon, created_at, updated_at)
+             VALUES (?1, ?2, 'folder', ?3, ?4, ?5, ?6, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)
              ON CONFLICT(id) DO UPDATE
              SET parent_id = excluded.parent_id,
                  kind = 'folder',
                  name = excluded.name,
                  order_index = excluded.order_index,
+                 color = excluded.color,
+                 icon = excluded.icon,
                  updated_at = CURRENT_TIMESTAMP",
         )
         .bind(&folder.id)
         .bind(&folder.parent_id)
         .bind(&folder.name)
         .bind(folder.order_index)
+        .bind(color)
+        .bind(&folder.icon)
         .execute(&self.pool)
         .await
         .context("upserting folder node")?;
@@ -173,17 +402,20 @@ impl Storage {
         if connection.kind == NodeKind::Folder {
             return Err(anyhow!("connection upsert cannot use folder kind"));
         }
+        let color = normalize_color(connection.color.as_deref())?;
 
         let mut tx = self.pool.begin().await.context("opening transaction")?;
 
         sqlx::query(
-            "INSERT INTO nodes (id, parent_id, kind, name, order_index, created_at, updated_at)
-             VALUES (?1, ?2, ?3, ?4, ?5, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)
+            "INSERT INTO nodes (id, parent_id, kind, name, order_index, color, icon, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)
              ON CONFLICT(id) DO UPDATE
              SET parent_id = excluded.parent_id,
                  kind = excluded.kind,
                  name = excluded.name,
                  order_index = excluded.order_index,
+                 color = excluded.color,
+                 icon = excluded.icon,
                  updated_at = CURRENT_TIMESTAMP",
         )
         .bind(&connection.id)
@@ -191,6 +423,8 @@ impl Storage {
         .bind(connection.kind.as_db_str())
         .bind(&connection.name)
         .bind(connection.order_index)
+        .bind(color)
+        .bind(&connection.icon)
         .execute(&mut *tx)
         .await
         .context("upserting connection node")?;
@@ -200,6 +434,8 @@ impl Storage {
                 let Some(ssh) = connection.ssh.as_ref() else {
                     return Err(anyhow!("missing ssh payload"));
                 };
+                let auth_methods = auth_methods_to_db(&ssh.auth_methods)?;
+                let bind_address = bind_address_to_db(ssh.bind_address.as_deref())?;
 
                 sqlx::query("DELETE FROM rdp_configs WHERE node_id = ?1")
                     .bind(&connection.id)
@@ -208,14 +444,24 @@ impl Storage {
                     .context("clearing stale rdp config")?;
 
                 sqlx::query(
-                    "INSERT INTO ssh_configs (node_id, host, port, username, strict_host_key, key_path, auth_ref, key_passphrase_ref)
-                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+                    "INSERT INTO ssh_configs (node_id, host, port, username, strict_host_key, key_path, auth_ref, key_passphrase_ref, host_key_mode, cert_path, retain_scrollback, default_cols, default_rows, proxy_command, remember_sftp_path, auth_methods, bind_address)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17)
                      ON CONFLICT(node_id) DO UPDATE
                      SET host = excluded.host,
                          port = excluded.port,
                          username = excluded.username,
                          strict_host_key = excluded.strict_host_key,
+                         host_key_mode = excluded.host_key_mode,
                          key_path = excluded.key_path,
+                         cert_path = excluded.cert_path,
+                         retain_scrollback = excluded.retain_scrollback,
+                         default_cols = excluded.default_cols,
+                         default_rows = excluded.default_rows,
+                         proxy_command = excluded.proxy_command,
+                         remember_sftp_path = excluded.remember_sftp_path,
+                         last_sftp_path = CASE WHEN excluded.remember_sftp_path THEN ssh_configs.last_sftp_path END,
+                         auth_methods = excluded.auth_methods,
+                         bind_address = excluded.bind_address,
                          auth_ref = COALESCE(excluded.auth_ref, ssh_configs.auth_ref),
                          key_passphrase_ref = COALESCE(excluded.key_passphrase_ref, ssh_configs.key_passphrase_ref)",
                 )
@@ -223,10 +469,23 @@ impl Storage {
                 .bind(&ssh.host)
                 .bind(ssh.port)
                 .bind(&ssh.username)
-                .bind(if ssh.strict_host_key { 1_i64 } else { 0_i64 })
+                .bind(if ssh.host_key_mode == HostKeyMode::Off {
+                    0_i64
+                } else {
+                    1_i64
+                })
                 .bind(&ssh.key_path)
                 .bind(&refs.ssh_password_ref)
                 .bind(&refs.ssh_key_passphrase_ref)
+                .bind(ssh.host_key_mode.as_db_str())
+                .bind(&ssh.cert_path)
+                .bind(ssh.retain_scrollback)
+                .bind(ssh.default_cols)
+                .bind(ssh.default_rows)
+                .bind(&ssh.proxy_command)
+                .bind(ssh.remember_sftp_path)
+                .bind(auth_methods)
+                .bind(bind_address)
                 .execute(&mut *tx)
                 .await
                 .context("upserting ssh config")?;
@@ -245,8 +504,8 @@ impl Storage {
                 let credential_ref = refs.rdp_password_ref.clone();
 
                 sqlx::query(
-                    "INSERT INTO rdp_configs (node_id, host, port, username, domain, screen_mode, width, height, credential_ref)
-                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
+                    "INSERT INTO rdp_configs (node_id, host, port, username, domain, screen_mode, width, height, credential_ref, connect_timeout_secs, connect_retries, redirect_smartcards, bitmap_cache, wallpaper, font_smoothing, desktop_composition, menu_animations, themes)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18)
                      ON CONFLICT(node_id) DO UPDATE
                      SET host = excluded.host,
                          port = excluded.port,
@@ -255,6 +514,15 @@ impl Storage {
                          screen_mode = excluded.screen_mode,
                          width = excluded.width,
                          height = excluded.height,
+                         connect_timeout_secs = excluded.connect_timeout_secs,
+                         connect_retries = excluded.connect_retries,
+                         redirect_smartcards = excluded.redirect_smartcards,
+                         bitmap_cache = excluded.bitmap_cache,
+                         wallpaper = excluded.wallpaper,
+                         font_smoothing = excluded.font_smoothing,
+                         desktop_composition = excluded.desktop_composition,
+                         menu_animations = excluded.menu_animations,
+                         themes = excluded.themes,
                          credential_ref = COALESCE(excluded.credential_ref, rdp_configs.credential_ref)",
                 )
                 .bind(&connection.id)
@@ -266,6 +534,15 @@ impl Storage {
                 .bind(rdp.width)
                 .bind(rdp.height)
                 .bind(credential_ref)
+                .bind(rdp.connect_timeout_secs)
+                .bind(rdp.connect_retries)
+                .bind(rdp.redirect_smartcards)
+                .bind(rdp.bitmap_cache)
+                .bind(rdp.experience.wallpaper)
+                .bind(rdp.experience.font_smoothing)
+                .bind(rdp.experience.desktop_composition)
+                .bind(rdp.experience.menu_animations)
+                .bind(rdp.experience.themes)
                 .execute(&mut *tx)
                 .await
                 .context("upserting rdp config")?;
@@ -277,6 +554,17 @@ impl Storage {
         Ok(())
     }
 
+    /// Runs the validation of [`Storage::move_node`] without moving anything, so the UI
+    /// can tell whether a drop is allowed before making it.
+    pub async fn can_move(&self, request: &NodeMoveRequest) -> Result<()> {
+        let mut conn = self
+            .pool
+            .acquire()
+            .await
+            .context("acquiring connection for move validation")?;
+        validate_move(&mut conn, request).await.map(|_| ())
+    }
+
     pub async fn move_node(&self, request: &NodeMoveRequest) -> Result<()> {
         let mut tx = self
             .pool
@@ -284,52 +572,7 @@ impl Storage {
             .await
             .context("opening node move transaction")?;
 
-        let rows = sqlx::query("SELECT id, parent_id, kind FROM nodes")
-            .fetch_all(&mut *tx)
-            .await
-            .context("loading nodes for move validation")?;
-
-        let mut parents = HashMap::<String, Option<String>>::with_capacity(rows.len());
-        let mut kinds = HashMap::<String, NodeKind>::with_capacity(rows.len());
-        for row in rows {
-            let id: String = row.try_get("id")?;
-            let kind_raw: String = row.try_get("kind")?;
-            let kind = NodeKind::from_db_str(&kind_raw)
-                .ok_or_else(|| anyhow!("invalid node kind in db: {kind_raw}"))?;
-            let parent_id: Option<String> = row.try_get("parent_id")?;
-            parents.insert(id.clone(), parent_id);
-            kinds.insert(id, kind);
-        }
-
-        let Some(old_parent_id) = parents.get(&request.node_id).cloned() else {
-            return Err(anyhow!("node not found"));
-        };
-        let Some(moving_kind) = kinds.get(&request.node_id).copied() else {
-            return Err(anyhow!("node kind not found"));
-        };
-
-        if let Some(new_parent_id) = request.new_parent_id.as_deref() {
-            if new_parent_id == request.node_id {
-                return Err(anyhow!("cannot move a node into itself"));
-            }
-
-            let Some(parent_kind) = kinds.get(new_parent_id).copied() else {
-                return Err(anyhow!("destination folder not found"));
-            };
-            if parent_kind != NodeKind::Folder {
-                return Err(anyhow!("destination parent must be a folder"));
-            }
-        }
-
-        if moving_kind == NodeKind::Folder {
-            let mut cursor = request.new_parent_id.clone();
-            while let Some(parent_id) = cursor {
-                if parent_id == request.node_id {
-                    return Err(anyhow!("cannot move a folder into its descendant"));
-                }
-                cursor = parents.get(&parent_id).cloned().flatten();
-            }
-        }
+        let old_parent_id = validate_move(&mut tx, request).await?;
 
         let requested_index = request.new_index.max(0) as usize;
         let same_parent = old_parent_id == request.new_parent_id;
@@ -378,7 +621,9 @@ impl Storage {
                 .context("renumbering sibling order after move")?;
             }
 
-            tx.commit().await.context("committing node move transaction")?;
+            tx.commit()
+                .await
+                .context("committing node move transaction")?;
             return Ok(());
         }
 
@@ -435,10 +680,64 @@ impl Storage {
             .context("renumbering new sibling order after move")?;
         }
 
-        tx.commit().await.context("committing node move transaction")?;
+        tx.commit()
+            .await
+            .context("committing node move transaction")?;
         Ok(())
     }
 
+    /// Renumbers every folder's children, and the root's, to `0..n` in their current
+    /// display order, in one transaction. Returns how many nodes changed index.
+    pub async fn normalize_order(&self) -> Result<usize> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .context("starting order normalization transaction")?;
+
+        let rows = sqlx::query(
+            "SELECT id, parent_id, order_index
+             FROM nodes
+             ORDER BY COALESCE(parent_id, ''), order_index, name COLLATE NOCASE, id",
+        )
+        .fetch_all(&mut *tx)
+        .await
+        .context("loading nodes for order normalization")?;
+
+        let mut next_index = HashMap::<Option<String>, i64>::new();
+        let mut renumbered = 0;
+        for row in rows {
+            let id: String = row.try_get("id")?;
+            let parent_id: Option<String> = row.try_get("parent_id")?;
+            let order_index: i64 = row.try_get("order_index")?;
+
+            let slot = next_index.entry(parent_id).or_insert(0);
+            let normalized = *slot;
+            *slot += 1;
+            if order_index == normalized {
+                continue;
+            }
+
+            sqlx::query(
+                "UPDATE nodes
+                 SET order_index = ?1,
+                     updated_at = CURRENT_TIMESTAMP
+                 WHERE id = ?2",
+            )
+            .bind(normalized)
+            .bind(&id)
+            .execute(&mut *tx)
+            .await
+            .context("renumbering node order")?;
+            renumbered += 1;
+        }
+
+        tx.commit()
+            .await
+            .context("committing order normalization transaction")?;
+        Ok(renumbered)
+    }
+
     pub async fn delete_node(&self, node_id: &str) -> Result<()> {
         sqlx::query("DELETE FROM nodes WHERE id = ?1")
             .bind(node_id)
@@ -448,9 +747,86 @@ impl Storage {
         Ok(())
     }
 
+    /// Deletes a node and all of its descendants, along with the vault secrets only they
+    /// referenced.
+    ///
+    /// `delete_secrets` gets those secret ids and runs before the rows are committed, so
+    /// when it fails (a locked vault, say) nothing is deleted and no node is left without
+    /// its secrets. Secrets that nodes outside the subtree still use are not passed.
+    pub async fn delete_subtree<F, Fut>(
+        &self,
+        node_id: &str,
+        delete_secrets: F,
+    ) -> Result<SubtreeDeleteReport>
+    where
+        F: FnOnce(Vec<String>) -> Fut,
+        Fut: Future<Output = Result<usize>>,
+    {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .context("starting subtree delete transaction")?;
+
+        const SUBTREE: &str = "WITH RECURSIVE subtree(id) AS (
+                 SELECT id FROM nodes WHERE id = ?1
+                 UNION
+                 SELECT nodes.id FROM nodes JOIN subtree ON nodes.parent_id = subtree.id
+             )";
+        let nodes_removed: i64 =
+            sqlx::query_scalar(&format!("{SUBTREE} SELECT COUNT(*) FROM subtree"))
+                .bind(node_id)
+                .fetch_one(&mut *tx)
+                .await
+                .context("counting subtree")?;
+
+        let secret_refs: Vec<String> = sqlx::query_scalar(&format!(
+            "{SUBTREE},
+             refs(node_id, secret_ref) AS (
+                 SELECT node_id, auth_ref FROM ssh_configs
+                 UNION ALL
+                 SELECT node_id, key_passphrase_ref FROM ssh_configs
+                 UNION ALL
+                 SELECT node_id, credential_ref FROM rdp_configs
+             )
+             SELECT secret_ref FROM refs
+             WHERE secret_ref IS NOT NULL AND node_id IN (SELECT id FROM subtree)
+             EXCEPT
+             SELECT secret_ref FROM refs
+             WHERE secret_ref IS NOT NULL AND node_id NOT IN (SELECT id FROM subtree)
+             ORDER BY 1"
+        ))
+        .bind(node_id)
+        .fetch_all(&mut *tx)
+        .await
+        .context("collecting subtree secret refs")?;
+
+        let secrets_removed = if secret_refs.is_empty() {
+            0
+        } else {
+            delete_secrets(secret_refs).await?
+        };
+
+        sqlx::query("DELETE FROM nodes WHERE id = ?1")
+            .bind(node_id)
+            .execute(&mut *tx)
+            .await
+            .context("deleting subtree")?;
+
+        tx.commit()
+            .await
+            .context("committing subtree delete transaction")?;
+
+        Ok(SubtreeDeleteReport {
+            nodes_removed: nodes_removed as usize,
+            secrets_removed,
+        })
+    }
+
     pub async fn get_ssh_known_host(&self, host: &str, port: i64) -> Result<Option<SshKnownHost>> {
         let row = sqlx::query(
-            "SELECT host, port, key_type, public_key, created_at, updated_at, last_seen_at
+            "SELECT host, port, key_type, public_key, weak_advisory, created_at, updated_at,
+                    last_seen_at
              FROM ssh_known_hosts
              WHERE host = ?1 AND port = ?2",
         )
@@ -469,6 +845,7 @@ impl Storage {
             port: row.try_get("port")?,
             key_type: row.try_get("key_type")?,
             public_key: row.try_get("public_key")?,
+            weak_advisory: row.try_get("weak_advisory")?,
             created_at: row.try_get("created_at")?,
             updated_at: row.try_get("updated_at")?,
             last_seen_at: row.try_get("last_seen_at")?,
@@ -481,13 +858,27 @@ impl Storage {
         port: i64,
         key_type: &str,
         public_key: &str,
+    ) -> Result<()> {
+        self.upsert_ssh_known_host_with_advisory(host, port, key_type, public_key, None)
+            .await
+    }
+
+    /// Pins a host key along with why it is weak or deprecated, if it is.
+    pub async fn upsert_ssh_known_host_with_advisory(
+        &self,
+        host: &str,
+        port: i64,
+        key_type: &str,
+        public_key: &str,
+        weak_advisory: Option<&str>,
     ) -> Result<()> {
         sqlx::query(
-            "INSERT INTO ssh_known_hosts (host, port, key_type, public_key, created_at, updated_at, last_seen_at)
-             VALUES (?1, ?2, ?3, ?4, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)
+            "INSERT INTO ssh_known_hosts (host, port, key_type, public_key, weak_advisory, created_at, updated_at, last_seen_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)
              ON CONFLICT(host, port) DO UPDATE
              SET key_type = excluded.key_type,
                  public_key = excluded.public_key,
+                 weak_advisory = excluded.weak_advisory,
                  updated_at = CURRENT_TIMESTAMP,
                  last_seen_at = CURRENT_TIMESTAMP",
         )
@@ -495,6 +886,7 @@ impl Storage {
         .bind(port)
         .bind(key_type)
         .bind(public_key)
+        .bind(weak_advisory)
         .execute(&self.pool)
         .await
         .context("upserting ssh known host")?;
@@ -517,13 +909,150 @@ impl Storage {
         Ok(())
     }
 
-    fn parse_bool(value: i64) -> bool {
-        value != 0
+    /// Records the directory an SFTP session on `node_id` last listed, unless the
+    /// connection opted out of resuming there.
+    pub async fn record_last_sftp_path(&self, node_id: &str, path: &str) -> Result<()> {
+        sqlx::query(
+            "UPDATE ssh_configs
+             SET last_sftp_path = ?2
+             WHERE node_id = ?1 AND remember_sftp_path = 1",
+        )
+        .bind(node_id)
+        .bind(path)
+        .execute(&self.pool)
+        .await
+        .context("recording last sftp path")?;
+
+        Ok(())
+    }
+
+    /// Lists the password secrets SSH and RDP connections reference, by node id.
+    pub async fn password_refs(&self) -> Result<Vec<PasswordRef>> {
+        let rows = sqlx::query(
+            "SELECT node_id, auth_ref AS secret_ref FROM ssh_configs WHERE auth_ref IS NOT NULL
+             UNION ALL
+             SELECT node_id, credential_ref FROM rdp_configs WHERE credential_ref IS NOT NULL
+             ORDER BY node_id",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("listing password refs")?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(PasswordRef {
+                    node_id: row.try_get("node_id")?,
+                    secret_ref: row.try_get("secret_ref")?,
+                })
+            })
+            .collect()
+    }
+
+    /// Points each `(node_id, secret_ref)` connection's password at `secret_ref`, all in
+    /// one transaction. Returns how many connections were updated.
+    pub async fn set_password_refs(&self, refs: &[(String, String)]) -> Result<usize> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .context("starting password ref transaction")?;
+
+        let mut updated = 0;
+        for (node_id, secret_ref) in refs {
+            let ssh = sqlx::query("UPDATE ssh_configs SET auth_ref = ?2 WHERE node_id = ?1")
+                .bind(node_id)
+                .bind(secret_ref)
+                .execute(&mut *tx)
+                .await
+                .context("updating ssh password ref")?;
+            let rdp = sqlx::query("UPDATE rdp_configs SET credential_ref = ?2 WHERE node_id = ?1")
+                .bind(node_id)
+                .bind(secret_ref)
+                .execute(&mut *tx)
+                .await
+                .context("updating rdp password ref")?;
+            updated += (ssh.rows_affected() + rdp.rows_affected()) as usize;
+        }
+
+        tx.commit()
+            .await
+            .context("committing password ref transaction")?;
+        Ok(updated)
+    }
+
+    pub async fn list_snippets(&self) -> Result<Vec<Snippet>> {
+        let rows =
+            sqlx::query("SELECT id, name, body FROM snippets ORDER BY name COLLATE NOCASE, id")
+                .fetch_all(&self.pool)
+                .await
+                .context("listing snippets")?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(Snippet {
+                    id: row.try_get("id")?,
+                    name: row.try_get("name")?,
+                    body: row.try_get("body")?,
+                })
+            })
+            .collect()
+    }
+
+    pub async fn get_snippet(&self, snippet_id: &str) -> Result<Option<Snippet>> {
+        let row = sqlx::query("SELECT id, name, body FROM snippets WHERE id = ?1")
+            .bind(snippet_id)
+            .fetch_optional(&self.pool)
+            .await
+            .context("fetching snippet")?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+
+        Ok(Some(Snippet {
+            id: row.try_get("id")?,
+            name: row.try_get("name")?,
+            body: row.try_get("body")?,
+        }))
+    }
+
+    pub async fn upsert_snippet(&self, snippet: &Snippet) -> Result<()> {
+        let name = snippet.name.trim();
+        if name.is_empty() {
+            return Err(anyhow!("snippet name is required"));
+        }
+
+        sqlx::query(
+            "INSERT INTO snippets (id, name, body, created_at, updated_at)
+             VALUES (?1, ?2, ?3, CURRENT_TIMESTAMP, CURRENT_TIMESTAMP)
+             ON CONFLICT(id) DO UPDATE
+             SET name = excluded.name,
+                 body = excluded.body,
+                 updated_at = CURRENT_TIMESTAMP",
+        )
+        .bind(&snippet.id)
+        .bind(name)
+        .bind(&snippet.body)
+        .execute(&self.pool)
+        .await
+        .context("upserting snippet")?;
+
+        Ok(())
+    }
+
+    pub async fn delete_snippet(&self, snippet_id: &str) -> Result<()> {
+        sqlx::query("DELETE FROM snippets WHERE id = ?1")
+            .bind(snippet_id)
+            .execute(&self.pool)
+            .await
+            .context("deleting snippet")?;
+
+        Ok(())
     }
 
     async fn get_ssh_config(&self, node_id: &str) -> Result<SshConfig> {
         let row = sqlx::query(
-            "SELECT host, port, username, strict_host_key, key_path, auth_ref, key_passphrase_ref
+            "SELECT host, port, username, host_key_mode, key_path, cert_path, retain_scrollback, default_cols, default_rows, proxy_command, bind_address, remember_sftp_path, last_sftp_path, auth_methods, auth_ref, key_passphrase_ref
              FROM ssh_configs WHERE node_id = ?1",
         )
         .bind(node_id)
@@ -532,20 +1061,12 @@ impl Storage {
         .context("fetching ssh config")?
         .ok_or_else(|| anyhow!("missing ssh config for node {node_id}"))?;
 
-        Ok(SshConfig {
-            host: row.try_get("host")?,
-            port: row.try_get("port")?,
-            username: row.try_get("username")?,
-            strict_host_key: Self::parse_bool(row.try_get("strict_host_key")?),
-            key_path: row.try_get("key_path")?,
-            auth_ref: row.try_get("auth_ref")?,
-            key_passphrase_ref: row.try_get("key_passphrase_ref")?,
-        })
+        ssh_config_from_row(&row)
     }
 
     async fn get_rdp_config(&self, node_id: &str) -> Result<RdpConfig> {
         let row = sqlx::query(
-            "SELECT host, port, username, domain, screen_mode, width, height, credential_ref
+            "SELECT host, port, username, domain, screen_mode, width, height, connect_timeout_secs, connect_retries, redirect_smartcards, bitmap_cache, wallpaper, font_smoothing, desktop_composition, menu_animations, themes, credential_ref
              FROM rdp_configs WHERE node_id = ?1",
         )
         .bind(node_id)
@@ -554,15 +1075,100 @@ impl Storage {
         .context("fetching rdp config")?
         .ok_or_else(|| anyhow!("missing rdp config for node {node_id}"))?;
 
-        Ok(RdpConfig {
-            host: row.try_get("host")?,
-            port: row.try_get("port")?,
-            username: row.try_get("username")?,
-            domain: row.try_get("domain")?,
-            screen_mode: row.try_get("screen_mode")?,
-            width: row.try_get("width")?,
-            height: row.try_get("height")?,
-            credential_ref: row.try_get("credential_ref")?,
-        })
+        rdp_config_from_row(&row)
     }
 }
+
+/// SSH and RDP configs by node id, consumed as nodes are built.
+#[derive(Default)]
+struct NodeConfigs {
+    ssh: HashMap<String, SshConfig>,
+    rdp: HashMap<String, RdpConfig>,
+}
+
+fn nodes_from_rows(rows: Vec<SqliteRow>, mut configs: NodeConfigs) -> Result<Vec<ConnectionNode>> {
+    let mut nodes = Vec::with_capacity(rows.len());
+    for row in rows {
+        let id: String = row.try_get("id")?;
+        let kind_raw: String = row.try_get("kind")?;
+        let kind = NodeKind::from_db_str(&kind_raw)
+            .ok_or_else(|| anyhow!("invalid node kind in db: {kind_raw}"))?;
+
+        let ssh = if kind == NodeKind::Ssh {
+            let config = configs.ssh.remove(&id);
+            Some(config.ok_or_else(|| anyhow!("missing ssh config for node {id}"))?)
+        } else {
+            None
+        };
+
+        let rdp = if kind == NodeKind::Rdp {
+            let config = configs.rdp.remove(&id);
+            Some(config.ok_or_else(|| anyhow!("missing rdp config for node {id}"))?)
+        } else {
+            None
+        };
+
+        nodes.push(ConnectionNode {
+            id,
+            parent_id: row.try_get("parent_id")?,
+            kind,
+            name: row.try_get("name")?,
+            order_index: row.try_get("order_index")?,
+            color: row.try_get("color")?,
+            icon: row.try_get("icon")?,
+            ssh,
+            rdp,
+        });
+    }
+
+    Ok(nodes)
+}
+
+fn ssh_config_from_row(row: &SqliteRow) -> Result<SshConfig> {
+    let host_key_mode_raw: String = row.try_get("host_key_mode")?;
+    let host_key_mode = HostKeyMode::from_db_str(&host_key_mode_raw)
+        .ok_or_else(|| anyhow!("invalid host key mode in db: {host_key_mode_raw}"))?;
+
+    Ok(SshConfig {
+        host: row.try_get("host")?,
+        port: row.try_get("port")?,
+        username: row.try_get("username")?,
+        host_key_mode,
+        key_path: row.try_get("key_path")?,
+        cert_path: row.try_get("cert_path")?,
+        retain_scrollback: row.try_get("retain_scrollback")?,
+        default_cols: row.try_get("default_cols")?,
+        default_rows: row.try_get("default_rows")?,
+        proxy_command: row.try_get("proxy_command")?,
+        bind_address: row.try_get("bind_address")?,
+        remember_sftp_path: row.try_get("remember_sftp_path")?,
+        last_sftp_path: row.try_get("last_sftp_path")?,
+        auth_methods: auth_methods_from_db(row.try_get("auth_methods")?)?,
+        auth_ref: row.try_get("auth_ref")?,
+        key_passphrase_ref: row.try_get("key_passphrase_ref")?,
+    })
+}
+
+fn rdp_config_from_row(row: &SqliteRow) -> Result<RdpConfig> {
+    Ok(RdpConfig {
+        host: row.try_get("host")?,
+        port: row.try_get("port")?,
+        username: row.try_get("username")?,
+        domain: row.try_get("domain")?,
+        screen_mode: row.try_get("screen_mode")?,
+        width: row.try_get("width")?,
+        height: row.try_get("height")?,
+        connect_timeout_secs: row.try_get("connect_timeout_secs")?,
+        connect_retries: row.try_get("connect_retries")?,
+        redirect_smartcards: row.try_get("redirect_smartcards")?,
+        bitmap_cache: row.try_get("bitmap_cache")?,
+        experience: RdpExperience {
+            wallpaper: row.try_get("wallpaper")?,
+            font_smoothing: row.try_get("font_smoothing")?,
+            desktop_composition: row.try_get("desktop_composition")?,
+            menu_animations: row.try_get("menu_animations")?,
+            themes: row.try_get("themes")?,
+        },
+        credential_ref: row.try_get("credential_ref")?,
+    })
+}
@@ -1,5 +1,10 @@
-use janus_domain::{ConnectionUpsert, FolderUpsert, NodeKind, NodeMoveRequest, SshConfigInput};
-use janus_storage::{ResolvedSecretRefs, Storage};
+use janus_domain::{
+    ConnectionUpsert, FolderUpsert, HostKeyMode, NodeKind, NodeMoveRequest, RdpConfigInput,
+    RdpExperience, Snippet, SshAuthMethod, SshConfigInput,
+};
+use janus_storage::{
+    supported_schema_version, PasswordRef, ResolvedSecretRefs, Storage, SubtreeDeleteReport,
+};
 
 fn ssh_connection(
     id: &str,
@@ -13,12 +18,22 @@ fn ssh_connection(
         kind: NodeKind::Ssh,
         name: name.into(),
         order_index,
+        color: None,
+        icon: None,
         ssh: Some(SshConfigInput {
             host: "localhost".into(),
             port: 22,
             username: "user".into(),
-            strict_host_key: true,
+            host_key_mode: HostKeyMode::Tofu,
             key_path: None,
+            cert_path: None,
+            retain_scrollback: false,
+            default_cols: None,
+            default_rows: None,
+            proxy_command: None,
+            bind_address: None,
+            remember_sftp_path: true,
+            auth_methods: SshAuthMethod::DEFAULT_ORDER.to_vec(),
             password: None,
             key_passphrase: None,
         }),
@@ -37,6 +52,8 @@ async fn upserts_and_reads_tree() {
             parent_id: None,
             name: "Folder".into(),
             order_index: 1,
+            color: None,
+            icon: None,
         })
         .await
         .expect("folder upsert");
@@ -47,12 +64,22 @@ async fn upserts_and_reads_tree() {
         kind: NodeKind::Ssh,
         name: "SSH".into(),
         order_index: 2,
+        color: None,
+        icon: None,
         ssh: Some(SshConfigInput {
             host: "localhost".into(),
             port: 22,
             username: "user".into(),
-            strict_host_key: true,
+            host_key_mode: HostKeyMode::Tofu,
             key_path: None,
+            cert_path: None,
+            retain_scrollback: false,
+            default_cols: None,
+            default_rows: None,
+            proxy_command: None,
+            bind_address: None,
+            remember_sftp_path: true,
+            auth_methods: SshAuthMethod::DEFAULT_ORDER.to_vec(),
             password: None,
             key_passphrase: None,
         }),
@@ -70,6 +97,94 @@ async fn upserts_and_reads_tree() {
     let _ = std::fs::remove_file(db_path);
 }
 
+#[tokio::test]
+async fn persists_node_color_and_icon() {
+    let db_path = std::env::temp_dir().join(format!("janus-test-{}.sqlite", uuid::Uuid::new_v4()));
+    let storage = Storage::new(&db_path).await.expect("storage init");
+
+    let mut conn = ssh_connection("conn-1", None, "SSH", 0);
+    conn.color = Some("1E90FF".into());
+    conn.icon = Some("server".into());
+    storage
+        .upsert_connection(&conn, &ResolvedSecretRefs::default())
+        .await
+        .expect("connection upsert");
+
+    let tree = storage.list_tree().await.expect("list tree");
+    let listed = tree
+        .iter()
+        .find(|node| node.id == "conn-1")
+        .expect("listed");
+    assert_eq!(listed.color.as_deref(), Some("#1e90ff"));
+    assert_eq!(listed.icon.as_deref(), Some("server"));
+
+    let _ = std::fs::remove_file(db_path);
+}
+
+#[tokio::test]
+async fn persists_ssh_session_options() {
+    let db_path = std::env::temp_dir().join(format!("janus-test-{}.sqlite", uuid::Uuid::new_v4()));
+    let storage = Storage::new(&db_path).await.expect("storage init");
+
+    let mut conn = ssh_connection("conn-1", None, "SSH", 0);
+    let ssh = conn.ssh.as_mut().expect("ssh payload");
+    ssh.key_path = Some("/home/user/.ssh/id_ed25519".into());
+    ssh.cert_path = Some("/home/user/.ssh/id_ed25519-cert.pub".into());
+    ssh.retain_scrollback = true;
+    ssh.default_cols = Some(200);
+    ssh.default_rows = Some(50);
+    ssh.proxy_command = Some("cloudflared access ssh --hostname %h".into());
+    ssh.bind_address = Some(" 10.8.0.2 ".into());
+    storage
+        .upsert_connection(&conn, &ResolvedSecretRefs::default())
+        .await
+        .expect("connection upsert");
+
+    let node = storage
+        .get_node("conn-1")
+        .await
+        .expect("get node")
+        .expect("node exists");
+    let ssh = node.ssh.expect("ssh config");
+    assert_eq!(
+        ssh.cert_path.as_deref(),
+        Some("/home/user/.ssh/id_ed25519-cert.pub")
+    );
+    assert!(ssh.retain_scrollback);
+    assert_eq!((ssh.default_cols, ssh.default_rows), (Some(200), Some(50)));
+    assert_eq!(
+        ssh.proxy_command.as_deref(),
+        Some("cloudflared access ssh --hostname %h")
+    );
+    assert_eq!(ssh.bind_address.as_deref(), Some("10.8.0.2"));
+
+    let _ = std::fs::remove_file(db_path);
+}
+
+#[tokio::test]
+async fn rejects_invalid_node_colors() {
+    let db_path = std::env::temp_dir().join(format!("janus-test-{}.sqlite", uuid::Uuid::new_v4()));
+    let storage = Storage::new(&db_path).await.expect("storage init");
+
+    for color in ["red", "#12345", "#12345g", "#1234567"] {
+        let error = storage
+            .upsert_folder(&FolderUpsert {
+                id: "folder-1".into(),
+                parent_id: None,
+                name: "Folder".into(),
+                order_index: 0,
+                color: Some(color.into()),
+                icon: None,
+            })
+            .await
+            .expect_err("invalid color");
+        assert!(error.to_string().contains("invalid color"), "{error}");
+    }
+    assert!(storage.list_tree().await.expect("list tree").is_empty());
+
+    let _ = std::fs::remove_file(db_path);
+}
+
 #[tokio::test]
 async fn upserts_and_reads_ssh_known_hosts() {
     let db_path = std::env::temp_dir().join(format!("janus-test-{}.sqlite", uuid::Uuid::new_v4()));
@@ -123,6 +238,50 @@ async fn upserts_and_reads_ssh_known_hosts() {
     let _ = std::fs::remove_file(db_path);
 }
 
+#[tokio::test]
+async fn records_the_weak_advisory_of_a_pinned_host_key() {
+    let db_path = std::env::temp_dir().join(format!("janus-test-{}.sqlite", uuid::Uuid::new_v4()));
+    let storage = Storage::new(&db_path).await.expect("storage init");
+
+    storage
+        .upsert_ssh_known_host_with_advisory(
+            "legacy.example.com",
+            22,
+            "ssh-dss",
+            "ssh-dss AAAAB3NzaC1kc3MAAACBAMockKeyData",
+            Some("DSA host keys are deprecated"),
+        )
+        .await
+        .expect("upsert weak known host");
+    let weak = storage
+        .get_ssh_known_host("legacy.example.com", 22)
+        .await
+        .expect("read known host")
+        .expect("known host present");
+    assert_eq!(
+        weak.weak_advisory.as_deref(),
+        Some("DSA host keys are deprecated")
+    );
+
+    storage
+        .upsert_ssh_known_host(
+            "legacy.example.com",
+            22,
+            "ssh-ed25519",
+            "ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIMockKeyData",
+        )
+        .await
+        .expect("replace with a strong key");
+    let replaced = storage
+        .get_ssh_known_host("legacy.example.com", 22)
+        .await
+        .expect("read known host after replacement")
+        .expect("known host present");
+    assert_eq!(replaced.weak_advisory, None);
+
+    let _ = std::fs::remove_file(db_path);
+}
+
 #[tokio::test]
 async fn moves_nodes_across_parents_and_reorders() {
     let db_path = std::env::temp_dir().join(format!("janus-test-{}.sqlite", uuid::Uuid::new_v4()));
@@ -134,17 +293,25 @@ async fn moves_nodes_across_parents_and_reorders() {
             parent_id: None,
             name: "Folder A".into(),
             order_index: 0,
+            color: None,
+            icon: None,
         })
         .await
         .expect("folder a upsert");
 
     storage
-        .upsert_connection(&ssh_connection("conn-a", None, "Conn A", 1), &ResolvedSecretRefs::default())
+        .upsert_connection(
+            &ssh_connection("conn-a", None, "Conn A", 1),
+            &ResolvedSecretRefs::default(),
+        )
         .await
         .expect("conn a upsert");
 
     storage
-        .upsert_connection(&ssh_connection("conn-b", None, "Conn B", 2), &ResolvedSecretRefs::default())
+        .upsert_connection(
+            &ssh_connection("conn-b", None, "Conn B", 2),
+            &ResolvedSecretRefs::default(),
+        )
         .await
         .expect("conn b upsert");
 
@@ -189,7 +356,10 @@ async fn moves_nodes_across_parents_and_reorders() {
         .filter(|node| node.parent_id.is_none())
         .map(|node| (node.id.as_str(), node.order_index))
         .collect();
-    assert_eq!(root_ids, vec![("conn-c", 0), ("folder-a", 1), ("conn-b", 2)]);
+    assert_eq!(
+        root_ids,
+        vec![("conn-c", 0), ("folder-a", 1), ("conn-b", 2)]
+    );
 
     let folder_a_children: Vec<_> = tree
         .iter()
@@ -201,17 +371,77 @@ async fn moves_nodes_across_parents_and_reorders() {
     let _ = std::fs::remove_file(db_path);
 }
 
+#[tokio::test]
+async fn lists_a_subtree() {
+    let db_path = std::env::temp_dir().join(format!("janus-test-{}.sqlite", uuid::Uuid::new_v4()));
+    let storage = Storage::new(&db_path).await.expect("storage init");
+
+    for (id, parent_id, order_index) in [("folder-a", None, 0), ("folder-b", Some("folder-a"), 1)] {
+        storage
+            .upsert_folder(&FolderUpsert {
+                id: id.into(),
+                parent_id: parent_id.map(Into::into),
+                name: id.into(),
+                order_index,
+                color: None,
+                icon: None,
+            })
+            .await
+            .expect("folder upsert");
+    }
+    for (id, parent_id, order_index) in [
+        ("conn-root", None, 1),
+        ("conn-a", Some("folder-a"), 0),
+        ("conn-b", Some("folder-b"), 0),
+    ] {
+        storage
+            .upsert_connection(
+                &ssh_connection(id, parent_id, id, order_index),
+                &ResolvedSecretRefs::default(),
+            )
+            .await
+            .expect("connection upsert");
+    }
+
+    let subtree = storage.get_subtree("folder-a").await.expect("subtree");
+    let ids: Vec<_> = subtree.iter().map(|node| node.id.as_str()).collect();
+    assert_eq!(ids, vec!["folder-a", "conn-a", "folder-b", "conn-b"]);
+    assert!(subtree
+        .iter()
+        .find(|node| node.id == "conn-b")
+        .is_some_and(|node| node.ssh.is_some()));
+
+    let leaf = storage
+        .get_subtree("conn-root")
+        .await
+        .expect("leaf subtree");
+    assert_eq!(leaf.len(), 1);
+    let missing = storage
+        .get_subtree("missing")
+        .await
+        .expect("missing subtree");
+    assert!(missing.is_empty());
+
+    let _ = std::fs::remove_file(db_path);
+}
+
 #[tokio::test]
 async fn move_node_rejects_non_folder_parent() {
     let db_path = std::env::temp_dir().join(format!("janus-test-{}.sqlite", uuid::Uuid::new_v4()));
     let storage = Storage::new(&db_path).await.expect("storage init");
 
     storage
-        .upsert_connection(&ssh_connection("conn-a", None, "Conn A", 0), &ResolvedSecretRefs::default())
+        .upsert_connection(
+            &ssh_connection("conn-a", None, "Conn A", 0),
+            &ResolvedSecretRefs::default(),
+        )
         .await
         .expect("conn a upsert");
     storage
-        .upsert_connection(&ssh_connection("conn-b", None, "Conn B", 1), &ResolvedSecretRefs::default())
+        .upsert_connection(
+            &ssh_connection("conn-b", None, "Conn B", 1),
+            &ResolvedSecretRefs::default(),
+        )
         .await
         .expect("conn b upsert");
 
@@ -240,6 +470,8 @@ async fn move_node_rejects_folder_cycle() {
             parent_id: None,
             name: "Folder A".into(),
             order_index: 0,
+            color: None,
+            icon: None,
         })
         .await
         .expect("folder a upsert");
@@ -249,6 +481,8 @@ async fn move_node_rejects_folder_cycle() {
             parent_id: Some("folder-a".into()),
             name: "Folder B".into(),
             order_index: 0,
+            color: None,
+            icon: None,
         })
         .await
         .expect("folder b upsert");
@@ -266,3 +500,679 @@ async fn move_node_rejects_folder_cycle() {
 
     let _ = std::fs::remove_file(db_path);
 }
+
+#[tokio::test]
+async fn can_move_rejects_what_move_node_rejects_without_moving() {
+    let db_path = std::env::temp_dir().join(format!("janus-test-{}.sqlite", uuid::Uuid::new_v4()));
+    let storage = Storage::new(&db_path).await.expect("storage init");
+
+    storage
+        .upsert_folder(&FolderUpsert {
+            id: "folder-a".into(),
+            parent_id: None,
+            name: "Folder A".into(),
+            order_index: 0,
+            color: None,
+            icon: None,
+        })
+        .await
+        .expect("folder a upsert");
+    storage
+        .upsert_folder(&FolderUpsert {
+            id: "folder-b".into(),
+            parent_id: Some("folder-a".into()),
+            name: "Folder B".into(),
+            order_index: 0,
+            color: None,
+            icon: None,
+        })
+        .await
+        .expect("folder b upsert");
+
+    let error = storage
+        .move_node(&NodeMoveRequest {
+            node_id: "folder-a".into(),
+            new_parent_id: Some("folder-b".into()),
+            new_index: 0,
+        })
+        .await
+        .expect_err("folder cycle should fail");
+
+    let can_move_error = storage
+        .can_move(&NodeMoveRequest {
+            node_id: "folder-a".into(),
+            new_parent_id: Some("folder-b".into()),
+            new_index: 0,
+        })
+        .await
+        .expect_err("dry run should reject the cycle too");
+    assert_eq!(can_move_error.to_string(), error.to_string());
+
+    storage
+        .can_move(&NodeMoveRequest {
+            node_id: "folder-b".into(),
+            new_parent_id: None,
+            new_index: 0,
+        })
+        .await
+        .expect("moving folder b to the root is allowed");
+    let tree = storage.list_tree().await.expect("list tree");
+    let folder_b = tree
+        .iter()
+        .find(|node| node.id == "folder-b")
+        .expect("folder b");
+    assert_eq!(folder_b.parent_id.as_deref(), Some("folder-a"));
+
+    let _ = std::fs::remove_file(db_path);
+}
+
+#[tokio::test]
+async fn normalize_order_compacts_sparse_and_duplicate_indices() {
+    let db_path = std::env::temp_dir().join(format!("janus-test-{}.sqlite", uuid::Uuid::new_v4()));
+    let storage = Storage::new(&db_path).await.expect("storage init");
+
+    storage
+        .upsert_folder(&FolderUpsert {
+            id: "folder".into(),
+            parent_id: None,
+            name: "Folder".into(),
+            order_index: 7,
+            color: None,
+            icon: None,
+        })
+        .await
+        .expect("folder upsert");
+    for (id, order_index) in [("charlie", 10), ("bravo", 5), ("alpha", 5), ("delta", 40)] {
+        storage
+            .upsert_connection(
+                &ssh_connection(id, Some("folder"), id, order_index),
+                &ResolvedSecretRefs::default(),
+            )
+            .await
+            .expect("connection upsert");
+    }
+
+    let order = |nodes: &[janus_domain::ConnectionNode]| -> Vec<(String, i64)> {
+        nodes
+            .iter()
+            .map(|node| (node.id.clone(), node.order_index))
+            .collect()
+    };
+    let before = storage.list_tree().await.expect("list tree");
+    assert_eq!(storage.normalize_order().await.expect("normalize"), 5);
+    let after = storage.list_tree().await.expect("list tree");
+
+    let ids = |nodes: &[(String, i64)]| nodes.iter().map(|(id, _)| id.clone()).collect::<Vec<_>>();
+    assert_eq!(ids(&order(&before)), ids(&order(&after)));
+    assert_eq!(
+        order(&after),
+        vec![
+            ("folder".to_string(), 0),
+            ("alpha".to_string(), 0),
+            ("bravo".to_string(), 1),
+            ("charlie".to_string(), 2),
+            ("delta".to_string(), 3),
+        ]
+    );
+    assert_eq!(storage.normalize_order().await.expect("normalize again"), 0);
+
+    let _ = std::fs::remove_file(db_path);
+}
+
+#[tokio::test]
+async fn normalize_order_breaks_ties_by_name_ignoring_case() {
+    let db_path = std::env::temp_dir().join(format!("janus-test-{}.sqlite", uuid::Uuid::new_v4()));
+    let storage = Storage::new(&db_path).await.expect("storage init");
+
+    for (id, name) in [("upper", "Bravo"), ("lower", "alpha"), ("mixed", "Charlie")] {
+        storage
+            .upsert_connection(
+                &ssh_connection(id, None, name, 3),
+                &ResolvedSecretRefs::default(),
+            )
+            .await
+            .expect("connection upsert");
+    }
+
+    assert_eq!(storage.normalize_order().await.expect("normalize"), 3);
+    let mut order = storage
+        .list_tree()
+        .await
+        .expect("list tree")
+        .into_iter()
+        .map(|node| (node.order_index, node.name))
+        .collect::<Vec<_>>();
+    order.sort();
+    assert_eq!(
+        order,
+        vec![
+            (0, "alpha".to_string()),
+            (1, "Bravo".to_string()),
+            (2, "Charlie".to_string()),
+        ]
+    );
+
+    let _ = std::fs::remove_file(db_path);
+}
+
+#[tokio::test]
+async fn lists_configs_for_every_node_in_a_large_tree() {
+    let db_path = std::env::temp_dir().join(format!("janus-test-{}.sqlite", uuid::Uuid::new_v4()));
+    let storage = Storage::new(&db_path).await.expect("storage init");
+
+    for folder in 0..10 {
+        storage
+            .upsert_folder(&FolderUpsert {
+                id: format!("folder-{folder}"),
+                parent_id: None,
+                name: format!("Folder {folder}"),
+                order_index: folder,
+                color: None,
+                icon: None,
+            })
+            .await
+            .expect("folder upsert");
+    }
+    for index in 0..300 {
+        let id = format!("conn-{index}");
+        let parent_id = format!("folder-{}", index % 10);
+        let mut conn = ssh_connection(&id, Some(&parent_id), &id, index);
+        if index % 3 == 0 {
+            conn.kind = NodeKind::Rdp;
+            conn.ssh = None;
+            conn.rdp = Some(RdpConfigInput {
+                host: format!("rdp-{index}.local"),
+                port: 3389,
+                username: None,
+                domain: None,
+                screen_mode: 2,
+                width: None,
+                height: None,
+                connect_timeout_secs: None,
+                connect_retries: 0,
+                redirect_smartcards: false,
+                bitmap_cache: false,
+                experience: RdpExperience::default(),
+                password: None,
+            });
+        } else {
+            conn.ssh.as_mut().expect("ssh payload").host = format!("ssh-{index}.local");
+        }
+        storage
+            .upsert_connection(&conn, &ResolvedSecretRefs::default())
+            .await
+            .expect("connection upsert");
+    }
+
+    let tree = storage.list_tree().await.expect("list tree");
+    assert_eq!(tree.len(), 310);
+    for node in &tree {
+        let Some(index) = node.id.strip_prefix("conn-") else {
+            assert!(node.ssh.is_none() && node.rdp.is_none());
+            continue;
+        };
+        match (&node.ssh, &node.rdp) {
+            (Some(ssh), None) => assert_eq!(ssh.host, format!("ssh-{index}.local")),
+            (None, Some(rdp)) => assert_eq!(rdp.host, format!("rdp-{index}.local")),
+            _ => panic!("{} should have exactly one config", node.id),
+        }
+        let fetched = storage
+            .get_node(&node.id)
+            .await
+            .expect("get node")
+            .expect("node exists");
+        assert_eq!(
+            fetched.ssh.map(|ssh| ssh.host),
+            node.ssh.as_ref().map(|ssh| ssh.host.clone())
+        );
+        assert_eq!(
+            fetched.rdp.map(|rdp| rdp.host),
+            node.rdp.as_ref().map(|rdp| rdp.host.clone())
+        );
+    }
+
+    let _ = std::fs::remove_file(db_path);
+}
+
+#[tokio::test]
+async fn persists_host_key_mode() {
+    let db_path = std::env::temp_dir().join(format!("janus-test-{}.sqlite", uuid::Uuid::new_v4()));
+    let storage = Storage::new(&db_path).await.expect("storage init");
+
+    for (id, mode) in [
+        ("conn-off", HostKeyMode::Off),
+        ("conn-tofu", HostKeyMode::Tofu),
+        ("conn-strict", HostKeyMode::Strict),
+    ] {
+        let mut conn = ssh_connection(id, None, id, 0);
+        conn.ssh.as_mut().expect("ssh payload").host_key_mode = mode;
+        storage
+            .upsert_connection(&conn, &ResolvedSecretRefs::default())
+            .await
+            .expect("connection upsert");
+
+        let node = storage
+            .get_node(id)
+            .await
+            .expect("get node")
+            .expect("node exists");
+        assert_eq!(node.ssh.expect("ssh config").host_key_mode, mode);
+    }
+
+    let _ = std::fs::remove_file(db_path);
+}
+
+#[tokio::test]
+async fn persists_ssh_auth_methods_in_order() {
+    let db_path = std::env::temp_dir().join(format!("janus-test-{}.sqlite", uuid::Uuid::new_v4()));
+    let storage = Storage::new(&db_path).await.expect("storage init");
+
+    let mut conn = ssh_connection("conn-a", None, "a", 0);
+    let methods = vec![SshAuthMethod::Password, SshAuthMethod::PublicKey];
+    conn.ssh.as_mut().expect("ssh payload").auth_methods = methods.clone();
+    storage
+        .upsert_connection(&conn, &ResolvedSecretRefs::default())
+        .await
+        .expect("connection upsert");
+    let node = storage
+        .get_node("conn-a")
+        .await
+        .expect("get node")
+        .expect("node exists");
+    assert_eq!(node.ssh.expect("ssh config").auth_methods, methods);
+
+    for invalid in [vec![], vec![SshAuthMethod::None, SshAuthMethod::None]] {
+        conn.ssh.as_mut().expect("ssh payload").auth_methods = invalid;
+        assert!(storage
+            .upsert_connection(&conn, &ResolvedSecretRefs::default())
+            .await
+            .is_err());
+    }
+
+    let _ = std::fs::remove_file(db_path);
+}
+
+#[tokio::test]
+async fn rejects_ssh_bind_addresses_that_are_not_ips() {
+    let db_path = std::env::temp_dir().join(format!("janus-test-{}.sqlite", uuid::Uuid::new_v4()));
+    let storage = Storage::new(&db_path).await.expect("storage init");
+
+    let mut conn = ssh_connection("conn-a", None, "a", 0);
+    conn.ssh.as_mut().expect("ssh payload").bind_address = Some("eth0".into());
+    assert!(storage
+        .upsert_connection(&conn, &ResolvedSecretRefs::default())
+        .await
+        .is_err());
+
+    conn.ssh.as_mut().expect("ssh payload").bind_address = Some("  ".into());
+    storage
+        .upsert_connection(&conn, &ResolvedSecretRefs::default())
+        .await
+        .expect("blank bind address is none");
+    let node = storage
+        .get_node("conn-a")
+        .await
+        .expect("get node")
+        .expect("node exists");
+    assert_eq!(node.ssh.expect("ssh config").bind_address, None);
+
+    let _ = std::fs::remove_file(db_path);
+}
+
+#[tokio::test]
+async fn persists_rdp_connection_settings() {
+    let db_path = std::env::temp_dir().join(format!("janus-test-{}.sqlite", uuid::Uuid::new_v4()));
+    let storage = Storage::new(&db_path).await.expect("storage init");
+
+    let conn = ConnectionUpsert {
+        id: "conn-vm".into(),
+        parent_id: None,
+        kind: NodeKind::Rdp,
+        name: "vm".into(),
+        order_index: 0,
+        color: None,
+        icon: None,
+        ssh: None,
+        rdp: Some(RdpConfigInput {
+            host: "vm.local".into(),
+            port: 3389,
+            username: None,
+            domain: None,
+            screen_mode: 2,
+            width: None,
+            height: None,
+            connect_timeout_secs: Some(20),
+            connect_retries: 3,
+            redirect_smartcards: true,
+            bitmap_cache: true,
+            experience: RdpExperience::BROADBAND,
+            password: None,
+        }),
+    };
+    storage
+        .upsert_connection(&conn, &ResolvedSecretRefs::default())
+        .await
+        .expect("connection upsert");
+
+    let rdp = storage
+        .get_node("conn-vm")
+        .await
+        .expect("get node")
+        .expect("node exists")
+        .rdp
+        .expect("rdp config");
+    assert_eq!(rdp.connect_timeout_secs, Some(20));
+    assert_eq!(rdp.connect_retries, 3);
+    assert!(rdp.redirect_smartcards);
+    assert!(rdp.bitmap_cache);
+    assert_eq!(rdp.experience, RdpExperience::BROADBAND);
+
+    let _ = std::fs::remove_file(db_path);
+}
+
+#[tokio::test]
+async fn lists_and_replaces_password_refs() {
+    let db_path = std::env::temp_dir().join(format!("janus-test-{}.sqlite", uuid::Uuid::new_v4()));
+    let storage = Storage::new(&db_path).await.expect("storage init");
+
+    storage
+        .upsert_connection(
+            &ssh_connection("conn-ssh", None, "ssh", 0),
+            &ResolvedSecretRefs {
+                ssh_password_ref: Some("ssh-password".into()),
+                ssh_key_passphrase_ref: Some("ssh-passphrase".into()),
+                ..ResolvedSecretRefs::default()
+            },
+        )
+        .await
+        .expect("ssh upsert");
+    storage
+        .upsert_connection(
+            &ssh_connection("conn-key", None, "key only", 1),
+            &ResolvedSecretRefs::default(),
+        )
+        .await
+        .expect("key-only upsert");
+    let rdp = ConnectionUpsert {
+        id: "conn-rdp".into(),
+        parent_id: None,
+        kind: NodeKind::Rdp,
+        name: "rdp".into(),
+        order_index: 2,
+        color: None,
+        icon: None,
+        ssh: None,
+        rdp: Some(RdpConfigInput {
+            host: "vm.local".into(),
+            port: 3389,
+            username: None,
+            domain: None,
+            screen_mode: 2,
+            width: None,
+            height: None,
+            connect_timeout_secs: None,
+            connect_retries: 0,
+            redirect_smartcards: false,
+            bitmap_cache: false,
+            experience: RdpExperience::default(),
+            password: None,
+        }),
+    };
+    storage
+        .upsert_connection(
+            &rdp,
+            &ResolvedSecretRefs {
+                rdp_password_ref: Some("rdp-password".into()),
+                ..ResolvedSecretRefs::default()
+            },
+        )
+        .await
+        .expect("rdp upsert");
+
+    let password_ref = |node_id: &str, secret_ref: &str| PasswordRef {
+        node_id: node_id.into(),
+        secret_ref: secret_ref.into(),
+    };
+    assert_eq!(
+        storage.password_refs().await.expect("password refs"),
+        vec![
+            password_ref("conn-rdp", "rdp-password"),
+            password_ref("conn-ssh", "ssh-password"),
+        ]
+    );
+
+    let updated = storage
+        .set_password_refs(&[
+            ("conn-ssh".into(), "rotated".into()),
+            ("conn-rdp".into(), "rotated".into()),
+            ("missing".into(), "rotated".into()),
+        ])
+        .await
+        .expect("set password refs");
+    assert_eq!(updated, 2);
+    assert_eq!(
+        storage.password_refs().await.expect("password refs"),
+        vec![
+            password_ref("conn-rdp", "rotated"),
+            password_ref("conn-ssh", "rotated"),
+        ]
+    );
+    let ssh = storage
+        .get_node("conn-ssh")
+        .await
+        .expect("get node")
+        .expect("node exists")
+        .ssh
+        .expect("ssh config");
+    assert_eq!(ssh.key_passphrase_ref.as_deref(), Some("ssh-passphrase"));
+
+    let _ = std::fs::remove_file(db_path);
+}
+
+#[tokio::test]
+async fn remembers_last_sftp_path_until_opted_out() {
+    let db_path = std::env::temp_dir().join(format!("janus-test-{}.sqlite", uuid::Uuid::new_v4()));
+    let storage = Storage::new(&db_path).await.expect("storage init");
+    let refs = ResolvedSecretRefs::default();
+    let last_path = |storage: Storage| async move {
+        let node = storage
+            .get_node("conn-web")
+            .await
+            .expect("get node")
+            .expect("node exists");
+        node.ssh.expect("ssh config").last_sftp_path
+    };
+
+    let mut conn = ssh_connection("conn-web", None, "web", 0);
+    storage
+        .upsert_connection(&conn, &refs)
+        .await
+        .expect("connection upsert");
+    storage
+        .record_last_sftp_path("conn-web", "/var/www")
+        .await
+        .expect("record path");
+    assert_eq!(last_path(storage.clone()).await, Some("/var/www".into()));
+
+    // Editing the connection keeps the path; opting out forgets it and stops recording.
+    storage
+        .upsert_connection(&conn, &refs)
+        .await
+        .expect("connection re-upsert");
+    assert_eq!(last_path(storage.clone()).await, Some("/var/www".into()));
+    conn.ssh.as_mut().expect("ssh payload").remember_sftp_path = false;
+    storage
+        .upsert_connection(&conn, &refs)
+        .await
+        .expect("opt out");
+    assert_eq!(last_path(storage.clone()).await, None);
+    storage
+        .record_last_sftp_path("conn-web", "/srv")
+        .await
+        .expect("record ignored path");
+    assert_eq!(last_path(storage.clone()).await, None);
+
+    let _ = std::fs::remove_file(db_path);
+}
+
+#[tokio::test]
+async fn rejects_databases_from_newer_versions() {
+    let db_path = std::env::temp_dir().join(format!("janus-test-{}.sqlite", uuid::Uuid::new_v4()));
+    let storage = Storage::new(&db_path).await.expect("storage init");
+    assert_eq!(
+        storage.schema_version().await.expect("schema version"),
+        supported_schema_version()
+    );
+    drop(storage);
+
+    // Simulate a migration applied by a newer build.
+    let pool = sqlx::SqlitePool::connect(&format!("sqlite://{}", db_path.display()))
+        .await
+        .expect("raw connect");
+    sqlx::query(
+        "INSERT INTO _sqlx_migrations
+         (version, description, installed_on, success, checksum, execution_time)
+         VALUES (?1, 'from the future', CURRENT_TIMESTAMP, 1, x'00', 0)",
+    )
+    .bind(supported_schema_version() + 1)
+    .execute(&pool)
+    .await
+    .expect("insert future migration");
+    pool.close().await;
+
+    let error = match Storage::new(&db_path).await {
+        Ok(_) => panic!("newer schema should be rejected"),
+        Err(error) => error,
+    };
+    assert!(error
+        .to_string()
+        .contains("created by a newer version of Janus"));
+
+    let _ = std::fs::remove_file(db_path);
+}
+
+#[tokio::test]
+async fn delete_subtree_removes_secrets_only_it_uses_before_the_rows() {
+    let db_path = std::env::temp_dir().join(format!("janus-test-{}.sqlite", uuid::Uuid::new_v4()));
+    let storage = Storage::new(&db_path).await.expect("storage init");
+
+    storage
+        .upsert_folder(&FolderUpsert {
+            id: "folder".into(),
+            parent_id: None,
+            name: "Folder".into(),
+            order_index: 0,
+            color: None,
+            icon: None,
+        })
+        .await
+        .expect("folder upsert");
+    for (index, id) in ["conn-a", "conn-b"].into_iter().enumerate() {
+        storage
+            .upsert_connection(
+                &ssh_connection(id, Some("folder"), id, index as i64),
+                &ResolvedSecretRefs {
+                    ssh_password_ref: Some(format!("{id}-password")),
+                    ..ResolvedSecretRefs::default()
+                },
+            )
+            .await
+            .expect("connection upsert");
+    }
+
+    storage
+        .upsert_connection(
+            &ssh_connection("outside", None, "Outside", 1),
+            &ResolvedSecretRefs {
+                ssh_password_ref: Some("conn-b-password".into()),
+                ..ResolvedSecretRefs::default()
+            },
+        )
+        .await
+        .expect("outside upsert");
+
+    storage
+        .delete_subtree("folder", |_| async {
+            Err(anyhow::anyhow!("vault is locked"))
+        })
+        .await
+        .expect_err("secrets not deleted");
+    assert_eq!(storage.list_tree().await.expect("list tree").len(), 4);
+
+    let mut deleted = Vec::new();
+    let report = storage
+        .delete_subtree("folder", |secret_refs| {
+            deleted = secret_refs;
+            async { Ok(1) }
+        })
+        .await
+        .expect("subtree delete");
+    assert_eq!(
+        report,
+        SubtreeDeleteReport {
+            nodes_removed: 3,
+            secrets_removed: 1,
+        }
+    );
+    assert_eq!(deleted, ["conn-a-password"]);
+    let remaining: Vec<_> = storage
+        .list_tree()
+        .await
+        .expect("list tree")
+        .into_iter()
+        .map(|node| node.id)
+        .collect();
+    assert_eq!(remaining, vec!["outside"]);
+
+    let _ = std::fs::remove_file(db_path);
+}
+
+#[tokio::test]
+async fn snippet_crud_roundtrip() {
+    let db_path = std::env::temp_dir().join(format!("janus-test-{}.sqlite", uuid::Uuid::new_v4()));
+    let storage = Storage::new(&db_path).await.expect("storage init");
+
+    let mut setup = Snippet {
+        id: "snip-1".to_string(),
+        name: "setup".to_string(),
+        body: "cd ${dir}\nexport TERM=xterm".to_string(),
+    };
+    let tail = Snippet {
+        id: "snip-2".to_string(),
+        name: "Tail logs".to_string(),
+        body: "tail -f /var/log/syslog".to_string(),
+    };
+    storage.upsert_snippet(&setup).await.expect("insert setup");
+    storage.upsert_snippet(&tail).await.expect("insert tail");
+
+    let names: Vec<_> = storage
+        .list_snippets()
+        .await
+        .expect("list snippets")
+        .into_iter()
+        .map(|snippet| snippet.name)
+        .collect();
+    assert_eq!(names, ["setup", "Tail logs"]);
+
+    setup.body = "cd ${dir}".to_string();
+    storage.upsert_snippet(&setup).await.expect("update setup");
+    assert_eq!(
+        storage.get_snippet("snip-1").await.expect("get setup"),
+        Some(setup.clone())
+    );
+
+    let blank = Snippet {
+        name: "  ".to_string(),
+        ..tail.clone()
+    };
+    assert!(storage.upsert_snippet(&blank).await.is_err());
+
+    storage.delete_snippet("snip-2").await.expect("delete tail");
+    assert_eq!(storage.get_snippet("snip-2").await.expect("get tail"), None);
+    assert_eq!(
+        storage.list_snippets().await.expect("list snippets").len(),
+        1
+    );
+
+    let _ = std::fs::remove_file(db_path);
+}
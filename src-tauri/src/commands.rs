@@ -1,27 +1,120 @@
-use std::net::{TcpStream, ToSocketAddrs};
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use std::net::{IpAddr, TcpStream, ToSocketAddrs};
 use std::path::{Path, PathBuf};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use janus_domain::{
-    ConnectionNode, ConnectionUpsert, FolderUpsert, ImportMode, ImportReport, ImportScope,
-    NodeMoveRequest, RdpLaunchOptions, SessionOptions,
+    ConnectionNode, ConnectionUpsert, FileSort, FileSortFields, FolderUpsert, ImportMode,
+    ImportReport, ImportScope, NodeKind, NodeMoveRequest, RdpConfigInput, RdpLaunchOptions, Secret,
+    SecretRef, SessionOptions, Snippet, SshConfigInput,
+};
+use janus_import_export::{
+    apply_report, export_mremoteng as export_xml, nest_under, nests_inside_itself,
+    parse_mremoteng_with_progress, ParsedImport,
+};
+use janus_protocol_rdp::{
+    describe_disconnect, normalize_rdp_logon, probe_security, RdpActiveXEvent, RdpConnectFailure,
+    RdpMetrics, RdpNegotiationFailure, RdpSessionConfig,
 };
-use janus_import_export::{apply_report, export_mremoteng as export_xml, parse_mremoteng};
-use janus_protocol_rdp::{RdpActiveXEvent, RdpSessionConfig};
 use janus_protocol_ssh::{
-    SftpFileKind, SftpListResult, SftpTransferProgress, SshEvent, SshLaunchConfig,
+    expand_snippet, format_permissions, PublicKeyInstall, SftpConflictPolicy, SftpDirChanges,
+    SftpDownloadOptions, SftpEndpoint, SftpError, SftpErrorCode, SftpFileConflict, SftpFileEntry,
+    SftpFileKind, SftpFileStat, SftpListPage, SftpListPageOptions, SftpListResult,
+    SftpTransferProgress, SftpUploadOutcome, SshAuthError, SshConnectError, SshEvent,
+    SshHostKeyFingerprints, SshKeyError, SshKeyErrorKind, SshLaunchConfig, SshMetrics,
+    SshNegotiatedAlgorithms, SshSessionEnv, SshSessionExit, SshWriteQueueStats,
 };
-use janus_storage::ResolvedSecretRefs;
+use janus_secrets::{
+    generate_ssh_keypair, SshKeyType, VaultInfo, VaultManager, Vaults, DEFAULT_VAULT_ID,
+};
+use janus_storage::{PasswordRef, ResolvedSecretRefs, Storage};
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use tauri::{AppHandle, Emitter, Manager, State};
+use tauri_plugin_opener::OpenerExt;
 use uuid::Uuid;
 
+use crate::connection_monitor::MonitorTarget;
+use crate::external_open::is_executable_type;
+use crate::fs_transfer::{copy_path, move_path, LocalTransferProgress};
+use crate::host_keys::{PendingHostKey, PendingHostKeyReason};
+use crate::log_buffer::{LogBuffer, LogRecord, LOG_BUFFER_CAPACITY};
 use crate::state::AppState;
 
 fn err<E: std::fmt::Display>(error: E) -> String {
     error.to_string()
 }
 
+/// Blank means connect from any address.
+fn ssh_bind_address(value: Option<&str>) -> Result<Option<IpAddr>, String> {
+    let Some(value) = value.map(str::trim).filter(|value| !value.is_empty()) else {
+        return Ok(None);
+    };
+    value
+        .parse()
+        .map(Some)
+        .map_err(|_| format!("SSH bind address {value} is not an IP address"))
+}
+
+/// Error returned by the `ssh_sftp_*` commands: a display message plus a code the UI can branch on.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SftpErrorDto {
+    code: &'static str,
+    message: String,
+    /// Set with `alreadyExists` when an upload's remote file exists, for the UI to
+    /// offer the other `SftpConflictPolicyDto`s.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    conflict: Option<SftpFileConflictDto>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SftpFileStatDto {
+    size: Option<u64>,
+    modified_time: Option<u64>,
+}
+
+impl From<SftpFileStat> for SftpFileStatDto {
+    fn from(stat: SftpFileStat) -> Self {
+        Self {
+            size: stat.size,
+            modified_time: stat.modified_time,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SftpFileConflictDto {
+    remote_path: String,
+    local: SftpFileStatDto,
+    remote: SftpFileStatDto,
+}
+
+fn sftp_err(error: anyhow::Error) -> SftpErrorDto {
+    if let Some(conflict) = error.downcast_ref::<SftpFileConflict>() {
+        return SftpErrorDto {
+            code: SftpErrorCode::AlreadyExists.as_str(),
+            message: error.to_string(),
+            conflict: Some(SftpFileConflictDto {
+                remote_path: conflict.remote_path.clone(),
+                local: conflict.local.into(),
+                remote: conflict.remote.into(),
+            }),
+        };
+    }
+    let code = error
+        .downcast_ref::<SftpError>()
+        .map_or(SftpErrorCode::Other, |sftp_error| sftp_error.code);
+    SftpErrorDto {
+        code: code.as_str(),
+        message: error.to_string(),
+        conflict: None,
+    }
+}
+
 fn parse_rdp_port(port: i64) -> Result<u16, String> {
     u16::try_from(port).map_err(|_| format!("invalid RDP port: {port}"))
 }
@@ -30,6 +123,16 @@ fn parse_connection_probe_port(kind: &str, port: i64) -> Result<u16, String> {
     u16::try_from(port).map_err(|_| format!("invalid {kind} port: {port}"))
 }
 
+fn connection_probe_target(node: ConnectionNode) -> Result<(String, u16), String> {
+    if let Some(ssh) = node.ssh {
+        Ok((ssh.host, parse_connection_probe_port("SSH", ssh.port)?))
+    } else if let Some(rdp) = node.rdp {
+        Ok((rdp.host, parse_connection_probe_port("RDP", rdp.port)?))
+    } else {
+        Err("connection is not SSH or RDP or missing config".to_string())
+    }
+}
+
 fn parse_rdp_dimension(label: &str, value: Option<i64>) -> Result<Option<u16>, String> {
     match value {
         Some(v) => u16::try_from(v)
@@ -39,6 +142,15 @@ fn parse_rdp_dimension(label: &str, value: Option<i64>) -> Result<Option<u16>, S
     }
 }
 
+/// The control accepts connect timeouts of up to ten minutes.
+fn parse_rdp_connect_timeout(value: Option<i64>) -> Result<Option<u32>, String> {
+    match value {
+        Some(v @ 1..=600) => Ok(Some(v as u32)),
+        Some(v) => Err(format!("invalid RDP connect timeout: {v}s")),
+        None => Ok(None),
+    }
+}
+
 fn main_window(app: &AppHandle) -> Result<tauri::WebviewWindow, String> {
     app.get_webview_window("main")
         .ok_or_else(|| "main window not found".to_string())
@@ -132,7 +244,78 @@ fn local_default_dir() -> Result<PathBuf, String> {
     std::env::current_dir().map_err(err)
 }
 
-fn local_list_impl(path: &str) -> Result<FileListResultDto, String> {
+/// Expands a path typed into the path bar the way a shell would: a leading `~` is
+/// `home`, and `$NAME`, `${NAME}` and `%NAME%` are replaced with the variable's value.
+/// Unset variables are left as typed.
+fn expand_local_path(
+    path: &str,
+    home: Option<&Path>,
+    var: impl Fn(&str) -> Option<String>,
+) -> PathBuf {
+    let tilde_rest = match path.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with(['/', '\\']) => Some(rest),
+        _ => None,
+    };
+    match (tilde_rest, home) {
+        (Some(rest), Some(home)) => {
+            let rest = expand_env_vars(rest, &var);
+            home.join(rest.trim_start_matches(['/', '\\']))
+        }
+        _ => PathBuf::from(expand_env_vars(path, &var)),
+    }
+}
+
+fn expand_env_vars(text: &str, var: &impl Fn(&str) -> Option<String>) -> String {
+    let mut expanded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(index) = rest.find(['$', '%']) {
+        expanded.push_str(&rest[..index]);
+        let reference = &rest[index..];
+        match env_var_reference(reference)
+            .and_then(|(name, len)| var(name).map(|value| (value, len)))
+        {
+            Some((value, len)) => {
+                expanded.push_str(&value);
+                rest = &reference[len..];
+            }
+            None => {
+                expanded.push_str(&reference[..1]);
+                rest = &reference[1..];
+            }
+        }
+    }
+    expanded.push_str(rest);
+    expanded
+}
+
+/// The name in a `$NAME`, `${NAME}` or `%NAME%` at the start of `text`, and the
+/// reference's length in bytes.
+fn env_var_reference(text: &str) -> Option<(&str, usize)> {
+    let is_name_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let (name, len) = if let Some(braced) = text.strip_prefix("${") {
+        let end = braced.find('}')?;
+        (&braced[..end], end + 3)
+    } else if let Some(bare) = text.strip_prefix('$') {
+        let end = bare.find(|c: char| !is_name_char(c)).unwrap_or(bare.len());
+        (&bare[..end], end + 1)
+    } else {
+        let percent = text.strip_prefix('%')?;
+        let end = percent.find('%')?;
+        // Windows names such as `ProgramFiles(x86)` include parentheses.
+        let name = &percent[..end];
+        let valid = name
+            .chars()
+            .all(|c| is_name_char(c) || c == '(' || c == ')');
+        return (!name.is_empty() && valid).then_some((name, end + 2));
+    };
+    (!name.is_empty() && name.chars().all(is_name_char)).then_some((name, len))
+}
+
+fn local_list_impl(
+    path: &str,
+    sort: FileSort,
+    show_hidden: bool,
+) -> Result<FileListResultDto, String> {
     let requested = if path.trim().is_empty() {
         local_default_dir()?
     } else {
@@ -145,6 +328,13 @@ fn local_list_impl(path: &str) -> Result<FileListResultDto, String> {
     let read_dir = std::fs::read_dir(&cwd).map_err(err)?;
     for entry in read_dir {
         let entry = entry.map_err(err)?;
+        let file_name = entry.file_name();
+        let name_lossy = file_name.to_str().is_none();
+        let name = file_name.to_string_lossy().to_string();
+        let hidden = name.starts_with('.');
+        if hidden && !show_hidden {
+            continue;
+        }
         let path = entry.path();
         let meta = entry.metadata().map_err(err)?;
         let modified_at = meta
@@ -152,8 +342,6 @@ fn local_list_impl(path: &str) -> Result<FileListResultDto, String> {
             .ok()
             .and_then(|ts| ts.duration_since(std::time::UNIX_EPOCH).ok())
             .map(|d| d.as_secs());
-        let name = entry.file_name().to_string_lossy().to_string();
-        let hidden = name.starts_with('.');
 
         entries.push(FileEntryDto {
             name,
@@ -167,17 +355,13 @@ fn local_list_impl(path: &str) -> Result<FileListResultDto, String> {
             modified_at,
             owner: None,
             permissions: None,
+            permissions_str: None,
             hidden,
+            name_lossy,
         });
     }
 
-    entries.sort_by(|a, b| {
-        let a_dir = a.kind == "dir";
-        let b_dir = b.kind == "dir";
-        b_dir
-            .cmp(&a_dir)
-            .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
-    });
+    sort.sort(&mut entries, file_entry_sort_fields);
 
     Ok(FileListResultDto {
         cwd: normalize_path_string(&cwd),
@@ -185,22 +369,60 @@ fn local_list_impl(path: &str) -> Result<FileListResultDto, String> {
     })
 }
 
+fn file_entry_sort_fields(entry: &FileEntryDto) -> FileSortFields<'_> {
+    FileSortFields {
+        name: &entry.name,
+        is_dir: entry.kind == "dir",
+        size: entry.size,
+        modified: entry.modified_at,
+        kind: &entry.kind,
+    }
+}
+
+fn sftp_entry_to_dto(entry: SftpFileEntry) -> FileEntryDto {
+    FileEntryDto {
+        hidden: entry.name.starts_with('.'),
+        name: entry.name,
+        path: entry.path,
+        kind: file_kind_label(entry.kind),
+        size: entry.size,
+        modified_at: entry.modified_time,
+        owner: entry.owner,
+        permissions_str: entry
+            .permissions
+            .map(|mode| format_permissions(entry.kind, mode)),
+        permissions: entry.permissions,
+        name_lossy: entry.name_lossy,
+    }
+}
+
 fn sftp_list_to_dto(result: SftpListResult) -> FileListResultDto {
     FileListResultDto {
         cwd: result.cwd,
-        entries: result
-            .entries
+        entries: result.entries.into_iter().map(sftp_entry_to_dto).collect(),
+    }
+}
+
+fn sftp_list_page_to_dto(page: SftpListPage) -> FileListPageDto {
+    FileListPageDto {
+        cwd: page.cwd,
+        entries: page.entries.into_iter().map(sftp_entry_to_dto).collect(),
+        total: page.total,
+        sorted: page.sorted,
+        has_more: page.has_more,
+    }
+}
+
+fn sftp_dir_changes_to_dto(watch_id: &str, changes: SftpDirChanges) -> SftpDirChangedDto {
+    SftpDirChangedDto {
+        watch_id: watch_id.to_string(),
+        cwd: changes.cwd,
+        added: changes.added.into_iter().map(sftp_entry_to_dto).collect(),
+        removed: changes.removed.into_iter().map(sftp_entry_to_dto).collect(),
+        modified: changes
+            .modified
             .into_iter()
-            .map(|entry| FileEntryDto {
-                hidden: entry.name.starts_with('.'),
-                name: entry.name,
-                path: entry.path,
-                kind: file_kind_label(entry.kind),
-                size: entry.size,
-                modified_at: entry.modified_time,
-                owner: entry.owner,
-                permissions: entry.permissions,
-            })
+            .map(sftp_entry_to_dto)
             .collect(),
     }
 }
@@ -209,6 +431,28 @@ fn sftp_transfer_event_name(sftp_session_id: &str) -> String {
     format!("sftp://{sftp_session_id}/transfer")
 }
 
+fn sftp_dir_changed_event_name(sftp_session_id: &str) -> String {
+    format!("sftp://{sftp_session_id}/dir-changed")
+}
+
+fn sftp_external_saved_event_name(sftp_session_id: &str) -> String {
+    format!("sftp://{sftp_session_id}/external-saved")
+}
+
+fn local_fs_changed_event_name(token: &str) -> String {
+    format!("fs://{token}/changed")
+}
+
+const LOCAL_FS_TRANSFER_EVENT: &str = "fs://transfer";
+/// Minimum time between `fs://transfer` progress events; local copies report every chunk.
+const LOCAL_FS_TRANSFER_PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+const TREE_CHANGED_EVENT: &str = "tree://changed";
+
+const IMPORT_PROGRESS_EVENT: &str = "import://progress";
+/// Nodes applied between `import://progress` events, so huge files don't flood the webview.
+const IMPORT_PROGRESS_INTERVAL: usize = 100;
+
 fn emit_sftp_transfer_progress(
     app: &AppHandle,
     sftp_session_id: &str,
@@ -227,10 +471,237 @@ fn emit_sftp_transfer_progress(
             remote_path,
             bytes_transferred: progress.bytes_transferred,
             total_bytes: progress.total_bytes,
+            warning: None,
+        },
+    );
+}
+
+fn emit_sftp_transfer_complete(
+    app: &AppHandle,
+    sftp_session_id: &str,
+    direction: SftpTransferDirectionDto,
+    local_path: &str,
+    remote_path: &str,
+    progress: SftpTransferProgress,
+    warning: Option<&str>,
+) {
+    let _ = app.emit(
+        &sftp_transfer_event_name(sftp_session_id),
+        SftpTransferProgressDto {
+            direction,
+            phase: "complete",
+            local_path,
+            remote_path,
+            bytes_transferred: progress.bytes_transferred,
+            total_bytes: progress.total_bytes,
+            warning,
         },
     );
 }
 
+fn emit_sftp_remote_copy_progress(
+    app: &AppHandle,
+    request: &SftpTransferBetweenRequest,
+    phase: &'static str,
+    progress: SftpTransferProgress,
+    warning: Option<&str>,
+) {
+    let payload = SftpRemoteCopyProgressDto {
+        direction: SftpTransferDirectionDto::RemoteCopy,
+        phase,
+        src_sftp_session_id: &request.src_sftp_session_id,
+        src_path: &request.src_path,
+        dst_sftp_session_id: &request.dst_sftp_session_id,
+        dst_path: &request.dst_path,
+        bytes_transferred: progress.bytes_transferred,
+        total_bytes: progress.total_bytes,
+        warning,
+    };
+    let _ = app.emit(
+        &sftp_transfer_event_name(&request.src_sftp_session_id),
+        payload.clone(),
+    );
+    if request.dst_sftp_session_id != request.src_sftp_session_id {
+        let _ = app.emit(
+            &sftp_transfer_event_name(&request.dst_sftp_session_id),
+            payload,
+        );
+    }
+}
+
+/// Progress for a transfer's "complete" event, taken from the destination's size once
+/// written rather than the last report, which can lag the final chunk. Returns a warning
+/// when that size differs from the source's.
+fn completed_transfer_progress(
+    last: SftpTransferProgress,
+    final_size: Option<u64>,
+    destination: &str,
+) -> (SftpTransferProgress, Option<String>) {
+    let Some(final_size) = final_size else {
+        return (last, None);
+    };
+    let warning = last
+        .total_bytes
+        .filter(|&expected| expected != final_size)
+        .map(|expected| {
+            format!("{destination} is {final_size} bytes after the transfer; expected {expected}")
+        });
+    if let Some(warning) = &warning {
+        tracing::warn!("{warning}");
+    }
+    let progress = SftpTransferProgress {
+        bytes_transferred: final_size,
+        total_bytes: last.total_bytes.or(Some(final_size)),
+    };
+    (progress, warning)
+}
+
+async fn local_file_size(path: &str) -> Option<u64> {
+    let path = PathBuf::from(path);
+    tauri::async_runtime::spawn_blocking(move || std::fs::metadata(path).ok())
+        .await
+        .ok()
+        .flatten()
+        .map(|metadata| metadata.len())
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct SshSystemInfoDto {
+    os: Option<String>,
+    shell: Option<String>,
+    hostname: Option<String>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SshAlgorithmsDto {
+    kex: String,
+    host_key_alg: String,
+    cipher: String,
+    mac: Option<String>,
+}
+
+impl From<SshNegotiatedAlgorithms> for SshAlgorithmsDto {
+    fn from(value: SshNegotiatedAlgorithms) -> Self {
+        Self {
+            kex: value.kex,
+            host_key_alg: value.host_key_alg,
+            cipher: value.cipher,
+            mac: value.mac,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SshSessionExitDto {
+    exit_code: Option<i32>,
+    reason: String,
+}
+
+impl From<SshSessionExit> for SshSessionExitDto {
+    fn from(value: SshSessionExit) -> Self {
+        Self {
+            exit_code: value.exit_code,
+            reason: value.reason,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SshWriteQueueStatsDto {
+    queued: usize,
+    capacity: usize,
+}
+
+impl From<SshWriteQueueStats> for SshWriteQueueStatsDto {
+    fn from(value: SshWriteQueueStats) -> Self {
+        Self {
+            queued: value.queued,
+            capacity: value.capacity,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SshSessionEnvDto {
+    term: String,
+    /// `[name, value]` pairs, in the order they were sent.
+    env: Vec<(String, String)>,
+}
+
+impl From<SshSessionEnv> for SshSessionEnvDto {
+    fn from(value: SshSessionEnv) -> Self {
+        Self {
+            term: value.term,
+            env: value.env,
+        }
+    }
+}
+
+const SSH_ALGORITHMS_PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+const RDP_TEST_CONNECTION_TIMEOUT: Duration = Duration::from_secs(10);
+const SSH_RUN_COMMAND_TIMEOUT: Duration = Duration::from_secs(30);
+const SSH_RUN_COMMAND_MAX_OUTPUT: usize = 1024 * 1024;
+/// Filtered commands may read far more output than they return, so they get longer.
+const SSH_RUN_FILTERED_TIMEOUT: Duration = Duration::from_secs(120);
+const SSH_RUN_FILTERED_MAX_MATCHES: usize = 10_000;
+/// Output kept per terminal for connections with `retain_scrollback` enabled.
+const SSH_SCROLLBACK_BYTES: usize = 256 * 1024;
+const SSH_DEFAULT_COLS: u16 = 120;
+const SSH_DEFAULT_ROWS: u16 = 32;
+const SFTP_LIST_PAGE_SIZE: usize = 500;
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SshRunCommandResultDto {
+    stdout: String,
+    stderr: String,
+    exit_code: Option<i32>,
+    truncated: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SshFilteredCommandResultDto {
+    lines: Vec<String>,
+    total_lines: u64,
+    stderr: String,
+    exit_code: Option<i32>,
+    truncated: bool,
+}
+
+/// What changed in a `tree://changed` event.
+#[derive(Serialize, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+enum TreeChangeKindDto {
+    /// A folder or connection was created or edited.
+    Upserted,
+    /// A node changed parent or position; both parents are affected.
+    Moved,
+    Deleted,
+    /// Too much changed to list; refetch the whole tree.
+    Reset,
+}
+
+/// `affected_ids` are the nodes whose subtrees should be refetched; `None` is the root.
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct TreeChangedDto {
+    kind: TreeChangeKindDto,
+    affected_ids: Vec<Option<String>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeDeleteResultDto {
+    nodes_removed: usize,
+    secrets_removed: usize,
+}
+
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct VaultStatus {
@@ -238,6 +709,24 @@ pub struct VaultStatus {
     unlocked: bool,
 }
 
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VaultInfoDto {
+    id: String,
+    initialized: bool,
+    unlocked: bool,
+}
+
+impl From<VaultInfo> for VaultInfoDto {
+    fn from(value: VaultInfo) -> Self {
+        Self {
+            id: value.id,
+            initialized: value.initialized,
+            unlocked: value.unlocked,
+        }
+    }
+}
+
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TcpProbeResult {
@@ -250,6 +739,9 @@ pub struct TcpProbeResult {
 pub enum SshSessionOpenResult {
     Opened {
         session_id: String,
+        /// Set when the host key was pinned on first sight and is weak or deprecated.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        weak_host_key: Option<&'static str>,
     },
     HostKeyMismatch {
         token: String,
@@ -257,10 +749,150 @@ pub enum SshSessionOpenResult {
         port: i64,
         stored_key_type: String,
         stored_fingerprint: String,
+        stored_fingerprints: Option<Box<HostKeyFingerprintsDto>>,
+        /// Set when the saved key was weak or deprecated when it was pinned.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        stored_weak_host_key: Option<String>,
         presented_key_type: String,
         presented_fingerprint: String,
+        presented_fingerprints: Box<HostKeyFingerprintsDto>,
+        warning: String,
+        /// Set when the presented key is weak or deprecated.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        weak_host_key: Option<&'static str>,
+    },
+    HostKeyUnknown {
+        token: String,
+        host: String,
+        port: i64,
+        key_type: String,
+        fingerprint: String,
+        fingerprints: Box<HostKeyFingerprintsDto>,
         warning: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        weak_host_key: Option<&'static str>,
+    },
+    /// The private key is encrypted; retry with `SessionOptions::key_passphrase`.
+    KeyNeedsPassphrase {
+        key_path: String,
+        passphrase_rejected: bool,
+        message: String,
+    },
+    /// No TCP connection could be made; `reason` is a `SshConnectFailure` code.
+    ConnectFailed {
+        reason: &'static str,
+        host: String,
+        port: u16,
+        message: String,
+    },
+}
+
+/// Result of `ssh_session_open_with_sftp`.
+#[derive(Serialize)]
+#[serde(
+    tag = "type",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum SshSftpOpenResult {
+    /// The shell and the SFTP subsession both opened.
+    Opened {
+        session_id: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        weak_host_key: Option<&'static str>,
+        sftp: SftpSessionOpenDto,
+    },
+    /// The shell opened but SFTP didn't; the shell is left open.
+    SftpFailed {
+        session_id: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        weak_host_key: Option<&'static str>,
+        error: SftpErrorDto,
     },
+    /// The shell didn't open, as `ssh_session_open` reports it.
+    Blocked { result: Box<SshSessionOpenResult> },
+}
+
+impl SshSftpOpenResult {
+    fn shell_opened(
+        session_id: String,
+        weak_host_key: Option<&'static str>,
+        sftp: Result<SftpSessionOpenDto, SftpErrorDto>,
+    ) -> Self {
+        match sftp {
+            Ok(sftp) => Self::Opened {
+                session_id,
+                weak_host_key,
+                sftp,
+            },
+            Err(error) => Self::SftpFailed {
+                session_id,
+                weak_host_key,
+                error,
+            },
+        }
+    }
+}
+
+fn host_key_prompt_result(pending: PendingHostKey) -> SshSessionOpenResult {
+    match pending.reason {
+        PendingHostKeyReason::Unknown => SshSessionOpenResult::HostKeyUnknown {
+            warning: format!(
+                "The authenticity of {}:{} can't be established. Verify the fingerprint before trusting this host.",
+                pending.host, pending.port
+            ),
+            token: pending.token,
+            host: pending.host,
+            port: pending.port,
+            key_type: pending.presented_key_type,
+            weak_host_key: pending.presented_weak_advisory,
+            fingerprint: pending.presented_fingerprints.sha256_base64.clone(),
+            fingerprints: Box::new(pending.presented_fingerprints.into()),
+        },
+        PendingHostKeyReason::Mismatch {
+            stored_key_type,
+            stored_fingerprints,
+            stored_weak_advisory,
+        } => SshSessionOpenResult::HostKeyMismatch {
+            warning: format!(
+                "Host key for {}:{} has changed. This may indicate a man-in-the-middle attack or a legitimate server key rotation.",
+                pending.host, pending.port
+            ),
+            token: pending.token,
+            host: pending.host,
+            port: pending.port,
+            stored_key_type,
+            stored_fingerprint: stored_fingerprints
+                .as_ref()
+                .map_or_else(|| "unknown".to_string(), |f| f.sha256_base64.clone()),
+            stored_fingerprints: stored_fingerprints.map(|f| Box::new(f.into())),
+            stored_weak_host_key: stored_weak_advisory,
+            presented_key_type: pending.presented_key_type,
+            weak_host_key: pending.presented_weak_advisory,
+            presented_fingerprint: pending.presented_fingerprints.sha256_base64.clone(),
+            presented_fingerprints: Box::new(pending.presented_fingerprints.into()),
+        },
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HostKeyFingerprintsDto {
+    sha256_base64: String,
+    sha256_hex: String,
+    md5_base64: String,
+    md5_hex: String,
+}
+
+impl From<SshHostKeyFingerprints> for HostKeyFingerprintsDto {
+    fn from(fingerprints: SshHostKeyFingerprints) -> Self {
+        Self {
+            sha256_base64: fingerprints.sha256_base64,
+            sha256_hex: fingerprints.sha256_hex,
+            md5_base64: fingerprints.md5_base64,
+            md5_hex: fingerprints.md5_hex,
+        }
+    }
 }
 
 #[derive(Deserialize, Clone, Copy)]
@@ -278,12 +910,23 @@ pub enum RdpLifecyclePayload {
     Connecting,
     Connected,
     LoginComplete,
+    Retrying {
+        attempt: u32,
+        retries: u32,
+        reason: i32,
+    },
+    /// `failure` is a `RdpConnectFailure` code when `reason` means the connection failed.
     Disconnected {
         reason: i32,
+        extended: i32,
+        failure: Option<&'static str>,
+        message: String,
     },
     FatalError {
         error_code: i32,
     },
+    /// Only the Windows ActiveX host reports logon errors.
+    #[cfg(windows)]
     LogonError {
         error_code: i32,
     },
@@ -294,7 +937,7 @@ pub enum RdpLifecyclePayload {
     },
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct FileEntryDto {
     name: String,
@@ -304,7 +947,11 @@ pub struct FileEntryDto {
     modified_at: Option<u64>,
     owner: Option<String>,
     permissions: Option<u32>,
+    /// `permissions` as `ls -l` shows them, e.g. `drwxr-xr-x`.
+    permissions_str: Option<String>,
     hidden: bool,
+    /// `name` isn't the file's real name: it wasn't valid UTF-8 and was decoded lossily.
+    name_lossy: bool,
 }
 
 #[derive(Serialize)]
@@ -314,6 +961,35 @@ pub struct FileListResultDto {
     entries: Vec<FileEntryDto>,
 }
 
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileListPageDto {
+    cwd: String,
+    entries: Vec<FileEntryDto>,
+    total: Option<u64>,
+    sorted: bool,
+    has_more: bool,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SftpDirChangedDto {
+    watch_id: String,
+    cwd: String,
+    added: Vec<FileEntryDto>,
+    removed: Vec<FileEntryDto>,
+    modified: Vec<FileEntryDto>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SftpRenameResultDto {
+    /// The path the entry was renamed away from, for removing its old row.
+    old_path: String,
+    /// `None` when the renamed entry couldn't be read back; the UI reloads the folder.
+    entry: Option<FileEntryDto>,
+}
+
 #[derive(Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SftpSessionOpenDto {
@@ -327,6 +1003,8 @@ pub struct SftpPathRequest {
     pub ssh_session_id: String,
     pub sftp_session_id: String,
     pub path: String,
+    #[serde(default)]
+    pub create_parents: bool,
 }
 
 #[derive(Deserialize)]
@@ -347,31 +1025,160 @@ pub struct SftpDeleteRequest {
     pub is_dir: bool,
 }
 
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SftpFsStatsDto {
+    total: u64,
+    free: u64,
+    available: u64,
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct SftpListRequest {
     pub ssh_session_id: String,
     pub sftp_session_id: String,
     pub path: String,
+    pub sort: Option<FileSort>,
+    /// When `false`, dotfiles are left out of the listing. Defaults to `true`.
+    pub show_hidden: Option<bool>,
 }
 
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct SftpTransferRequest {
+pub struct SftpListPageRequest {
     pub ssh_session_id: String,
     pub sftp_session_id: String,
-    pub local_path: String,
-    pub remote_path: String,
-    pub overwrite: Option<bool>,
+    pub path: String,
+    #[serde(default)]
+    pub offset: u64,
+    pub limit: Option<usize>,
+    pub sort: Option<bool>,
+    /// How sorted pages are ordered; defaults to directories first, then by name.
+    pub order: Option<FileSort>,
+    /// When `false`, dotfiles are left out of the listing. Defaults to `true`.
+    pub show_hidden: Option<bool>,
 }
 
-#[derive(Serialize, Clone, Copy)]
+#[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
-enum SftpTransferDirectionDto {
-    Upload,
-    Download,
-}
-
+pub struct SftpWatchRequest {
+    pub ssh_session_id: String,
+    pub sftp_session_id: String,
+    pub path: String,
+    pub interval_secs: Option<u64>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SftpTransferRequest {
+    pub ssh_session_id: String,
+    pub sftp_session_id: String,
+    pub local_path: String,
+    pub remote_path: String,
+    pub overwrite: Option<bool>,
+    /// Uploads only: what to do when the remote file exists. Overrides `overwrite`,
+    /// which otherwise picks between `overwrite` and `error`.
+    pub on_conflict: Option<SftpConflictPolicyDto>,
+    /// Downloads only: fetch large files as concurrent ranges. Defaults to on.
+    pub parallel: Option<bool>,
+}
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+pub enum SftpConflictPolicyDto {
+    Error,
+    Overwrite,
+    Resume,
+    Skip,
+    Rename,
+}
+
+impl From<SftpConflictPolicyDto> for SftpConflictPolicy {
+    fn from(policy: SftpConflictPolicyDto) -> Self {
+        match policy {
+            SftpConflictPolicyDto::Error => Self::Error,
+            SftpConflictPolicyDto::Overwrite => Self::Overwrite,
+            SftpConflictPolicyDto::Resume => Self::Resume,
+            SftpConflictPolicyDto::Skip => Self::Skip,
+            SftpConflictPolicyDto::Rename => Self::Rename,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(
+    tag = "type",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum SftpUploadOutcomeDto {
+    /// `remote_path` is where the file went, a numbered name under `rename`.
+    Uploaded {
+        remote_path: String,
+    },
+    Resumed {
+        offset: u64,
+    },
+    Skipped,
+}
+
+impl From<SftpUploadOutcome> for SftpUploadOutcomeDto {
+    fn from(outcome: SftpUploadOutcome) -> Self {
+        match outcome {
+            SftpUploadOutcome::Uploaded { remote_path } => Self::Uploaded { remote_path },
+            SftpUploadOutcome::Resumed { offset } => Self::Resumed { offset },
+            SftpUploadOutcome::Skipped => Self::Skipped,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SftpOpenExternalRequest {
+    pub ssh_session_id: String,
+    pub sftp_session_id: String,
+    pub remote_path: String,
+    /// Open files the OS would execute; without it they are reported but not downloaded.
+    #[serde(default)]
+    pub allow_executable: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SftpOpenExternalResultDto {
+    /// `None` when an executable was not opened because `allowExecutable` was unset.
+    local_path: Option<String>,
+    executable: bool,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SftpExternalSavedDto {
+    local_path: String,
+    remote_path: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SftpTransferBetweenRequest {
+    pub src_ssh_session_id: String,
+    pub src_sftp_session_id: String,
+    pub src_path: String,
+    pub dst_ssh_session_id: String,
+    pub dst_sftp_session_id: String,
+    pub dst_path: String,
+    pub overwrite: Option<bool>,
+}
+
+#[derive(Serialize, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+enum SftpTransferDirectionDto {
+    Upload,
+    Download,
+    RemoteCopy,
+}
+
 #[derive(Serialize, Clone)]
 #[serde(rename_all = "camelCase")]
 struct SftpTransferProgressDto<'a> {
@@ -381,6 +1188,35 @@ struct SftpTransferProgressDto<'a> {
     remote_path: &'a str,
     bytes_transferred: u64,
     total_bytes: Option<u64>,
+    /// Set on "complete" when the destination's size differs from the source's.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    warning: Option<&'a str>,
+}
+
+/// Progress for an SFTP-to-SFTP copy, emitted on both sessions' transfer channels.
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct SftpRemoteCopyProgressDto<'a> {
+    direction: SftpTransferDirectionDto,
+    phase: &'static str,
+    src_sftp_session_id: &'a str,
+    src_path: &'a str,
+    dst_sftp_session_id: &'a str,
+    dst_path: &'a str,
+    bytes_transferred: u64,
+    total_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    warning: Option<&'a str>,
+}
+
+/// `phase` is "parsed" as nodes are read from the file, then "applied" as they are saved.
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct ImportProgressDto<'a> {
+    import_id: &'a str,
+    phase: &'static str,
+    done: usize,
+    total: usize,
 }
 
 #[derive(Deserialize)]
@@ -403,29 +1239,115 @@ pub struct LocalDeleteRequest {
     pub is_dir: bool,
 }
 
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalTransferRequest {
+    pub src_path: String,
+    pub dst_path: String,
+    pub overwrite: Option<bool>,
+}
+
+#[derive(Serialize, Clone, Copy)]
+#[serde(rename_all = "camelCase")]
+enum LocalTransferOperationDto {
+    Copy,
+    Move,
+}
+
+/// Same shape as [`SftpTransferProgressDto`], with source/destination in place of local/remote.
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct LocalTransferProgressDto<'a> {
+    operation: LocalTransferOperationDto,
+    phase: &'static str,
+    src_path: &'a str,
+    dst_path: &'a str,
+    bytes_transferred: u64,
+    total_bytes: Option<u64>,
+}
+
 #[tauri::command]
 pub async fn vault_initialize(
     passphrase: String,
     state: State<'_, AppState>,
 ) -> Result<(), String> {
-    state.vault.initialize(&passphrase).await.map_err(err)
+    state
+        .vaults
+        .default_vault()
+        .initialize(&passphrase)
+        .await
+        .map_err(err)
+}
+
+/// Unlocks `vault_id`, or the default vault when it is omitted.
+#[tauri::command]
+pub async fn vault_unlock(
+    passphrase: String,
+    vault_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let vault_id = vault_id.as_deref().unwrap_or(DEFAULT_VAULT_ID);
+    state
+        .vaults
+        .unlock_vault(vault_id, &passphrase)
+        .await
+        .map_err(err)
+}
+
+/// Locks `vault_id`, or the default vault when it is omitted. Other vaults stay unlocked.
+#[tauri::command]
+pub fn vault_lock(vault_id: Option<String>, state: State<'_, AppState>) -> Result<(), String> {
+    let vault_id = vault_id.as_deref().unwrap_or(DEFAULT_VAULT_ID);
+    state.vaults.lock_vault(vault_id).map_err(err)?;
+    // Cached keys were decrypted with vault-held passphrases, so they go with it.
+    state.ssh.clear_private_key_cache();
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn vault_create(
+    name: String,
+    passphrase: String,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    state
+        .vaults
+        .create_vault(&name, &passphrase)
+        .await
+        .map_err(err)
 }
 
 #[tauri::command]
-pub async fn vault_unlock(passphrase: String, state: State<'_, AppState>) -> Result<(), String> {
-    state.vault.unlock(&passphrase).await.map_err(err)
+pub async fn vault_list(state: State<'_, AppState>) -> Result<Vec<VaultInfoDto>, String> {
+    let vaults = state.vaults.list_vaults().await.map_err(err)?;
+    Ok(vaults.into_iter().map(VaultInfoDto::from).collect())
 }
 
 #[tauri::command]
-pub fn vault_lock(state: State<'_, AppState>) -> Result<(), String> {
-    state.vault.lock().map_err(err)
+pub fn vault_secret_list(
+    vault_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<SecretRef>, String> {
+    let vault_id = vault_id.as_deref().unwrap_or(DEFAULT_VAULT_ID);
+    state
+        .vaults
+        .vault(vault_id)
+        .and_then(|vault| vault.list_secrets_meta())
+        .map_err(err)
+}
+
+/// Returns `false` when no secret had the id.
+#[tauri::command]
+pub async fn vault_secret_delete(id: String, state: State<'_, AppState>) -> Result<bool, String> {
+    state.vaults.delete_secret(&id).await.map_err(err)
 }
 
 #[tauri::command]
 pub async fn vault_status(state: State<'_, AppState>) -> Result<VaultStatus, String> {
+    let vault = state.vaults.default_vault();
     Ok(VaultStatus {
-        initialized: state.vault.is_initialized().await.map_err(err)?,
-        unlocked: state.vault.is_unlocked(),
+        initialized: vault.is_initialized().await.map_err(err)?,
+        unlocked: vault.is_unlocked(),
     })
 }
 
@@ -436,64 +1358,182 @@ pub async fn connection_tree_list(
     state.storage.list_tree().await.map_err(err)
 }
 
+/// Lists a node and its descendants, for patching one branch after `tree://changed`.
 #[tauri::command]
-pub async fn folder_upsert(folder: FolderUpsert, state: State<'_, AppState>) -> Result<(), String> {
-    state.storage.upsert_folder(&folder).await.map_err(err)
+pub async fn connection_subtree_get(
+    node_id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<ConnectionNode>, String> {
+    state.storage.get_subtree(&node_id).await.map_err(err)
+}
+
+/// Renumbers sibling order without changing it. Returns how many nodes changed index.
+#[tauri::command]
+pub async fn connection_tree_tidy(
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<usize, String> {
+    let changed = state.storage.normalize_order().await.map_err(err)?;
+    if changed > 0 {
+        emit_tree_changed(&app, TreeChangeKindDto::Reset, Vec::new());
+    }
+    Ok(changed)
+}
+
+#[tauri::command]
+pub async fn folder_upsert(
+    folder: FolderUpsert,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    state.storage.upsert_folder(&folder).await.map_err(err)?;
+    emit_tree_changed(&app, TreeChangeKindDto::Upserted, vec![Some(folder.id)]);
+    Ok(())
 }
 
+/// New secrets go into `vault_id`, or the default vault when it is omitted.
 #[tauri::command]
 pub async fn connection_upsert(
-    mut connection: ConnectionUpsert,
+    connection: ConnectionUpsert,
+    vault_id: Option<String>,
+    app: AppHandle,
     state: State<'_, AppState>,
 ) -> Result<(), String> {
+    let vault_id = vault_id.as_deref().unwrap_or(DEFAULT_VAULT_ID);
+    let connection_id = connection.id.clone();
+    upsert_connection_with_secrets(&state.storage, &state.vaults, vault_id, connection)
+        .await
+        .map_err(err)?;
+    emit_tree_changed(&app, TreeChangeKindDto::Upserted, vec![Some(connection_id)]);
+    Ok(())
+}
+
+fn emit_tree_changed(app: &AppHandle, kind: TreeChangeKindDto, affected_ids: Vec<Option<String>>) {
+    let _ = app.emit(TREE_CHANGED_EVENT, TreeChangedDto { kind, affected_ids });
+}
+
+/// Stores the connection's plaintext secrets in the vault and upserts the connection.
+///
+/// Secrets are staged first and removed again if the DB write fails, so a failed
+/// (or retried) upsert never leaves unreferenced secrets behind in the vault.
+async fn upsert_connection_with_secrets(
+    storage: &Storage,
+    vaults: &Vaults,
+    vault_id: &str,
+    mut connection: ConnectionUpsert,
+) -> anyhow::Result<ResolvedSecretRefs> {
+    let vault = vaults.vault(vault_id)?;
+    let mut staged = Vec::new();
+    let result = match stage_connection_secrets(&vault, &mut connection, &mut staged).await {
+        Ok(refs) => storage
+            .upsert_connection(&connection, &refs)
+            .await
+            .map(|_| refs),
+        Err(error) => Err(error),
+    };
+
+    if result.is_err() {
+        for secret_id in staged {
+            if let Err(error) = vault.delete_secret(&secret_id).await {
+                tracing::warn!(%secret_id, %error, "failed to discard staged secret");
+            }
+        }
+    }
+
+    result
+}
+
+async fn stage_connection_secrets(
+    vault: &VaultManager,
+    connection: &mut ConnectionUpsert,
+    staged: &mut Vec<String>,
+) -> anyhow::Result<ResolvedSecretRefs> {
     let mut refs = ResolvedSecretRefs::default();
 
     if let Some(ssh) = connection.ssh.as_mut() {
         if let Some(password) = ssh.password.take() {
-            let secret = state
-                .vault
+            let secret = vault
                 .put_secret(janus_domain::SecretKind::Password, &password)
-                .await
-                .map_err(err)?;
+                .await?;
+            staged.push(secret.id.clone());
             refs.ssh_password_ref = Some(secret.id);
         }
 
         if let Some(key_passphrase) = ssh.key_passphrase.take() {
-            let secret = state
-                .vault
+            let secret = vault
                 .put_secret(janus_domain::SecretKind::KeyPassphrase, &key_passphrase)
-                .await
-                .map_err(err)?;
+                .await?;
+            staged.push(secret.id.clone());
             refs.ssh_key_passphrase_ref = Some(secret.id);
         }
     }
 
     if let Some(rdp) = connection.rdp.as_mut() {
         if let Some(password) = rdp.password.take() {
-            let secret = state
-                .vault
+            let secret = vault
                 .put_secret(janus_domain::SecretKind::RdpPassword, &password)
-                .await
-                .map_err(err)?;
+                .await?;
+            staged.push(secret.id.clone());
             refs.rdp_password_ref = Some(secret.id);
         }
     }
 
-    state
+    Ok(refs)
+}
+
+#[tauri::command]
+pub async fn node_move(
+    request: NodeMoveRequest,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let old_parent_id = state
         .storage
-        .upsert_connection(&connection, &refs)
+        .get_node(&request.node_id)
         .await
-        .map_err(err)
+        .map_err(err)?
+        .and_then(|node| node.parent_id);
+    state.storage.move_node(&request).await.map_err(err)?;
+
+    let mut affected_ids = vec![old_parent_id];
+    if !affected_ids.contains(&request.new_parent_id) {
+        affected_ids.push(request.new_parent_id);
+    }
+    emit_tree_changed(&app, TreeChangeKindDto::Moved, affected_ids);
+    Ok(())
 }
 
+/// Checks a move without making it, e.g. while a drag hovers over a drop target.
 #[tauri::command]
-pub async fn node_move(request: NodeMoveRequest, state: State<'_, AppState>) -> Result<(), String> {
-    state.storage.move_node(&request).await.map_err(err)
+pub async fn node_move_check(
+    request: NodeMoveRequest,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    state.storage.can_move(&request).await.map_err(err)
 }
 
 #[tauri::command]
-pub async fn node_delete(node_id: String, state: State<'_, AppState>) -> Result<(), String> {
-    state.storage.delete_node(&node_id).await.map_err(err)
+pub async fn node_delete(
+    node_id: String,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<NodeDeleteResultDto, String> {
+    let vaults = &state.vaults;
+    let report = state
+        .storage
+        .delete_subtree(&node_id, |secret_refs| async move {
+            vaults
+                .delete_secrets(secret_refs.iter().map(String::as_str))
+                .await
+        })
+        .await
+        .map_err(|error| format!("{error:#}"))?;
+    emit_tree_changed(&app, TreeChangeKindDto::Deleted, vec![Some(node_id)]);
+
+    Ok(NodeDeleteResultDto {
+        nodes_removed: report.nodes_removed,
+        secrets_removed: report.secrets_removed,
+    })
 }
 
 #[tauri::command]
@@ -508,13 +1548,7 @@ pub async fn connection_tcp_probe(
         .map_err(err)?
         .ok_or_else(|| "connection not found".to_string())?;
 
-    let (host, port) = if let Some(ssh) = node.ssh {
-        (ssh.host, parse_connection_probe_port("SSH", ssh.port)?)
-    } else if let Some(rdp) = node.rdp {
-        (rdp.host, parse_connection_probe_port("RDP", rdp.port)?)
-    } else {
-        return Err("connection is not SSH or RDP or missing config".to_string());
-    };
+    let (host, port) = connection_probe_target(node)?;
 
     let probe_host = host.clone();
     let reachable =
@@ -525,6 +1559,152 @@ pub async fn connection_tcp_probe(
     Ok(TcpProbeResult { host, reachable })
 }
 
+/// Starts background reachability probes for `connection_ids`, emitting
+/// `connection://{id}/status` after each probe. Replaces any running monitor.
+#[tauri::command]
+pub async fn connection_monitor_start(
+    connection_ids: Vec<String>,
+    interval_secs: u64,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let mut targets = Vec::with_capacity(connection_ids.len());
+    for connection_id in connection_ids {
+        let node = state
+            .storage
+            .get_node(&connection_id)
+            .await
+            .map_err(err)?
+            .ok_or_else(|| format!("connection not found: {connection_id}"))?;
+        let (host, port) = connection_probe_target(node)?;
+        targets.push(MonitorTarget {
+            connection_id,
+            host,
+            port,
+        });
+    }
+
+    state.connection_monitor.start(
+        targets,
+        Duration::from_secs(interval_secs),
+        move |connection_id, status| {
+            let _ = app.emit(&format!("connection://{connection_id}/status"), status);
+        },
+    );
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn connection_monitor_stop(state: State<'_, AppState>) -> Result<(), String> {
+    state.connection_monitor.stop();
+    Ok(())
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SshMetricsDto {
+    active_sessions: u64,
+    sessions_opened: u64,
+    sessions_closed: u64,
+    failed_connects: u64,
+    bytes_uploaded: u64,
+    bytes_downloaded: u64,
+}
+
+impl From<SshMetrics> for SshMetricsDto {
+    fn from(metrics: SshMetrics) -> Self {
+        Self {
+            active_sessions: metrics.active_sessions,
+            sessions_opened: metrics.sessions_opened,
+            sessions_closed: metrics.sessions_closed,
+            failed_connects: metrics.failed_connects,
+            bytes_uploaded: metrics.bytes_uploaded,
+            bytes_downloaded: metrics.bytes_downloaded,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RdpMetricsDto {
+    active_sessions: u64,
+    sessions_opened: u64,
+    sessions_closed: u64,
+    failed_connects: u64,
+}
+
+impl From<RdpMetrics> for RdpMetricsDto {
+    fn from(metrics: RdpMetrics) -> Self {
+        Self {
+            active_sessions: metrics.active_sessions,
+            sessions_opened: metrics.sessions_opened,
+            sessions_closed: metrics.sessions_closed,
+            failed_connects: metrics.failed_connects,
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetricsDto {
+    ssh: SshMetricsDto,
+    rdp: RdpMetricsDto,
+    /// Mean TCP connect time of the connection monitor's successful probes.
+    average_latency_ms: Option<u64>,
+}
+
+/// Counters since the app started, for the diagnostics view. Kept in memory only.
+#[tauri::command]
+pub async fn metrics(state: State<'_, AppState>) -> Result<MetricsDto, String> {
+    Ok(MetricsDto {
+        ssh: state.ssh.metrics().await.into(),
+        rdp: state.rdp.metrics().into(),
+        average_latency_ms: state.connection_monitor.average_latency_ms(),
+    })
+}
+
+/// The most recent log lines, oldest first, for the in-app log viewer. `level` is the
+/// least severe level included (`info` when omitted); lines below `info` are never kept.
+#[tauri::command]
+pub fn log_list(
+    level: Option<String>,
+    limit: Option<usize>,
+    logs: State<'_, LogBuffer>,
+) -> Result<Vec<LogRecord>, String> {
+    let level = match level.as_deref() {
+        Some(level) => level
+            .parse::<tracing::Level>()
+            .map_err(|_| format!("unknown log level: {level}"))?,
+        None => tracing::Level::INFO,
+    };
+    Ok(logs.recent(level, limit.unwrap_or(LOG_BUFFER_CAPACITY)))
+}
+
+/// Reports the algorithms an SSH server negotiates with us, without logging in.
+#[tauri::command]
+pub async fn ssh_algorithms_probe(
+    connection_id: String,
+    state: State<'_, AppState>,
+) -> Result<SshAlgorithmsDto, String> {
+    let node = state
+        .storage
+        .get_node(&connection_id)
+        .await
+        .map_err(err)?
+        .ok_or_else(|| "connection not found".to_string())?;
+    let ssh = node
+        .ssh
+        .ok_or_else(|| "connection is not SSH or missing SSH config".to_string())?;
+    let port = parse_connection_probe_port("SSH", ssh.port)?;
+
+    state
+        .ssh
+        .probe_algorithms(&ssh.host, port, SSH_ALGORITHMS_PROBE_TIMEOUT)
+        .await
+        .map(Into::into)
+        .map_err(err)
+}
+
 #[tauri::command]
 pub async fn connection_saved_password_get(
     connection_id: String,
@@ -547,7 +1727,7 @@ pub async fn connection_saved_password_get(
 
     let secret_ref = secret_ref.ok_or_else(|| "no saved password for connection".to_string())?;
     let password = state
-        .vault
+        .vaults
         .get_secret(secret_ref)
         .map_err(err)?
         .ok_or_else(|| "saved password secret not found".to_string())?;
@@ -559,16 +1739,151 @@ pub async fn connection_saved_password_get(
     Ok(password)
 }
 
+/// Saves `new_password` on `connection_id` or, with `shared`, on every connection whose
+/// saved password matches this one's. Returns how many connections were updated.
 #[tauri::command]
-pub async fn ssh_session_open(
+pub async fn connection_password_update(
     connection_id: String,
-    session_opts: Option<SessionOptions>,
-    app: AppHandle,
+    new_password: String,
+    shared: Option<bool>,
     state: State<'_, AppState>,
-) -> Result<SshSessionOpenResult, String> {
+) -> Result<usize, String> {
+    if new_password.is_empty() {
+        return Err("new password is empty".to_string());
+    }
+    let refs = state.storage.password_refs().await.map_err(err)?;
+    let current = refs
+        .iter()
+        .find(|password_ref| password_ref.node_id == connection_id)
+        .ok_or_else(|| "no saved password for connection".to_string())?;
+
+    let targets = if shared.unwrap_or(false) {
+        let password = state
+            .vaults
+            .get_secret(&current.secret_ref)
+            .map_err(err)?
+            .ok_or_else(|| "saved password secret not found".to_string())?;
+        matching_password_refs(&state.vaults, &refs, &password).map_err(err)?
+    } else {
+        vec![current.clone()]
+    };
+
+    rotate_passwords(&state.storage, &state.vaults, &refs, targets, &new_password)
+        .await
+        .map_err(err)
+}
+
+/// Replaces `old_password` with `new_password` on every connection that saves it, as
+/// when a shared service account's password rotates. Returns how many were updated.
+#[tauri::command]
+pub async fn vault_password_rotate(
+    old_password: String,
+    new_password: String,
+    state: State<'_, AppState>,
+) -> Result<usize, String> {
+    if old_password.is_empty() || new_password.is_empty() {
+        return Err("passwords must not be empty".to_string());
+    }
+    let refs = state.storage.password_refs().await.map_err(err)?;
+    let targets = matching_password_refs(&state.vaults, &refs, &old_password).map_err(err)?;
+
+    rotate_passwords(&state.storage, &state.vaults, &refs, targets, &new_password)
+        .await
+        .map_err(err)
+}
+
+/// The saved passwords equal to `password`. Every vault they are in must be unlocked.
+fn matching_password_refs(
+    vaults: &Vaults,
+    refs: &[PasswordRef],
+    password: &str,
+) -> anyhow::Result<Vec<PasswordRef>> {
+    let mut matching = Vec::new();
+    for password_ref in refs {
+        if vaults.get_secret(&password_ref.secret_ref)?.as_deref() == Some(password) {
+            matching.push(password_ref.clone());
+        }
+    }
+    Ok(matching)
+}
+
+/// Gives the `targets` connections a new secret holding `new_password`, then removes the
+/// secrets they used unless a connection outside `targets` still references them.
+///
+/// Connections that shared a secret keep sharing its replacement, which goes into the
+/// same vault. The new secrets are removed again if the DB update fails.
+async fn rotate_passwords(
+    storage: &Storage,
+    vaults: &Vaults,
+    refs: &[PasswordRef],
+    targets: Vec<PasswordRef>,
+    new_password: &str,
+) -> anyhow::Result<usize> {
+    let mut replacements: HashMap<String, String> = HashMap::new();
+    let updated = async {
+        for target in &targets {
+            if replacements.contains_key(&target.secret_ref) {
+                continue;
+            }
+            let secret = vaults
+                .put_secret_like(&target.secret_ref, new_password)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("saved password secret not found"))?;
+            replacements.insert(target.secret_ref.clone(), secret.id);
+        }
+        let node_refs: Vec<(String, String)> = targets
+            .iter()
+            .map(|target| {
+                let secret_ref = replacements[&target.secret_ref].clone();
+                (target.node_id.clone(), secret_ref)
+            })
+            .collect();
+        storage.set_password_refs(&node_refs).await
+    }
+    .await;
+
+    let updated = match updated {
+        Ok(updated) => updated,
+        Err(error) => {
+            if let Err(error) = vaults
+                .delete_secrets(replacements.values().map(String::as_str))
+                .await
+            {
+                tracing::warn!(%error, "failed to discard staged secrets");
+            }
+            return Err(error);
+        }
+    };
+
+    let still_used: HashSet<&str> = refs
+        .iter()
+        .filter(|password_ref| {
+            !targets
+                .iter()
+                .any(|target| target.node_id == password_ref.node_id)
+        })
+        .map(|password_ref| password_ref.secret_ref.as_str())
+        .collect();
+    let replaced = replacements
+        .keys()
+        .map(String::as_str)
+        .filter(|secret_ref| !still_used.contains(secret_ref));
+    if let Err(error) = vaults.delete_secrets(replaced).await {
+        tracing::warn!(%error, "failed to remove replaced secrets");
+    }
+
+    Ok(updated)
+}
+
+/// Builds the launch config for a stored SSH connection, resolving its vault secrets.
+async fn ssh_launch_config(
+    state: &AppState,
+    connection_id: &str,
+    session_opts: Option<&SessionOptions>,
+) -> Result<SshLaunchConfig, String> {
     let node = state
         .storage
-        .get_node(&connection_id)
+        .get_node(connection_id)
         .await
         .map_err(err)?
         .ok_or_else(|| "connection not found".to_string())?;
@@ -578,62 +1893,137 @@ pub async fn ssh_session_open(
         .ok_or_else(|| "connection is not SSH or missing SSH config".to_string())?;
 
     let password = match ssh.auth_ref.as_ref() {
-        Some(id) => state.vault.get_secret(id).map_err(err)?,
+        Some(id) => state.vaults.get_secret(id).map_err(err)?,
         None => None,
     };
-    let key_passphrase = match ssh.key_passphrase_ref.as_ref() {
-        Some(id) => state.vault.get_secret(id).map_err(err)?,
-        None => None,
+    let key_passphrase = match (
+        session_opts.and_then(|opts| opts.key_passphrase.clone()),
+        ssh.key_passphrase_ref.as_ref(),
+    ) {
+        (Some(passphrase), _) => Some(passphrase),
+        (None, Some(id)) => state.vaults.get_secret(id).map_err(err)?.map(Secret::new),
+        (None, None) => None,
+    };
+    let cols = terminal_dimension(
+        session_opts.and_then(|opts| opts.cols),
+        ssh.default_cols,
+        SSH_DEFAULT_COLS,
+    );
+    let rows = terminal_dimension(
+        session_opts.and_then(|opts| opts.rows),
+        ssh.default_rows,
+        SSH_DEFAULT_ROWS,
+    );
+    let scrollback_limit = if ssh.retain_scrollback {
+        SSH_SCROLLBACK_BYTES
+    } else {
+        0
     };
-    let session_id_hint = session_opts.as_ref().and_then(|o| o.session_id.clone());
-    let cols = session_opts
-        .as_ref()
-        .and_then(|opts| opts.cols)
-        .unwrap_or(120);
-    let rows = session_opts
-        .as_ref()
-        .and_then(|opts| opts.rows)
-        .unwrap_or(32);
 
-    let config = SshLaunchConfig {
+    Ok(SshLaunchConfig {
         host: ssh.host,
         port: ssh.port,
         username: ssh.username,
-        strict_host_key: ssh.strict_host_key,
+        host_key_mode: ssh.host_key_mode,
         key_path: ssh.key_path,
+        cert_path: ssh.cert_path,
         key_passphrase,
-        password,
+        password: password.map(Secret::new),
         cols,
         rows,
-    };
+        cache_private_key: session_opts
+            .and_then(|opts| opts.cache_private_key)
+            .unwrap_or(false),
+        probe_system: session_opts
+            .and_then(|opts| opts.probe_system)
+            .unwrap_or(false),
+        scrollback_limit,
+        proxy_command: ssh
+            .proxy_command
+            .filter(|command| !command.trim().is_empty()),
+        bind_address: ssh_bind_address(ssh.bind_address.as_deref())?,
+        auth_methods: ssh.auth_methods,
+    })
+}
 
-    let (session_id, mut events) = match state.ssh.open_session(&config, session_id_hint).await {
-        Ok(result) => result,
-        Err(error) => {
-            if let Some(mismatch) = state
-                .ssh_host_keys
-                .pending_mismatch_for_host_port(&config.host, config.port)
-                .await
-            {
-                return Ok(SshSessionOpenResult::HostKeyMismatch {
-                    token: mismatch.token,
-                    host: mismatch.host.clone(),
-                    port: mismatch.port,
-                    stored_key_type: mismatch.stored_key_type,
-                    stored_fingerprint: mismatch.stored_fingerprint,
-                    presented_key_type: mismatch.presented_key_type,
-                    presented_fingerprint: mismatch.presented_fingerprint,
-                    warning: format!(
-                        "Host key for {}:{} has changed. This may indicate a man-in-the-middle attack or a legitimate server key rotation.",
-                        mismatch.host, mismatch.port
-                    ),
-                });
-            }
-            return Err(err(error));
+/// The size the frontend asked for, else the connection's configured default.
+fn terminal_dimension(requested: Option<u16>, configured: Option<i64>, fallback: u16) -> u16 {
+    requested
+        .or_else(|| {
+            configured
+                .and_then(|value| u16::try_from(value).ok())
+                .filter(|value| *value > 0)
+        })
+        .unwrap_or(fallback)
+}
+
+#[tauri::command]
+pub async fn ssh_session_open(
+    connection_id: String,
+    session_opts: Option<SessionOptions>,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<SshSessionOpenResult, String> {
+    open_ssh_session(&connection_id, session_opts, app, &state).await
+}
+
+/// Opens the SSH session and then an SFTP subsession on it in one round trip. Prompts
+/// (host key, passphrase) come back as `Blocked`; a failed SFTP open still leaves the
+/// shell open and is reported as `SftpFailed`.
+#[tauri::command]
+pub async fn ssh_session_open_with_sftp(
+    connection_id: String,
+    session_opts: Option<SessionOptions>,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<SshSftpOpenResult, String> {
+    match open_ssh_session(&connection_id, session_opts, app, &state).await? {
+        SshSessionOpenResult::Opened {
+            session_id,
+            weak_host_key,
+        } => {
+            let sftp = open_sftp(&session_id, &state).await;
+            Ok(SshSftpOpenResult::shell_opened(
+                session_id,
+                weak_host_key,
+                sftp,
+            ))
         }
+        blocked => Ok(SshSftpOpenResult::Blocked {
+            result: Box::new(blocked),
+        }),
+    }
+}
+
+async fn open_ssh_session(
+    connection_id: &str,
+    session_opts: Option<SessionOptions>,
+    app: AppHandle,
+    state: &AppState,
+) -> Result<SshSessionOpenResult, String> {
+    let config = ssh_launch_config(state, connection_id, session_opts.as_ref()).await?;
+    let session_id_hint = session_opts.and_then(|opts| opts.session_id);
+
+    let opened = state.ssh.open_session(&config, session_id_hint).await;
+    // Taken on failure too, so a failed open doesn't leave it for the next one to report.
+    let weak_host_key = state
+        .ssh_host_keys
+        .take_weak_pin_advisory(&config.host, config.port);
+    let (session_id, mut events) = match opened {
+        Ok(result) => result,
+        Err(error) => return ssh_open_failure(state, &config, error).await,
     };
     let stdout_event = format!("ssh://{session_id}/stdout");
     let exit_event = format!("ssh://{session_id}/exit");
+    let system_info_event = format!("ssh://{session_id}/system-info");
+    let negotiated_event = format!("ssh://{session_id}/negotiated");
+    let title_event = format!("ssh://{session_id}/title");
+    let heartbeat_event = format!("ssh://{session_id}/heartbeat");
+    let monitor = state.connection_monitor.clone();
+    monitor.session_opened(&session_id, connection_id);
+    let ssh_connections = state.ssh_connections.clone();
+    ssh_connections.opened(&session_id, connection_id);
+    let monitor_session_id = session_id.clone();
 
     tauri::async_runtime::spawn(async move {
         while let Some(event) = events.recv().await {
@@ -642,13 +2032,369 @@ pub async fn ssh_session_open(
                     let _ = app.emit(&stdout_event, chunk);
                 }
                 SshEvent::Exit(code) => {
+                    monitor.session_closed(&monitor_session_id);
+                    ssh_connections.closed(&monitor_session_id);
                     let _ = app.emit(&exit_event, code);
                 }
+                SshEvent::SystemInfo {
+                    os,
+                    shell,
+                    hostname,
+                } => {
+                    let _ = app.emit(
+                        &system_info_event,
+                        SshSystemInfoDto {
+                            os,
+                            shell,
+                            hostname,
+                        },
+                    );
+                }
+                SshEvent::Negotiated(algorithms) => {
+                    let _ = app.emit(&negotiated_event, SshAlgorithmsDto::from(algorithms));
+                }
+                SshEvent::Title(title) => {
+                    let _ = app.emit(&title_event, title);
+                }
+                SshEvent::Heartbeat { last_activity_secs } => {
+                    let _ = app.emit(&heartbeat_event, last_activity_secs);
+                }
             }
         }
-    });
+    });
+
+    Ok(SshSessionOpenResult::Opened {
+        session_id,
+        weak_host_key,
+    })
+}
+
+/// Turns a failed connect into the prompt the UI can act on (host key, passphrase,
+/// unreachable host), or else a message with the credentials redacted.
+async fn ssh_open_failure(
+    state: &AppState,
+    config: &SshLaunchConfig,
+    error: anyhow::Error,
+) -> Result<SshSessionOpenResult, String> {
+    if let Some(pending) = state
+        .ssh_host_keys
+        .pending_host_key_for_host_port(&config.host, config.port)
+        .await
+    {
+        return Ok(host_key_prompt_result(pending));
+    }
+    if let Some(key_error) = error.downcast_ref::<SshKeyError>() {
+        return Ok(SshSessionOpenResult::KeyNeedsPassphrase {
+            key_path: key_error.key_path.clone(),
+            passphrase_rejected: key_error.kind == SshKeyErrorKind::WrongPassphrase,
+            message: key_error.to_string(),
+        });
+    }
+    if let Some(connect_error) = error.downcast_ref::<SshConnectError>() {
+        return Ok(SshSessionOpenResult::ConnectFailed {
+            reason: connect_error.kind.as_str(),
+            host: connect_error.host.clone(),
+            port: connect_error.port,
+            message: connect_error.to_string(),
+        });
+    }
+    let secrets = config.password.iter().chain(&config.key_passphrase);
+    Err(janus_domain::redact(
+        &error.to_string(),
+        secrets.map(|secret| secret.expose().as_str()),
+    ))
+}
+
+#[derive(Serialize)]
+#[serde(
+    tag = "type",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum SshTestResult {
+    Succeeded {
+        /// Set when the host key was seen for the first time and is weak or deprecated.
+        #[serde(skip_serializing_if = "Option::is_none")]
+        weak_host_key: Option<&'static str>,
+    },
+    /// Connected, but the server accepted none of the credentials.
+    AuthFailed { username: String, message: String },
+    /// Stopped before authenticating: a host key or passphrase prompt, or a connect
+    /// failure, as `ssh_session_open` reports them.
+    Blocked { result: Box<SshSessionOpenResult> },
+}
+
+/// Connects and authenticates with the settings and plaintext credentials from the
+/// connection form, then disconnects, so they can be checked before being saved. A host
+/// key seen for the first time is not pinned.
+#[tauri::command]
+pub async fn ssh_test_connection(
+    config_input: SshConfigInput,
+    state: State<'_, AppState>,
+) -> Result<SshTestResult, String> {
+    parse_connection_probe_port("SSH", config_input.port)?;
+    let config = SshLaunchConfig {
+        host: config_input.host,
+        port: config_input.port,
+        username: config_input.username,
+        host_key_mode: config_input.host_key_mode,
+        key_path: config_input.key_path,
+        cert_path: config_input.cert_path,
+        key_passphrase: config_input.key_passphrase.map(Secret::new),
+        password: config_input.password.map(Secret::new),
+        cols: SSH_DEFAULT_COLS,
+        rows: SSH_DEFAULT_ROWS,
+        cache_private_key: false,
+        probe_system: false,
+        scrollback_limit: 0,
+        proxy_command: config_input
+            .proxy_command
+            .filter(|command| !command.trim().is_empty()),
+        bind_address: ssh_bind_address(config_input.bind_address.as_deref())?,
+        auth_methods: config_input.auth_methods,
+    };
+
+    let tested = state.ssh.test_connection(&config).await;
+    let weak_host_key = state
+        .ssh_host_keys
+        .take_weak_pin_advisory(&config.host, config.port);
+    match tested {
+        Ok(()) => Ok(SshTestResult::Succeeded { weak_host_key }),
+        Err(error) => {
+            if let Some(auth_error) = error.downcast_ref::<SshAuthError>() {
+                return Ok(SshTestResult::AuthFailed {
+                    username: auth_error.username.clone(),
+                    message: auth_error.to_string(),
+                });
+            }
+            ssh_open_failure(&state, &config, error)
+                .await
+                .map(|result| SshTestResult::Blocked {
+                    result: Box::new(result),
+                })
+        }
+    }
+}
+
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum RdpTestResult {
+    /// The server answered the X.224 security negotiation; `security` is the protocol it
+    /// picked (`standard`, `tls` or `nla`). Nothing past the negotiation, credentials
+    /// included, is checked.
+    SecurityNegotiated { security: &'static str },
+    /// The server answered but refused the protocols offered.
+    NegotiationFailed { message: String },
+    Unreachable {
+        host: String,
+        port: u16,
+        message: String,
+    },
+}
+
+/// Connects to the RDP server from the connection form and runs only the X.224 security
+/// negotiation, reporting whether it requires NLA. No TLS or NLA handshake is attempted
+/// and credentials aren't sent.
+#[tauri::command]
+pub async fn rdp_test_connection(config_input: RdpConfigInput) -> Result<RdpTestResult, String> {
+    let port = parse_connection_probe_port("RDP", config_input.port)?;
+    let host = config_input.host;
+    match probe_security(&host, port, RDP_TEST_CONNECTION_TIMEOUT).await {
+        Ok(security) => Ok(RdpTestResult::SecurityNegotiated {
+            security: security.as_str(),
+        }),
+        Err(error) if error.is::<RdpNegotiationFailure>() => Ok(RdpTestResult::NegotiationFailed {
+            message: error.to_string(),
+        }),
+        Err(error) if error.is::<std::io::Error>() => Ok(RdpTestResult::Unreachable {
+            message: format!("could not connect to {host}:{port} ({error})"),
+            host,
+            port,
+        }),
+        Err(error) => Err(error.to_string()),
+    }
+}
+
+#[derive(Serialize)]
+#[serde(
+    tag = "type",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum SshPublicKeyInstallResult {
+    Installed {
+        authorized_keys: String,
+    },
+    /// The key was already listed; nothing was written.
+    AlreadyInstalled {
+        authorized_keys: String,
+    },
+    AuthFailed {
+        username: String,
+        message: String,
+    },
+    /// Stopped before authenticating, as in `ssh_test_connection`.
+    Blocked {
+        result: Box<SshSessionOpenResult>,
+    },
+    /// Logged in, but the key couldn't be written; `code` is an `SftpErrorCode`.
+    Failed {
+        code: &'static str,
+        message: String,
+    },
+}
+
+/// Adds `public_key` to `~/.ssh/authorized_keys` on a stored connection's server, like
+/// `ssh-copy-id`, logging in with the connection's current credentials.
+#[tauri::command]
+pub async fn ssh_install_public_key(
+    connection_id: String,
+    public_key: String,
+    state: State<'_, AppState>,
+) -> Result<SshPublicKeyInstallResult, String> {
+    let config = ssh_launch_config(&state, &connection_id, None).await?;
+    let installed = state.ssh.install_public_key(&config, &public_key).await;
+    forget_weak_pin(&state, &config);
+    match installed {
+        Ok(PublicKeyInstall::Installed { authorized_keys }) => {
+            Ok(SshPublicKeyInstallResult::Installed { authorized_keys })
+        }
+        Ok(PublicKeyInstall::AlreadyInstalled { authorized_keys }) => {
+            Ok(SshPublicKeyInstallResult::AlreadyInstalled { authorized_keys })
+        }
+        Err(error) => {
+            if let Some(auth_error) = error.downcast_ref::<SshAuthError>() {
+                return Ok(SshPublicKeyInstallResult::AuthFailed {
+                    username: auth_error.username.clone(),
+                    message: auth_error.to_string(),
+                });
+            }
+            if let Some(sftp_error) = error.downcast_ref::<SftpError>() {
+                return Ok(SshPublicKeyInstallResult::Failed {
+                    code: sftp_error.code.as_str(),
+                    message: sftp_error.to_string(),
+                });
+            }
+            ssh_open_failure(&state, &config, error)
+                .await
+                .map(|result| SshPublicKeyInstallResult::Blocked {
+                    result: Box::new(result),
+                })
+        }
+    }
+}
+
+/// Runs a one-off command against a stored connection without opening a terminal.
+#[tauri::command]
+pub async fn ssh_run_command(
+    connection_id: String,
+    command: String,
+    state: State<'_, AppState>,
+) -> Result<SshRunCommandResultDto, String> {
+    if command.trim().is_empty() {
+        return Err("command must not be empty".to_string());
+    }
+
+    let config = ssh_launch_config(&state, &connection_id, None).await?;
+    let output = match state
+        .ssh
+        .exec(
+            &config,
+            &command,
+            SSH_RUN_COMMAND_TIMEOUT,
+            SSH_RUN_COMMAND_MAX_OUTPUT,
+        )
+        .await
+    {
+        Ok(output) => {
+            forget_weak_pin(&state, &config);
+            output
+        }
+        Err(error) => return Err(run_command_error(&state, &config, error).await),
+    };
+
+    Ok(SshRunCommandResultDto {
+        stdout: output.stdout,
+        stderr: output.stderr,
+        exit_code: output.exit_code,
+        truncated: output.truncated,
+    })
+}
+
+/// Runs a one-off command and returns only the stdout lines matching the `pattern` regex,
+/// so huge outputs are filtered here instead of being sent to the UI.
+#[tauri::command]
+pub async fn ssh_run_command_filtered(
+    connection_id: String,
+    command: String,
+    pattern: String,
+    state: State<'_, AppState>,
+) -> Result<SshFilteredCommandResultDto, String> {
+    if command.trim().is_empty() {
+        return Err("command must not be empty".to_string());
+    }
+    let pattern = Regex::new(&pattern).map_err(|error| format!("invalid pattern: {error}"))?;
+
+    let config = ssh_launch_config(&state, &connection_id, None).await?;
+    let output = match state
+        .ssh
+        .exec_filtered(
+            &config,
+            &command,
+            SSH_RUN_FILTERED_TIMEOUT,
+            SSH_RUN_FILTERED_MAX_MATCHES,
+            |line| pattern.is_match(line),
+        )
+        .await
+    {
+        Ok(output) => {
+            forget_weak_pin(&state, &config);
+            output
+        }
+        Err(error) => return Err(run_command_error(&state, &config, error).await),
+    };
+
+    Ok(SshFilteredCommandResultDto {
+        lines: output.lines,
+        total_lines: output.total_lines,
+        stderr: output.stderr,
+        exit_code: output.exit_code,
+        truncated: output.truncated,
+    })
+}
+
+async fn run_command_error(
+    state: &AppState,
+    config: &SshLaunchConfig,
+    error: anyhow::Error,
+) -> String {
+    forget_weak_pin(state, config);
+    // There's no terminal to show the trust prompt in; the host key has to be approved
+    // through a regular session first.
+    if state
+        .ssh_host_keys
+        .pending_host_key_for_host_port(&config.host, config.port)
+        .await
+        .is_some()
+    {
+        return format!(
+            "host key for {}:{} is not trusted yet; open a terminal session to review it",
+            config.host, config.port
+        );
+    }
+    let secrets = config.password.iter().chain(&config.key_passphrase);
+    janus_domain::redact(
+        &error.to_string(),
+        secrets.map(|secret| secret.expose().as_str()),
+    )
+}
 
-    Ok(SshSessionOpenResult::Opened { session_id })
+/// Drops the advisory for a weak host key pinned by a command that has no way to show
+/// it, so the next session open doesn't report it as its own.
+fn forget_weak_pin(state: &AppState, config: &SshLaunchConfig) {
+    state
+        .ssh_host_keys
+        .take_weak_pin_advisory(&config.host, config.port);
 }
 
 fn tcp_socket_probe(host: &str, port: u16) -> Result<bool, String> {
@@ -688,6 +2434,178 @@ pub async fn ssh_host_key_update_from_mismatch(
         .map_err(err)
 }
 
+/// Lets the next connection use the changed key without saving it.
+#[tauri::command]
+pub async fn ssh_host_key_accept_once(
+    token: String,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    state
+        .ssh_host_keys
+        .accept_pending_mismatch_once(&token)
+        .map_err(err)
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SshKeyGenerateRequest {
+    /// Where the private key goes; the public key is written next to it with `.pub`
+    /// appended. `~` and environment variables are expanded.
+    pub path: String,
+    #[serde(default)]
+    pub key_type: SshKeyType,
+    #[serde(default)]
+    pub comment: String,
+    pub passphrase: Option<Secret<String>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SshKeyGeneratedDto {
+    private_key_path: String,
+    public_key_path: String,
+    /// The line to add to the server's `authorized_keys`.
+    public_key: String,
+    fingerprint: String,
+}
+
+/// Generates a keypair for users without one. Existing files are never overwritten.
+#[tauri::command]
+pub async fn ssh_key_generate(
+    request: SshKeyGenerateRequest,
+) -> Result<SshKeyGeneratedDto, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let private_path = expand_local_path(&request.path, local_home_dir().as_deref(), |name| {
+            std::env::var(name).ok()
+        });
+        let mut public_path = private_path.clone().into_os_string();
+        public_path.push(".pub");
+        let public_path = PathBuf::from(public_path);
+        for path in [&private_path, &public_path] {
+            if path.exists() {
+                return Err(format!("{} already exists", path.display()));
+            }
+        }
+
+        let key = generate_ssh_keypair(
+            request.key_type,
+            &request.comment,
+            request
+                .passphrase
+                .as_ref()
+                .map(|passphrase| passphrase.expose().as_str()),
+        )
+        .map_err(err)?;
+        if let Some(parent) = private_path.parent() {
+            if !parent.as_os_str().is_empty() {
+                std::fs::create_dir_all(parent).map_err(err)?;
+            }
+        }
+        write_new_key_file(&private_path, key.private_openssh.as_bytes(), true).map_err(err)?;
+        if let Err(error) = write_new_key_file(
+            &public_path,
+            format!("{}\n", key.public_openssh).as_bytes(),
+            false,
+        ) {
+            let _ = std::fs::remove_file(&private_path);
+            return Err(err(error));
+        }
+
+        Ok(SshKeyGeneratedDto {
+            private_key_path: private_path.to_string_lossy().to_string(),
+            public_key_path: public_path.to_string_lossy().to_string(),
+            public_key: key.public_openssh,
+            fingerprint: key.fingerprint,
+        })
+    })
+    .await
+    .map_err(err)?
+}
+
+/// Creates `path`, failing if it exists. Private keys are readable only by the owner,
+/// since OpenSSH refuses keys others can read.
+fn write_new_key_file(path: &Path, contents: &[u8], private: bool) -> std::io::Result<()> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    if private {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    #[cfg(not(unix))]
+    let _ = private;
+    options.open(path)?.write_all(contents)
+}
+
+#[tauri::command]
+pub async fn ssh_host_key_approve(token: String, state: State<'_, AppState>) -> Result<(), String> {
+    state
+        .ssh_host_keys
+        .approve_pending_unknown(&token)
+        .await
+        .map_err(err)
+}
+
+/// Idle sessions are only reaped when a limit is set; `None` turns reaping back off.
+#[tauri::command]
+pub async fn ssh_idle_timeout_set(
+    max_idle_secs: Option<u64>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    if max_idle_secs == Some(0) {
+        return Err("idle timeout must be at least 1 second".to_string());
+    }
+
+    state
+        .ssh
+        .set_idle_timeout(max_idle_secs.map(Duration::from_secs));
+    Ok(())
+}
+
+/// Heartbeats are only sent when an interval is set; `None` turns them back off.
+#[tauri::command]
+pub async fn ssh_heartbeat_interval_set(
+    interval_secs: Option<u64>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    if interval_secs == Some(0) {
+        return Err("heartbeat interval must be at least 1 second".to_string());
+    }
+
+    state
+        .ssh
+        .set_heartbeat_interval(interval_secs.map(Duration::from_secs));
+    Ok(())
+}
+
+/// Ranges large SFTP transfers are split into; higher helps on long round trips.
+#[tauri::command]
+pub async fn ssh_sftp_pipeline_depth_set(
+    depth: usize,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    if depth == 0 {
+        return Err("pipeline depth must be at least 1".to_string());
+    }
+
+    state.ssh.set_sftp_pipeline_depth(depth);
+    Ok(())
+}
+
+/// SFTP subsessions allowed per SSH session; keep it under the server's `MaxSessions`.
+#[tauri::command]
+pub async fn ssh_sftp_channel_limit_set(
+    limit: usize,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    if limit == 0 {
+        return Err("SFTP channel limit must be at least 1".to_string());
+    }
+
+    state.ssh.set_sftp_channel_limit(limit);
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn ssh_session_write(
     session_id: String,
@@ -697,6 +2615,75 @@ pub async fn ssh_session_write(
     state.ssh.write(&session_id, &data).await.map_err(err)
 }
 
+#[tauri::command]
+pub async fn ssh_session_paste(
+    session_id: String,
+    data: String,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    state.ssh.write_paste(&session_id, &data).await.map_err(err)
+}
+
+/// Echoes typing locally for remote shells that don't echo it back.
+#[tauri::command]
+pub async fn ssh_session_local_echo_set(
+    session_id: String,
+    enabled: bool,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    state
+        .ssh
+        .set_local_echo(&session_id, enabled)
+        .await
+        .map_err(err)
+}
+
+#[tauri::command]
+pub async fn snippet_list(state: State<'_, AppState>) -> Result<Vec<Snippet>, String> {
+    state.storage.list_snippets().await.map_err(err)
+}
+
+#[tauri::command]
+pub async fn snippet_upsert(snippet: Snippet, state: State<'_, AppState>) -> Result<(), String> {
+    state.storage.upsert_snippet(&snippet).await.map_err(err)
+}
+
+#[tauri::command]
+pub async fn snippet_delete(snippet_id: String, state: State<'_, AppState>) -> Result<(), String> {
+    state.storage.delete_snippet(&snippet_id).await.map_err(err)
+}
+
+/// Types a saved snippet into the session, filling its `${name}` placeholders from
+/// `values`. Lines go out `line_delay_ms` apart; with `trailing_newline` the last one
+/// is submitted too.
+#[tauri::command]
+pub async fn ssh_session_send_snippet(
+    session_id: String,
+    snippet_id: String,
+    values: Option<HashMap<String, String>>,
+    trailing_newline: Option<bool>,
+    line_delay_ms: Option<u64>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let snippet = state
+        .storage
+        .get_snippet(&snippet_id)
+        .await
+        .map_err(err)?
+        .ok_or_else(|| format!("unknown snippet: {snippet_id}"))?;
+    let text = expand_snippet(&snippet.body, &values.unwrap_or_default()).map_err(err)?;
+    state
+        .ssh
+        .write_lines(
+            &session_id,
+            &text,
+            trailing_newline.unwrap_or(true),
+            Duration::from_millis(line_delay_ms.unwrap_or(0)),
+        )
+        .await
+        .map_err(err)
+}
+
 #[tauri::command]
 pub async fn ssh_session_resize(
     session_id: String,
@@ -707,6 +2694,55 @@ pub async fn ssh_session_resize(
     state.ssh.resize(&session_id, cols, rows).await.map_err(err)
 }
 
+/// Returns the output retained for a session, or `None` when its connection doesn't
+/// keep scrollback.
+#[tauri::command]
+pub async fn ssh_session_scrollback(
+    session_id: String,
+    state: State<'_, AppState>,
+) -> Result<Option<String>, String> {
+    state.ssh.scrollback(&session_id).await.map_err(err)
+}
+
+/// Why a session ended, for a UI that missed its exit event (e.g. after a reload);
+/// `None` while it is open or once it ended more than a few minutes ago.
+#[tauri::command]
+pub async fn ssh_session_last_error(
+    session_id: String,
+    state: State<'_, AppState>,
+) -> Result<Option<SshSessionExitDto>, String> {
+    Ok(state.ssh.last_exit(&session_id).map(Into::into))
+}
+
+/// Reports how much terminal input is waiting on a slow or stalled remote.
+#[tauri::command]
+pub async fn ssh_session_write_queue(
+    session_id: String,
+    state: State<'_, AppState>,
+) -> Result<SshWriteQueueStatsDto, String> {
+    state
+        .ssh
+        .write_queue_stats(&session_id)
+        .await
+        .map(Into::into)
+        .map_err(err)
+}
+
+/// Reports the `TERM` and environment a session's shell was started with, for the
+/// terminal to pick character widths that match the remote.
+#[tauri::command]
+pub async fn ssh_session_env(
+    session_id: String,
+    state: State<'_, AppState>,
+) -> Result<SshSessionEnvDto, String> {
+    state
+        .ssh
+        .session_env(&session_id)
+        .await
+        .map(Into::into)
+        .map_err(err)
+}
+
 #[tauri::command]
 pub async fn ssh_session_close(
     session_id: String,
@@ -715,12 +2751,49 @@ pub async fn ssh_session_close(
     state.ssh.close(&session_id).await.map_err(err)
 }
 
+/// Aborts an `ssh_session_open` still connecting or authenticating; `pending_id` is the
+/// `sessionId` it was given. False when there is nothing left to cancel.
+#[tauri::command]
+pub fn ssh_cancel_open(pending_id: String, state: State<'_, AppState>) -> Result<bool, String> {
+    Ok(state.ssh.cancel_open(&pending_id))
+}
+
 #[tauri::command]
 pub async fn ssh_sftp_open(
     ssh_session_id: String,
     state: State<'_, AppState>,
-) -> Result<SftpSessionOpenDto, String> {
-    let (sftp_session_id, remote_cwd) = state.ssh.sftp_open(&ssh_session_id).await.map_err(err)?;
+) -> Result<SftpSessionOpenDto, SftpErrorDto> {
+    open_sftp(&ssh_session_id, &state).await
+}
+
+async fn open_sftp(
+    ssh_session_id: &str,
+    state: &AppState,
+) -> Result<SftpSessionOpenDto, SftpErrorDto> {
+    let connection_id = state.ssh_connections.connection(ssh_session_id);
+    let start_dir = match &connection_id {
+        Some(connection_id) => state
+            .storage
+            .get_node(connection_id)
+            .await
+            .ok()
+            .flatten()
+            .and_then(|node| node.ssh)
+            .filter(|ssh| ssh.remember_sftp_path)
+            .and_then(|ssh| ssh.last_sftp_path),
+        None => None,
+    };
+    let (sftp_session_id, remote_cwd) = state
+        .ssh
+        .sftp_open(ssh_session_id, start_dir.as_deref())
+        .await
+        .map_err(sftp_err)?;
+    // Starting out where the connection already remembers needn't be written again.
+    if start_dir.as_deref() == Some(remote_cwd.as_str()) {
+        state
+            .ssh_connections
+            .sftp_path_changed(ssh_session_id, &remote_cwd);
+    }
     Ok(SftpSessionOpenDto {
         sftp_session_id,
         remote_cwd,
@@ -732,69 +2805,176 @@ pub async fn ssh_sftp_close(
     ssh_session_id: String,
     sftp_session_id: String,
     state: State<'_, AppState>,
-) -> Result<(), String> {
+) -> Result<(), SftpErrorDto> {
+    state.external_opens.forget_session(&sftp_session_id);
     state
         .ssh
         .sftp_close(&ssh_session_id, &sftp_session_id)
         .await
-        .map_err(err)
+        .map_err(sftp_err)
 }
 
 #[tauri::command]
 pub async fn ssh_sftp_list(
     request: SftpListRequest,
     state: State<'_, AppState>,
-) -> Result<FileListResultDto, String> {
+) -> Result<FileListResultDto, SftpErrorDto> {
     let list = state
         .ssh
         .sftp_list(
             &request.ssh_session_id,
             &request.sftp_session_id,
             &request.path,
+            request.sort.unwrap_or_default(),
         )
         .await
-        .map_err(err)?;
-    Ok(sftp_list_to_dto(list))
+        .map_err(sftp_err)?;
+    record_sftp_cwd(&state, &request.ssh_session_id, &list.cwd).await;
+    let mut list = sftp_list_to_dto(list);
+    if !request.show_hidden.unwrap_or(true) {
+        list.entries.retain(|entry| !entry.hidden);
+    }
+    Ok(list)
+}
+
+/// Remembers `cwd` as where the session's connection opens SFTP next time.
+async fn record_sftp_cwd(state: &AppState, ssh_session_id: &str, cwd: &str) {
+    let Some(connection_id) = state.ssh_connections.sftp_path_changed(ssh_session_id, cwd) else {
+        return;
+    };
+    if let Err(error) = state
+        .storage
+        .record_last_sftp_path(&connection_id, cwd)
+        .await
+    {
+        tracing::debug!(%connection_id, %error, "failed to record last sftp path");
+    }
+}
+
+/// Expands `~`, `~user` and `$HOME` in a path typed into the remote path bar; listings
+/// take their path as it is.
+#[tauri::command]
+pub async fn ssh_sftp_expand_path(
+    ssh_session_id: String,
+    path: String,
+    state: State<'_, AppState>,
+) -> Result<String, SftpErrorDto> {
+    state
+        .ssh
+        .sftp_expand_path(&ssh_session_id, &path)
+        .await
+        .map_err(sftp_err)
+}
+
+/// Lists a directory one page at a time; see `SshSessionManager::sftp_list_page`.
+#[tauri::command]
+pub async fn ssh_sftp_list_page(
+    request: SftpListPageRequest,
+    state: State<'_, AppState>,
+) -> Result<FileListPageDto, SftpErrorDto> {
+    let page = state
+        .ssh
+        .sftp_list_page(
+            &request.ssh_session_id,
+            &request.sftp_session_id,
+            &request.path,
+            request.offset,
+            request.limit.unwrap_or(SFTP_LIST_PAGE_SIZE),
+            SftpListPageOptions {
+                sort: request
+                    .sort
+                    .unwrap_or(true)
+                    .then(|| request.order.unwrap_or_default()),
+                show_hidden: request.show_hidden.unwrap_or(true),
+            },
+        )
+        .await
+        .map_err(sftp_err)?;
+    if request.offset == 0 {
+        record_sftp_cwd(&state, &request.ssh_session_id, &page.cwd).await;
+    }
+    Ok(sftp_list_page_to_dto(page))
+}
+
+#[tauri::command]
+pub async fn ssh_sftp_watch(
+    request: SftpWatchRequest,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<String, SftpErrorDto> {
+    let event_name = sftp_dir_changed_event_name(&request.sftp_session_id);
+    let interval = Duration::from_secs(request.interval_secs.unwrap_or(5));
+
+    state
+        .ssh
+        .sftp_watch(
+            &request.ssh_session_id,
+            &request.sftp_session_id,
+            &request.path,
+            interval,
+            move |watch_id, changes| {
+                let _ = app.emit(&event_name, sftp_dir_changes_to_dto(watch_id, changes));
+            },
+        )
+        .await
+        .map_err(sftp_err)
+}
+
+#[tauri::command]
+pub async fn ssh_sftp_unwatch(
+    ssh_session_id: String,
+    watch_id: String,
+    state: State<'_, AppState>,
+) -> Result<(), SftpErrorDto> {
+    state
+        .ssh
+        .sftp_unwatch(&ssh_session_id, &watch_id)
+        .await
+        .map_err(sftp_err)
 }
 
 #[tauri::command]
 pub async fn ssh_sftp_new_file(
     request: SftpPathRequest,
     state: State<'_, AppState>,
-) -> Result<(), String> {
+) -> Result<Option<FileEntryDto>, SftpErrorDto> {
     state
         .ssh
         .sftp_new_file(
             &request.ssh_session_id,
             &request.sftp_session_id,
             &request.path,
+            request.create_parents,
         )
         .await
-        .map_err(err)
+        .map(|entry| entry.map(sftp_entry_to_dto))
+        .map_err(sftp_err)
 }
 
 #[tauri::command]
 pub async fn ssh_sftp_new_folder(
     request: SftpPathRequest,
     state: State<'_, AppState>,
-) -> Result<(), String> {
+) -> Result<Option<FileEntryDto>, SftpErrorDto> {
     state
         .ssh
         .sftp_new_folder(
             &request.ssh_session_id,
             &request.sftp_session_id,
             &request.path,
+            request.create_parents,
         )
         .await
-        .map_err(err)
+        .map(|entry| entry.map(sftp_entry_to_dto))
+        .map_err(sftp_err)
 }
 
 #[tauri::command]
 pub async fn ssh_sftp_rename(
     request: SftpRenameRequest,
     state: State<'_, AppState>,
-) -> Result<(), String> {
-    state
+) -> Result<SftpRenameResultDto, SftpErrorDto> {
+    let entry = state
         .ssh
         .sftp_rename(
             &request.ssh_session_id,
@@ -803,14 +2983,18 @@ pub async fn ssh_sftp_rename(
             &request.new_path,
         )
         .await
-        .map_err(err)
+        .map_err(sftp_err)?;
+    Ok(SftpRenameResultDto {
+        old_path: request.old_path,
+        entry: entry.map(sftp_entry_to_dto),
+    })
 }
 
 #[tauri::command]
 pub async fn ssh_sftp_delete(
     request: SftpDeleteRequest,
     state: State<'_, AppState>,
-) -> Result<(), String> {
+) -> Result<(), SftpErrorDto> {
     state
         .ssh
         .sftp_delete(
@@ -820,7 +3004,28 @@ pub async fn ssh_sftp_delete(
             request.is_dir,
         )
         .await
-        .map_err(err)
+        .map_err(sftp_err)
+}
+
+/// Free space on the remote file system holding `path`; fails with the `unsupported`
+/// code when the server lacks the `statvfs@openssh.com` extension.
+#[tauri::command]
+pub async fn ssh_sftp_statvfs(
+    ssh_session_id: String,
+    sftp_session_id: String,
+    path: String,
+    state: State<'_, AppState>,
+) -> Result<SftpFsStatsDto, SftpErrorDto> {
+    let stats = state
+        .ssh
+        .sftp_statvfs(&ssh_session_id, &sftp_session_id, &path)
+        .await
+        .map_err(sftp_err)?;
+    Ok(SftpFsStatsDto {
+        total: stats.total,
+        free: stats.free,
+        available: stats.available,
+    })
 }
 
 #[tauri::command]
@@ -828,21 +3033,26 @@ pub async fn ssh_sftp_upload_file(
     request: SftpTransferRequest,
     app: AppHandle,
     state: State<'_, AppState>,
-) -> Result<(), String> {
+) -> Result<SftpUploadOutcomeDto, SftpErrorDto> {
     let mut started = false;
     let mut last_progress = SftpTransferProgress {
         bytes_transferred: 0,
         total_bytes: None,
     };
+    let on_conflict = match (request.on_conflict, request.overwrite) {
+        (Some(policy), _) => policy.into(),
+        (None, Some(true)) => SftpConflictPolicy::Overwrite,
+        (None, _) => SftpConflictPolicy::Error,
+    };
 
-    let result = state
+    let outcome = state
         .ssh
         .sftp_upload_file_with_progress(
             &request.ssh_session_id,
             &request.sftp_session_id,
             Path::new(&request.local_path),
             &request.remote_path,
-            request.overwrite.unwrap_or(false),
+            on_conflict,
             |progress| {
                 let phase = if started { "progress" } else { "start" };
                 started = true;
@@ -859,21 +3069,35 @@ pub async fn ssh_sftp_upload_file(
             },
         )
         .await
-        .map_err(err);
+        .map_err(sftp_err)?;
 
-    if result.is_ok() {
-        emit_sftp_transfer_progress(
-            &app,
+    let remote_path = match &outcome {
+        SftpUploadOutcome::Skipped => return Ok(outcome.into()),
+        SftpUploadOutcome::Uploaded { remote_path } => remote_path,
+        SftpUploadOutcome::Resumed { .. } => &request.remote_path,
+    };
+    let final_size = state
+        .ssh
+        .sftp_stat(
+            &request.ssh_session_id,
             &request.sftp_session_id,
-            SftpTransferDirectionDto::Upload,
-            "complete",
-            &request.local_path,
-            &request.remote_path,
-            last_progress,
-        );
-    }
-
-    result
+            remote_path,
+        )
+        .await
+        .ok()
+        .flatten()
+        .and_then(|stat| stat.size);
+    let (progress, warning) = completed_transfer_progress(last_progress, final_size, remote_path);
+    emit_sftp_transfer_complete(
+        &app,
+        &request.sftp_session_id,
+        SftpTransferDirectionDto::Upload,
+        &request.local_path,
+        remote_path,
+        progress,
+        warning.as_deref(),
+    );
+    Ok(outcome.into())
 }
 
 #[tauri::command]
@@ -881,7 +3105,7 @@ pub async fn ssh_sftp_download_file(
     request: SftpTransferRequest,
     app: AppHandle,
     state: State<'_, AppState>,
-) -> Result<(), String> {
+) -> Result<(), SftpErrorDto> {
     let mut started = false;
     let mut last_progress = SftpTransferProgress {
         bytes_transferred: 0,
@@ -895,7 +3119,10 @@ pub async fn ssh_sftp_download_file(
             &request.sftp_session_id,
             &request.remote_path,
             Path::new(&request.local_path),
-            request.overwrite.unwrap_or(false),
+            SftpDownloadOptions {
+                overwrite: request.overwrite.unwrap_or(false),
+                parallel: request.parallel.unwrap_or(true),
+            },
             |progress| {
                 let phase = if started { "progress" } else { "start" };
                 started = true;
@@ -912,28 +3139,162 @@ pub async fn ssh_sftp_download_file(
             },
         )
         .await
-        .map_err(err);
+        .map_err(sftp_err);
 
     if result.is_ok() {
-        emit_sftp_transfer_progress(
+        let final_size = local_file_size(&request.local_path).await;
+        let (progress, warning) =
+            completed_transfer_progress(last_progress, final_size, &request.local_path);
+        emit_sftp_transfer_complete(
             &app,
             &request.sftp_session_id,
             SftpTransferDirectionDto::Download,
-            "complete",
             &request.local_path,
             &request.remote_path,
-            last_progress,
+            progress,
+            warning.as_deref(),
         );
     }
 
     result
 }
 
+/// Downloads a remote file to a temp copy and opens it with the system default
+/// application. Saves to the copy are reported as `sftp://{id}/external-saved` until the
+/// SFTP session closes, so the UI can offer to upload them.
 #[tauri::command]
-pub async fn local_fs_list(path: String) -> Result<FileListResultDto, String> {
-    tauri::async_runtime::spawn_blocking(move || local_list_impl(&path))
+pub async fn ssh_sftp_open_external(
+    request: SftpOpenExternalRequest,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<SftpOpenExternalResultDto, SftpErrorDto> {
+    let executable = is_executable_type(&request.remote_path);
+    if executable && !request.allow_executable {
+        return Ok(SftpOpenExternalResultDto {
+            local_path: None,
+            executable,
+        });
+    }
+
+    let local_path = state
+        .external_opens
+        .prepare(&request.remote_path)
+        .map_err(sftp_err)?;
+    state
+        .ssh
+        .sftp_download_file(
+            &request.ssh_session_id,
+            &request.sftp_session_id,
+            &request.remote_path,
+            &local_path,
+            true,
+        )
         .await
-        .map_err(err)?
+        .map_err(sftp_err)?;
+
+    let local_path_str = local_path.to_string_lossy().to_string();
+    app.opener()
+        .open_path(local_path_str.clone(), None::<&str>)
+        .map_err(|error| sftp_err(anyhow::anyhow!("opening {local_path_str}: {error}")))?;
+
+    let event_name = sftp_external_saved_event_name(&request.sftp_session_id);
+    let payload = SftpExternalSavedDto {
+        local_path: local_path_str.clone(),
+        remote_path: request.remote_path.clone(),
+    };
+    if let Err(error) =
+        state
+            .external_opens
+            .watch(&request.sftp_session_id, &local_path, move || {
+                let _ = app.emit(&event_name, payload.clone());
+            })
+    {
+        tracing::debug!("not watching {local_path_str} for saves: {error:#}");
+    }
+
+    Ok(SftpOpenExternalResultDto {
+        local_path: Some(local_path_str),
+        executable,
+    })
+}
+
+#[tauri::command]
+pub async fn ssh_sftp_transfer_between(
+    request: SftpTransferBetweenRequest,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<(), SftpErrorDto> {
+    let mut started = false;
+    let mut last_progress = SftpTransferProgress {
+        bytes_transferred: 0,
+        total_bytes: None,
+    };
+
+    let result = state
+        .ssh
+        .sftp_transfer_between_with_progress(
+            SftpEndpoint {
+                session_id: &request.src_ssh_session_id,
+                sftp_session_id: &request.src_sftp_session_id,
+                path: &request.src_path,
+            },
+            SftpEndpoint {
+                session_id: &request.dst_ssh_session_id,
+                sftp_session_id: &request.dst_sftp_session_id,
+                path: &request.dst_path,
+            },
+            request.overwrite.unwrap_or(false),
+            |progress| {
+                let phase = if started { "progress" } else { "start" };
+                started = true;
+                last_progress = progress;
+                emit_sftp_remote_copy_progress(&app, &request, phase, progress, None);
+            },
+        )
+        .await
+        .map_err(sftp_err);
+
+    if result.is_ok() {
+        let final_size = state
+            .ssh
+            .sftp_stat(
+                &request.dst_ssh_session_id,
+                &request.dst_sftp_session_id,
+                &request.dst_path,
+            )
+            .await
+            .ok()
+            .flatten()
+            .and_then(|stat| stat.size);
+        let (progress, warning) =
+            completed_transfer_progress(last_progress, final_size, &request.dst_path);
+        emit_sftp_remote_copy_progress(&app, &request, "complete", progress, warning.as_deref());
+    }
+
+    result
+}
+
+#[tauri::command]
+pub async fn local_fs_list(
+    path: String,
+    sort: Option<FileSort>,
+    show_hidden: Option<bool>,
+) -> Result<FileListResultDto, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        local_list_impl(&path, sort.unwrap_or_default(), show_hidden.unwrap_or(true))
+    })
+    .await
+    .map_err(err)?
+}
+
+/// Expands `~` and environment variables in a path typed into the local path bar;
+/// listings take their path as it is.
+#[tauri::command]
+pub fn local_fs_expand_path(path: String) -> Result<String, String> {
+    let expanded = expand_local_path(&path, local_home_dir().as_deref(), |name| {
+        std::env::var(name).ok()
+    });
+    Ok(normalize_path_string(&expanded))
 }
 
 #[tauri::command]
@@ -988,6 +3349,95 @@ pub async fn local_fs_delete(request: LocalDeleteRequest) -> Result<(), String>
     .map_err(err)?
 }
 
+#[tauri::command]
+pub async fn local_fs_copy(request: LocalTransferRequest, app: AppHandle) -> Result<(), String> {
+    run_local_transfer(request, app, LocalTransferOperationDto::Copy).await
+}
+
+#[tauri::command]
+pub async fn local_fs_move(request: LocalTransferRequest, app: AppHandle) -> Result<(), String> {
+    run_local_transfer(request, app, LocalTransferOperationDto::Move).await
+}
+
+async fn run_local_transfer(
+    request: LocalTransferRequest,
+    app: AppHandle,
+    operation: LocalTransferOperationDto,
+) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let emit = |phase: &'static str, progress: LocalTransferProgress| {
+            let _ = app.emit(
+                LOCAL_FS_TRANSFER_EVENT,
+                LocalTransferProgressDto {
+                    operation,
+                    phase,
+                    src_path: &request.src_path,
+                    dst_path: &request.dst_path,
+                    bytes_transferred: progress.bytes_transferred,
+                    total_bytes: Some(progress.total_bytes),
+                },
+            );
+        };
+
+        let mut last_emitted: Option<Instant> = None;
+        let mut last_progress = LocalTransferProgress {
+            bytes_transferred: 0,
+            total_bytes: 0,
+        };
+        let on_progress = |progress| {
+            last_progress = progress;
+            match last_emitted {
+                None => emit("start", progress),
+                Some(at) if at.elapsed() >= LOCAL_FS_TRANSFER_PROGRESS_INTERVAL => {
+                    emit("progress", progress)
+                }
+                Some(_) => return,
+            }
+            last_emitted = Some(Instant::now());
+        };
+
+        let src = Path::new(&request.src_path);
+        let dst = Path::new(&request.dst_path);
+        let overwrite = request.overwrite.unwrap_or(false);
+        match operation {
+            LocalTransferOperationDto::Copy => copy_path(src, dst, overwrite, on_progress),
+            LocalTransferOperationDto::Move => move_path(src, dst, overwrite, on_progress),
+        }
+        .map_err(err)?;
+
+        emit("complete", last_progress);
+        Ok(())
+    })
+    .await
+    .map_err(err)?
+}
+
+#[tauri::command]
+pub async fn local_fs_watch(
+    path: String,
+    app: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let requested = if path.trim().is_empty() {
+        local_default_dir()?
+    } else {
+        PathBuf::from(path)
+    };
+    let dir = std::fs::canonicalize(&requested).unwrap_or(requested);
+
+    state
+        .local_fs_watchers
+        .watch(&dir, move |token, changes| {
+            let _ = app.emit(&local_fs_changed_event_name(token), changes);
+        })
+        .map_err(err)
+}
+
+#[tauri::command]
+pub fn local_fs_unwatch(token: String, state: State<'_, AppState>) -> Result<(), String> {
+    state.local_fs_watchers.unwatch(&token).map_err(err)
+}
+
 #[tauri::command]
 pub async fn rdp_launch(
     _connection_id: String,
@@ -1016,7 +3466,7 @@ pub async fn rdp_session_open(
         .ok_or_else(|| "connection is not RDP or missing RDP config".to_string())?;
 
     let password = match rdp.credential_ref.as_ref() {
-        Some(id) => state.vault.get_secret(id).map_err(err)?,
+        Some(id) => state.vaults.get_secret(id).map_err(err)?,
         None => None,
     };
 
@@ -1027,16 +3477,26 @@ pub async fn rdp_session_open(
     let lifecycle_event = format!("rdp://{session_id}/state");
     let exit_event = format!("rdp://{session_id}/exit");
     let app_for_events = app.clone();
+    let monitor = state.connection_monitor.clone();
+    let monitor_session_id = session_id.clone();
 
+    let (username, domain) = normalize_rdp_logon(rdp.username, rdp.domain);
     let config = RdpSessionConfig {
         host: rdp.host,
         port: parse_rdp_port(rdp.port)?,
-        username: rdp.username,
-        password,
-        domain: rdp.domain,
+        username,
+        password: password.map(Secret::new),
+        domain,
         width: parse_rdp_dimension("width", rdp.width)?,
         height: parse_rdp_dimension("height", rdp.height)?,
+        connect_timeout_secs: parse_rdp_connect_timeout(rdp.connect_timeout_secs)?,
+        connect_retries: u32::try_from(rdp.connect_retries)
+            .map_err(|_| format!("invalid RDP connect retries: {}", rdp.connect_retries))?,
+        redirect_smartcards: rdp.redirect_smartcards,
+        bitmap_cache: rdp.bitmap_cache,
+        experience: rdp.experience,
     };
+    let connect_retries = config.connect_retries;
 
     tauri::async_runtime::spawn(async move {
         while let Some(event) = event_rx.recv().await {
@@ -1045,17 +3505,30 @@ pub async fn rdp_session_open(
                     let _ = app_for_events.emit(&lifecycle_event, RdpLifecyclePayload::Connecting);
                 }
                 RdpActiveXEvent::Connected { .. } => {
+                    monitor.session_opened(&monitor_session_id, &connection_id);
                     let _ = app_for_events.emit(&lifecycle_event, RdpLifecyclePayload::Connected);
                 }
                 RdpActiveXEvent::LoginComplete { .. } => {
                     let _ =
                         app_for_events.emit(&lifecycle_event, RdpLifecyclePayload::LoginComplete);
                 }
-                RdpActiveXEvent::Disconnected { reason, .. } => {
+                RdpActiveXEvent::Retrying {
+                    attempt, reason, ..
+                } => {
                     let _ = app_for_events.emit(
                         &lifecycle_event,
-                        RdpLifecyclePayload::Disconnected { reason },
+                        RdpLifecyclePayload::Retrying {
+                            attempt,
+                            retries: connect_retries,
+                            reason,
+                        },
                     );
+                }
+                RdpActiveXEvent::Disconnected {
+                    reason, extended, ..
+                } => {
+                    let _ = app_for_events
+                        .emit(&lifecycle_event, rdp_disconnected_payload(reason, extended));
                     let _ = app_for_events.emit(&exit_event, reason.to_string());
                     break;
                 }
@@ -1067,6 +3540,7 @@ pub async fn rdp_session_open(
                     let _ = app_for_events.emit(&exit_event, format!("fatal:{error_code}"));
                     break;
                 }
+                #[cfg(windows)]
                 RdpActiveXEvent::LogonError { error_code, .. } => {
                     let _ = app_for_events.emit(
                         &lifecycle_event,
@@ -1092,6 +3566,7 @@ pub async fn rdp_session_open(
                 }
             }
         }
+        monitor.session_closed(&monitor_session_id);
     });
 
     state
@@ -1114,6 +3589,48 @@ pub async fn rdp_session_open(
     Ok(session_id)
 }
 
+fn rdp_disconnected_payload(reason: i32, extended: i32) -> RdpLifecyclePayload {
+    RdpLifecyclePayload::Disconnected {
+        reason,
+        extended,
+        failure: RdpConnectFailure::from_disconnect_reason(reason).map(|failure| failure.as_str()),
+        message: describe_disconnect(reason, extended),
+    }
+}
+
+/// The last error an RDP session reported, as its `rdp://{id}/state` event carried it,
+/// for a UI that missed the event; `None` if there was none in the last few minutes.
+#[tauri::command]
+pub async fn rdp_session_last_error(
+    session_id: String,
+    state: State<'_, AppState>,
+) -> Result<Option<RdpLifecyclePayload>, String> {
+    let payload = match state.rdp.last_error(&session_id) {
+        Some(RdpActiveXEvent::Disconnected {
+            reason, extended, ..
+        }) => rdp_disconnected_payload(reason, extended),
+        Some(RdpActiveXEvent::FatalError { error_code, .. }) => {
+            RdpLifecyclePayload::FatalError { error_code }
+        }
+        #[cfg(windows)]
+        Some(RdpActiveXEvent::LogonError { error_code, .. }) => {
+            RdpLifecyclePayload::LogonError { error_code }
+        }
+        Some(RdpActiveXEvent::HostInitFailed {
+            stage,
+            hresult,
+            message,
+            ..
+        }) => RdpLifecyclePayload::HostInitFailed {
+            stage,
+            hresult,
+            message,
+        },
+        _ => return Ok(None),
+    };
+    Ok(Some(payload))
+}
+
 #[tauri::command]
 pub async fn rdp_session_close(
     session_id: String,
@@ -1158,49 +3675,531 @@ pub async fn rdp_session_hide(
     state.rdp.hide(&session_id).map_err(err)
 }
 
+/// Imports under the `target_parent_id` folder, or the tree root when it is omitted.
+/// With `new_folder`, everything goes into a new "Imported <date>" folder there first.
+///
+/// Progress is emitted on `import://progress`, tagged with `import_id`.
+/// [`import_mremoteng_cancel`] with the same id stops an apply between nodes; whatever was
+/// saved before then is kept and counted in the report.
 #[tauri::command]
 pub async fn import_mremoteng(
+    import_id: String,
     path: String,
     mode: ImportMode,
+    target_parent_id: Option<String>,
+    new_folder: Option<bool>,
+    app: AppHandle,
     state: State<'_, AppState>,
 ) -> Result<ImportReport, String> {
-    let parsed = parse_mremoteng(Path::new(&path)).map_err(err)?;
+    let running = state.imports.start(&import_id);
+    let mut parsed = tauri::async_runtime::spawn_blocking({
+        let app = app.clone();
+        let import_id = import_id.clone();
+        move || {
+            parse_mremoteng_with_progress(Path::new(&path), |done, total| {
+                if done % IMPORT_PROGRESS_INTERVAL == 0 || done == total {
+                    emit_import_progress(&app, &import_id, "parsed", done, total);
+                }
+            })
+            .map_err(err)
+        }
+    })
+    .await
+    .map_err(err)??;
+    let new_folder = new_folder.unwrap_or(false);
+
+    let nodes = state.storage.list_tree().await.map_err(err)?;
+    if let Some(target) = target_parent_id.as_deref() {
+        match nodes.iter().find(|node| node.id == target) {
+            Some(node) if node.kind == NodeKind::Folder => {}
+            Some(_) => return Err("import target is not a folder".to_string()),
+            None => return Err("import target folder not found".to_string()),
+        }
+        if nests_inside_itself(&parsed, &nodes, target) {
+            return Err("can't import into a folder that is part of the import".to_string());
+        }
+    }
+    let next_order_index = nodes
+        .iter()
+        .filter(|node| node.parent_id == target_parent_id)
+        .map(|node| node.order_index + 1)
+        .max()
+        .unwrap_or(0);
 
-    let created_estimate = parsed.folders.len() + parsed.connections.len();
+    // Re-importing a file upserts the nodes it created before; those count as updated.
+    let existing: HashSet<String> = nodes.into_iter().map(|node| node.id).collect();
     if matches!(mode, ImportMode::DryRun) {
+        let updated = already_imported(&parsed, &existing);
+        let total = parsed.folders.len() + parsed.connections.len() + usize::from(new_folder);
         return Ok(apply_report(
             &parsed,
-            created_estimate,
-            0,
+            total - updated,
+            updated,
             parsed.warnings.len(),
         ));
     }
 
     let mut created = 0;
-    for folder in &parsed.folders {
-        state.storage.upsert_folder(folder).await.map_err(err)?;
+    if new_folder {
+        let folder = FolderUpsert {
+            id: Uuid::new_v4().to_string(),
+            parent_id: target_parent_id,
+            name: format!("Imported {}", chrono::Local::now().format("%Y-%m-%d")),
+            order_index: next_order_index,
+            color: None,
+            icon: None,
+        };
+        state.storage.upsert_folder(&folder).await.map_err(err)?;
         created += 1;
+        nest_under(&mut parsed, &folder.id, 0);
+    } else if let Some(target) = target_parent_id.as_deref() {
+        nest_under(&mut parsed, target, next_order_index);
     }
 
+    let total = parsed.folders.len() + parsed.connections.len();
+
+    // Folders come first, so a cancelled import never leaves a connection without its parent.
+    let mut applied = 0;
+    let mut updated = 0;
     let refs = ResolvedSecretRefs::default();
-    for connection in &parsed.connections {
-        state
-            .storage
-            .upsert_connection(connection, &refs)
-            .await
-            .map_err(err)?;
-        created += 1;
+    let cancelled = 'apply: {
+        for folder in &parsed.folders {
+            if running.is_cancelled() {
+                break 'apply true;
+            }
+            state.storage.upsert_folder(folder).await.map_err(err)?;
+            applied += 1;
+            updated += usize::from(existing.contains(&folder.id));
+            if applied % IMPORT_PROGRESS_INTERVAL == 0 {
+                emit_import_progress(&app, &import_id, "applied", applied, total);
+            }
+        }
+        for connection in &parsed.connections {
+            if running.is_cancelled() {
+                break 'apply true;
+            }
+            state
+                .storage
+                .upsert_connection(connection, &refs)
+                .await
+                .map_err(err)?;
+            applied += 1;
+            updated += usize::from(existing.contains(&connection.id));
+            if applied % IMPORT_PROGRESS_INTERVAL == 0 {
+                emit_import_progress(&app, &import_id, "applied", applied, total);
+            }
+        }
+        false
+    };
+    emit_import_progress(&app, &import_id, "applied", applied, total);
+    emit_tree_changed(&app, TreeChangeKindDto::Reset, Vec::new());
+
+    let mut report = apply_report(
+        &parsed,
+        created + applied - updated,
+        updated,
+        parsed.warnings.len(),
+    );
+    if cancelled {
+        report.cancelled = true;
+        report.warnings.push(format!(
+            "Import cancelled; {} of {total} nodes were not imported",
+            total - applied
+        ));
     }
+    Ok(report)
+}
+
+/// How many of the parsed nodes are already in the tree.
+fn already_imported(parsed: &ParsedImport, existing: &HashSet<String>) -> usize {
+    parsed
+        .folders
+        .iter()
+        .map(|folder| &folder.id)
+        .chain(parsed.connections.iter().map(|connection| &connection.id))
+        .filter(|id| existing.contains(*id))
+        .count()
+}
+
+/// Asks the [`import_mremoteng`] started with `import_id` to stop applying before its
+/// next node. False when that import is not running.
+#[tauri::command]
+pub fn import_mremoteng_cancel(
+    import_id: String,
+    state: State<'_, AppState>,
+) -> Result<bool, String> {
+    Ok(state.imports.cancel(&import_id))
+}
 
-    Ok(apply_report(&parsed, created, 0, parsed.warnings.len()))
+fn emit_import_progress(
+    app: &AppHandle,
+    import_id: &str,
+    phase: &'static str,
+    done: usize,
+    total: usize,
+) {
+    let _ = app.emit(
+        IMPORT_PROGRESS_EVENT,
+        ImportProgressDto {
+            import_id,
+            phase,
+            done,
+            total,
+        },
+    );
 }
 
+/// Exports the whole tree, or with `root_node_id` just that node and its descendants.
 #[tauri::command]
 pub async fn export_mremoteng(
     path: String,
     _scope: Option<ImportScope>,
+    root_node_id: Option<String>,
     state: State<'_, AppState>,
 ) -> Result<(), String> {
     let nodes = state.storage.list_tree().await.map_err(err)?;
-    export_xml(Path::new(&path), &nodes).map_err(err)
+    export_xml(Path::new(&path), &nodes, root_node_id.as_deref()).map_err(err)
+}
+
+#[cfg(test)]
+mod tests {
+    use janus_domain::{HostKeyMode, SshConfigInput};
+
+    use super::*;
+
+    fn temp_path(extension: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("janus-test-{}.{extension}", Uuid::new_v4()))
+    }
+
+    #[test]
+    fn reports_a_failed_sftp_open_apart_from_the_shell() {
+        let opened = SshSftpOpenResult::shell_opened(
+            "s1".to_string(),
+            None,
+            Ok(SftpSessionOpenDto {
+                sftp_session_id: "f1".to_string(),
+                remote_cwd: "/home/ops".to_string(),
+            }),
+        );
+        assert_eq!(
+            serde_json::to_value(opened).expect("serialize"),
+            serde_json::json!({
+                "type": "opened",
+                "sessionId": "s1",
+                "sftp": { "sftpSessionId": "f1", "remoteCwd": "/home/ops" },
+            })
+        );
+
+        let failed = SshSftpOpenResult::shell_opened(
+            "s1".to_string(),
+            Some("ssh-rsa is deprecated"),
+            Err(SftpErrorDto {
+                code: "permissionDenied",
+                message: "subsystem request failed".to_string(),
+                conflict: None,
+            }),
+        );
+        assert_eq!(
+            serde_json::to_value(failed).expect("serialize"),
+            serde_json::json!({
+                "type": "sftpFailed",
+                "sessionId": "s1",
+                "weakHostKey": "ssh-rsa is deprecated",
+                "error": { "code": "permissionDenied", "message": "subsystem request failed" },
+            })
+        );
+    }
+
+    #[test]
+    fn completes_transfers_with_the_destination_size() {
+        let last = SftpTransferProgress {
+            bytes_transferred: 900,
+            total_bytes: Some(1000),
+        };
+
+        let (progress, warning) = completed_transfer_progress(last, Some(1000), "/tmp/a");
+        assert_eq!(progress.bytes_transferred, 1000);
+        assert_eq!(progress.total_bytes, Some(1000));
+        assert_eq!(warning, None);
+
+        let (progress, warning) = completed_transfer_progress(last, Some(800), "/tmp/a");
+        assert_eq!(progress.bytes_transferred, 800);
+        assert_eq!(progress.total_bytes, Some(1000));
+        assert_eq!(
+            warning.as_deref(),
+            Some("/tmp/a is 800 bytes after the transfer; expected 1000")
+        );
+
+        let (progress, warning) = completed_transfer_progress(last, None, "/tmp/a");
+        assert_eq!(progress.bytes_transferred, 900);
+        assert_eq!(warning, None);
+    }
+
+    #[test]
+    fn expands_home_and_variables_in_local_paths() {
+        let home = Path::new("/home/me");
+        let var = |name: &str| match name {
+            "LOGS" => Some("/var/log".to_string()),
+            "ProgramFiles(x86)" => Some("C:\\Program Files (x86)".to_string()),
+            _ => None,
+        };
+        let expand = |path: &str| expand_local_path(path, Some(home), var);
+
+        assert_eq!(expand("~"), home);
+        assert_eq!(expand("~/logs"), home.join("logs"));
+        assert_eq!(expand("~/$LOGS"), home.join("var/log"));
+        assert_eq!(expand("$LOGS/app"), PathBuf::from("/var/log/app"));
+        assert_eq!(expand("${LOGS}x"), PathBuf::from("/var/logx"));
+        assert_eq!(
+            expand("%ProgramFiles(x86)%\\Janus"),
+            PathBuf::from("C:\\Program Files (x86)\\Janus")
+        );
+        assert_eq!(expand("$UNSET/x"), PathBuf::from("$UNSET/x"));
+        assert_eq!(expand("100%/50%"), PathBuf::from("100%/50%"));
+        assert_eq!(expand("~other/x"), PathBuf::from("~other/x"));
+        assert_eq!(expand_local_path("~/x", None, var), PathBuf::from("~/x"));
+    }
+
+    #[test]
+    fn requested_terminal_size_beats_the_connection_default() {
+        assert_eq!(terminal_dimension(Some(90), Some(200), 120), 90);
+        assert_eq!(terminal_dimension(None, Some(200), 120), 200);
+        assert_eq!(terminal_dimension(None, None, 120), 120);
+        assert_eq!(terminal_dimension(None, Some(0), 120), 120);
+        assert_eq!(terminal_dimension(None, Some(70_000), 120), 120);
+    }
+
+    #[test]
+    fn counts_reimported_nodes_as_already_imported() {
+        let folder = |id: &str| FolderUpsert {
+            id: id.to_string(),
+            parent_id: None,
+            name: id.to_string(),
+            order_index: 0,
+            color: None,
+            icon: None,
+        };
+        let parsed = ParsedImport {
+            folders: vec![folder("kept"), folder("new")],
+            connections: Vec::new(),
+            warnings: Vec::new(),
+        };
+        let existing = HashSet::from(["kept".to_string(), "other".to_string()]);
+
+        assert_eq!(already_imported(&parsed, &existing), 1);
+    }
+
+    #[tokio::test]
+    async fn failed_upsert_discards_staged_secrets() {
+        let db_path = temp_path("sqlite");
+        let vault_path = temp_path("json");
+        let vault_dir = std::env::temp_dir().join(format!("janus-test-vaults-{}", Uuid::new_v4()));
+        let storage = Storage::new(&db_path).await.expect("storage init");
+        let vaults = Vaults::new(&vault_path, &vault_dir);
+        let vault = vaults.default_vault();
+        vault.initialize("passphrase").await.expect("vault init");
+        vault.unlock("passphrase").await.expect("vault unlock");
+
+        // The parent folder does not exist, so the node insert trips the foreign key.
+        let connection = ConnectionUpsert {
+            id: Uuid::new_v4().to_string(),
+            parent_id: Some("missing-folder".to_string()),
+            kind: NodeKind::Ssh,
+            name: "orphan check".to_string(),
+            order_index: 0,
+            color: None,
+            icon: None,
+            ssh: Some(SshConfigInput {
+                host: "example.com".to_string(),
+                port: 22,
+                username: "root".to_string(),
+                host_key_mode: HostKeyMode::Tofu,
+                key_path: None,
+                cert_path: None,
+                retain_scrollback: false,
+                default_cols: None,
+                default_rows: None,
+                proxy_command: None,
+                bind_address: None,
+                remember_sftp_path: true,
+                auth_methods: janus_domain::SshAuthMethod::DEFAULT_ORDER.to_vec(),
+                password: Some("hunter2".to_string()),
+                key_passphrase: Some("passphrase".to_string()),
+            }),
+            rdp: None,
+        };
+
+        assert!(
+            upsert_connection_with_secrets(&storage, &vaults, DEFAULT_VAULT_ID, connection)
+                .await
+                .is_err()
+        );
+        assert_eq!(vault.secret_count().expect("secret count"), 0);
+
+        let _ = std::fs::remove_file(db_path);
+        let _ = std::fs::remove_file(vault_path);
+        let _ = std::fs::remove_dir_all(vault_dir);
+    }
+
+    #[tokio::test]
+    async fn failed_upsert_discards_secrets_staged_in_a_named_vault() {
+        let db_path = temp_path("sqlite");
+        let vault_dir = std::env::temp_dir().join(format!("janus-test-vaults-{}", Uuid::new_v4()));
+        let storage = Storage::new(&db_path).await.expect("storage init");
+        let vaults = Vaults::new(&vault_dir.join("vault.enc.json"), &vault_dir);
+        vaults
+            .create_vault("work", "passphrase")
+            .await
+            .expect("vault init");
+        vaults
+            .unlock_vault("work", "passphrase")
+            .await
+            .expect("vault unlock");
+
+        // The parent folder does not exist, so the node insert trips the foreign key.
+        let connection = ConnectionUpsert {
+            id: Uuid::new_v4().to_string(),
+            parent_id: Some("missing-folder".to_string()),
+            kind: NodeKind::Ssh,
+            name: "orphan check".to_string(),
+            order_index: 0,
+            color: None,
+            icon: None,
+            ssh: Some(SshConfigInput {
+                host: "example.com".to_string(),
+                port: 22,
+                username: "root".to_string(),
+                host_key_mode: HostKeyMode::Tofu,
+                key_path: None,
+                cert_path: None,
+                retain_scrollback: false,
+                default_cols: None,
+                default_rows: None,
+                proxy_command: None,
+                bind_address: None,
+                remember_sftp_path: true,
+                auth_methods: janus_domain::SshAuthMethod::DEFAULT_ORDER.to_vec(),
+                password: Some("hunter2".to_string()),
+                key_passphrase: Some("passphrase".to_string()),
+            }),
+            rdp: None,
+        };
+
+        assert!(
+            upsert_connection_with_secrets(&storage, &vaults, "work", connection)
+                .await
+                .is_err()
+        );
+        let work = vaults.vault("work").expect("work vault");
+        assert_eq!(work.secret_count().expect("secret count"), 0);
+
+        let _ = std::fs::remove_file(db_path);
+        let _ = std::fs::remove_dir_all(vault_dir);
+    }
+
+    #[tokio::test]
+    async fn rotates_every_connection_saving_a_password() {
+        let db_path = temp_path("sqlite");
+        let vault_dir = std::env::temp_dir().join(format!("janus-test-vaults-{}", Uuid::new_v4()));
+        let storage = Storage::new(&db_path).await.expect("storage init");
+        let vaults = Vaults::new(&vault_dir.join("vault.enc.json"), &vault_dir);
+        vaults
+            .create_vault("work", "passphrase")
+            .await
+            .expect("vault init");
+        vaults
+            .unlock_vault("work", "passphrase")
+            .await
+            .expect("vault unlock");
+
+        for (id, password) in [("web-1", "old"), ("web-2", "old"), ("db", "other")] {
+            let connection = ConnectionUpsert {
+                id: id.to_string(),
+                parent_id: None,
+                kind: NodeKind::Ssh,
+                name: id.to_string(),
+                order_index: 0,
+                color: None,
+                icon: None,
+                ssh: Some(SshConfigInput {
+                    host: "example.com".to_string(),
+                    port: 22,
+                    username: "svc".to_string(),
+                    host_key_mode: HostKeyMode::Tofu,
+                    key_path: None,
+                    cert_path: None,
+                    retain_scrollback: false,
+                    default_cols: None,
+                    default_rows: None,
+                    proxy_command: None,
+                    bind_address: None,
+                    remember_sftp_path: true,
+                    auth_methods: janus_domain::SshAuthMethod::DEFAULT_ORDER.to_vec(),
+                    password: Some(password.to_string()),
+                    key_passphrase: None,
+                }),
+                rdp: None,
+            };
+            upsert_connection_with_secrets(&storage, &vaults, "work", connection)
+                .await
+                .expect("connection upsert");
+        }
+        let saved = |refs: &[PasswordRef], id: &str| {
+            let password_ref = refs.iter().find(|password_ref| password_ref.node_id == id);
+            vaults
+                .get_secret(&password_ref.expect("password ref").secret_ref)
+                .expect("get secret")
+        };
+
+        let refs = storage.password_refs().await.expect("password refs");
+        let targets = matching_password_refs(&vaults, &refs, "old").expect("matching refs");
+        assert_eq!(targets.len(), 2);
+        let updated = rotate_passwords(&storage, &vaults, &refs, targets, "new")
+            .await
+            .expect("rotate");
+        assert_eq!(updated, 2);
+
+        let refs = storage.password_refs().await.expect("password refs");
+        assert_eq!(saved(&refs, "web-1").as_deref(), Some("new"));
+        assert_eq!(saved(&refs, "web-2").as_deref(), Some("new"));
+        assert_eq!(saved(&refs, "db").as_deref(), Some("other"));
+        let work = vaults.vault("work").expect("work vault");
+        assert_eq!(work.secret_count().expect("secret count"), 3);
+
+        let db = refs
+            .iter()
+            .find(|password_ref| password_ref.node_id == "db");
+        let targets = vec![db.expect("db ref").clone()];
+        let updated = rotate_passwords(&storage, &vaults, &refs, targets, "newer")
+            .await
+            .expect("rotate one");
+        assert_eq!(updated, 1);
+        let refs = storage.password_refs().await.expect("password refs");
+        assert_eq!(saved(&refs, "db").as_deref(), Some("newer"));
+        assert_eq!(work.secret_count().expect("secret count"), 3);
+
+        let _ = std::fs::remove_file(db_path);
+        let _ = std::fs::remove_dir_all(vault_dir);
+    }
+
+    #[test]
+    fn legacy_strict_host_key_flag_maps_to_host_key_mode() {
+        let legacy: SshConfigInput = serde_json::from_str(
+            r#"{"host":"example.com","port":22,"username":"root","strictHostKey":false}"#,
+        )
+        .expect("legacy payload");
+        assert_eq!(legacy.host_key_mode, HostKeyMode::Off);
+
+        let legacy: SshConfigInput = serde_json::from_str(
+            r#"{"host":"example.com","port":22,"username":"root","strictHostKey":true}"#,
+        )
+        .expect("legacy payload");
+        assert_eq!(legacy.host_key_mode, HostKeyMode::Tofu);
+
+        let current: SshConfigInput = serde_json::from_str(
+            r#"{"host":"example.com","port":22,"username":"root","hostKeyMode":"strict"}"#,
+        )
+        .expect("current payload");
+        assert_eq!(current.host_key_mode, HostKeyMode::Strict);
+    }
 }
@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tauri::async_runtime::JoinHandle;
+
+pub const MIN_MONITOR_INTERVAL: Duration = Duration::from_secs(5);
+const MONITOR_PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+#[derive(Debug, Clone)]
+pub struct MonitorTarget {
+    pub connection_id: String,
+    pub host: String,
+    pub port: u16,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionStatus {
+    pub reachable: bool,
+    /// Time to establish the TCP connection. `None` when unreachable, or when the
+    /// connection has an open session and was not probed.
+    pub latency_ms: Option<u64>,
+}
+
+/// Periodically TCP-probes a set of connections in the background.
+///
+/// Connections with an open session are reported reachable without being probed;
+/// session commands keep the open set up to date through `session_opened` and
+/// `session_closed`.
+#[derive(Clone, Default)]
+pub struct ConnectionMonitor {
+    task: Arc<Mutex<Option<JoinHandle<()>>>>,
+    /// Session id to connection id.
+    open_sessions: Arc<Mutex<HashMap<String, String>>>,
+    latency: Arc<LatencyTotals>,
+}
+
+/// Sum and count of the latencies of successful probes, for the diagnostics view.
+#[derive(Default)]
+struct LatencyTotals {
+    total_ms: AtomicU64,
+    probes: AtomicU64,
+}
+
+impl ConnectionMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts probing `targets` every `interval`, replacing any running monitor.
+    pub fn start<F>(&self, targets: Vec<MonitorTarget>, interval: Duration, on_status: F)
+    where
+        F: Fn(&str, ConnectionStatus) + Send + Sync + 'static,
+    {
+        let interval = interval.max(MIN_MONITOR_INTERVAL);
+        let on_status = Arc::new(on_status);
+        let open_sessions = Arc::clone(&self.open_sessions);
+        let latency = Arc::clone(&self.latency);
+
+        let task = tauri::async_runtime::spawn(async move {
+            let offsets = probe_offsets(targets.len(), interval);
+            loop {
+                let round_start = tokio::time::Instant::now();
+                for (target, offset) in targets.iter().zip(&offsets) {
+                    tokio::time::sleep_until(round_start + *offset).await;
+
+                    let is_open = open_sessions
+                        .lock()
+                        .expect("open sessions lock poisoned")
+                        .values()
+                        .any(|connection_id| *connection_id == target.connection_id);
+                    if is_open {
+                        on_status(
+                            &target.connection_id,
+                            ConnectionStatus {
+                                reachable: true,
+                                latency_ms: None,
+                            },
+                        );
+                        continue;
+                    }
+
+                    let target = target.clone();
+                    let on_status = Arc::clone(&on_status);
+                    let latency = Arc::clone(&latency);
+                    tauri::async_runtime::spawn(async move {
+                        let status = probe(&target.host, target.port).await;
+                        if let Some(latency_ms) = status.latency_ms {
+                            latency.total_ms.fetch_add(latency_ms, Ordering::Relaxed);
+                            latency.probes.fetch_add(1, Ordering::Relaxed);
+                        }
+                        on_status(&target.connection_id, status);
+                    });
+                }
+                tokio::time::sleep_until(round_start + interval).await;
+            }
+        });
+
+        let previous = self
+            .task
+            .lock()
+            .expect("monitor task lock poisoned")
+            .replace(task);
+        if let Some(previous) = previous {
+            previous.abort();
+        }
+    }
+
+    /// Mean latency of every successful probe since the app started, if there was one.
+    pub fn average_latency_ms(&self) -> Option<u64> {
+        let probes = self.latency.probes.load(Ordering::Relaxed);
+        (probes > 0).then(|| self.latency.total_ms.load(Ordering::Relaxed) / probes)
+    }
+
+    pub fn stop(&self) {
+        let task = self.task.lock().expect("monitor task lock poisoned").take();
+        if let Some(task) = task {
+            task.abort();
+        }
+    }
+
+    pub fn session_opened(&self, session_id: &str, connection_id: &str) {
+        self.open_sessions
+            .lock()
+            .expect("open sessions lock poisoned")
+            .insert(session_id.to_string(), connection_id.to_string());
+    }
+
+    pub fn session_closed(&self, session_id: &str) {
+        self.open_sessions
+            .lock()
+            .expect("open sessions lock poisoned")
+            .remove(session_id);
+    }
+}
+
+async fn probe(host: &str, port: u16) -> ConnectionStatus {
+    let started = Instant::now();
+    match tokio::time::timeout(
+        MONITOR_PROBE_TIMEOUT,
+        tokio::net::TcpStream::connect((host, port)),
+    )
+    .await
+    {
+        Ok(Ok(_)) => ConnectionStatus {
+            reachable: true,
+            latency_ms: Some(started.elapsed().as_millis() as u64),
+        },
+        _ => ConnectionStatus {
+            reachable: false,
+            latency_ms: None,
+        },
+    }
+}
+
+/// Spreads `count` probes evenly over one interval so they don't all fire at once.
+fn probe_offsets(count: usize, interval: Duration) -> Vec<Duration> {
+    (0..count)
+        .map(|index| {
+            let nanos = interval.as_nanos() * index as u128 / count as u128;
+            Duration::from_nanos(nanos as u64)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spreads_probes_across_the_interval() {
+        assert_eq!(
+            probe_offsets(4, Duration::from_secs(10)),
+            [0, 2500, 5000, 7500].map(Duration::from_millis)
+        );
+        assert_eq!(probe_offsets(1, Duration::from_secs(10)), [Duration::ZERO]);
+        assert!(probe_offsets(0, Duration::from_secs(10)).is_empty());
+    }
+
+    #[tokio::test]
+    async fn probes_report_reachability_and_latency() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+            .await
+            .expect("bind");
+        let port = listener.local_addr().expect("local addr").port();
+
+        let status = probe("127.0.0.1", port).await;
+        assert!(status.reachable);
+        assert!(status.latency_ms.is_some());
+
+        drop(listener);
+        let status = probe("127.0.0.1", port).await;
+        assert_eq!(
+            status,
+            ConnectionStatus {
+                reachable: false,
+                latency_ms: None,
+            }
+        );
+    }
+}
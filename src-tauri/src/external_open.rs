@@ -0,0 +1,220 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use anyhow::{anyhow, Context, Result};
+use uuid::Uuid;
+
+use crate::fs_watch::{LocalFsChangeKind, LocalFsWatchers};
+
+/// Downloaded copies older than this are removed the next time Janus starts.
+pub const EXTERNAL_OPEN_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Extensions the OS would run rather than display.
+const EXECUTABLE_EXTENSIONS: &[&str] = &[
+    "app",
+    "appimage",
+    "appref-ms",
+    "bat",
+    "cmd",
+    "com",
+    "command",
+    "cpl",
+    "deb",
+    "dmg",
+    "exe",
+    "hta",
+    "jar",
+    "js",
+    "jse",
+    "lnk",
+    "msc",
+    "msi",
+    "pif",
+    "pkg",
+    "ps1",
+    "reg",
+    "rpm",
+    "scr",
+    "sh",
+    "vbe",
+    "vbs",
+    "wsf",
+    "wsh",
+];
+
+/// Local copies of remote files opened in the system default application.
+///
+/// Each file gets its own directory under `root` so it keeps its remote name, and is
+/// watched until its SFTP session closes so saved edits can be offered for re-upload.
+#[derive(Clone)]
+pub struct ExternalOpens {
+    root: PathBuf,
+    watchers: LocalFsWatchers,
+    /// SFTP session id to the watch tokens of the files opened from it.
+    watches: Arc<Mutex<HashMap<String, Vec<String>>>>,
+}
+
+impl ExternalOpens {
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            watchers: LocalFsWatchers::new(),
+            watches: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Creates an empty directory for a copy of `remote_path` and returns the file path in it.
+    pub fn prepare(&self, remote_path: &str) -> Result<PathBuf> {
+        let name = remote_path
+            .rsplit('/')
+            .next()
+            .filter(|name| !name.is_empty() && *name != "." && *name != "..")
+            .ok_or_else(|| anyhow!("not a file path: {remote_path}"))?;
+        // Remote names may contain characters Windows doesn't allow in file names.
+        let name: String = name
+            .chars()
+            .map(|c| match c {
+                '<' | '>' | ':' | '"' | '\\' | '|' | '?' | '*' => '_',
+                c => c,
+            })
+            .collect();
+        let dir = self.root.join(Uuid::new_v4().to_string());
+        std::fs::create_dir_all(&dir)
+            .with_context(|| format!("creating temp directory {}", dir.display()))?;
+        Ok(dir.join(name))
+    }
+
+    /// Calls `on_saved` whenever `local_path` is written, until `forget_session`.
+    pub fn watch<F>(&self, sftp_session_id: &str, local_path: &Path, on_saved: F) -> Result<()>
+    where
+        F: Fn() + Send + 'static,
+    {
+        let dir = local_path
+            .parent()
+            .ok_or_else(|| anyhow!("{} has no parent directory", local_path.display()))?;
+        let file_name = local_path.file_name().map(ToOwned::to_owned);
+        // Editors often save by writing a new file and renaming it over the old one.
+        let token = self.watchers.watch(dir, move |_, changes| {
+            let saved = changes.iter().any(|change| {
+                change.kind != LocalFsChangeKind::Removed
+                    && Path::new(&change.path).file_name() == file_name.as_deref()
+            });
+            if saved {
+                on_saved();
+            }
+        })?;
+
+        self.watches
+            .lock()
+            .expect("external open lock poisoned")
+            .entry(sftp_session_id.to_string())
+            .or_default()
+            .push(token);
+        Ok(())
+    }
+
+    /// Stops watching files opened from an SFTP session. The copies stay until cleanup.
+    pub fn forget_session(&self, sftp_session_id: &str) {
+        let tokens = self
+            .watches
+            .lock()
+            .expect("external open lock poisoned")
+            .remove(sftp_session_id)
+            .unwrap_or_default();
+        for token in tokens {
+            let _ = self.watchers.unwatch(&token);
+        }
+    }
+
+    /// Removes copies last modified more than `ttl` ago.
+    pub fn remove_expired(&self, ttl: Duration) {
+        let Ok(entries) = std::fs::read_dir(&self.root) else {
+            return;
+        };
+        let now = SystemTime::now();
+        for entry in entries.flatten() {
+            let expired = entry
+                .metadata()
+                .and_then(|metadata| metadata.modified())
+                .is_ok_and(|modified| {
+                    now.duration_since(modified)
+                        .is_ok_and(|elapsed| elapsed >= ttl)
+                });
+            if expired {
+                let _ = std::fs::remove_dir_all(entry.path());
+            }
+        }
+    }
+
+    /// Stops every watch and removes all copies. Files still held open by another
+    /// application may survive until `remove_expired` runs on a later start.
+    pub fn clear(&self) {
+        self.watches
+            .lock()
+            .expect("external open lock poisoned")
+            .clear();
+        self.watchers.clear();
+        let _ = std::fs::remove_dir_all(&self.root);
+    }
+}
+
+pub fn is_executable_type(path: &str) -> bool {
+    let name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+    // Windows drops trailing dots and spaces, so `setup.exe. ` still runs as `setup.exe`.
+    let name = name.trim_end_matches(['.', ' ']);
+    name.rsplit_once('.').is_some_and(|(_, extension)| {
+        EXECUTABLE_EXTENSIONS
+            .iter()
+            .any(|candidate| candidate.eq_ignore_ascii_case(extension))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_executable_extensions() {
+        assert!(is_executable_type("/home/me/setup.EXE"));
+        assert!(is_executable_type("deploy.sh"));
+        assert!(is_executable_type("C:\\tools\\run.ps1"));
+        assert!(is_executable_type("/srv/setup.exe."));
+        assert!(is_executable_type("/srv/setup.exe . "));
+        assert!(is_executable_type("/srv/install.appref-ms"));
+        assert!(is_executable_type("/srv/shortcut.LNK"));
+        assert!(!is_executable_type("/etc/nginx/nginx.conf"));
+        assert!(!is_executable_type("/srv/exe/readme"));
+        assert!(!is_executable_type("/srv/.bashrc.d/notes.txt"));
+    }
+
+    #[test]
+    fn keeps_the_remote_name_and_cleans_up() {
+        let root = std::env::temp_dir().join(format!("janus-open-test-{}", Uuid::new_v4()));
+        let opens = ExternalOpens::new(root.clone());
+
+        let local = opens.prepare("/var/log/app.log").expect("prepare");
+        assert_eq!(
+            local.file_name().and_then(|name| name.to_str()),
+            Some("app.log")
+        );
+        assert!(local.parent().is_some_and(Path::exists));
+        assert!(opens.prepare("/var/log/").is_err());
+        let odd = opens.prepare("/srv/a:b\\c?.txt").expect("prepare odd name");
+        assert_eq!(
+            odd.file_name().and_then(|name| name.to_str()),
+            Some("a_b_c_.txt")
+        );
+
+        std::fs::write(&local, b"log").expect("write copy");
+        opens.remove_expired(EXTERNAL_OPEN_TTL);
+        assert!(local.exists());
+        opens.remove_expired(Duration::ZERO);
+        assert!(!local.exists());
+
+        opens.prepare("/tmp/other.txt").expect("prepare again");
+        opens.clear();
+        assert!(!root.exists());
+    }
+}
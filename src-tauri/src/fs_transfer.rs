@@ -0,0 +1,350 @@
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+
+const COPY_CHUNK_SIZE: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LocalTransferProgress {
+    pub bytes_transferred: u64,
+    pub total_bytes: u64,
+}
+
+/// Copies a file or, recursively, a directory tree from `src` to `dst`.
+///
+/// With `overwrite`, an existing destination is replaced (not merged). The copy is made
+/// beside it first, so the existing destination survives a copy that fails partway.
+pub fn copy_path<F>(src: &Path, dst: &Path, overwrite: bool, mut on_progress: F) -> Result<()>
+where
+    F: FnMut(LocalTransferProgress),
+{
+    let replace = prepare_destination(src, dst, overwrite)?;
+
+    let mut progress = LocalTransferProgress {
+        bytes_transferred: 0,
+        total_bytes: tree_size(src)?,
+    };
+    on_progress(progress);
+    if !replace {
+        return copy_entry(src, dst, &mut progress, &mut on_progress);
+    }
+
+    let staged = staging_path(dst);
+    let copied = copy_entry(src, &staged, &mut progress, &mut on_progress).and_then(|()| {
+        replace_entry(&staged, dst)
+            .with_context(|| format!("replacing existing destination {}", dst.display()))
+    });
+    if copied.is_err() {
+        let _ = remove_entry(&staged);
+    }
+    copied
+}
+
+/// Moves `src` to `dst`, falling back to copy-and-delete across filesystems.
+pub fn move_path<F>(src: &Path, dst: &Path, overwrite: bool, mut on_progress: F) -> Result<()>
+where
+    F: FnMut(LocalTransferProgress),
+{
+    let replace = prepare_destination(src, dst, overwrite)?;
+
+    let renamed = if replace {
+        replace_entry(src, dst)
+    } else {
+        fs::rename(src, dst)
+    };
+    match renamed {
+        Ok(()) => {
+            let total_bytes = tree_size(dst)?;
+            on_progress(LocalTransferProgress {
+                bytes_transferred: total_bytes,
+                total_bytes,
+            });
+            Ok(())
+        }
+        Err(error) if error.kind() == io::ErrorKind::CrossesDevices => {
+            copy_path(src, dst, overwrite, on_progress)?;
+            remove_entry(src)
+                .with_context(|| format!("removing {} after copying it", src.display()))
+        }
+        Err(error) => {
+            Err(error).with_context(|| format!("moving {} to {}", src.display(), dst.display()))
+        }
+    }
+}
+
+/// Checks that `src` can be copied or moved to `dst`. Returns whether `dst` exists and
+/// is to be replaced.
+fn prepare_destination(src: &Path, dst: &Path, overwrite: bool) -> Result<bool> {
+    let src_meta =
+        fs::symlink_metadata(src).with_context(|| format!("reading source {}", src.display()))?;
+
+    let src_abs = fs::canonicalize(src)?;
+    let dst_parent = dst
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .map(fs::canonicalize)
+        .transpose()
+        .with_context(|| format!("resolving destination {}", dst.display()))?;
+    let dst_abs = match (dst_parent, dst.file_name()) {
+        (Some(parent), Some(name)) => parent.join(name),
+        _ => dst.to_path_buf(),
+    };
+    if dst_abs == src_abs {
+        return Err(anyhow!(
+            "source and destination are the same: {}",
+            src.display()
+        ));
+    }
+    if src_meta.is_dir() && dst_abs.starts_with(&src_abs) {
+        return Err(anyhow!("cannot copy or move {} into itself", src.display()));
+    }
+
+    match fs::symlink_metadata(dst) {
+        Ok(_) if !overwrite => Err(anyhow!("destination already exists: {}", dst.display())),
+        Ok(_) => Ok(true),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(false),
+        Err(error) => Err(error).with_context(|| format!("reading destination {}", dst.display())),
+    }
+}
+
+fn copy_entry<F>(
+    src: &Path,
+    dst: &Path,
+    progress: &mut LocalTransferProgress,
+    on_progress: &mut F,
+) -> Result<()>
+where
+    F: FnMut(LocalTransferProgress),
+{
+    let meta = fs::symlink_metadata(src)?;
+    let file_type = meta.file_type();
+
+    if file_type.is_symlink() {
+        copy_symlink(src, dst)
+    } else if file_type.is_dir() {
+        fs::create_dir(dst).with_context(|| format!("creating directory {}", dst.display()))?;
+        for entry in fs::read_dir(src)? {
+            let entry = entry?;
+            copy_entry(
+                &entry.path(),
+                &dst.join(entry.file_name()),
+                progress,
+                on_progress,
+            )?;
+        }
+        fs::set_permissions(dst, meta.permissions())?;
+        Ok(())
+    } else {
+        copy_file(src, dst, progress, on_progress)?;
+        fs::set_permissions(dst, meta.permissions())?;
+        Ok(())
+    }
+}
+
+fn copy_file<F>(
+    src: &Path,
+    dst: &Path,
+    progress: &mut LocalTransferProgress,
+    on_progress: &mut F,
+) -> Result<()>
+where
+    F: FnMut(LocalTransferProgress),
+{
+    let mut reader =
+        File::open(src).with_context(|| format!("opening local file {}", src.display()))?;
+    let mut writer = File::options()
+        .write(true)
+        .create_new(true)
+        .open(dst)
+        .with_context(|| format!("creating local file {}", dst.display()))?;
+
+    let mut buf = vec![0u8; COPY_CHUNK_SIZE];
+    loop {
+        let read = reader.read(&mut buf)?;
+        if read == 0 {
+            break;
+        }
+        writer.write_all(&buf[..read])?;
+        progress.bytes_transferred = progress.bytes_transferred.saturating_add(read as u64);
+        on_progress(*progress);
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+#[cfg(unix)]
+fn copy_symlink(src: &Path, dst: &Path) -> Result<()> {
+    let target = fs::read_link(src)?;
+    std::os::unix::fs::symlink(target, dst)
+        .with_context(|| format!("creating symlink {}", dst.display()))
+}
+
+#[cfg(windows)]
+fn copy_symlink(src: &Path, dst: &Path) -> Result<()> {
+    let target = fs::read_link(src)?;
+    let result = if fs::metadata(src).is_ok_and(|meta| meta.is_dir()) {
+        std::os::windows::fs::symlink_dir(target, dst)
+    } else {
+        std::os::windows::fs::symlink_file(target, dst)
+    };
+    result.with_context(|| format!("creating symlink {}", dst.display()))
+}
+
+/// A hidden, unused name next to `path`, so renaming from it stays on one filesystem.
+fn staging_path(path: &Path) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    path.with_file_name(format!(".{name}.janus-{}", uuid::Uuid::new_v4()))
+}
+
+/// Renames `src` over the existing `dst`. A directory can't be renamed over, so when
+/// either is one, `dst` is moved aside first and put back if the rename fails.
+fn replace_entry(src: &Path, dst: &Path) -> io::Result<()> {
+    if !fs::symlink_metadata(src)?.is_dir() && !fs::symlink_metadata(dst)?.is_dir() {
+        return fs::rename(src, dst);
+    }
+
+    let displaced = staging_path(dst);
+    fs::rename(dst, &displaced)?;
+    if let Err(error) = fs::rename(src, dst) {
+        let _ = fs::rename(&displaced, dst);
+        return Err(error);
+    }
+    let _ = remove_entry(&displaced);
+    Ok(())
+}
+
+fn remove_entry(path: &Path) -> io::Result<()> {
+    if fs::symlink_metadata(path)?.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+fn tree_size(path: &Path) -> Result<u64> {
+    let meta = fs::symlink_metadata(path)?;
+    if meta.is_dir() {
+        let mut total = 0u64;
+        for entry in fs::read_dir(path)? {
+            total = total.saturating_add(tree_size(&entry?.path())?);
+        }
+        Ok(total)
+    } else if meta.is_file() {
+        Ok(meta.len())
+    } else {
+        Ok(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("janus-fs-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).expect("create temp dir");
+        dir
+    }
+
+    #[test]
+    fn copies_directory_trees_and_reports_progress() {
+        let root = temp_dir();
+        let src = root.join("src");
+        fs::create_dir_all(src.join("nested")).expect("create tree");
+        fs::write(src.join("a.txt"), b"hello").expect("write a");
+        fs::write(src.join("nested").join("b.txt"), b"world!").expect("write b");
+
+        let mut last = None;
+        copy_path(&src, &root.join("dst"), false, |progress| {
+            last = Some(progress)
+        })
+        .expect("copy tree");
+
+        assert_eq!(
+            fs::read(root.join("dst").join("nested").join("b.txt")).expect("read copy"),
+            b"world!"
+        );
+        assert_eq!(
+            last,
+            Some(LocalTransferProgress {
+                bytes_transferred: 11,
+                total_bytes: 11,
+            })
+        );
+        assert!(src.join("a.txt").exists());
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn respects_overwrite_flag() {
+        let root = temp_dir();
+        let src = root.join("src.txt");
+        let dst = root.join("dst.txt");
+        fs::write(&src, b"new").expect("write src");
+        fs::write(&dst, b"old").expect("write dst");
+
+        let error = move_path(&src, &dst, false, |_| {}).expect_err("destination exists");
+        assert!(error.to_string().contains("already exists"));
+        assert_eq!(fs::read(&dst).expect("read dst"), b"old");
+
+        move_path(&src, &dst, true, |_| {}).expect("overwrite move");
+        assert_eq!(fs::read(&dst).expect("read dst"), b"new");
+        assert!(!src.exists());
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn replaces_a_directory_with_a_file() {
+        let root = temp_dir();
+        let src = root.join("src.txt");
+        let dst = root.join("dst");
+        fs::write(&src, b"new").expect("write src");
+        fs::create_dir_all(dst.join("nested")).expect("create dst");
+
+        copy_path(&src, &dst, true, |_| {}).expect("overwrite copy");
+        assert_eq!(fs::read(&dst).expect("read dst"), b"new");
+        let names: Vec<_> = fs::read_dir(&root)
+            .expect("list root")
+            .map(|entry| entry.expect("entry").file_name())
+            .collect();
+        assert_eq!(names.len(), 2, "no staged copies left behind: {names:?}");
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn failed_overwrite_keeps_the_destination() {
+        let root = temp_dir();
+        let src = root.join("src");
+        let dst = root.join("dst");
+        fs::create_dir_all(&src).expect("create src");
+        fs::write(src.join("a.txt"), b"new").expect("write a");
+        // Sockets can't be opened for reading, so copying this one fails.
+        let _socket =
+            std::os::unix::net::UnixListener::bind(src.join("socket")).expect("bind socket");
+        fs::create_dir_all(&dst).expect("create dst");
+        fs::write(dst.join("a.txt"), b"old").expect("write old");
+
+        assert!(copy_path(&src, &dst, true, |_| {}).is_err());
+        assert_eq!(fs::read(dst.join("a.txt")).expect("read old"), b"old");
+        assert_eq!(fs::read_dir(&root).expect("list root").count(), 2);
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn refuses_to_copy_a_directory_into_itself() {
+        let root = temp_dir();
+        let src = root.join("src");
+        fs::create_dir_all(&src).expect("create src");
+
+        assert!(copy_path(&src, &src.join("inner"), false, |_| {}).is_err());
+
+        let _ = fs::remove_dir_all(root);
+    }
+}
@@ -0,0 +1,204 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc as std_mpsc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use notify::event::{ModifyKind, RenameMode};
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use uuid::Uuid;
+
+const LOCAL_FS_DEBOUNCE: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum LocalFsChangeKind {
+    Created,
+    Modified,
+    Removed,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LocalFsChange {
+    pub path: String,
+    pub kind: LocalFsChangeKind,
+}
+
+/// Non-recursive directory watchers for the local file-manager pane.
+///
+/// Dropping a watcher closes its event channel, which in turn ends the
+/// debounce thread, so removing an entry from the map is all the cleanup needed.
+#[derive(Clone, Default)]
+pub struct LocalFsWatchers {
+    watchers: Arc<Mutex<HashMap<String, RecommendedWatcher>>>,
+}
+
+impl LocalFsWatchers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn watch<F>(&self, path: &Path, on_changes: F) -> Result<String>
+    where
+        F: Fn(&str, Vec<LocalFsChange>) + Send + 'static,
+    {
+        let token = Uuid::new_v4().to_string();
+        let (event_tx, event_rx) = std_mpsc::channel::<Event>();
+        let mut watcher =
+            notify::recommended_watcher(move |result: notify::Result<Event>| match result {
+                Ok(event) => {
+                    let _ = event_tx.send(event);
+                }
+                Err(error) => tracing::debug!(?error, "local fs watcher error"),
+            })
+            .context("creating local filesystem watcher")?;
+        watcher
+            .watch(path, RecursiveMode::NonRecursive)
+            .with_context(|| format!("watching local directory {}", path.display()))?;
+
+        let thread_token = token.clone();
+        std::thread::Builder::new()
+            .name("local-fs-watch".into())
+            .spawn(move || debounce_loop(event_rx, |changes| on_changes(&thread_token, changes)))
+            .context("spawning local fs watch thread")?;
+
+        self.watchers
+            .lock()
+            .expect("local fs watcher lock poisoned")
+            .insert(token.clone(), watcher);
+        Ok(token)
+    }
+
+    pub fn unwatch(&self, token: &str) -> Result<()> {
+        self.watchers
+            .lock()
+            .expect("local fs watcher lock poisoned")
+            .remove(token)
+            .map(|_| ())
+            .ok_or_else(|| anyhow!("unknown local fs watch: {token}"))
+    }
+
+    pub fn clear(&self) {
+        self.watchers
+            .lock()
+            .expect("local fs watcher lock poisoned")
+            .clear();
+    }
+}
+
+fn debounce_loop<F>(event_rx: std_mpsc::Receiver<Event>, on_changes: F)
+where
+    F: Fn(Vec<LocalFsChange>),
+{
+    while let Ok(first) = event_rx.recv() {
+        let mut pending = BTreeMap::new();
+        absorb_event(&mut pending, first);
+
+        let disconnected = loop {
+            match event_rx.recv_timeout(LOCAL_FS_DEBOUNCE) {
+                Ok(event) => absorb_event(&mut pending, event),
+                Err(std_mpsc::RecvTimeoutError::Timeout) => break false,
+                Err(std_mpsc::RecvTimeoutError::Disconnected) => break true,
+            }
+        };
+
+        if !pending.is_empty() {
+            on_changes(
+                pending
+                    .into_iter()
+                    .map(|(path, kind)| LocalFsChange {
+                        path: path.to_string_lossy().to_string(),
+                        kind,
+                    })
+                    .collect(),
+            );
+        }
+
+        if disconnected {
+            break;
+        }
+    }
+}
+
+fn absorb_event(pending: &mut BTreeMap<PathBuf, LocalFsChangeKind>, event: Event) {
+    let Some(kind) = change_kind(&event.kind) else {
+        return;
+    };
+
+    for path in event.paths {
+        let merged = match (pending.get(&path).copied(), kind) {
+            // A file created and then written within one window is still just "created".
+            (Some(LocalFsChangeKind::Created), LocalFsChangeKind::Modified) => {
+                LocalFsChangeKind::Created
+            }
+            (_, kind) => kind,
+        };
+        pending.insert(path, merged);
+    }
+}
+
+fn change_kind(kind: &EventKind) -> Option<LocalFsChangeKind> {
+    match kind {
+        EventKind::Create(_) | EventKind::Modify(ModifyKind::Name(RenameMode::To)) => {
+            Some(LocalFsChangeKind::Created)
+        }
+        EventKind::Remove(_) | EventKind::Modify(ModifyKind::Name(RenameMode::From)) => {
+            Some(LocalFsChangeKind::Removed)
+        }
+        EventKind::Modify(_) | EventKind::Any => Some(LocalFsChangeKind::Modified),
+        EventKind::Access(_) | EventKind::Other => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use notify::event::{CreateKind, DataChange, RemoveKind};
+
+    use super::*;
+
+    fn event(kind: EventKind, path: &str) -> Event {
+        Event::new(kind).add_path(PathBuf::from(path))
+    }
+
+    #[test]
+    fn coalesces_create_then_modify_into_created() {
+        let mut pending = BTreeMap::new();
+        absorb_event(
+            &mut pending,
+            event(EventKind::Create(CreateKind::File), "/tmp/a"),
+        );
+        absorb_event(
+            &mut pending,
+            event(
+                EventKind::Modify(ModifyKind::Data(DataChange::Content)),
+                "/tmp/a",
+            ),
+        );
+        absorb_event(
+            &mut pending,
+            event(EventKind::Remove(RemoveKind::File), "/tmp/b"),
+        );
+
+        assert_eq!(
+            pending.get(Path::new("/tmp/a")),
+            Some(&LocalFsChangeKind::Created)
+        );
+        assert_eq!(
+            pending.get(Path::new("/tmp/b")),
+            Some(&LocalFsChangeKind::Removed)
+        );
+    }
+
+    #[test]
+    fn ignores_access_events() {
+        let mut pending = BTreeMap::new();
+        absorb_event(
+            &mut pending,
+            event(EventKind::Access(notify::event::AccessKind::Any), "/tmp/a"),
+        );
+        assert!(pending.is_empty());
+    }
+}
@@ -3,64 +3,103 @@ use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
 use anyhow::{anyhow, Result};
-#[cfg(test)]
-use janus_protocol_ssh::SshHostKey;
-use janus_protocol_ssh::{HostKeyCheck, HostKeyDecision, HostKeyPolicy};
+use janus_domain::HostKeyMode;
+use janus_protocol_ssh::{
+    HostKeyCheck, HostKeyDecision, HostKeyPolicy, SshHostKey, SshHostKeyFingerprints,
+};
 use janus_storage::Storage;
 use uuid::Uuid;
 
-const PENDING_MISMATCH_TTL: Duration = Duration::from_secs(10 * 60);
-const PENDING_MISMATCH_OPEN_WINDOW: Duration = Duration::from_secs(15);
+const PENDING_HOST_KEY_TTL: Duration = Duration::from_secs(10 * 60);
+const PENDING_HOST_KEY_OPEN_WINDOW: Duration = Duration::from_secs(15);
+
+/// Why a presented host key is waiting on the user before it can be pinned.
+#[derive(Debug, Clone)]
+pub enum PendingHostKeyReason {
+    /// First contact with a host in strict mode.
+    Unknown,
+    /// The host presented a different key than the one saved.
+    Mismatch {
+        stored_key_type: String,
+        /// `None` when the saved key can no longer be parsed.
+        stored_fingerprints: Option<SshHostKeyFingerprints>,
+        /// Why the saved key is weak or deprecated, as recorded when it was pinned.
+        stored_weak_advisory: Option<String>,
+    },
+}
 
 #[derive(Debug, Clone)]
-pub struct PendingHostKeyMismatch {
+pub struct PendingHostKey {
     pub token: String,
     pub host: String,
     pub port: i64,
-    pub stored_key_type: String,
-    pub stored_fingerprint: String,
+    pub reason: PendingHostKeyReason,
     pub presented_key_type: String,
-    pub presented_fingerprint: String,
+    pub presented_fingerprints: SshHostKeyFingerprints,
     pub presented_public_key: String,
+    pub presented_weak_advisory: Option<&'static str>,
     created_at: Instant,
 }
 
 #[derive(Debug, Default)]
-struct PendingMismatchStore {
-    by_token: HashMap<String, PendingHostKeyMismatch>,
+struct PendingHostKeyStore {
+    by_token: HashMap<String, PendingHostKey>,
     by_host_port: HashMap<(String, i64), String>,
 }
 
+/// A changed key the user let through for one connection without pinning it.
+#[derive(Debug)]
+struct OneTimeHostKey {
+    key_type: String,
+    public_key: String,
+    accepted_at: Instant,
+}
+
 #[derive(Clone)]
 pub struct DbHostKeyPolicy {
     storage: Storage,
-    pending: Arc<Mutex<PendingMismatchStore>>,
+    pending: Arc<Mutex<PendingHostKeyStore>>,
+    /// Weak keys accepted on first sight, until the connection that saw them reports them.
+    weak_pins: Arc<Mutex<HashMap<(String, i64), &'static str>>>,
+    accepted_once: Arc<Mutex<HashMap<(String, i64), OneTimeHostKey>>>,
 }
 
 impl DbHostKeyPolicy {
     pub fn new(storage: Storage) -> Self {
         Self {
             storage,
-            pending: Arc::new(Mutex::new(PendingMismatchStore::default())),
+            pending: Arc::new(Mutex::new(PendingHostKeyStore::default())),
+            weak_pins: Arc::new(Mutex::new(HashMap::new())),
+            accepted_once: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
-    pub async fn pending_mismatch_for_host_port(
+    /// Returns, once, the advisory for a weak host key that was just accepted on first
+    /// sight for `host:port`.
+    pub fn take_weak_pin_advisory(&self, host: &str, port: i64) -> Option<&'static str> {
+        self.weak_pins
+            .lock()
+            .expect("weak pin lock poisoned")
+            .remove(&(host.to_string(), port))
+    }
+
+    pub async fn pending_host_key_for_host_port(
         &self,
         host: &str,
         port: i64,
-    ) -> Option<PendingHostKeyMismatch> {
-        self.pending_mismatch_for_host_port_within(host, port, PENDING_MISMATCH_OPEN_WINDOW)
+    ) -> Option<PendingHostKey> {
+        self.pending_host_key_for_host_port_within(host, port, PENDING_HOST_KEY_OPEN_WINDOW)
             .await
     }
 
     pub async fn apply_pending_mismatch(&self, token: &str, host: &str, port: i64) -> Result<()> {
-        let pending = {
-            let mut store = self.pending.lock().expect("pending mismatch lock poisoned");
-            Self::prune_pending_locked(&mut store);
-            store.by_token.get(token).cloned()
+        let pending = self
+            .pending_by_token(token)
+            .ok_or_else(|| anyhow!("host key mismatch token is invalid or expired"))?;
+
+        if !matches!(pending.reason, PendingHostKeyReason::Mismatch { .. }) {
+            return Err(anyhow!("host key token is not for a key mismatch"));
         }
-        .ok_or_else(|| anyhow!("host key mismatch token is invalid or expired"))?;
 
         if pending.host != host || pending.port != port {
             return Err(anyhow!(
@@ -68,19 +107,73 @@ impl DbHostKeyPolicy {
             ));
         }
 
+        self.pin_pending(&pending).await
+    }
+
+    /// Lets the next connection to the mismatched host through with the key it presented,
+    /// without replacing the saved key; the one after that is checked as before.
+    pub fn accept_pending_mismatch_once(&self, token: &str) -> Result<()> {
+        let pending = self
+            .pending_by_token(token)
+            .ok_or_else(|| anyhow!("host key mismatch token is invalid or expired"))?;
+
+        if !matches!(pending.reason, PendingHostKeyReason::Mismatch { .. }) {
+            return Err(anyhow!("host key token is not for a key mismatch"));
+        }
+
+        self.accepted_once
+            .lock()
+            .expect("accepted host key lock poisoned")
+            .insert(
+                (pending.host.clone(), pending.port),
+                OneTimeHostKey {
+                    key_type: pending.presented_key_type.clone(),
+                    public_key: pending.presented_public_key.clone(),
+                    accepted_at: Instant::now(),
+                },
+            );
+        self.forget_pending(&pending.token);
+        Ok(())
+    }
+
+    /// Pins the key a strict-mode host presented on first contact.
+    pub async fn approve_pending_unknown(&self, token: &str) -> Result<()> {
+        let pending = self
+            .pending_by_token(token)
+            .ok_or_else(|| anyhow!("host key approval token is invalid or expired"))?;
+
+        if !matches!(pending.reason, PendingHostKeyReason::Unknown) {
+            return Err(anyhow!("host key token is not for an unknown host"));
+        }
+
+        self.pin_pending(&pending).await
+    }
+
+    fn pending_by_token(&self, token: &str) -> Option<PendingHostKey> {
+        let mut store = self.pending.lock().expect("pending host key lock poisoned");
+        Self::prune_pending_locked(&mut store);
+        store.by_token.get(token).cloned()
+    }
+
+    async fn pin_pending(&self, pending: &PendingHostKey) -> Result<()> {
         self.storage
-            .upsert_ssh_known_host(
-                host,
-                port,
+            .upsert_ssh_known_host_with_advisory(
+                &pending.host,
+                pending.port,
                 &pending.presented_key_type,
                 &pending.presented_public_key,
+                pending.presented_weak_advisory,
             )
             .await?;
 
-        let mut store = self.pending.lock().expect("pending mismatch lock poisoned");
-        if let Some(current) = store.by_token.get(token) {
-            let host_port = (current.host.clone(), current.port);
-            store.by_token.remove(token);
+        self.forget_pending(&pending.token);
+        Ok(())
+    }
+
+    fn forget_pending(&self, token: &str) {
+        let mut store = self.pending.lock().expect("pending host key lock poisoned");
+        if let Some(current) = store.by_token.remove(token) {
+            let host_port = (current.host, current.port);
             if store
                 .by_host_port
                 .get(&host_port)
@@ -89,17 +182,30 @@ impl DbHostKeyPolicy {
                 store.by_host_port.remove(&host_port);
             }
         }
+    }
 
-        Ok(())
+    /// Consumes a one-time acceptance for `host:port` if it was given for `presented`.
+    fn take_accepted_once(&self, host: &str, port: i64, presented: &SshHostKey) -> bool {
+        let mut accepted = self
+            .accepted_once
+            .lock()
+            .expect("accepted host key lock poisoned");
+        let host_port = (host.to_string(), port);
+        let Some(once) = accepted.remove(&host_port) else {
+            return false;
+        };
+        once.accepted_at.elapsed() <= PENDING_HOST_KEY_TTL
+            && once.key_type == presented.key_type
+            && once.public_key == presented.public_key
     }
 
-    async fn pending_mismatch_for_host_port_within(
+    async fn pending_host_key_for_host_port_within(
         &self,
         host: &str,
         port: i64,
         max_age: Duration,
-    ) -> Option<PendingHostKeyMismatch> {
-        let mut store = self.pending.lock().expect("pending mismatch lock poisoned");
+    ) -> Option<PendingHostKey> {
+        let mut store = self.pending.lock().expect("pending host key lock poisoned");
         Self::prune_pending_locked(&mut store);
         let token = store.by_host_port.get(&(host.to_string(), port))?.clone();
         let pending = store.by_token.get(&token)?.clone();
@@ -109,30 +215,26 @@ impl DbHostKeyPolicy {
         Some(pending)
     }
 
-    #[allow(clippy::too_many_arguments)]
-    fn register_pending_mismatch(
+    fn register_pending(
         &self,
         host: &str,
         port: i64,
-        stored_key_type: &str,
-        stored_fingerprint: &str,
-        presented_key_type: &str,
-        presented_fingerprint: &str,
-        presented_public_key: &str,
+        reason: PendingHostKeyReason,
+        presented: &SshHostKey,
     ) -> String {
-        let mut store = self.pending.lock().expect("pending mismatch lock poisoned");
+        let mut store = self.pending.lock().expect("pending host key lock poisoned");
         Self::prune_pending_locked(&mut store);
 
         let token = Uuid::new_v4().to_string();
-        let pending = PendingHostKeyMismatch {
+        let pending = PendingHostKey {
             token: token.clone(),
             host: host.to_string(),
             port,
-            stored_key_type: stored_key_type.to_string(),
-            stored_fingerprint: stored_fingerprint.to_string(),
-            presented_key_type: presented_key_type.to_string(),
-            presented_fingerprint: presented_fingerprint.to_string(),
-            presented_public_key: presented_public_key.to_string(),
+            reason,
+            presented_key_type: presented.key_type.clone(),
+            presented_fingerprints: presented.fingerprints.clone(),
+            presented_public_key: presented.public_key.clone(),
+            presented_weak_advisory: presented.weak_advisory,
             created_at: Instant::now(),
         };
 
@@ -144,12 +246,12 @@ impl DbHostKeyPolicy {
         token
     }
 
-    fn prune_pending_locked(store: &mut PendingMismatchStore) {
+    fn prune_pending_locked(store: &mut PendingHostKeyStore) {
         let expired_tokens: Vec<String> = store
             .by_token
             .iter()
             .filter_map(|(token, pending)| {
-                if pending.created_at.elapsed() > PENDING_MISMATCH_TTL {
+                if pending.created_at.elapsed() > PENDING_HOST_KEY_TTL {
                     Some(token.clone())
                 } else {
                     None
@@ -175,7 +277,22 @@ impl DbHostKeyPolicy {
 #[async_trait::async_trait]
 impl HostKeyPolicy for DbHostKeyPolicy {
     async fn check_host_key(&self, check: HostKeyCheck<'_>) -> Result<HostKeyDecision> {
-        if !check.strict_host_key {
+        self.check(check, true).await
+    }
+
+    async fn check_host_key_unpinned(&self, check: HostKeyCheck<'_>) -> Result<HostKeyDecision> {
+        self.check(check, false).await
+    }
+}
+
+impl DbHostKeyPolicy {
+    /// With `pin_first_seen` false, a key `Tofu` would pin is let through unsaved.
+    async fn check(
+        &self,
+        check: HostKeyCheck<'_>,
+        pin_first_seen: bool,
+    ) -> Result<HostKeyDecision> {
+        if check.host_key_mode == HostKeyMode::Off {
             return Ok(HostKeyDecision::Accept);
         }
 
@@ -183,13 +300,49 @@ impl HostKeyPolicy for DbHostKeyPolicy {
         let existing = self.storage.get_ssh_known_host(check.host, port).await?;
 
         match existing {
+            None if check.host_key_mode == HostKeyMode::Strict => {
+                let token = self.register_pending(
+                    check.host,
+                    port,
+                    PendingHostKeyReason::Unknown,
+                    check.server_key,
+                );
+
+                tracing::warn!(
+                    host = check.host,
+                    port = check.port,
+                    approval_token = %token,
+                    key_type = %check.server_key.key_type,
+                    fingerprint = %check.server_key.fingerprints.sha256_base64,
+                    "unknown SSH host key; connection rejected until user approves it"
+                );
+
+                return Ok(HostKeyDecision::Reject);
+            }
+            None if !pin_first_seen => {
+                tracing::debug!(
+                    host = check.host,
+                    port = check.port,
+                    key_type = %check.server_key.key_type,
+                    fingerprint = %check.server_key.fingerprints.sha256_base64,
+                    "accepted first-seen SSH host key without pinning it"
+                );
+
+                if let Some(advisory) = check.server_key.weak_advisory {
+                    self.weak_pins
+                        .lock()
+                        .expect("weak pin lock poisoned")
+                        .insert((check.host.to_string(), port), advisory);
+                }
+            }
             None => {
                 self.storage
-                    .upsert_ssh_known_host(
+                    .upsert_ssh_known_host_with_advisory(
                         check.host,
                         port,
                         &check.server_key.key_type,
                         &check.server_key.public_key,
+                        check.server_key.weak_advisory,
                     )
                     .await?;
 
@@ -197,9 +350,23 @@ impl HostKeyPolicy for DbHostKeyPolicy {
                     host = check.host,
                     port = check.port,
                     key_type = %check.server_key.key_type,
-                    fingerprint = %check.server_key.sha256_fingerprint,
+                    fingerprint = %check.server_key.fingerprints.sha256_base64,
                     "pinned first-seen SSH host key"
                 );
+
+                if let Some(advisory) = check.server_key.weak_advisory {
+                    tracing::warn!(
+                        host = check.host,
+                        port = check.port,
+                        key_type = %check.server_key.key_type,
+                        advisory,
+                        "pinned a weak SSH host key"
+                    );
+                    self.weak_pins
+                        .lock()
+                        .expect("weak pin lock poisoned")
+                        .insert((check.host.to_string(), port), advisory);
+                }
             }
             Some(known_host) => {
                 let keys_match = known_host.key_type == check.server_key.key_type
@@ -214,20 +381,36 @@ impl HostKeyPolicy for DbHostKeyPolicy {
                         host = check.host,
                         port = check.port,
                         key_type = %check.server_key.key_type,
-                        fingerprint = %check.server_key.sha256_fingerprint,
+                        fingerprint = %check.server_key.fingerprints.sha256_base64,
                         "SSH host key matched saved key"
                     );
+                } else if self.take_accepted_once(check.host, port, check.server_key) {
+                    tracing::warn!(
+                        host = check.host,
+                        port = check.port,
+                        stored_key_type = %known_host.key_type,
+                        presented_key_type = %check.server_key.key_type,
+                        presented_fingerprint = %check.server_key.fingerprints.sha256_base64,
+                        "accepted changed SSH host key for this connection only"
+                    );
+
+                    return Ok(HostKeyDecision::AcceptOnce);
                 } else {
-                    let stored_fingerprint = fingerprint_from_public_key(&known_host.public_key)
-                        .unwrap_or_else(|| "unknown".to_string());
-                    let token = self.register_pending_mismatch(
+                    let stored_fingerprints =
+                        SshHostKeyFingerprints::from_openssh(&known_host.public_key).ok();
+                    let stored_fingerprint = stored_fingerprints
+                        .as_ref()
+                        .map_or("unknown", |fingerprints| &fingerprints.sha256_base64)
+                        .to_string();
+                    let token = self.register_pending(
                         check.host,
                         port,
-                        &known_host.key_type,
-                        &stored_fingerprint,
-                        &check.server_key.key_type,
-                        &check.server_key.sha256_fingerprint,
-                        &check.server_key.public_key,
+                        PendingHostKeyReason::Mismatch {
+                            stored_key_type: known_host.key_type.clone(),
+                            stored_fingerprints,
+                            stored_weak_advisory: known_host.weak_advisory.clone(),
+                        },
+                        check.server_key,
                     );
 
                     tracing::warn!(
@@ -237,7 +420,7 @@ impl HostKeyPolicy for DbHostKeyPolicy {
                         stored_key_type = %known_host.key_type,
                         stored_fingerprint = %stored_fingerprint,
                         presented_key_type = %check.server_key.key_type,
-                        presented_fingerprint = %check.server_key.sha256_fingerprint,
+                        presented_fingerprint = %check.server_key.fingerprints.sha256_base64,
                         "SSH host key mismatch; connection rejected until user confirms key update"
                     );
 
@@ -250,16 +433,10 @@ impl HostKeyPolicy for DbHostKeyPolicy {
     }
 }
 
-fn fingerprint_from_public_key(openssh_public_key: &str) -> Option<String> {
-    let key = russh::keys::ssh_key::PublicKey::from_openssh(openssh_public_key).ok()?;
-    Some(
-        key.fingerprint(russh::keys::ssh_key::HashAlg::Sha256)
-            .to_string(),
-    )
-}
-
 #[cfg(test)]
 mod tests {
+    use janus_protocol_ssh::weak_host_key_advisory;
+
     use super::*;
 
     fn temp_db_path() -> std::path::PathBuf {
@@ -271,10 +448,17 @@ mod tests {
     }
 
     fn mock_key(key_type: &str, key_data: &str, fingerprint: &str) -> SshHostKey {
+        let digest = fingerprint.trim_start_matches("SHA256:");
         SshHostKey {
+            weak_advisory: weak_host_key_advisory(key_type, Some(key_type)),
             key_type: key_type.to_string(),
             public_key: format!("{key_type} {key_data}"),
-            sha256_fingerprint: fingerprint.to_string(),
+            fingerprints: SshHostKeyFingerprints {
+                sha256_base64: fingerprint.to_string(),
+                sha256_hex: format!("SHA256:{digest}:hex"),
+                md5_base64: format!("MD5:{digest}"),
+                md5_hex: format!("MD5:{digest}:hex"),
+            },
         }
     }
 
@@ -293,7 +477,7 @@ mod tests {
             .check_host_key(HostKeyCheck {
                 host: "example.com",
                 port: 22,
-                strict_host_key: false,
+                host_key_mode: HostKeyMode::Off,
                 server_key: &key,
             })
             .await
@@ -309,6 +493,136 @@ mod tests {
         let _ = std::fs::remove_file(db_path);
     }
 
+    #[tokio::test]
+    async fn reports_weak_keys_pinned_on_first_sight_once() {
+        let db_path = temp_db_path();
+        let storage = Storage::new(&db_path).await.expect("storage init");
+        let policy = DbHostKeyPolicy::new(storage.clone());
+
+        for (host, key_type) in [
+            ("old.example.com", "ssh-dss"),
+            ("new.example.com", "ssh-ed25519"),
+        ] {
+            let key = mock_key(key_type, "AAAATestKeyData", "SHA256:first");
+            let result = policy
+                .check_host_key(HostKeyCheck {
+                    host,
+                    port: 22,
+                    host_key_mode: HostKeyMode::Tofu,
+                    server_key: &key,
+                })
+                .await
+                .expect("host key check");
+            assert_eq!(result, HostKeyDecision::Accept);
+        }
+
+        let pinned = storage
+            .get_ssh_known_host("old.example.com", 22)
+            .await
+            .expect("read stored host")
+            .expect("weak key pinned");
+        assert!(pinned.weak_advisory.is_some());
+        assert_eq!(
+            policy.take_weak_pin_advisory("old.example.com", 22),
+            pinned.weak_advisory.as_deref()
+        );
+        assert_eq!(policy.take_weak_pin_advisory("old.example.com", 22), None);
+        assert_eq!(policy.take_weak_pin_advisory("new.example.com", 22), None);
+
+        let _ = std::fs::remove_file(db_path);
+    }
+
+    #[tokio::test]
+    async fn unpinned_checks_accept_first_seen_keys_without_saving_them() {
+        let db_path = temp_db_path();
+        let storage = Storage::new(&db_path).await.expect("storage init");
+        let policy = DbHostKeyPolicy::new(storage.clone());
+        let key = mock_key("ssh-dss", "AAAATestKeyData", "SHA256:first");
+
+        let result = policy
+            .check_host_key_unpinned(HostKeyCheck {
+                host: "test.example.com",
+                port: 22,
+                host_key_mode: HostKeyMode::Tofu,
+                server_key: &key,
+            })
+            .await
+            .expect("host key check");
+        assert_eq!(result, HostKeyDecision::Accept);
+
+        let stored = storage
+            .get_ssh_known_host("test.example.com", 22)
+            .await
+            .expect("read stored host");
+        assert!(stored.is_none());
+        assert!(policy
+            .take_weak_pin_advisory("test.example.com", 22)
+            .is_some());
+
+        let _ = std::fs::remove_file(db_path);
+    }
+
+    #[tokio::test]
+    async fn strict_mode_stages_unknown_host_until_approved() {
+        let db_path = temp_db_path();
+        let storage = Storage::new(&db_path).await.expect("storage init");
+        let policy = DbHostKeyPolicy::new(storage.clone());
+        let key = mock_key(
+            "ssh-ed25519",
+            "AAAAC3NzaC1lZDI1NTE5AAAAITestKeyData",
+            "SHA256:first",
+        );
+
+        let result = policy
+            .check_host_key(HostKeyCheck {
+                host: "example.com",
+                port: 22,
+                host_key_mode: HostKeyMode::Strict,
+                server_key: &key,
+            })
+            .await
+            .expect("host key check");
+        assert_eq!(result, HostKeyDecision::Reject);
+
+        let stored = storage
+            .get_ssh_known_host("example.com", 22)
+            .await
+            .expect("read stored host");
+        assert!(stored.is_none());
+
+        let pending = policy
+            .pending_host_key_for_host_port("example.com", 22)
+            .await
+            .expect("unknown host is staged");
+        assert!(matches!(pending.reason, PendingHostKeyReason::Unknown));
+        assert!(policy
+            .apply_pending_mismatch(&pending.token, "example.com", 22)
+            .await
+            .is_err());
+
+        policy
+            .approve_pending_unknown(&pending.token)
+            .await
+            .expect("approve unknown host");
+
+        let result = policy
+            .check_host_key(HostKeyCheck {
+                host: "example.com",
+                port: 22,
+                host_key_mode: HostKeyMode::Strict,
+                server_key: &key,
+            })
+            .await
+            .expect("host key check after approval");
+        assert_eq!(result, HostKeyDecision::Accept);
+        assert!(policy
+            .approve_pending_unknown(&pending.token)
+            .await
+            .is_err());
+
+        let _ = std::fs::remove_file(db_path);
+    }
+
     #[tokio::test]
     async fn strict_enabled_keeps_original_key_on_mismatch() {
         let db_path = temp_db_path();
@@ -324,7 +638,7 @@ mod tests {
             .check_host_key(HostKeyCheck {
                 host: "example.com",
                 port: 22,
-                strict_host_key: true,
+                host_key_mode: HostKeyMode::Tofu,
                 server_key: &first_key,
             })
             .await
@@ -339,20 +653,21 @@ mod tests {
             .check_host_key(HostKeyCheck {
                 host: "example.com",
                 port: 22,
-                strict_host_key: true,
+                host_key_mode: HostKeyMode::Tofu,
                 server_key: &mismatch_key,
             })
             .await
             .expect("mismatch strict check");
         assert_eq!(decision, HostKeyDecision::Reject);
         let mismatch = policy
-            .pending_mismatch_for_host_port("example.com", 22)
+            .pending_host_key_for_host_port("example.com", 22)
             .await
             .expect("mismatch is staged");
 
         assert_eq!(mismatch.host, "example.com");
         assert_eq!(mismatch.port, 22);
         assert_eq!(mismatch.presented_key_type, "ssh-rsa");
+        assert_eq!(mismatch.presented_fingerprints, mismatch_key.fingerprints);
 
         let stored = storage
             .get_ssh_known_host("example.com", 22)
@@ -379,4 +694,109 @@ mod tests {
 
         let _ = std::fs::remove_file(db_path);
     }
+
+    #[tokio::test]
+    async fn mismatch_reports_that_the_saved_key_was_weak() {
+        let db_path = temp_db_path();
+        let storage = Storage::new(&db_path).await.expect("storage init");
+        let policy = DbHostKeyPolicy::new(storage.clone());
+
+        for (key_type, key_data) in [
+            ("ssh-dss", "AAAAOldKeyData"),
+            ("ssh-ed25519", "AAAANewKeyData"),
+        ] {
+            policy
+                .check_host_key(HostKeyCheck {
+                    host: "example.com",
+                    port: 22,
+                    host_key_mode: HostKeyMode::Tofu,
+                    server_key: &mock_key(key_type, key_data, "SHA256:key"),
+                })
+                .await
+                .expect("host key check");
+        }
+
+        let mismatch = policy
+            .pending_host_key_for_host_port("example.com", 22)
+            .await
+            .expect("mismatch is staged");
+        let PendingHostKeyReason::Mismatch {
+            stored_weak_advisory,
+            ..
+        } = mismatch.reason
+        else {
+            panic!("expected a mismatch, got {:?}", mismatch.reason);
+        };
+        assert_eq!(
+            stored_weak_advisory.as_deref(),
+            weak_host_key_advisory("ssh-dss", Some("ssh-dss"))
+        );
+
+        let _ = std::fs::remove_file(db_path);
+    }
+
+    #[tokio::test]
+    async fn accept_once_allows_one_connection_without_repinning() {
+        let db_path = temp_db_path();
+        let storage = Storage::new(&db_path).await.expect("storage init");
+        let policy = DbHostKeyPolicy::new(storage.clone());
+        let saved_key = mock_key(
+            "ssh-ed25519",
+            "AAAAC3NzaC1lZDI1NTE5AAAAIFirstKeyData",
+            "SHA256:first",
+        );
+        let changed_key = mock_key(
+            "ssh-ed25519",
+            "AAAAC3NzaC1lZDI1NTE5AAAAIChangedKeyData",
+            "SHA256:changed",
+        );
+        let check = |server_key| HostKeyCheck {
+            host: "example.com",
+            port: 22,
+            host_key_mode: HostKeyMode::Tofu,
+            server_key,
+        };
+
+        policy
+            .check_host_key(check(&saved_key))
+            .await
+            .expect("first check");
+        let decision = policy
+            .check_host_key(check(&changed_key))
+            .await
+            .expect("mismatch check");
+        assert_eq!(decision, HostKeyDecision::Reject);
+        let mismatch = policy
+            .pending_host_key_for_host_port("example.com", 22)
+            .await
+            .expect("mismatch is staged");
+
+        policy
+            .accept_pending_mismatch_once(&mismatch.token)
+            .expect("accept once");
+        assert!(policy
+            .accept_pending_mismatch_once(&mismatch.token)
+            .is_err());
+
+        let decision = policy
+            .check_host_key(check(&changed_key))
+            .await
+            .expect("check after accept once");
+        assert_eq!(decision, HostKeyDecision::AcceptOnce);
+
+        let stored = storage
+            .get_ssh_known_host("example.com", 22)
+            .await
+            .expect("read stored host")
+            .expect("stored host exists");
+        assert_eq!(stored.public_key, saved_key.public_key);
+
+        let decision = policy
+            .check_host_key(check(&changed_key))
+            .await
+            .expect("check after the one connection");
+        assert_eq!(decision, HostKeyDecision::Reject);
+
+        let _ = std::fs::remove_file(db_path);
+    }
 }
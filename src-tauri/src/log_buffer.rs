@@ -0,0 +1,148 @@
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::Layer;
+
+/// Log lines kept for the in-app log viewer; older lines are dropped first.
+pub const LOG_BUFFER_CAPACITY: usize = 2_000;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogRecord {
+    /// Milliseconds since the Unix epoch.
+    pub timestamp_ms: u64,
+    pub level: String,
+    pub target: String,
+    pub message: String,
+    pub fields: BTreeMap<String, String>,
+}
+
+/// A `tracing` layer keeping the most recent events in memory, so users can attach
+/// them to bug reports without a console.
+///
+/// Fields are stored as they format, so a `Secret` logged as a field is redacted here
+/// just as it is on the console.
+#[derive(Clone)]
+pub struct LogBuffer {
+    capacity: usize,
+    records: Arc<Mutex<VecDeque<(Level, LogRecord)>>>,
+}
+
+impl LogBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            records: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+        }
+    }
+
+    /// Up to `limit` of the most recent records at `min_level` or more severe, oldest
+    /// first.
+    pub fn recent(&self, min_level: Level, limit: usize) -> Vec<LogRecord> {
+        let records = self.records.lock().expect("log buffer lock poisoned");
+        let mut recent: Vec<LogRecord> = records
+            .iter()
+            .rev()
+            .filter(|(level, _)| *level <= min_level)
+            .take(limit)
+            .map(|(_, record)| record.clone())
+            .collect();
+        recent.reverse();
+        recent
+    }
+
+    fn push(&self, level: Level, record: LogRecord) {
+        let mut records = self.records.lock().expect("log buffer lock poisoned");
+        if records.len() == self.capacity {
+            records.pop_front();
+        }
+        records.push_back((level, record));
+    }
+}
+
+impl<S: Subscriber> Layer<S> for LogBuffer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        if self.capacity == 0 {
+            return;
+        }
+        let metadata = event.metadata();
+        let mut visitor = RecordVisitor::default();
+        event.record(&mut visitor);
+        let timestamp_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since_epoch| since_epoch.as_millis() as u64);
+        self.push(
+            *metadata.level(),
+            LogRecord {
+                timestamp_ms,
+                level: metadata.level().to_string(),
+                target: metadata.target().to_string(),
+                message: visitor.message,
+                fields: visitor.fields,
+            },
+        );
+    }
+}
+
+#[derive(Default)]
+struct RecordVisitor {
+    message: String,
+    fields: BTreeMap<String, String>,
+}
+
+impl Visit for RecordVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.record(field, value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.record(field, format!("{value:?}"));
+    }
+}
+
+impl RecordVisitor {
+    fn record(&mut self, field: &Field, value: String) {
+        if field.name() == "message" {
+            self.message = value;
+        } else {
+            self.fields.insert(field.name().to_string(), value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use janus_domain::{Secret, REDACTED};
+    use tracing_subscriber::layer::SubscriberExt;
+
+    use super::*;
+
+    #[test]
+    fn keeps_recent_events_with_their_fields() {
+        let logs = LogBuffer::new(2);
+        let subscriber = tracing_subscriber::registry().with(logs.clone());
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!("dropped once the buffer is full");
+            tracing::warn!(host = "db1", password = ?Secret::new("hunter2"), "login failed");
+            tracing::debug!(attempt = 2, "retrying");
+        });
+
+        let recent = logs.recent(Level::TRACE, 10);
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].message, "login failed");
+        assert_eq!(recent[0].level, "WARN");
+        assert_eq!(recent[0].fields["host"], "db1");
+        assert_eq!(recent[0].fields["password"], REDACTED);
+        assert_eq!(recent[1].fields["attempt"], "2");
+
+        let warnings = logs.recent(Level::WARN, 10);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(logs.recent(Level::TRACE, 1)[0].message, "retrying");
+    }
+}
@@ -1,15 +1,25 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 mod commands;
+mod connection_monitor;
+mod external_open;
+mod fs_transfer;
+mod fs_watch;
 mod host_keys;
+mod log_buffer;
 mod state;
 
 use tauri::Manager;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::EnvFilter;
 
 fn main() {
-    tracing_subscriber::fmt()
-        .with_env_filter("info")
-        .compact()
+    let logs = log_buffer::LogBuffer::new(log_buffer::LOG_BUFFER_CAPACITY);
+    tracing_subscriber::registry()
+        .with(EnvFilter::new("info"))
+        .with(tracing_subscriber::fmt::layer().compact())
+        .with(logs.clone())
         .init();
 
     install_rustls_provider();
@@ -17,14 +27,32 @@ fn main() {
     tauri::Builder::default()
         .plugin(tauri_plugin_clipboard_manager::init())
         .plugin(tauri_plugin_opener::init())
+        .manage(logs)
         .setup(|app| {
             let app_data_dir = app
                 .path()
                 .app_data_dir()
                 .expect("failed to resolve app data directory");
 
-            let state = tauri::async_runtime::block_on(state::AppState::new(app_data_dir))
-                .expect("failed to initialize app state");
+            let data_dir = state::resolve_data_dir(app_data_dir);
+            let cache_dir = app
+                .path()
+                .app_cache_dir()
+                .expect("failed to resolve app cache directory");
+
+            let state = match tauri::async_runtime::block_on(state::AppState::new(
+                data_dir.clone(),
+                cache_dir,
+            )) {
+                Ok(state) => state,
+                Err(error) => {
+                    tracing::error!(
+                        data_dir = %data_dir.display(),
+                        "failed to initialize app state: {error:#}"
+                    );
+                    return Err(error.into());
+                }
+            };
 
             app.manage(state);
 
@@ -35,48 +63,115 @@ fn main() {
 
             Ok(())
         })
+        .on_window_event(|window, event| {
+            if let tauri::WindowEvent::Destroyed = event {
+                if let Some(state) = window.try_state::<state::AppState>() {
+                    state.local_fs_watchers.clear();
+                    state.external_opens.clear();
+                }
+            }
+        })
         .invoke_handler(tauri::generate_handler![
             commands::vault_initialize,
             commands::vault_unlock,
             commands::vault_lock,
             commands::vault_status,
+            commands::vault_create,
+            commands::vault_list,
+            commands::vault_secret_list,
+            commands::vault_secret_delete,
+            commands::vault_password_rotate,
             commands::connection_tree_list,
+            commands::connection_subtree_get,
+            commands::connection_tree_tidy,
             commands::folder_upsert,
             commands::connection_upsert,
             commands::node_move,
+            commands::node_move_check,
             commands::node_delete,
             commands::connection_tcp_probe,
+            commands::connection_monitor_start,
+            commands::connection_monitor_stop,
+            commands::metrics,
+            commands::log_list,
             commands::connection_saved_password_get,
+            commands::connection_password_update,
             commands::ssh_session_open,
+            commands::ssh_session_open_with_sftp,
+            commands::ssh_algorithms_probe,
+            commands::ssh_test_connection,
+            commands::rdp_test_connection,
+            commands::ssh_run_command,
+            commands::ssh_run_command_filtered,
             commands::ssh_host_key_update_from_mismatch,
+            commands::ssh_host_key_accept_once,
+            commands::ssh_host_key_approve,
+            commands::ssh_key_generate,
+            commands::ssh_install_public_key,
+            commands::ssh_idle_timeout_set,
+            commands::ssh_heartbeat_interval_set,
+            commands::ssh_sftp_pipeline_depth_set,
+            commands::ssh_sftp_channel_limit_set,
             commands::ssh_session_write,
+            commands::ssh_session_paste,
+            commands::ssh_session_local_echo_set,
+            commands::snippet_list,
+            commands::snippet_upsert,
+            commands::snippet_delete,
+            commands::ssh_session_send_snippet,
             commands::ssh_session_resize,
+            commands::ssh_session_scrollback,
+            commands::ssh_session_last_error,
+            commands::ssh_session_write_queue,
+            commands::ssh_session_env,
             commands::ssh_session_close,
+            commands::ssh_cancel_open,
             commands::ssh_sftp_open,
             commands::ssh_sftp_close,
             commands::ssh_sftp_list,
+            commands::ssh_sftp_list_page,
+            commands::ssh_sftp_expand_path,
+            commands::ssh_sftp_watch,
+            commands::ssh_sftp_unwatch,
             commands::ssh_sftp_new_file,
             commands::ssh_sftp_new_folder,
             commands::ssh_sftp_rename,
             commands::ssh_sftp_delete,
+            commands::ssh_sftp_statvfs,
             commands::ssh_sftp_upload_file,
             commands::ssh_sftp_download_file,
+            commands::ssh_sftp_transfer_between,
+            commands::ssh_sftp_open_external,
             commands::local_fs_list,
+            commands::local_fs_expand_path,
             commands::local_fs_new_file,
             commands::local_fs_new_folder,
             commands::local_fs_rename,
             commands::local_fs_delete,
+            commands::local_fs_copy,
+            commands::local_fs_move,
+            commands::local_fs_watch,
+            commands::local_fs_unwatch,
             commands::rdp_launch,
             commands::rdp_session_open,
             commands::rdp_session_close,
+            commands::rdp_session_last_error,
             commands::rdp_session_set_bounds,
             commands::rdp_session_show,
             commands::rdp_session_hide,
             commands::import_mremoteng,
+            commands::import_mremoteng_cancel,
             commands::export_mremoteng,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running Janus");
+        .build(tauri::generate_context!())
+        .expect("error while building Janus")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                if let Some(state) = app.try_state::<state::AppState>() {
+                    tauri::async_runtime::block_on(state.shutdown());
+                }
+            }
+        });
 }
 
 fn install_rustls_provider() {
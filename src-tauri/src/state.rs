@@ -1,39 +1,275 @@
-use std::path::PathBuf;
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use janus_protocol_rdp::RdpActiveXManager;
 use janus_protocol_ssh::SshSessionManager;
-use janus_secrets::VaultManager;
+use janus_secrets::Vaults;
 use janus_storage::Storage;
 
+use crate::connection_monitor::ConnectionMonitor;
+use crate::external_open::{ExternalOpens, EXTERNAL_OPEN_TTL};
+use crate::fs_watch::LocalFsWatchers;
 use crate::host_keys::DbHostKeyPolicy;
 
+/// How long quitting waits for sessions to close before leaving the rest to the OS.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Overrides where the database and vault are stored (for synced folders or portable installs).
+pub const DATA_DIR_ENV: &str = "JANUS_DATA_DIR";
+
+/// Returns `JANUS_DATA_DIR` when set, otherwise the platform app-data directory.
+pub fn resolve_data_dir(default_dir: PathBuf) -> PathBuf {
+    match std::env::var_os(DATA_DIR_ENV) {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => default_dir,
+    }
+}
+
+fn ensure_writable_dir(dir: &Path) -> Result<()> {
+    std::fs::create_dir_all(dir)
+        .with_context(|| format!("creating data directory {}", dir.display()))?;
+
+    let probe = dir.join(format!(".janus-write-test-{}", uuid::Uuid::new_v4()));
+    std::fs::write(&probe, b"")
+        .with_context(|| format!("data directory {} is not writable", dir.display()))?;
+    let _ = std::fs::remove_file(&probe);
+    Ok(())
+}
+
+/// The connection each open SSH session was opened from, and the SFTP directory last
+/// remembered for it so the database is only written when that changes.
+#[derive(Clone, Default)]
+pub struct SshSessionConnections {
+    sessions: Arc<Mutex<HashMap<String, SshSessionConnection>>>,
+}
+
+struct SshSessionConnection {
+    connection_id: String,
+    sftp_path: Option<String>,
+}
+
+impl SshSessionConnections {
+    pub fn opened(&self, session_id: &str, connection_id: &str) {
+        self.sessions
+            .lock()
+            .expect("ssh session connections lock poisoned")
+            .insert(
+                session_id.to_string(),
+                SshSessionConnection {
+                    connection_id: connection_id.to_string(),
+                    sftp_path: None,
+                },
+            );
+    }
+
+    pub fn connection(&self, session_id: &str) -> Option<String> {
+        self.sessions
+            .lock()
+            .expect("ssh session connections lock poisoned")
+            .get(session_id)
+            .map(|session| session.connection_id.clone())
+    }
+
+    /// Notes `path` as the session's SFTP directory. Returns the connection to remember
+    /// it for, or `None` when it is unchanged or the session is unknown.
+    pub fn sftp_path_changed(&self, session_id: &str, path: &str) -> Option<String> {
+        let mut sessions = self
+            .sessions
+            .lock()
+            .expect("ssh session connections lock poisoned");
+        let session = sessions.get_mut(session_id)?;
+        if session.sftp_path.as_deref() == Some(path) {
+            return None;
+        }
+        session.sftp_path = Some(path.to_string());
+        Some(session.connection_id.clone())
+    }
+
+    pub fn closed(&self, session_id: &str) {
+        self.sessions
+            .lock()
+            .expect("ssh session connections lock poisoned")
+            .remove(session_id);
+    }
+}
+
+/// Cancel flags for the mRemoteNG imports in progress, keyed by the id the caller gave
+/// each one, so cancelling one import never stops another.
+#[derive(Clone, Default)]
+pub struct ImportCancellations {
+    imports: Arc<Mutex<HashMap<String, Arc<AtomicBool>>>>,
+}
+
+impl ImportCancellations {
+    /// Registers `import_id` until the returned guard is dropped.
+    pub fn start(&self, import_id: &str) -> RunningImport {
+        let cancelled = Arc::new(AtomicBool::new(false));
+        self.imports
+            .lock()
+            .expect("import cancellations lock poisoned")
+            .insert(import_id.to_string(), cancelled.clone());
+        RunningImport {
+            imports: self.imports.clone(),
+            import_id: import_id.to_string(),
+            cancelled,
+        }
+    }
+
+    /// Asks `import_id` to stop. False when no such import is running.
+    pub fn cancel(&self, import_id: &str) -> bool {
+        match self
+            .imports
+            .lock()
+            .expect("import cancellations lock poisoned")
+            .get(import_id)
+        {
+            Some(cancelled) => {
+                cancelled.store(true, Ordering::Relaxed);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+pub struct RunningImport {
+    imports: Arc<Mutex<HashMap<String, Arc<AtomicBool>>>>,
+    import_id: String,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl RunningImport {
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for RunningImport {
+    fn drop(&mut self) {
+        let mut imports = self
+            .imports
+            .lock()
+            .expect("import cancellations lock poisoned");
+        // A later import may have reused the id; leave its flag alone.
+        if imports
+            .get(&self.import_id)
+            .is_some_and(|cancelled| Arc::ptr_eq(cancelled, &self.cancelled))
+        {
+            imports.remove(&self.import_id);
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct AppState {
     pub storage: Storage,
-    pub vault: VaultManager,
+    pub vaults: Vaults,
     pub ssh: SshSessionManager,
     pub ssh_host_keys: DbHostKeyPolicy,
+    pub ssh_connections: SshSessionConnections,
     pub rdp: RdpActiveXManager,
+    pub local_fs_watchers: LocalFsWatchers,
+    pub connection_monitor: ConnectionMonitor,
+    pub external_opens: ExternalOpens,
+    pub imports: ImportCancellations,
 }
 
 impl AppState {
-    pub async fn new(base_dir: PathBuf) -> Result<Self> {
+    /// `cache_dir` is the per-user app cache directory; files opened in another
+    /// application are downloaded under it.
+    pub async fn new(base_dir: PathBuf, cache_dir: PathBuf) -> Result<Self> {
+        ensure_writable_dir(&base_dir)?;
+
         let db_path = base_dir.join("janus.sqlite");
         let vault_path = base_dir.join("vault.enc.json");
 
         let storage = Storage::new(&db_path).await?;
-        let vault = VaultManager::new(&vault_path);
+        // Moves and deletes leave gaps and ties in sibling order; start from a clean numbering.
+        if let Err(error) = storage.normalize_order().await {
+            tracing::warn!(%error, "failed to normalize node order");
+        }
+        let vaults = Vaults::new(&vault_path, &base_dir.join("vaults"));
         let ssh_host_keys = DbHostKeyPolicy::new(storage.clone());
         let ssh_host_key_policy = Arc::new(ssh_host_keys.clone());
+        let external_opens = ExternalOpens::new(cache_dir.join("open"));
+        external_opens.remove_expired(EXTERNAL_OPEN_TTL);
 
         Ok(Self {
             storage,
-            vault,
+            vaults,
             ssh: SshSessionManager::with_host_key_policy(ssh_host_key_policy),
             ssh_host_keys,
+            ssh_connections: SshSessionConnections::default(),
             rdp: RdpActiveXManager::new(),
+            local_fs_watchers: LocalFsWatchers::new(),
+            connection_monitor: ConnectionMonitor::new(),
+            external_opens,
+            imports: ImportCancellations::default(),
         })
     }
+
+    /// Closes every SSH session (SFTP subsessions first) and every RDP session, so servers
+    /// aren't left holding connections after quit. Gives up after `SHUTDOWN_TIMEOUT`.
+    pub async fn shutdown(&self) {
+        let rdp = async {
+            if let Err(error) = self.rdp.close_all().await {
+                tracing::warn!("failed to close RDP sessions: {error:#}");
+            }
+        };
+        let closing = async { tokio::join!(self.ssh.close_all(), rdp) };
+        if tokio::time::timeout(SHUTDOWN_TIMEOUT, closing)
+            .await
+            .is_err()
+        {
+            tracing::warn!(
+                "sessions did not close within {}s of quitting",
+                SHUTDOWN_TIMEOUT.as_secs()
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_sftp_paths_only_when_they_change() {
+        let connections = SshSessionConnections::default();
+        assert_eq!(connections.sftp_path_changed("s1", "/var/www"), None);
+
+        connections.opened("s1", "conn-1");
+        assert_eq!(connections.connection("s1").as_deref(), Some("conn-1"));
+        assert_eq!(
+            connections.sftp_path_changed("s1", "/var/www").as_deref(),
+            Some("conn-1")
+        );
+        assert_eq!(connections.sftp_path_changed("s1", "/var/www"), None);
+        assert_eq!(
+            connections.sftp_path_changed("s1", "/var/log").as_deref(),
+            Some("conn-1")
+        );
+
+        connections.closed("s1");
+        assert_eq!(connections.connection("s1"), None);
+    }
+
+    #[test]
+    fn cancels_only_the_named_import() {
+        let imports = ImportCancellations::default();
+        let first = imports.start("import-1");
+        let second = imports.start("import-2");
+
+        assert!(imports.cancel("import-1"));
+        assert!(first.is_cancelled());
+        assert!(!second.is_cancelled());
+
+        drop(first);
+        assert!(!imports.cancel("import-1"));
+        assert!(imports.cancel("import-2"));
+    }
 }
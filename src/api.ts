@@ -1,67 +1,213 @@
 import { invoke } from '@tauri-apps/api/core';
 import { listen, type UnlistenFn } from '@tauri-apps/api/event';
 import type {
+  AppMetrics,
   ConnectionNode,
+  ConnectionStatus,
   ConnectionUpsert,
+  FileEntry,
+  FileListPage,
   FileListResult,
+  FileSort,
   FolderUpsert,
+  LocalFsChange,
+  LocalTransferProgressEvent,
+  LogRecord,
   NodeMoveRequest,
+  RdpConfigInput,
+  RdpTestResult,
   TcpProbeResult,
+  TreeChangedEvent,
+  ImportProgressEvent,
   ImportReport,
   ImportRequest,
   RdpLifecycleEvent,
   RdpViewport,
+  SecretRef,
   SftpDeleteRequest,
+  SftpDirChangedEvent,
+  SftpExternalSavedEvent,
+  SftpFsStats,
+  SftpListPageRequest,
   SftpListRequest,
+  SftpOpenExternalRequest,
+  SftpOpenExternalResult,
   SftpPathRequest,
   SftpRenameRequest,
+  SftpRenameResult,
   SftpSessionOpenResult,
-  SftpTransferProgressEvent,
+  SftpTransferBetweenRequest,
+  SftpTransferEvent,
   SftpTransferRequest,
+  SftpUploadOutcome,
+  SftpWatchRequest,
+  NodeDeleteResult,
+  SshAlgorithms,
+  SshConfigInput,
+  SshFilteredCommandResult,
+  SshKeyGenerated,
+  SshKeyGenerateRequest,
+  SshPublicKeyInstallResult,
+  SshRunCommandResult,
+  SshSessionEnv,
+  SshSessionExit,
   SshSessionOpenResult,
+  SshSftpOpenResult,
+  SshSystemInfo,
+  SshTestResult,
+  SshWriteQueueStats,
   SessionOptions,
+  Snippet,
+  VaultInfo,
   VaultStatus
 } from './types';
 
 export const api = {
   vaultStatus: (): Promise<VaultStatus> => invoke('vault_status'),
   vaultInitialize: (passphrase: string) => invoke('vault_initialize', { passphrase }),
-  vaultUnlock: (passphrase: string) => invoke('vault_unlock', { passphrase }),
-  vaultLock: () => invoke('vault_lock'),
+  vaultUnlock: (passphrase: string, vaultId?: string) =>
+    invoke('vault_unlock', { passphrase, vaultId: vaultId ?? null }),
+  vaultLock: (vaultId?: string) => invoke('vault_lock', { vaultId: vaultId ?? null }),
+  createVault: (name: string, passphrase: string) => invoke<void>('vault_create', { name, passphrase }),
+  listVaults: () => invoke<VaultInfo[]>('vault_list'),
+  listVaultSecrets: (vaultId?: string) =>
+    invoke<SecretRef[]>('vault_secret_list', { vaultId: vaultId ?? null }),
+  deleteVaultSecret: (id: string) => invoke<boolean>('vault_secret_delete', { id }),
+  /** Replaces a saved password on every connection using it; resolves to how many changed. */
+  rotateVaultPassword: (oldPassword: string, newPassword: string) =>
+    invoke<number>('vault_password_rotate', { oldPassword, newPassword }),
   listTree: (): Promise<ConnectionNode[]> => invoke('connection_tree_list'),
+  getSubtree: (nodeId: string): Promise<ConnectionNode[]> =>
+    invoke('connection_subtree_get', { nodeId }),
+  tidyTree: () => invoke<number>('connection_tree_tidy'),
+  listenTreeChanged: (fn: (event: TreeChangedEvent) => void): Promise<UnlistenFn> =>
+    listen<TreeChangedEvent>('tree://changed', (event) => fn(event.payload)),
   upsertFolder: (folder: FolderUpsert) => invoke('folder_upsert', { folder }),
-  upsertConnection: (connection: ConnectionUpsert) => invoke('connection_upsert', { connection }),
+  /** New secrets go into `vaultId`, or the default vault. */
+  upsertConnection: (connection: ConnectionUpsert, vaultId?: string) =>
+    invoke('connection_upsert', { connection, vaultId: vaultId ?? null }),
   moveNode: (request: NodeMoveRequest) => invoke<void>('node_move', { request }),
-  deleteNode: (nodeId: string) => invoke('node_delete', { nodeId }),
+  /** Rejects with the same message `moveNode` would, without moving anything. */
+  checkMoveNode: (request: NodeMoveRequest) => invoke<void>('node_move_check', { request }),
+  deleteNode: (nodeId: string) => invoke<NodeDeleteResult>('node_delete', { nodeId }),
   probeConnectionTcp: (connectionId: string) =>
     invoke<TcpProbeResult>('connection_tcp_probe', { connectionId }),
+  startConnectionMonitor: (connectionIds: string[], intervalSecs: number) =>
+    invoke<void>('connection_monitor_start', { connectionIds, intervalSecs }),
+  stopConnectionMonitor: () => invoke<void>('connection_monitor_stop'),
+  getMetrics: () => invoke<AppMetrics>('metrics'),
+  /** Most recent log lines, oldest first; `level` is the least severe included. */
+  listLogs: (level?: 'error' | 'warn' | 'info', limit?: number) =>
+    invoke<LogRecord[]>('log_list', { level: level ?? null, limit: limit ?? null }),
   getConnectionSavedPassword: (connectionId: string) =>
     invoke<string>('connection_saved_password_get', { connectionId }),
+  /** With `shared`, also updates every connection saving the same password. */
+  updateConnectionPassword: (connectionId: string, newPassword: string, shared = false) =>
+    invoke<number>('connection_password_update', { connectionId, newPassword, shared }),
   openSsh: (connectionId: string, sessionOpts: SessionOptions | null = null) =>
     invoke<SshSessionOpenResult>('ssh_session_open', { connectionId, sessionOpts }),
+  /** Opens the session and an SFTP subsession on it in one call. */
+  openSshWithSftp: (connectionId: string, sessionOpts: SessionOptions | null = null) =>
+    invoke<SshSftpOpenResult>('ssh_session_open_with_sftp', { connectionId, sessionOpts }),
+  probeSshAlgorithms: (connectionId: string) =>
+    invoke<SshAlgorithms>('ssh_algorithms_probe', { connectionId }),
+  /** Connects and authenticates with unsaved form settings, then disconnects. */
+  testSshConnection: (configInput: SshConfigInput) =>
+    invoke<SshTestResult>('ssh_test_connection', { configInput }),
+  /** Runs the RDP security negotiation against unsaved form settings. */
+  testRdpConnection: (configInput: RdpConfigInput) =>
+    invoke<RdpTestResult>('rdp_test_connection', { configInput }),
+  runSshCommand: (connectionId: string, command: string) =>
+    invoke<SshRunCommandResult>('ssh_run_command', { connectionId, command }),
+  runSshCommandFiltered: (connectionId: string, command: string, pattern: string) =>
+    invoke<SshFilteredCommandResult>('ssh_run_command_filtered', { connectionId, command, pattern }),
   updateSshHostKeyFromMismatch: (connectionId: string, token: string) =>
     invoke<void>('ssh_host_key_update_from_mismatch', { connectionId, token }),
+  acceptSshHostKeyOnce: (token: string) => invoke<void>('ssh_host_key_accept_once', { token }),
+  approveSshHostKey: (token: string) => invoke<void>('ssh_host_key_approve', { token }),
+  generateSshKey: (request: SshKeyGenerateRequest) =>
+    invoke<SshKeyGenerated>('ssh_key_generate', { request }),
+  installSshPublicKey: (connectionId: string, publicKey: string) =>
+    invoke<SshPublicKeyInstallResult>('ssh_install_public_key', { connectionId, publicKey }),
   writeSsh: (sessionId: string, data: string) => invoke('ssh_session_write', { sessionId, data }),
+  pasteSsh: (sessionId: string, data: string) => invoke('ssh_session_paste', { sessionId, data }),
+  setSshLocalEcho: (sessionId: string, enabled: boolean) =>
+    invoke<void>('ssh_session_local_echo_set', { sessionId, enabled }),
+  listSnippets: () => invoke<Snippet[]>('snippet_list'),
+  upsertSnippet: (snippet: Snippet) => invoke<void>('snippet_upsert', { snippet }),
+  deleteSnippet: (snippetId: string) => invoke<void>('snippet_delete', { snippetId }),
+  /** Fills `${name}` placeholders from `values` and types the snippet a line at a time. */
+  sendSshSnippet: (
+    sessionId: string,
+    snippetId: string,
+    values: Record<string, string> = {},
+    trailingNewline = true,
+    lineDelayMs = 0,
+  ) =>
+    invoke<void>('ssh_session_send_snippet', {
+      sessionId,
+      snippetId,
+      values,
+      trailingNewline,
+      lineDelayMs,
+    }),
   resizeSsh: (sessionId: string, cols: number, rows: number) =>
     invoke('ssh_session_resize', { sessionId, cols, rows }),
   closeSsh: (sessionId: string) => invoke('ssh_session_close', { sessionId }),
+  /** Stops an open still connecting under `sessionId`; false when there was none. */
+  cancelSshOpen: (sessionId: string) => invoke<boolean>('ssh_cancel_open', { pendingId: sessionId }),
+  /** Why a session ended, if it did in the last few minutes; for tabs that missed the exit event. */
+  getSshLastError: (sessionId: string) =>
+    invoke<SshSessionExit | null>('ssh_session_last_error', { sessionId }),
+  getSshScrollback: (sessionId: string) =>
+    invoke<string | null>('ssh_session_scrollback', { sessionId }),
+  getSshWriteQueue: (sessionId: string) =>
+    invoke<SshWriteQueueStats>('ssh_session_write_queue', { sessionId }),
+  getSshSessionEnv: (sessionId: string) => invoke<SshSessionEnv>('ssh_session_env', { sessionId }),
+  setSshIdleTimeout: (maxIdleSecs: number | null) =>
+    invoke<void>('ssh_idle_timeout_set', { maxIdleSecs }),
+  /** Heartbeats are off until an interval is set; `null` turns them back off. */
+  setSshHeartbeatInterval: (intervalSecs: number | null) =>
+    invoke<void>('ssh_heartbeat_interval_set', { intervalSecs }),
+  /** Ranges (each its own remote handle) large SFTP transfers are split into. */
+  setSftpPipelineDepth: (depth: number) => invoke<void>('ssh_sftp_pipeline_depth_set', { depth }),
+  setSftpChannelLimit: (limit: number) => invoke<void>('ssh_sftp_channel_limit_set', { limit }),
   openSftp: (sshSessionId: string) =>
     invoke<SftpSessionOpenResult>('ssh_sftp_open', { sshSessionId }),
   closeSftp: (sshSessionId: string, sftpSessionId: string) =>
     invoke<void>('ssh_sftp_close', { sshSessionId, sftpSessionId }),
   listSftp: (request: SftpListRequest) =>
     invoke<FileListResult>('ssh_sftp_list', { request }),
-  sftpNewFile: (request: SftpPathRequest) => invoke<void>('ssh_sftp_new_file', { request }),
-  sftpNewFolder: (request: SftpPathRequest) => invoke<void>('ssh_sftp_new_folder', { request }),
-  sftpRename: (request: SftpRenameRequest) => invoke<void>('ssh_sftp_rename', { request }),
+  listSftpPage: (request: SftpListPageRequest) =>
+    invoke<FileListPage>('ssh_sftp_list_page', { request }),
+  sftpExpandPath: (sshSessionId: string, path: string) =>
+    invoke<string>('ssh_sftp_expand_path', { sshSessionId, path }),
+  watchSftp: (request: SftpWatchRequest) => invoke<string>('ssh_sftp_watch', { request }),
+  unwatchSftp: (sshSessionId: string, watchId: string) =>
+    invoke<void>('ssh_sftp_unwatch', { sshSessionId, watchId }),
+  listenSftpDirChanged: (sftpSessionId: string, fn: (event: SftpDirChangedEvent) => void): Promise<UnlistenFn> =>
+    listen<SftpDirChangedEvent>(`sftp://${sftpSessionId}/dir-changed`, (event) => fn(event.payload)),
+  sftpNewFile: (request: SftpPathRequest) => invoke<FileEntry | null>('ssh_sftp_new_file', { request }),
+  sftpNewFolder: (request: SftpPathRequest) => invoke<FileEntry | null>('ssh_sftp_new_folder', { request }),
+  sftpRename: (request: SftpRenameRequest) => invoke<SftpRenameResult>('ssh_sftp_rename', { request }),
   sftpDelete: (request: SftpDeleteRequest) => invoke<void>('ssh_sftp_delete', { request }),
+  sftpStatvfs: (sshSessionId: string, sftpSessionId: string, path: string) =>
+    invoke<SftpFsStats>('ssh_sftp_statvfs', { sshSessionId, sftpSessionId, path }),
   sftpUploadFile: (request: SftpTransferRequest) =>
-    invoke<void>('ssh_sftp_upload_file', { request }),
+    invoke<SftpUploadOutcome>('ssh_sftp_upload_file', { request }),
   sftpDownloadFile: (request: SftpTransferRequest) =>
     invoke<void>('ssh_sftp_download_file', { request }),
-  listenSftpTransferProgress: (sftpSessionId: string, fn: (event: SftpTransferProgressEvent) => void): Promise<UnlistenFn> =>
-    listen<SftpTransferProgressEvent>(`sftp://${sftpSessionId}/transfer`, (event) => fn(event.payload)),
-  localFsList: (path: string) => invoke<FileListResult>('local_fs_list', { path }),
+  sftpTransferBetween: (request: SftpTransferBetweenRequest) =>
+    invoke<void>('ssh_sftp_transfer_between', { request }),
+  sftpOpenExternal: (request: SftpOpenExternalRequest) =>
+    invoke<SftpOpenExternalResult>('ssh_sftp_open_external', { request }),
+  listenSftpExternalSaved: (sftpSessionId: string, fn: (event: SftpExternalSavedEvent) => void): Promise<UnlistenFn> =>
+    listen<SftpExternalSavedEvent>(`sftp://${sftpSessionId}/external-saved`, (event) => fn(event.payload)),
+  listenSftpTransferProgress: (sftpSessionId: string, fn: (event: SftpTransferEvent) => void): Promise<UnlistenFn> =>
+    listen<SftpTransferEvent>(`sftp://${sftpSessionId}/transfer`, (event) => fn(event.payload)),
+  localFsList: (path: string, sort: FileSort | null = null, showHidden = true) =>
+    invoke<FileListResult>('local_fs_list', { path, sort, showHidden }),
+  localFsExpandPath: (path: string) => invoke<string>('local_fs_expand_path', { path }),
   localFsNewFile: (path: string) => invoke<void>('local_fs_new_file', { request: { path } }),
   localFsNewFolder: (path: string) =>
     invoke<void>('local_fs_new_folder', { request: { path } }),
@@ -69,10 +215,23 @@ export const api = {
     invoke<void>('local_fs_rename', { request: { oldPath, newPath } }),
   localFsDelete: (path: string, isDir: boolean) =>
     invoke<void>('local_fs_delete', { request: { path, isDir } }),
+  localFsCopy: (srcPath: string, dstPath: string, overwrite = false) =>
+    invoke<void>('local_fs_copy', { request: { srcPath, dstPath, overwrite } }),
+  localFsMove: (srcPath: string, dstPath: string, overwrite = false) =>
+    invoke<void>('local_fs_move', { request: { srcPath, dstPath, overwrite } }),
+  listenLocalTransferProgress: (fn: (event: LocalTransferProgressEvent) => void): Promise<UnlistenFn> =>
+    listen<LocalTransferProgressEvent>('fs://transfer', (event) => fn(event.payload)),
+  localFsWatch: (path: string) => invoke<string>('local_fs_watch', { path }),
+  localFsUnwatch: (token: string) => invoke<void>('local_fs_unwatch', { token }),
+  listenLocalFsChanged: (token: string, fn: (changes: LocalFsChange[]) => void): Promise<UnlistenFn> =>
+    listen<LocalFsChange[]>(`fs://${token}/changed`, (event) => fn(event.payload)),
   launchRdp: (connectionId: string) => invoke('rdp_launch', { connectionId, launchOpts: null }),
   openRdp: (connectionId: string, viewport: RdpViewport) =>
     invoke<string>('rdp_session_open', { connectionId, viewport }),
   closeRdp: (sessionId: string) => invoke<void>('rdp_session_close', { sessionId }),
+  /** The last error an RDP session reported, as its state event carried it. */
+  getRdpLastError: (sessionId: string) =>
+    invoke<RdpLifecycleEvent | null>('rdp_session_last_error', { sessionId }),
   setRdpBounds: (sessionId: string, viewport: RdpViewport) =>
     invoke<void>('rdp_session_set_bounds', { sessionId, viewport }),
   showRdp: (sessionId: string) => invoke<void>('rdp_session_show', { sessionId }),
@@ -82,12 +241,34 @@ export const api = {
   listenRdpExit: (sessionId: string, fn: (reason: string) => void): Promise<UnlistenFn> =>
     listen<string>(`rdp://${sessionId}/exit`, (e) => fn(e.payload)),
   importMremote: (request: ImportRequest): Promise<ImportReport> =>
-    invoke('import_mremoteng', { path: request.path, mode: request.mode }),
-  exportMremote: (path: string) => invoke('export_mremoteng', { path, scope: null }),
+    invoke('import_mremoteng', {
+      importId: request.importId,
+      path: request.path,
+      mode: request.mode,
+      targetParentId: request.targetParentId ?? null,
+      newFolder: request.newFolder ?? false,
+    }),
+  /** False when no import with that id is running. */
+  cancelImport: (importId: string) => invoke<boolean>('import_mremoteng_cancel', { importId }),
+  listenImportProgress: (fn: (event: ImportProgressEvent) => void): Promise<UnlistenFn> =>
+    listen<ImportProgressEvent>('import://progress', (event) => fn(event.payload)),
+  /** With `rootNodeId`, exports only that node and its descendants. */
+  exportMremote: (path: string, rootNodeId: string | null = null) =>
+    invoke('export_mremoteng', { path, scope: null, rootNodeId }),
   listenStdout: (sessionId: string, fn: (data: string) => void): Promise<UnlistenFn> =>
     listen<string>(`ssh://${sessionId}/stdout`, (event) => fn(event.payload)),
   listenExit: (sessionId: string, fn: (code: number) => void): Promise<UnlistenFn> =>
     listen<number>(`ssh://${sessionId}/exit`, (event) => fn(event.payload)),
+  listenSystemInfo: (sessionId: string, fn: (info: SshSystemInfo) => void): Promise<UnlistenFn> =>
+    listen<SshSystemInfo>(`ssh://${sessionId}/system-info`, (event) => fn(event.payload)),
+  listenNegotiated: (sessionId: string, fn: (algorithms: SshAlgorithms) => void): Promise<UnlistenFn> =>
+    listen<SshAlgorithms>(`ssh://${sessionId}/negotiated`, (event) => fn(event.payload)),
+  listenTitle: (sessionId: string, fn: (title: string) => void): Promise<UnlistenFn> =>
+    listen<string>(`ssh://${sessionId}/title`, (event) => fn(event.payload)),
+  listenHeartbeat: (sessionId: string, fn: (lastActivitySecs: number) => void): Promise<UnlistenFn> =>
+    listen<number>(`ssh://${sessionId}/heartbeat`, (event) => fn(event.payload)),
+  listenConnectionStatus: (connectionId: string, fn: (status: ConnectionStatus) => void): Promise<UnlistenFn> =>
+    listen<ConnectionStatus>(`connection://${connectionId}/status`, (event) => fn(event.payload)),
   listenErrors: (fn: (message: string) => void): Promise<UnlistenFn> =>
     listen<string>('app://errors', (event) => fn(event.payload))
 };
@@ -3,12 +3,15 @@ import cargoToml from '../../src-tauri/Cargo.toml?raw';
 import { Terminal } from '@xterm/xterm';
 import { writeText as writeClipboardText } from '@tauri-apps/plugin-clipboard-manager';
 import { openUrl } from '@tauri-apps/plugin-opener';
+import type { UnlistenFn } from '@tauri-apps/api/event';
 import { api } from '../api';
 import type {
   ConnectionNode,
+  ConnectionStatus,
   FileEntry,
   FileEntryKind,
   FileListResult,
+  LogRecord,
   NodeKind,
   RdpLifecycleEvent,
   RdpViewport,
@@ -135,6 +138,11 @@ let selectedNodeId: string | null = null;
 let connectionCheckRequestSeq = 0;
 let treeSearchQuery = '';
 
+/** Connections with background reachability monitoring, and their status listeners. */
+const monitoredConnections = new Map<string, UnlistenFn>();
+const connectionStatuses = new Map<string, ConnectionStatus>();
+const CONNECTION_MONITOR_INTERVAL_SECS = 30;
+
 const modalController = createModalController({
   getModalOverlayEl: () => modalOverlayEl,
   getModalOnHide: () => modalOnHide,
@@ -190,6 +198,7 @@ const tabsController = createTabsController({
   getActiveSftpModalTabKey: () => activeSftpModal?.tabKey ?? null,
   hideModal,
   closeSsh: api.closeSsh,
+  cancelSshOpen: api.cancelSshOpen,
   closeRdp: api.closeRdp,
   resizeSsh: api.resizeSsh,
   setRdpBounds: api.setRdpBounds,
@@ -234,6 +243,7 @@ const protocolsController = createProtocolsController({
 
 const treeController = createTreeController({
   listTree: api.listTree,
+  getSubtree: api.getSubtree,
   moveNode: api.moveNode,
   getTreeEl: () => treeEl,
   getTreeSearchQuery: () => treeSearchQuery,
@@ -252,6 +262,7 @@ const treeController = createTreeController({
   clearConnectionCheckStatus,
   checkSelectedConnection,
   svgIcon,
+  getConnectionStatus: (nodeId) => connectionStatuses.get(nodeId),
   openConnectionNode: (node) => {
     if (node.kind === 'ssh') {
       void openSshWithStatus(node);
@@ -275,8 +286,9 @@ const connectionModalController = createConnectionModalController({
   getModalValue,
   getModalOptional,
   upsertConnection: api.upsertConnection,
+  testSshConnection: api.testSshConnection,
+  testRdpConnection: api.testRdpConnection,
   expandedFolders,
-  refreshTree,
   writeStatus,
   formatError,
 });
@@ -291,9 +303,10 @@ const crudModalController = createCrudModalController({
   upsertConnection: api.upsertConnection,
   deleteNode: api.deleteNode,
   importMremote: api.importMremote,
+  cancelImport: api.cancelImport,
+  listenImportProgress: api.listenImportProgress,
   exportMremote: api.exportMremote,
   expandedFolders,
-  refreshTree,
   writeStatus,
   formatError,
 });
@@ -311,6 +324,7 @@ const shellController = createShellController({
   showImportModal,
   showExportModal,
   showPreferencesModal,
+  showDiagnosticsModal,
   showAboutModal,
   hideContextMenu,
   hideModal,
@@ -338,6 +352,7 @@ export function bootApp(): void {
   bootstrapped = true;
 
   void api.listenErrors((message) => writeStatus(message));
+  void api.listenTreeChanged((change) => void treeController.applyTreeChange(change));
 
   window.addEventListener('resize', () => {
     scheduleActiveTabResize();
@@ -549,6 +564,7 @@ function renderMainApp(initiallyUnlocked: boolean): void {
           <button class="menu-trigger" id="settings-menu-trigger" aria-haspopup="menu" aria-expanded="false" aria-controls="settings-menu">Settings</button>
           <div id="settings-menu" class="menu-panel" role="menu" aria-hidden="true">
             <button class="menu-item" id="settings-preferences" role="menuitem"><i class="fa-solid fa-sliders" aria-hidden="true"></i> Preferences</button>
+            <button class="menu-item" id="settings-diagnostics" role="menuitem"><i class="fa-solid fa-chart-line" aria-hidden="true"></i> Diagnostics</button>
             <button class="menu-item" id="settings-about" role="menuitem"><i class="fa-solid fa-circle-info" aria-hidden="true"></i> About</button>
           </div>
         </div>
@@ -762,6 +778,7 @@ function buildFolderMenuActions(node: ConnectionNode | null, isRoot: boolean): M
   if (!isRoot && node) {
     items.push('separator');
     items.push({ label: 'Rename', icon: faIcon('fa-solid fa-i-cursor'), action: () => showRenameModal(node) });
+    items.push({ label: 'Export folder', icon: faIcon('fa-solid fa-file-export'), action: () => showExportModal(node) });
     items.push({ label: 'Delete', icon: faIcon('fa-solid fa-trash'), danger: true, action: () => showDeleteModal(node) });
   }
 
@@ -779,6 +796,18 @@ function buildConnectionMenuActions(node: ConnectionNode): MenuAction[] {
         void openSshWithStatus(node);
       }
     });
+    items.push({
+      label: 'Run Command...',
+      icon: faIcon('fa-solid fa-bolt'),
+      action: () => showRunCommandModal(node)
+    });
+    items.push({
+      label: 'Audit Algorithms',
+      icon: faIcon('fa-solid fa-shield-halved'),
+      action: () => {
+        void showAlgorithmsModal(node).catch((error) => writeStatus(formatError(error)));
+      }
+    });
   } else if (node.kind === 'rdp') {
     items.push({
       label: 'Open RDP',
@@ -789,6 +818,15 @@ function buildConnectionMenuActions(node: ConnectionNode): MenuAction[] {
     });
   }
 
+  const isMonitored = monitoredConnections.has(node.id);
+  items.push({
+    label: isMonitored ? 'Stop Monitoring' : 'Monitor Reachability',
+    icon: faIcon('fa-solid fa-heart-pulse'),
+    action: () => {
+      void toggleConnectionMonitor(node).catch((error) => writeStatus(formatError(error)));
+    }
+  });
+
   const hasSavedPassword =
     node.kind === 'ssh' ? Boolean(node.ssh?.authRef) : Boolean(node.rdp?.credentialRef);
   items.push({
@@ -809,6 +847,40 @@ function buildConnectionMenuActions(node: ConnectionNode): MenuAction[] {
   return items;
 }
 
+async function toggleConnectionMonitor(node: ConnectionNode): Promise<void> {
+  const unlisten = monitoredConnections.get(node.id);
+  if (unlisten) {
+    unlisten();
+    monitoredConnections.delete(node.id);
+    connectionStatuses.delete(node.id);
+  } else {
+    monitoredConnections.set(
+      node.id,
+      await api.listenConnectionStatus(node.id, (status) => {
+        if (!monitoredConnections.has(node.id)) return;
+        connectionStatuses.set(node.id, status);
+        renderTree();
+      })
+    );
+  }
+  renderTree();
+
+  // Connections deleted since they were picked drop out of the monitored set.
+  for (const [id, stale] of monitoredConnections) {
+    if (nodes.some((n) => n.id === id)) continue;
+    stale();
+    monitoredConnections.delete(id);
+    connectionStatuses.delete(id);
+  }
+
+  const ids = [...monitoredConnections.keys()];
+  if (ids.length === 0) {
+    await api.stopConnectionMonitor();
+  } else {
+    await api.startConnectionMonitor(ids, CONNECTION_MONITOR_INTERVAL_SECS);
+  }
+}
+
 function buildTabMenuActions(tabKey: string, tab: SessionTab): MenuAction[] {
   const items: MenuAction[] = [];
 
@@ -821,6 +893,22 @@ function buildTabMenuActions(tabKey: string, tab: SessionTab): MenuAction[] {
         void openSftpModalForTab(tabKey);
       }
     });
+    items.push({
+      label: tab.localEcho ? 'Turn Off Local Echo' : 'Turn On Local Echo',
+      icon: faIcon('fa-solid fa-keyboard'),
+      disabled: tab.sshState !== 'connected' || !tab.sessionId,
+      action: () => {
+        const sessionId = tab.sessionId;
+        if (!sessionId) return;
+        const enabled = !tab.localEcho;
+        void api
+          .setSshLocalEcho(sessionId, enabled)
+          .then(() => {
+            tab.localEcho = enabled;
+          })
+          .catch((error) => writeStatus(formatError(error)));
+      }
+    });
     items.push('separator');
   }
 
@@ -842,9 +930,14 @@ function buildTabMenuActions(tabKey: string, tab: SessionTab): MenuAction[] {
         writeStatus('Connection no longer exists');
         return;
       }
-      void closeTab(tabKey).then(() => {
+      const scrollback =
+        tab.kind === 'ssh' && tab.sessionId
+          ? api.getSshScrollback(tab.sessionId).catch(() => null)
+          : Promise.resolve(null);
+      void scrollback.then(async (replay) => {
+        await closeTab(tabKey);
         if (node.kind === 'ssh') {
-          void openSshWithStatus(node);
+          void openSshWithStatus(node, replay ?? undefined);
         } else if (node.kind === 'rdp') {
           void withStatus(`RDP ready: ${node.name}`, () => openRdp(node));
         }
@@ -944,6 +1037,80 @@ function showAboutModal(): void {
   });
 }
 
+const DIAGNOSTICS_LOG_LINES = 200;
+
+function formatLogRecord(record: LogRecord): string {
+  const time = new Date(record.timestampMs).toISOString();
+  const fields = Object.entries(record.fields)
+    .map(([name, value]) => ` ${name}=${value}`)
+    .join('');
+  return `${time} ${record.level.padEnd(5)} ${record.target}: ${record.message}${fields}`;
+}
+
+function showDiagnosticsModal(): void {
+  showModal('Diagnostics', (card) => {
+    const intro = document.createElement('p');
+    intro.textContent = 'Counted since Janus started. These numbers stay on this machine.';
+
+    const output = document.createElement('pre');
+    output.className = 'ssh-command-output';
+
+    const logsLabel = document.createElement('p');
+    logsLabel.textContent = 'Recent log lines, for attaching to a bug report:';
+
+    const logsOutput = document.createElement('pre');
+    logsOutput.className = 'ssh-command-output';
+
+    const refreshLogs = async (): Promise<void> => {
+      try {
+        const records = await api.listLogs('info', DIAGNOSTICS_LOG_LINES);
+        logsOutput.textContent = records.length ? records.map(formatLogRecord).join('\n') : '(nothing logged yet)';
+      } catch (error) {
+        logsOutput.textContent = `Failed to read logs: ${formatError(error)}`;
+      }
+    };
+
+    const refresh = async (): Promise<void> => {
+      void refreshLogs();
+      try {
+        const metrics = await api.getMetrics();
+        const { ssh, rdp } = metrics;
+        output.textContent = [
+          `SSH sessions:     ${ssh.activeSessions} active, ${ssh.sessionsOpened} opened, ${ssh.sessionsClosed} closed`,
+          `SSH failed opens: ${ssh.failedConnects}`,
+          `SFTP transferred: ${sftpController.formatFileSize(ssh.bytesUploaded)} up, ${sftpController.formatFileSize(ssh.bytesDownloaded)} down`,
+          `RDP sessions:     ${rdp.activeSessions} active, ${rdp.sessionsOpened} opened, ${rdp.sessionsClosed} closed`,
+          `RDP failed opens: ${rdp.failedConnects}`,
+          `Average latency:  ${metrics.averageLatencyMs === null ? '(no monitor probes yet)' : `${metrics.averageLatencyMs} ms`}`
+        ].join('\n');
+      } catch (error) {
+        output.textContent = `Failed to read metrics: ${formatError(error)}`;
+      }
+    };
+
+    const actions = document.createElement('div');
+    actions.className = 'modal-actions';
+
+    const refreshBtn = document.createElement('button');
+    refreshBtn.type = 'button';
+    refreshBtn.className = 'btn btn-ghost';
+    refreshBtn.textContent = 'Refresh';
+    refreshBtn.addEventListener('click', () => void refresh());
+
+    const closeBtn = document.createElement('button');
+    closeBtn.type = 'button';
+    closeBtn.className = 'btn btn-primary';
+    closeBtn.textContent = 'Close';
+    closeBtn.addEventListener('click', hideModal);
+
+    actions.append(refreshBtn, closeBtn);
+    card.append(intro, output, logsLabel, logsOutput, actions);
+    void refresh();
+
+    window.setTimeout(() => closeBtn.focus(), 0);
+  });
+}
+
 function showPreferencesModal(): void {
   showModal('Preferences', (card) => {
     card.classList.add('preferences-modal');
@@ -1066,6 +1233,104 @@ function showEditConnectionModal(node: ConnectionNode): void {
   connectionModalController.showEditConnectionModal(node);
 }
 
+async function showAlgorithmsModal(node: ConnectionNode): Promise<void> {
+  writeStatus(`Probing SSH algorithms: ${node.name}...`);
+  const algorithms = await api.probeSshAlgorithms(node.id);
+  writeStatus(`SSH algorithms probed: ${node.name}`);
+
+  showModal(`SSH algorithms for ${node.name}`, (card) => {
+    const intro = document.createElement('p');
+    intro.textContent = 'Negotiated without logging in; the host key was not verified.';
+
+    const output = document.createElement('pre');
+    output.className = 'ssh-command-output';
+    output.textContent = [
+      `Key exchange: ${algorithms.kex}`,
+      `Host key:     ${algorithms.hostKeyAlg}`,
+      `Cipher:       ${algorithms.cipher}`,
+      `MAC:          ${algorithms.mac ?? '(implicit, AEAD cipher)'}`
+    ].join('\n');
+
+    const actions = document.createElement('div');
+    actions.className = 'modal-actions';
+
+    const closeBtn = document.createElement('button');
+    closeBtn.type = 'button';
+    closeBtn.className = 'btn btn-primary';
+    closeBtn.textContent = 'Close';
+    closeBtn.addEventListener('click', hideModal);
+
+    actions.append(closeBtn);
+    card.append(intro, output, actions);
+  });
+}
+
+function showRunCommandModal(node: ConnectionNode): void {
+  showModal(`Run command on ${node.name}`, (card) => {
+    const field = document.createElement('div');
+    field.className = 'form-field';
+
+    const input = document.createElement('input');
+    input.type = 'text';
+    input.placeholder = 'e.g. uptime';
+    input.autocomplete = 'off';
+    input.spellcheck = false;
+    field.append(input);
+
+    const output = document.createElement('pre');
+    output.className = 'ssh-command-output';
+    output.hidden = true;
+
+    const actions = document.createElement('div');
+    actions.className = 'modal-actions';
+
+    const closeBtn = document.createElement('button');
+    closeBtn.type = 'button';
+    closeBtn.className = 'btn';
+    closeBtn.textContent = 'Close';
+    closeBtn.addEventListener('click', hideModal);
+
+    const runBtn = document.createElement('button');
+    runBtn.type = 'button';
+    runBtn.className = 'btn btn-primary';
+    runBtn.textContent = 'Run';
+
+    const run = async (): Promise<void> => {
+      const command = input.value.trim();
+      if (!command || runBtn.disabled) return;
+
+      runBtn.disabled = true;
+      runBtn.textContent = 'Running...';
+      try {
+        const result = await api.runSshCommand(node.id, command);
+        const text = [result.stdout, result.stderr].filter(Boolean).join('\n');
+        const footer = [
+          result.exitCode === null ? 'No exit status' : `Exit code ${result.exitCode}`,
+          result.truncated ? 'output truncated' : ''
+        ]
+          .filter(Boolean)
+          .join(', ');
+        output.textContent = `${text}${text.endsWith('\n') || !text ? '' : '\n'}[${footer}]`;
+      } catch (error) {
+        output.textContent = formatError(error);
+      } finally {
+        output.hidden = false;
+        runBtn.disabled = false;
+        runBtn.textContent = 'Run';
+      }
+    };
+
+    runBtn.addEventListener('click', () => void run());
+    input.addEventListener('keydown', (event) => {
+      if (event.key === 'Enter') void run();
+    });
+
+    actions.append(closeBtn, runBtn);
+    card.append(field, output, actions);
+    input.focus();
+  });
+}
+
 async function showSavedPasswordModal(node: ConnectionNode): Promise<void> {
   if (node.kind !== 'ssh' && node.kind !== 'rdp') {
     throw new Error('Saved password is available only for SSH and RDP connections');
@@ -1177,14 +1442,14 @@ function showImportModal(): void {
 
 /* ── Export Modal ──────────────────────────────────── */
 
-function showExportModal(): void {
-  crudModalController.showExportModal();
+function showExportModal(root?: ConnectionNode): void {
+  crudModalController.showExportModal(root);
 }
 
 /* ── SSH / RDP Session ────────────────────────────── */
 
-async function openSshWithStatus(node: ConnectionNode): Promise<void> {
-  await protocolsController.openSshWithStatus(node);
+async function openSshWithStatus(node: ConnectionNode, scrollback?: string): Promise<void> {
+  await protocolsController.openSshWithStatus(node, undefined, scrollback);
 }
 
 async function openSsh(node: ConnectionNode): Promise<boolean> {
@@ -1,7 +1,57 @@
-import type { ConnectionNode, ConnectionUpsert } from '../types';
+import type {
+  ConnectionNode,
+  ConnectionUpsert,
+  HostKeyMode,
+  RdpConfigInput,
+  RdpExperience,
+  RdpTestResult,
+  SshAuthMethod,
+  SshConfigInput,
+  SshTestResult,
+} from '../types';
 
 export type ConnectionProtocol = 'ssh' | 'rdp';
 
+/** Common orders of `SshAuthMethod`s, by their comma-joined value. */
+const SSH_AUTH_ORDERS: [string, string][] = [
+  ['publickey,password,none', 'Key, password, then none'],
+  ['publickey,password', 'Key, then password'],
+  ['password,publickey', 'Password, then key'],
+  ['publickey', 'Key only'],
+  ['password', 'Password only'],
+];
+
+const SSH_AUTH_METHOD_LABELS: Record<SshAuthMethod, string> = {
+  publickey: 'key',
+  password: 'password',
+  none: 'none',
+};
+
+function capitalize(text: string): string {
+  return text.charAt(0).toUpperCase() + text.slice(1);
+}
+
+type RdpExperiencePreset = 'default' | 'lan' | 'broadband' | 'modem';
+
+/** Mirrors `RdpExperience`'s default and presets in the domain crate. */
+const RDP_EXPERIENCE_PRESETS: Record<RdpExperiencePreset, RdpExperience> = {
+  default: { wallpaper: true, fontSmoothing: false, desktopComposition: false, menuAnimations: true, themes: true },
+  lan: { wallpaper: true, fontSmoothing: true, desktopComposition: true, menuAnimations: true, themes: true },
+  broadband: { wallpaper: false, fontSmoothing: true, desktopComposition: true, menuAnimations: false, themes: true },
+  modem: { wallpaper: false, fontSmoothing: false, desktopComposition: false, menuAnimations: false, themes: false },
+};
+
+/** The preset `experience` matches, or `custom` for other combinations, e.g. from an import. */
+function rdpExperiencePreset(experience: RdpExperience | undefined): RdpExperiencePreset | 'custom' {
+  if (!experience) return 'default';
+  const match = (Object.keys(RDP_EXPERIENCE_PRESETS) as RdpExperiencePreset[]).find((preset) =>
+    (Object.keys(experience) as (keyof RdpExperience)[]).every(
+      (key) => RDP_EXPERIENCE_PRESETS[preset][key] === experience[key],
+    ),
+  );
+  return match ?? 'custom';
+}
+
 type RequiredConnectionFieldRule = {
   selector: string;
   message: string;
@@ -16,8 +66,9 @@ export type ConnectionModalControllerDeps = {
   getModalValue: (container: HTMLElement, selector: string) => string;
   getModalOptional: (container: HTMLElement, selector: string) => string | null;
   upsertConnection: (payload: ConnectionUpsert) => Promise<unknown>;
+  testSshConnection: (configInput: SshConfigInput) => Promise<SshTestResult>;
+  testRdpConnection: (configInput: RdpConfigInput) => Promise<RdpTestResult>;
   expandedFolders: Set<string | null>;
-  refreshTree: () => Promise<void>;
   writeStatus: (message: string) => void;
   formatError: (error: unknown) => string;
 };
@@ -122,6 +173,12 @@ export function createConnectionModalController(deps: ConnectionModalControllerD
       const actionsDiv = document.createElement('div');
       actionsDiv.className = 'modal-actions';
 
+      const testBtn = document.createElement('button');
+      testBtn.className = 'btn';
+      testBtn.id = 'modal-test';
+      testBtn.textContent = 'Test';
+      testBtn.title = 'Connect with these settings without saving them';
+
       const cancelBtn = document.createElement('button');
       cancelBtn.className = 'btn';
       cancelBtn.id = 'modal-cancel';
@@ -132,10 +189,39 @@ export function createConnectionModalController(deps: ConnectionModalControllerD
       confirmBtn.id = 'modal-confirm';
       confirmBtn.textContent = isEdit ? 'Save' : 'Create';
 
-      actionsDiv.append(cancelBtn, confirmBtn);
+      actionsDiv.append(testBtn, cancelBtn, confirmBtn);
       card.appendChild(actionsDiv);
 
       cancelBtn.addEventListener('click', deps.hideModal);
+      testBtn.addEventListener('click', async () => {
+        clearModalValidation(card);
+        const validation = validateConnectionRequiredFields(card, currentProto);
+        if (!validation.ok) {
+          hasSubmitAttemptedValidation = true;
+          deps.writeStatus(validation.statusMessage);
+          validation.firstInvalid?.focus();
+          return;
+        }
+
+        const payload = buildConnectionPayload(card, currentProto, '', existing?.id ?? '', null, 0);
+        if (!payload) return;
+
+        testBtn.disabled = true;
+        testBtn.textContent = 'Testing...';
+        deps.writeStatus('Testing connection...');
+        try {
+          if (payload.ssh) {
+            deps.writeStatus(describeSshTest(await deps.testSshConnection(payload.ssh)));
+          } else if (payload.rdp) {
+            deps.writeStatus(describeRdpTest(await deps.testRdpConnection(payload.rdp)));
+          }
+        } catch (error) {
+          deps.writeStatus(deps.formatError(error));
+        } finally {
+          testBtn.disabled = false;
+          testBtn.textContent = 'Test';
+        }
+      });
       confirmBtn.addEventListener('click', async () => {
         clearModalValidation(card);
         const validation = validateConnectionRequiredFields(card, currentProto);
@@ -168,11 +254,14 @@ export function createConnectionModalController(deps: ConnectionModalControllerD
             confirmBtn.textContent = isEdit ? 'Save' : 'Create';
             return;
           }
+          if (isEdit) {
+            payload.color = existing!.color;
+            payload.icon = existing!.icon;
+          }
 
           await deps.upsertConnection(payload);
           if (parentId) deps.expandedFolders.add(parentId);
           deps.hideModal();
-          await deps.refreshTree();
           deps.writeStatus(isEdit ? 'Connection updated' : 'Connection created');
         } catch (error) {
           deps.writeStatus(deps.formatError(error));
@@ -185,12 +274,58 @@ export function createConnectionModalController(deps: ConnectionModalControllerD
     });
   }
 
+  function describeSshTest(result: SshTestResult): string {
+    switch (result.type) {
+      case 'succeeded':
+        return result.weakHostKey
+          ? `Connection test succeeded, but the host key is weak: ${result.weakHostKey}`
+          : 'Connection test succeeded';
+      case 'authFailed':
+        return `Authentication failed for ${result.username}`;
+      case 'blocked': {
+        const blocked = result.result;
+        switch (blocked.type) {
+          case 'hostKeyUnknown':
+            return `Host key ${blocked.fingerprint} is not trusted yet; connect to review it`;
+          case 'hostKeyMismatch':
+            return blocked.warning;
+          case 'keyNeedsPassphrase':
+          case 'connectFailed':
+            return blocked.message;
+          case 'opened':
+            return 'Connection test succeeded';
+        }
+      }
+    }
+  }
+
+  function describeRdpTest(result: RdpTestResult): string {
+    switch (result.type) {
+      case 'securityNegotiated': {
+        const security = { standard: 'legacy RDP security', tls: 'TLS', nla: 'NLA' }[result.security];
+        return `RDP security negotiation succeeded (${security}); only the negotiation was tested, not the login`;
+      }
+      case 'negotiationFailed':
+      case 'unreachable':
+        return result.message;
+    }
+  }
+
   function showEditConnectionModal(node: ConnectionNode): void {
     showConnectionModal(node.kind as ConnectionProtocol, node.parentId, node);
   }
 
   function renderSshFields(container: HTMLElement, existing: ConnectionNode | null): void {
     const ssh = existing?.ssh;
+    const hostKeyMode = ssh?.hostKeyMode ?? 'tofu';
+    const rememberSftpPath = ssh?.rememberSftpPath ?? true;
+    const authOrder = (ssh?.authMethods ?? ['publickey', 'password', 'none']).join(',');
+    const authOrders: [string, string][] = SSH_AUTH_ORDERS.some(([value]) => value === authOrder)
+      ? SSH_AUTH_ORDERS
+      : [
+          ...SSH_AUTH_ORDERS,
+          [authOrder, capitalize(authOrder.split(',').map((method) => SSH_AUTH_METHOD_LABELS[method as SshAuthMethod]).join(', '))],
+        ];
     container.innerHTML = `
       <div class="form-row">
         <div class="form-field">
@@ -214,13 +349,64 @@ export function createConnectionModalController(deps: ConnectionModalControllerD
         <label>Private Key Path</label>
         <input id="modal-ssh-key" type="text" placeholder="(optional)" value="${deps.escapeAttr(ssh?.keyPath ?? '')}" />
       </div>
+      <div class="form-field">
+        <label>Certificate Path</label>
+        <input id="modal-ssh-cert" type="text" placeholder="(optional, e.g. id_ed25519-cert.pub)" value="${deps.escapeAttr(ssh?.certPath ?? '')}" />
+      </div>
       <div class="form-field">
         <label>Key Passphrase</label>
         <input id="modal-ssh-key-pass" type="password" placeholder="${existing ? '(unchanged if empty)' : '(optional)'}" />
       </div>
-      <div class="form-checkbox">
-        <input id="modal-ssh-strict" type="checkbox" ${ssh?.strictHostKey !== false ? 'checked' : ''} />
-        <label for="modal-ssh-strict">Strict Host Key Checking</label>
+      <div class="form-field">
+        <label>Proxy Command</label>
+        <input id="modal-ssh-proxy" type="text" placeholder="(optional, e.g. cloudflared access ssh --hostname %h)" value="${deps.escapeAttr(ssh?.proxyCommand ?? '')}" />
+      </div>
+      <div class="form-field">
+        <label>Bind Address</label>
+        <input id="modal-ssh-bind" type="text" placeholder="(optional, local IP to connect from)" value="${deps.escapeAttr(ssh?.bindAddress ?? '')}" />
+      </div>
+      <div class="form-field">
+        <label>Authentication Order</label>
+        <select id="modal-ssh-auth-methods">
+          ${authOrders
+            .map(
+              ([value, label]) =>
+                `<option value="${value}" ${value === authOrder ? 'selected' : ''}>${label}</option>`,
+            )
+            .join('')}
+        </select>
+      </div>
+      <div class="form-field">
+        <label>Host Key Checking</label>
+        <select id="modal-ssh-host-key-mode">
+          <option value="tofu" ${hostKeyMode === 'tofu' ? 'selected' : ''}>Trust on first use</option>
+          <option value="strict" ${hostKeyMode === 'strict' ? 'selected' : ''}>Strict (approve new hosts)</option>
+          <option value="off" ${hostKeyMode === 'off' ? 'selected' : ''}>Off</option>
+        </select>
+      </div>
+      <div class="form-field">
+        <label>Scrollback on Reconnect</label>
+        <select id="modal-ssh-scrollback">
+          <option value="off" ${ssh?.retainScrollback ? '' : 'selected'}>Discard</option>
+          <option value="keep" ${ssh?.retainScrollback ? 'selected' : ''}>Keep recent output in memory</option>
+        </select>
+      </div>
+      <div class="form-field">
+        <label>SFTP Start Directory</label>
+        <select id="modal-ssh-sftp-start">
+          <option value="last" ${rememberSftpPath ? 'selected' : ''}>Last directory visited</option>
+          <option value="home" ${rememberSftpPath ? '' : 'selected'}>Home directory</option>
+        </select>
+      </div>
+      <div class="form-row">
+        <div class="form-field">
+          <label>Default Columns</label>
+          <input id="modal-ssh-cols" type="text" placeholder="(fit to window)" value="${deps.escapeAttr(ssh?.defaultCols != null ? String(ssh.defaultCols) : '')}" />
+        </div>
+        <div class="form-field">
+          <label>Default Rows</label>
+          <input id="modal-ssh-rows" type="text" placeholder="(fit to window)" value="${deps.escapeAttr(ssh?.defaultRows != null ? String(ssh.defaultRows) : '')}" />
+        </div>
       </div>
     `;
   }
@@ -228,6 +414,9 @@ export function createConnectionModalController(deps: ConnectionModalControllerD
   function renderRdpFields(container: HTMLElement, existing: ConnectionNode | null): void {
     const rdp = existing?.rdp;
     const screenMode = rdp?.screenMode ?? 2;
+    const experiencePreset = rdpExperiencePreset(rdp?.experience);
+    const experienceOption = (value: RdpExperiencePreset | 'custom', label: string) =>
+      `<option value="${value}" ${experiencePreset === value ? 'selected' : ''}>${label}</option>`;
     container.innerHTML = `
       <div class="form-row">
         <div class="form-field">
@@ -270,6 +459,40 @@ export function createConnectionModalController(deps: ConnectionModalControllerD
           <input id="modal-rdp-height" type="text" placeholder="(auto)" value="${deps.escapeAttr(rdp?.height != null ? String(rdp.height) : '')}" />
         </div>
       </div>
+      <div class="form-row">
+        <div class="form-field">
+          <label>Connect Timeout (s)</label>
+          <input id="modal-rdp-timeout" type="text" placeholder="(default)" value="${deps.escapeAttr(rdp?.connectTimeoutSecs != null ? String(rdp.connectTimeoutSecs) : '')}" />
+        </div>
+        <div class="form-field">
+          <label>Connect Retries</label>
+          <input id="modal-rdp-retries" type="text" placeholder="0" value="${deps.escapeAttr(rdp?.connectRetries ? String(rdp.connectRetries) : '')}" />
+        </div>
+      </div>
+      <div class="form-field">
+        <label>Smart Cards</label>
+        <select id="modal-rdp-smartcards">
+          <option value="off" ${rdp?.redirectSmartcards ? '' : 'selected'}>Don't redirect</option>
+          <option value="on" ${rdp?.redirectSmartcards ? 'selected' : ''}>Redirect (for smart-card logon)</option>
+        </select>
+      </div>
+      <div class="form-field">
+        <label>Bitmap Cache</label>
+        <select id="modal-rdp-bitmap-cache">
+          <option value="off" ${rdp?.bitmapCache ? '' : 'selected'}>Memory only</option>
+          <option value="on" ${rdp?.bitmapCache ? 'selected' : ''}>Keep on disk (faster reconnects)</option>
+        </select>
+      </div>
+      <div class="form-field">
+        <label>Experience</label>
+        <select id="modal-rdp-experience" data-custom="${deps.escapeAttr(JSON.stringify(rdp?.experience ?? null))}">
+          ${experienceOption('default', 'Client default')}
+          ${experienceOption('lan', 'LAN (all effects)')}
+          ${experienceOption('broadband', 'Broadband (no wallpaper or animations)')}
+          ${experienceOption('modem', 'Modem (no effects)')}
+          ${experiencePreset === 'custom' ? experienceOption('custom', 'Custom (imported)') : ''}
+        </select>
+      </div>
     `;
   }
 
@@ -384,8 +607,20 @@ export function createConnectionModalController(deps: ConnectionModalControllerD
 
       const password = deps.getModalOptional(card, '#modal-ssh-password');
       const keyPath = deps.getModalOptional(card, '#modal-ssh-key');
+      const certPath = deps.getModalOptional(card, '#modal-ssh-cert');
       const keyPassphrase = deps.getModalOptional(card, '#modal-ssh-key-pass');
-      const strictHostKey = (card.querySelector('#modal-ssh-strict') as HTMLInputElement)?.checked ?? true;
+      const proxyCommand = deps.getModalOptional(card, '#modal-ssh-proxy');
+      const bindAddress = deps.getModalOptional(card, '#modal-ssh-bind');
+      const hostKeyMode = ((card.querySelector('#modal-ssh-host-key-mode') as HTMLSelectElement)?.value ??
+        'tofu') as HostKeyMode;
+      const retainScrollback =
+        (card.querySelector('#modal-ssh-scrollback') as HTMLSelectElement)?.value === 'keep';
+      const rememberSftpPath =
+        (card.querySelector('#modal-ssh-sftp-start') as HTMLSelectElement)?.value !== 'home';
+      const authOrder = (card.querySelector('#modal-ssh-auth-methods') as HTMLSelectElement)?.value;
+      const authMethods = authOrder ? (authOrder.split(',') as SshAuthMethod[]) : undefined;
+      const colsStr = deps.getModalValue(card, '#modal-ssh-cols');
+      const rowsStr = deps.getModalValue(card, '#modal-ssh-rows');
 
       return {
         id,
@@ -397,9 +632,17 @@ export function createConnectionModalController(deps: ConnectionModalControllerD
           host,
           port: Number(deps.getModalValue(card, '#modal-ssh-port') || '22'),
           username,
-          strictHostKey,
+          hostKeyMode,
           password,
           keyPath,
+          certPath,
+          retainScrollback,
+          defaultCols: colsStr ? Number(colsStr) : null,
+          defaultRows: rowsStr ? Number(rowsStr) : null,
+          proxyCommand,
+          bindAddress,
+          rememberSftpPath,
+          authMethods,
           keyPassphrase,
         },
       };
@@ -413,6 +656,13 @@ export function createConnectionModalController(deps: ConnectionModalControllerD
     const domain = deps.getModalOptional(card, '#modal-rdp-domain');
     const widthStr = deps.getModalValue(card, '#modal-rdp-width');
     const heightStr = deps.getModalValue(card, '#modal-rdp-height');
+    const timeoutStr = deps.getModalValue(card, '#modal-rdp-timeout');
+    const experienceSelect = card.querySelector('#modal-rdp-experience') as HTMLSelectElement | null;
+    const experiencePreset = (experienceSelect?.value ?? 'default') as RdpExperiencePreset | 'custom';
+    const experience =
+      experiencePreset === 'custom'
+        ? (JSON.parse(experienceSelect!.dataset.custom!) as RdpExperience)
+        : RDP_EXPERIENCE_PRESETS[experiencePreset];
 
     return {
       id,
@@ -429,6 +679,13 @@ export function createConnectionModalController(deps: ConnectionModalControllerD
         password,
         width: widthStr ? Number(widthStr) : null,
         height: heightStr ? Number(heightStr) : null,
+        connectTimeoutSecs: timeoutStr ? Number(timeoutStr) : null,
+        connectRetries: Number(deps.getModalValue(card, '#modal-rdp-retries') || '0'),
+        redirectSmartcards:
+          (card.querySelector('#modal-rdp-smartcards') as HTMLSelectElement)?.value === 'on',
+        bitmapCache:
+          (card.querySelector('#modal-rdp-bitmap-cache') as HTMLSelectElement)?.value === 'on',
+        experience,
       },
     };
   }
@@ -12,9 +12,10 @@ export type CrudModalControllerDeps = {
   upsertConnection: ApiClient['upsertConnection'];
   deleteNode: ApiClient['deleteNode'];
   importMremote: ApiClient['importMremote'];
+  cancelImport: ApiClient['cancelImport'];
+  listenImportProgress: ApiClient['listenImportProgress'];
   exportMremote: ApiClient['exportMremote'];
   expandedFolders: Set<string | null>;
-  refreshTree: () => Promise<void>;
   writeStatus: (message: string) => void;
   formatError: (error: unknown) => string;
 };
@@ -24,7 +25,7 @@ export type CrudModalController = {
   showRenameModal: (node: ConnectionNode) => void;
   showDeleteModal: (node: ConnectionNode) => void;
   showImportModal: () => void;
-  showExportModal: () => void;
+  showExportModal: (root?: ConnectionNode) => void;
 };
 
 export function createCrudModalController(deps: CrudModalControllerDeps): CrudModalController {
@@ -60,7 +61,6 @@ export function createCrudModalController(deps: CrudModalControllerDeps): CrudMo
           await deps.upsertFolder(folder);
           if (parentId) deps.expandedFolders.add(parentId);
           deps.hideModal();
-          await deps.refreshTree();
           deps.writeStatus('Folder created');
         } catch (error) {
           deps.writeStatus(deps.formatError(error));
@@ -102,6 +102,8 @@ export function createCrudModalController(deps: CrudModalControllerDeps): CrudMo
               parentId: node.parentId,
               name,
               orderIndex: node.orderIndex,
+              color: node.color,
+              icon: node.icon,
             };
             await deps.upsertFolder(folder);
           } else {
@@ -111,6 +113,8 @@ export function createCrudModalController(deps: CrudModalControllerDeps): CrudMo
               kind: node.kind as 'ssh' | 'rdp',
               name,
               orderIndex: node.orderIndex,
+              color: node.color,
+              icon: node.icon,
             };
 
             if (node.kind === 'ssh' && node.ssh) {
@@ -118,8 +122,16 @@ export function createCrudModalController(deps: CrudModalControllerDeps): CrudMo
                 host: node.ssh.host,
                 port: node.ssh.port,
                 username: node.ssh.username,
-                strictHostKey: node.ssh.strictHostKey,
+                hostKeyMode: node.ssh.hostKeyMode,
                 keyPath: node.ssh.keyPath ?? null,
+                certPath: node.ssh.certPath ?? null,
+                retainScrollback: node.ssh.retainScrollback ?? false,
+                defaultCols: node.ssh.defaultCols ?? null,
+                defaultRows: node.ssh.defaultRows ?? null,
+                proxyCommand: node.ssh.proxyCommand ?? null,
+                bindAddress: node.ssh.bindAddress ?? null,
+                rememberSftpPath: node.ssh.rememberSftpPath ?? true,
+                authMethods: node.ssh.authMethods,
               };
             } else if (node.kind === 'rdp' && node.rdp) {
               payload.rdp = {
@@ -130,6 +142,11 @@ export function createCrudModalController(deps: CrudModalControllerDeps): CrudMo
                 screenMode: node.rdp.screenMode,
                 width: node.rdp.width ?? null,
                 height: node.rdp.height ?? null,
+                connectTimeoutSecs: node.rdp.connectTimeoutSecs ?? null,
+                connectRetries: node.rdp.connectRetries ?? 0,
+                redirectSmartcards: node.rdp.redirectSmartcards ?? false,
+                bitmapCache: node.rdp.bitmapCache ?? false,
+                experience: node.rdp.experience,
               };
             }
 
@@ -137,7 +154,6 @@ export function createCrudModalController(deps: CrudModalControllerDeps): CrudMo
           }
 
           deps.hideModal();
-          await deps.refreshTree();
           deps.writeStatus('Renamed');
         } catch (error) {
           deps.writeStatus(deps.formatError(error));
@@ -176,10 +192,13 @@ export function createCrudModalController(deps: CrudModalControllerDeps): CrudMo
         btn.textContent = 'Deleting...';
 
         try {
-          await deps.deleteNode(node.id);
+          const result = await deps.deleteNode(node.id);
           deps.hideModal();
-          await deps.refreshTree();
-          deps.writeStatus('Deleted');
+          deps.writeStatus(
+            result.nodesRemoved > 1
+              ? `Deleted ${result.nodesRemoved} items and ${result.secretsRemoved} saved credentials`
+              : 'Deleted',
+          );
         } catch (error) {
           deps.writeStatus(deps.formatError(error));
           btn.disabled = false;
@@ -202,7 +221,7 @@ export function createCrudModalController(deps: CrudModalControllerDeps): CrudMo
     busyText: string,
     idleText: string,
     reportPrefix: string,
-    onSuccess?: () => Promise<void>,
+    onSuccess?: (report: Awaited<ReturnType<ApiClient['importMremote']>>) => Promise<void>,
   ): void {
     const button = card.querySelector(buttonSelector);
     if (!(button instanceof HTMLButtonElement)) return;
@@ -210,18 +229,32 @@ export function createCrudModalController(deps: CrudModalControllerDeps): CrudMo
     button.addEventListener('click', async () => {
       const path = (card.querySelector('#modal-import-path') as HTMLInputElement).value.trim();
       if (!path) return;
+      const newFolder = (card.querySelector('#modal-import-into') as HTMLSelectElement)?.value !== 'root';
 
       button.disabled = true;
       button.textContent = busyText;
 
+      const importId = crypto.randomUUID();
+      const unlisten = await deps.listenImportProgress((progress) => {
+        const reportEl = card.querySelector('#modal-import-report');
+        if (!reportEl || progress.importId !== importId) return;
+        const verb = progress.phase === 'parsed' ? 'Read' : 'Imported';
+        reportEl.innerHTML = `<div class="import-report">${verb} ${progress.done} of ${progress.total}...</div>`;
+      });
+      // While an apply runs, Cancel stops it instead of closing the modal.
+      if (mode === 'apply') card.dataset.importing = importId;
+
       try {
-        const report = await deps.importMremote({ path, mode });
-        renderImportReport(card, reportPrefix, report);
+        const report = await deps.importMremote({ importId, path, mode, newFolder });
+        renderImportReport(card, report.cancelled ? 'Cancelled' : reportPrefix, report);
         if (onSuccess) {
-          await onSuccess();
+          await onSuccess(report);
         }
       } catch (error) {
         deps.writeStatus(deps.formatError(error));
+      } finally {
+        unlisten();
+        delete card.dataset.importing;
       }
 
       button.disabled = false;
@@ -236,6 +269,13 @@ export function createCrudModalController(deps: CrudModalControllerDeps): CrudMo
           <label>Path to mRemoteNG XML</label>
           <input id="modal-import-path" type="text" placeholder="C:\\path\\to\\confCons.xml" />
         </div>
+        <div class="form-field">
+          <label>Import Into</label>
+          <select id="modal-import-into">
+            <option value="folder" selected>New "Imported" folder</option>
+            <option value="root">Tree root</option>
+          </select>
+        </div>
         <div class="modal-actions">
           <button class="btn" id="modal-cancel">Cancel</button>
           <button class="btn" id="modal-dry-run">Dry Run</button>
@@ -244,7 +284,13 @@ export function createCrudModalController(deps: CrudModalControllerDeps): CrudMo
         <div id="modal-import-report"></div>
       `;
 
-      card.querySelector('#modal-cancel')!.addEventListener('click', deps.hideModal);
+      card.querySelector('#modal-cancel')!.addEventListener('click', () => {
+        if (card.dataset.importing) {
+          void deps.cancelImport(card.dataset.importing).catch((error) => deps.writeStatus(deps.formatError(error)));
+          return;
+        }
+        deps.hideModal();
+      });
 
       runImport(card, 'dry_run', '#modal-dry-run', 'Running...', 'Dry Run', 'Dry run');
       runImport(
@@ -254,16 +300,15 @@ export function createCrudModalController(deps: CrudModalControllerDeps): CrudMo
         'Importing...',
         'Apply',
         'Applied',
-        async () => {
-          await deps.refreshTree();
-          deps.writeStatus('Import applied');
+        async (report) => {
+          deps.writeStatus(report.cancelled ? 'Import cancelled' : 'Import applied');
         },
       );
     });
   }
 
-  function showExportModal(): void {
-    deps.showModal('Export mRemoteNG', (card) => {
+  function showExportModal(root?: ConnectionNode): void {
+    deps.showModal(root ? `Export "${root.name}"` : 'Export mRemoteNG', (card) => {
       card.innerHTML += `
         <div class="form-field">
           <label>Export path</label>
@@ -285,7 +330,7 @@ export function createCrudModalController(deps: CrudModalControllerDeps): CrudMo
         btn.textContent = 'Exporting...';
 
         try {
-          await deps.exportMremote(path);
+          await deps.exportMremote(path, root?.id ?? null);
           deps.hideModal();
           deps.writeStatus('Export complete');
         } catch (error) {
@@ -9,6 +9,8 @@ import type {
   RdpLifecycleEvent,
   RdpViewport,
   SshHostKeyMismatchResult,
+  SshHostKeyUnknownResult,
+  SshKeyNeedsPassphraseResult,
 } from '../types';
 import type { RdpSessionTab, SessionTab, SshSessionTab } from './types';
 
@@ -37,18 +39,22 @@ export type ProtocolsControllerDeps = {
 };
 
 export type ProtocolsController = {
-  openSshWithStatus: (node: ConnectionNode) => Promise<void>;
-  openSsh: (node: ConnectionNode) => Promise<boolean>;
+  openSshWithStatus: (node: ConnectionNode, keyPassphrase?: string, scrollback?: string) => Promise<void>;
+  openSsh: (node: ConnectionNode, keyPassphrase?: string, scrollback?: string) => Promise<boolean>;
   openRdp: (node: ConnectionNode) => Promise<void>;
   getRdpViewport: (element: HTMLElement) => RdpViewport | null;
   syncRdpTabVisibility: () => Promise<void>;
 };
 
 export function createProtocolsController(deps: ProtocolsControllerDeps): ProtocolsController {
-  async function openSshWithStatus(node: ConnectionNode): Promise<void> {
+  async function openSshWithStatus(
+    node: ConnectionNode,
+    keyPassphrase?: string,
+    scrollback?: string
+  ): Promise<void> {
     deps.writeStatus(`Opening SSH: ${node.name}...`);
     try {
-      const opened = await openSsh(node);
+      const opened = await openSsh(node, keyPassphrase, scrollback);
       if (opened) {
         deps.writeStatus(`SSH ready: ${node.name}`);
       }
@@ -57,7 +63,7 @@ export function createProtocolsController(deps: ProtocolsControllerDeps): Protoc
     }
   }
 
-  async function openSsh(node: ConnectionNode): Promise<boolean> {
+  async function openSsh(node: ConnectionNode, keyPassphrase?: string, scrollback?: string): Promise<boolean> {
     if (node.kind !== 'ssh') {
       throw new Error('cannot open non-SSH node');
     }
@@ -65,7 +71,7 @@ export function createProtocolsController(deps: ProtocolsControllerDeps): Protoc
       throw new Error('SSH workspace unavailable');
     }
 
-    const sessionId = await openSshSession(node);
+    const sessionId = await openSshSession(node, keyPassphrase, scrollback);
     if (!sessionId) {
       return false;
     }
@@ -78,7 +84,11 @@ export function createProtocolsController(deps: ProtocolsControllerDeps): Protoc
     return true;
   }
 
-  async function openSshSession(node: ConnectionNode): Promise<string | null> {
+  async function openSshSession(
+    node: ConnectionNode,
+    keyPassphrase?: string,
+    scrollback?: string
+  ): Promise<string | null> {
     const workspaceEl = deps.getWorkspaceEl();
     if (node.kind !== 'ssh' || !workspaceEl) {
       throw new Error('cannot open non-SSH node');
@@ -120,9 +130,18 @@ export function createProtocolsController(deps: ProtocolsControllerDeps): Protoc
 
     terminal.open(root);
     fitAddon.fit();
+    if (scrollback) {
+      // Output retained from the session this one replaces.
+      terminal.write(scrollback);
+      terminal.writeln('\r\n[restored output from previous session]');
+    }
 
-    const cols = Math.max(1, terminal.cols || 120);
-    const rows = Math.max(1, terminal.rows || 32);
+    // A tab that isn't laid out yet can't be measured; leaving the size out lets the
+    // connection's default size apply until the first resize.
+    const fitted = fitAddon.proposeDimensions();
+    const measured = !!fitted && Number.isFinite(fitted.cols) && Number.isFinite(fitted.rows);
+    const cols = measured ? Math.max(1, terminal.cols) : undefined;
+    const rows = measured ? Math.max(1, terminal.rows) : undefined;
 
     const cleanup: Array<() => void> = [];
     const tab: SshSessionTab = {
@@ -137,6 +156,11 @@ export function createProtocolsController(deps: ProtocolsControllerDeps): Protoc
       fitAddon,
       sshState: 'connecting',
       exitCode: null,
+      systemInfo: null,
+      algorithms: null,
+      localEcho: false,
+      remoteTitle: null,
+      lastActivitySecs: null,
       cleanup,
     };
     deps.tabs.set(sessionId, tab);
@@ -175,7 +199,9 @@ export function createProtocolsController(deps: ProtocolsControllerDeps): Protoc
       const latest = getCurrentSshTab();
       if (!latest || latest.sshState !== 'connected') return;
 
-      void deps.api.writeSsh(sessionId, text).catch(() => undefined);
+      void deps.api.pasteSsh(sessionId, text).catch((error) => {
+        deps.writeStatus(deps.formatError(error));
+      });
     }
 
     const onTerminalMouseUp = (event: MouseEvent): void => {
@@ -213,6 +239,38 @@ export function createProtocolsController(deps: ProtocolsControllerDeps): Protoc
       });
       cleanup.push(unlistenExit);
 
+      const unlistenSystemInfo = await deps.api.listenSystemInfo(sessionId, (info) => {
+        const current = deps.tabs.get(sessionId);
+        if (!current || current.kind !== 'ssh') return;
+        current.systemInfo = info;
+        deps.renderTabs();
+      });
+      cleanup.push(unlistenSystemInfo);
+
+      const unlistenNegotiated = await deps.api.listenNegotiated(sessionId, (algorithms) => {
+        const current = deps.tabs.get(sessionId);
+        if (!current || current.kind !== 'ssh') return;
+        current.algorithms = algorithms;
+        deps.renderTabs();
+      });
+      cleanup.push(unlistenNegotiated);
+
+      const unlistenTitle = await deps.api.listenTitle(sessionId, (title) => {
+        const current = deps.tabs.get(sessionId);
+        if (!current || current.kind !== 'ssh') return;
+        current.remoteTitle = title.trim() || null;
+        deps.renderTabs();
+      });
+      cleanup.push(unlistenTitle);
+
+      const unlistenHeartbeat = await deps.api.listenHeartbeat(sessionId, (lastActivitySecs) => {
+        const current = deps.tabs.get(sessionId);
+        if (!current || current.kind !== 'ssh') return;
+        current.lastActivitySecs = lastActivitySecs;
+        deps.renderTabs();
+      });
+      cleanup.push(unlistenHeartbeat);
+
       const onDataDisposable = terminal.onData((data) => {
         const current = deps.tabs.get(sessionId);
         if (!current || current.kind !== 'ssh' || current.sshState !== 'connected') {
@@ -231,7 +289,13 @@ export function createProtocolsController(deps: ProtocolsControllerDeps): Protoc
     }
 
     try {
-      const openPromise = deps.api.openSsh(node.id, { cols, rows, sessionId });
+      const openPromise = deps.api.openSsh(node.id, {
+        cols,
+        rows,
+        sessionId,
+        probeSystem: true,
+        keyPassphrase,
+      });
       let watchdogTimer: number | null = null;
       const watchdogPromise = new Promise<never>((_resolve, reject) => {
         watchdogTimer = window.setTimeout(() => {
@@ -285,10 +349,39 @@ export function createProtocolsController(deps: ProtocolsControllerDeps): Protoc
         return null;
       }
 
+      if (openResult.type === 'hostKeyUnknown') {
+        deps.writeStatus(`SSH host key approval required for ${openResult.host}:${openResult.port}`);
+        for (const fn of cleanup) fn();
+        deps.tabs.delete(sessionId);
+        terminal.dispose();
+        root.remove();
+        deps.finalizeTabRemoval(sessionId);
+        showSshHostKeyUnknownModal(node, openResult);
+        return null;
+      }
+
+      if (openResult.type === 'keyNeedsPassphrase') {
+        deps.writeStatus(openResult.message);
+        for (const fn of cleanup) fn();
+        deps.tabs.delete(sessionId);
+        terminal.dispose();
+        root.remove();
+        deps.finalizeTabRemoval(sessionId);
+        showSshKeyPassphraseModal(node, openResult);
+        return null;
+      }
+
+      if (openResult.type === 'connectFailed') {
+        throw new Error(openResult.message);
+      }
+
       if (current.sshState !== 'exited') {
         current.sshState = 'connected';
         setOverlayState(current.overlay, 'connected', '');
       }
+      if (openResult.weakHostKey) {
+        deps.writeStatus(openResult.weakHostKey);
+      }
       deps.renderTabs();
     } catch (error) {
       const failedTab = deps.tabs.get(sessionId);
@@ -318,12 +411,14 @@ export function createProtocolsController(deps: ProtocolsControllerDeps): Protoc
       card.innerHTML += `
         <div class="host-key-warning" role="alert">
           <p class="host-key-warning-summary">${deps.escapeHtml(mismatch.warning)}</p>
+          ${mismatch.weakHostKey ? `<p class="host-key-warning-summary">${deps.escapeHtml(mismatch.weakHostKey)}</p>` : ''}
           <p class="host-key-warning-target"><strong>Target:</strong> ${deps.escapeHtml(mismatch.host)}:${deps.escapeHtml(String(mismatch.port))}</p>
           <div class="host-key-warning-grid">
             <div>
               <p><strong>Saved key</strong></p>
               <p>Type: ${deps.escapeHtml(mismatch.storedKeyType)}</p>
               <p>Fingerprint: ${deps.escapeHtml(mismatch.storedFingerprint)}</p>
+              ${mismatch.storedWeakHostKey ? `<p>${deps.escapeHtml(mismatch.storedWeakHostKey)}</p>` : ''}
             </div>
             <div>
               <p><strong>Presented key</strong></p>
@@ -334,11 +429,26 @@ export function createProtocolsController(deps: ProtocolsControllerDeps): Protoc
         </div>
         <div class="modal-actions">
           <button class="btn" id="modal-cancel">Cancel</button>
+          <button class="btn" id="modal-accept-once">Connect Once</button>
           <button class="btn btn-danger" id="modal-confirm">Update Saved Key &amp; Connect</button>
         </div>
       `;
 
       card.querySelector('#modal-cancel')!.addEventListener('click', deps.hideModal);
+      card.querySelector('#modal-accept-once')!.addEventListener('click', async () => {
+        const btn = card.querySelector('#modal-accept-once') as HTMLButtonElement;
+        btn.disabled = true;
+
+        try {
+          await deps.api.acceptSshHostKeyOnce(mismatch.token);
+          deps.hideModal();
+          deps.writeStatus('Connecting with the presented key once; saved key kept...');
+          await openSshWithStatus(node);
+        } catch (error) {
+          deps.writeStatus(deps.formatError(error));
+          btn.disabled = false;
+        }
+      });
       card.querySelector('#modal-confirm')!.addEventListener('click', async () => {
         const btn = card.querySelector('#modal-confirm') as HTMLButtonElement;
         btn.disabled = true;
@@ -358,6 +468,83 @@ export function createProtocolsController(deps: ProtocolsControllerDeps): Protoc
     });
   }
 
+  function showSshHostKeyUnknownModal(node: ConnectionNode, unknown: SshHostKeyUnknownResult): void {
+    if (!deps.getModalOverlayEl()) {
+      deps.writeStatus(`${unknown.warning} Target ${unknown.host}:${unknown.port} (${unknown.fingerprint})`);
+      return;
+    }
+
+    deps.showModal('Unknown SSH Host', (card) => {
+      card.innerHTML += `
+        <div class="host-key-warning" role="alert">
+          <p class="host-key-warning-summary">${deps.escapeHtml(unknown.warning)}</p>
+          ${unknown.weakHostKey ? `<p class="host-key-warning-summary">${deps.escapeHtml(unknown.weakHostKey)}</p>` : ''}
+          <p class="host-key-warning-target"><strong>Target:</strong> ${deps.escapeHtml(unknown.host)}:${deps.escapeHtml(String(unknown.port))}</p>
+          <div>
+            <p>Type: ${deps.escapeHtml(unknown.keyType)}</p>
+            <p>Fingerprint: ${deps.escapeHtml(unknown.fingerprints.sha256Base64)}</p>
+            <p>Fingerprint: ${deps.escapeHtml(unknown.fingerprints.md5Hex)}</p>
+          </div>
+        </div>
+        <div class="modal-actions">
+          <button class="btn" id="modal-cancel">Cancel</button>
+          <button class="btn btn-primary" id="modal-confirm">Trust Host &amp; Connect</button>
+        </div>
+      `;
+
+      card.querySelector('#modal-cancel')!.addEventListener('click', deps.hideModal);
+      card.querySelector('#modal-confirm')!.addEventListener('click', async () => {
+        const btn = card.querySelector('#modal-confirm') as HTMLButtonElement;
+        btn.disabled = true;
+        btn.textContent = 'Saving...';
+
+        try {
+          await deps.api.approveSshHostKey(unknown.token);
+          deps.hideModal();
+          deps.writeStatus('Saved host key; reconnecting...');
+          await openSshWithStatus(node);
+        } catch (error) {
+          deps.writeStatus(deps.formatError(error));
+          btn.disabled = false;
+          btn.textContent = 'Trust Host & Connect';
+        }
+      });
+    });
+  }
+
+  function showSshKeyPassphraseModal(node: ConnectionNode, prompt: SshKeyNeedsPassphraseResult): void {
+    if (!deps.getModalOverlayEl()) return;
+
+    deps.showModal('Private Key Passphrase', (card) => {
+      card.innerHTML += `
+        <p>${deps.escapeHtml(prompt.passphraseRejected ? 'The passphrase was incorrect.' : 'This private key is encrypted.')}</p>
+        <p><strong>Key:</strong> ${deps.escapeHtml(prompt.keyPath)}</p>
+        <div class="form-field">
+          <input id="modal-key-passphrase" type="password" placeholder="Key passphrase" />
+        </div>
+        <div class="modal-actions">
+          <button class="btn" id="modal-cancel">Cancel</button>
+          <button class="btn btn-primary" id="modal-confirm">Connect</button>
+        </div>
+      `;
+
+      const input = card.querySelector('#modal-key-passphrase') as HTMLInputElement;
+      const submit = (): void => {
+        const passphrase = input.value;
+        if (!passphrase) return;
+        deps.hideModal();
+        void openSshWithStatus(node, passphrase);
+      };
+
+      card.querySelector('#modal-cancel')!.addEventListener('click', deps.hideModal);
+      card.querySelector('#modal-confirm')!.addEventListener('click', submit);
+      input.addEventListener('keydown', (event) => {
+        if (event.key === 'Enter') submit();
+      });
+      input.focus();
+    });
+  }
+
   async function openRdp(node: ConnectionNode): Promise<void> {
     if (node.kind !== 'rdp' || !deps.getWorkspaceEl()) return;
 
@@ -530,9 +717,20 @@ export function createProtocolsController(deps: ProtocolsControllerDeps): Protoc
       return;
     }
 
+    if (event.type === 'retrying') {
+      tab.rdpState = 'connecting';
+      setOverlayState(
+        tab.overlay,
+        'connecting',
+        `Server not reachable (${event.reason}), retrying ${event.attempt}/${event.retries}...`,
+      );
+      return;
+    }
+
     if (event.type === 'disconnected') {
       tab.rdpState = 'error';
-      setOverlayState(tab.overlay, 'error', `Disconnected (${event.reason})`);
+      const prefix = event.failure ? 'Could not connect' : 'Disconnected';
+      setOverlayState(tab.overlay, 'error', `${prefix}: ${event.message} (${event.reason})`);
       return;
     }
 
@@ -1,11 +1,21 @@
 import { getCurrentWebview, type DragDropEvent } from '@tauri-apps/api/webview';
-import type { FileEntry, FileListResult, SftpTransferProgressEvent } from '../types';
+import type {
+  FileEntry,
+  FileListResult,
+  FileSort,
+  SftpError,
+  SftpExternalSavedEvent,
+  SftpFsStats,
+  SftpRenameResult,
+  SftpTransferEvent,
+} from '../types';
 import type {
   FilePaneSide,
   SessionTab,
   SftpInlineEditState,
   SftpModalState,
   SftpPaneConfirmState,
+  SftpPaneSortKey,
   SftpPaneState,
   SftpTransferUiState,
 } from './types';
@@ -15,16 +25,30 @@ export type SftpControllerDeps = {
   api: {
     openSftp: (sshSessionId: string) => Promise<{ sftpSessionId: string; remoteCwd: string }>;
     closeSftp: (sshSessionId: string, sftpSessionId: string) => Promise<unknown>;
-    listSftp: (request: { sshSessionId: string; sftpSessionId: string; path: string }) => Promise<FileListResult>;
-    localFsList: (path: string) => Promise<FileListResult>;
+    listSftp: (request: {
+      sshSessionId: string;
+      sftpSessionId: string;
+      path: string;
+      sort?: FileSort;
+      showHidden?: boolean;
+    }) => Promise<FileListResult>;
+    localFsList: (path: string, sort?: FileSort | null, showHidden?: boolean) => Promise<FileListResult>;
+    sftpExpandPath: (sshSessionId: string, path: string) => Promise<string>;
+    localFsExpandPath: (path: string) => Promise<string>;
     localFsNewFile: (path: string) => Promise<unknown>;
     localFsNewFolder: (path: string) => Promise<unknown>;
-    sftpNewFile: (request: { sshSessionId: string; sftpSessionId: string; path: string }) => Promise<unknown>;
-    sftpNewFolder: (request: { sshSessionId: string; sftpSessionId: string; path: string }) => Promise<unknown>;
+    sftpNewFile: (request: { sshSessionId: string; sftpSessionId: string; path: string }) => Promise<FileEntry | null>;
+    sftpNewFolder: (request: { sshSessionId: string; sftpSessionId: string; path: string }) => Promise<FileEntry | null>;
     localFsRename: (oldPath: string, newPath: string) => Promise<unknown>;
-    sftpRename: (request: { sshSessionId: string; sftpSessionId: string; oldPath: string; newPath: string }) => Promise<unknown>;
+    sftpRename: (request: {
+      sshSessionId: string;
+      sftpSessionId: string;
+      oldPath: string;
+      newPath: string;
+    }) => Promise<SftpRenameResult>;
     localFsDelete: (path: string, isDir: boolean) => Promise<unknown>;
     sftpDelete: (request: { sshSessionId: string; sftpSessionId: string; path: string; isDir: boolean }) => Promise<unknown>;
+    sftpStatvfs: (sshSessionId: string, sftpSessionId: string, path: string) => Promise<SftpFsStats>;
     sftpUploadFile: (request: {
       sshSessionId: string;
       sftpSessionId: string;
@@ -41,7 +65,7 @@ export type SftpControllerDeps = {
     }) => Promise<unknown>;
     listenSftpTransferProgress: (
       sftpSessionId: string,
-      fn: (event: SftpTransferProgressEvent) => void,
+      fn: (event: SftpTransferEvent) => void,
     ) => Promise<() => void>;
   };
   showModal: (title: string, buildContent: (card: HTMLDivElement) => void) => void;
@@ -60,6 +84,15 @@ export type SftpControllerDeps = {
 
 export type SftpController = {
   openSftpModalForTab: (tabKey: string) => Promise<void>;
+  /** Byte counts as the file panes show them, e.g. `1.5 MB`; `-` when unknown. */
+  formatFileSize: (bytes: number | null | undefined) => string;
+};
+
+/** Both panes are listed in these orders, by the backend and again after local edits. */
+const SFTP_PANE_SORTS: Record<SftpPaneSortKey, { label: string; sort: Required<FileSort> }> = {
+  name: { label: 'Name', sort: { key: 'name', descending: false, dirsFirst: true } },
+  size: { label: 'Size', sort: { key: 'size', descending: true, dirsFirst: true } },
+  modified: { label: 'Modified', sort: { key: 'modified', descending: true, dirsFirst: true } },
 };
 
 type SftpDroppedUploadStats = {
@@ -80,6 +113,7 @@ export function createSftpController(deps: SftpControllerDeps): SftpController {
       cwd: '',
       entries: [],
       sortKey: 'name',
+      showHidden: true,
       selectedPath: null,
       selectedKind: null,
       loading: false,
@@ -129,6 +163,7 @@ export function createSftpController(deps: SftpControllerDeps): SftpController {
       transferState: null,
       transferProgressUnlisten: null,
       dragDropUnlisten: null,
+      externalSavedUnlisten: null,
       remoteDropHover: false,
       localDropReject: false,
       dropTransferRunning: false,
@@ -196,6 +231,7 @@ export function createSftpController(deps: SftpControllerDeps): SftpController {
 
       void sftpAttachTransferProgressListener(state);
       void sftpAttachDragDropListener(state);
+      void sftpAttachExternalSavedListener(state);
       void sftpRefreshBothPanes(state, '', opened.remoteCwd);
     });
 
@@ -225,6 +261,15 @@ export function createSftpController(deps: SftpControllerDeps): SftpController {
           // Ignore teardown errors during modal close.
         }
       }
+      const externalSavedUnlisten = state.externalSavedUnlisten;
+      state.externalSavedUnlisten = null;
+      if (externalSavedUnlisten) {
+        try {
+          externalSavedUnlisten();
+        } catch {
+          // Ignore teardown errors during modal close.
+        }
+      }
       sftpResolvePaneConfirm(state, false);
       state.inlineEdit = null;
       state.inlineEditCommitPromise = null;
@@ -268,40 +313,53 @@ export function createSftpController(deps: SftpControllerDeps): SftpController {
     sortToggle.setAttribute('role', 'group');
     sortToggle.setAttribute('aria-label', `${title} sort order`);
 
-    const sortNameBtn = document.createElement('button');
-    sortNameBtn.type = 'button';
-    sortNameBtn.className = 'sftp-pane-sort-btn';
-    sortNameBtn.textContent = 'Name';
-    sortNameBtn.addEventListener('click', (event) => {
-      event.stopPropagation();
-      sftpSetActivePane(state, pane.side);
-      pane.sortKey = 'name';
-      sftpRenderPane(state, pane);
-      updateSortButtons();
-    });
-
-    const sortSizeBtn = document.createElement('button');
-    sortSizeBtn.type = 'button';
-    sortSizeBtn.className = 'sftp-pane-sort-btn';
-    sortSizeBtn.textContent = 'Size';
-    sortSizeBtn.addEventListener('click', (event) => {
-      event.stopPropagation();
-      sftpSetActivePane(state, pane.side);
-      pane.sortKey = 'size';
-      sftpRenderPane(state, pane);
-      updateSortButtons();
+    const sortButtons = (Object.keys(SFTP_PANE_SORTS) as SftpPaneSortKey[]).map((sortKey) => {
+      const button = document.createElement('button');
+      button.type = 'button';
+      button.className = 'sftp-pane-sort-btn';
+      button.textContent = SFTP_PANE_SORTS[sortKey].label;
+      button.addEventListener('click', (event) => {
+        event.stopPropagation();
+        sftpSetActivePane(state, pane.side);
+        pane.sortKey = sortKey;
+        sftpRenderPane(state, pane);
+        updateSortButtons();
+      });
+      return { sortKey, button };
     });
 
     const updateSortButtons = (): void => {
-      sortNameBtn.classList.toggle('active', pane.sortKey === 'name');
-      sortSizeBtn.classList.toggle('active', pane.sortKey === 'size');
-      sortNameBtn.setAttribute('aria-pressed', String(pane.sortKey === 'name'));
-      sortSizeBtn.setAttribute('aria-pressed', String(pane.sortKey === 'size'));
+      for (const { sortKey, button } of sortButtons) {
+        button.classList.toggle('active', pane.sortKey === sortKey);
+        button.setAttribute('aria-pressed', String(pane.sortKey === sortKey));
+      }
     };
     updateSortButtons();
 
-    sortToggle.append(sortNameBtn, sortSizeBtn);
-    headerActions.append(sortToggle, upBtn);
+    sortToggle.append(...sortButtons.map(({ button }) => button));
+
+    const hiddenToggle = document.createElement('div');
+    hiddenToggle.className = 'sftp-pane-sort-toggle';
+    const hiddenBtn = document.createElement('button');
+    hiddenBtn.type = 'button';
+    hiddenBtn.className = 'sftp-pane-sort-btn';
+    hiddenBtn.textContent = 'Hidden';
+    hiddenBtn.title = 'Show dotfiles';
+    const updateHiddenButton = (): void => {
+      hiddenBtn.classList.toggle('active', pane.showHidden);
+      hiddenBtn.setAttribute('aria-pressed', String(pane.showHidden));
+    };
+    hiddenBtn.addEventListener('click', (event) => {
+      event.stopPropagation();
+      sftpSetActivePane(state, pane.side);
+      pane.showHidden = !pane.showHidden;
+      updateHiddenButton();
+      void sftpLoadPane(state, pane.side, pane.cwd);
+    });
+    updateHiddenButton();
+    hiddenToggle.append(hiddenBtn);
+
+    headerActions.append(sortToggle, hiddenToggle, upBtn);
     header.append(titleEl, headerActions);
 
     const actionsRow = document.createElement('div');
@@ -354,7 +412,7 @@ export function createSftpController(deps: SftpControllerDeps): SftpController {
       event.stopPropagation();
       const nextPath = pathEl.value.trim();
       if (!nextPath) return;
-      void sftpRunAfterInlineEditSettles(state, pane.side, () => sftpLoadPane(state, pane.side, nextPath));
+      void sftpRunAfterInlineEditSettles(state, pane.side, () => sftpOpenTypedPath(state, pane.side, nextPath));
     });
 
     pathEl.addEventListener('click', (event) => {
@@ -376,7 +434,7 @@ export function createSftpController(deps: SftpControllerDeps): SftpController {
         pathEl.readOnly = true;
         const nextPath = pathEl.value.trim();
         if (!nextPath) return;
-        void sftpRunAfterInlineEditSettles(state, pane.side, () => sftpLoadPane(state, pane.side, nextPath));
+        void sftpRunAfterInlineEditSettles(state, pane.side, () => sftpOpenTypedPath(state, pane.side, nextPath));
       } else if (event.key === 'Escape') {
         pathEl.readOnly = true;
         pathEl.value = pane.cwd;
@@ -656,6 +714,7 @@ export function createSftpController(deps: SftpControllerDeps): SftpController {
 
       edit.submitting = true;
       try {
+        let created: FileEntry | null = null;
         if (pane.side === 'local') {
           if (kind === 'file') {
             await deps.api.localFsNewFile(fullPath);
@@ -670,17 +729,20 @@ export function createSftpController(deps: SftpControllerDeps): SftpController {
             path: fullPath,
           };
           if (kind === 'file') {
-            await deps.api.sftpNewFile(request);
+            created = await deps.api.sftpNewFile(request);
           } else {
-            await deps.api.sftpNewFolder(request);
+            created = await deps.api.sftpNewFolder(request);
           }
         }
         if (state.inlineEdit === edit) {
           state.inlineEdit = null;
         }
         sftpSetStatus(state, `${kind === 'file' ? 'File' : 'Folder'} created`);
+        const patched = created !== null && sftpPatchPaneEntry(pane, created, null);
         sftpRenderAllPanes(state);
-        await sftpLoadPane(state, pane.side, pane.cwd);
+        if (!patched) {
+          await sftpLoadPane(state, pane.side, pane.cwd);
+        }
       } catch (error) {
         if (state.inlineEdit === edit) {
           edit.submitting = false;
@@ -717,11 +779,12 @@ export function createSftpController(deps: SftpControllerDeps): SftpController {
 
     edit.submitting = true;
     try {
+      let renamed: SftpRenameResult | null = null;
       if (pane.side === 'local') {
         await deps.api.localFsRename(targetPath, newPath);
       } else {
         if (!state.sftpSessionId) throw new Error('SFTP session is closed');
-        await deps.api.sftpRename({
+        renamed = await deps.api.sftpRename({
           sshSessionId: state.sshSessionId,
           sftpSessionId: state.sftpSessionId,
           oldPath: targetPath,
@@ -732,8 +795,12 @@ export function createSftpController(deps: SftpControllerDeps): SftpController {
         state.inlineEdit = null;
       }
       sftpSetStatus(state, 'Renamed');
+      const patched =
+        renamed !== null && renamed.entry !== null && sftpPatchPaneEntry(pane, renamed.entry, renamed.oldPath);
       sftpRenderAllPanes(state);
-      await sftpLoadPane(state, pane.side, pane.cwd);
+      if (!patched) {
+        await sftpLoadPane(state, pane.side, pane.cwd);
+      }
     } catch (error) {
       if (state.inlineEdit === edit) {
         edit.submitting = false;
@@ -743,6 +810,23 @@ export function createSftpController(deps: SftpControllerDeps): SftpController {
     }
   }
 
+  /**
+   * Puts `entry` into the pane's listing in place of `removedPath` (or of a row with its
+   * own path). Returns false, changing nothing, when the entry isn't in the folder the
+   * pane shows, so the caller should reload it instead.
+   */
+  function sftpPatchPaneEntry(pane: SftpPaneState, entry: FileEntry, removedPath: string | null): boolean {
+    if (pane.loading || sftpRemoteParentPath(entry.path) !== pane.cwd) return false;
+    pane.entries = pane.entries.filter((item) => item.path !== removedPath && item.path !== entry.path);
+    if (pane.showHidden || !entry.hidden) {
+      pane.entries.push(entry);
+    }
+    if (removedPath !== null && pane.selectedPath === removedPath) {
+      pane.selectedPath = entry.path;
+    }
+    return true;
+  }
+
   function sftpCancelInlineEdit(state: SftpModalState, edit: SftpInlineEditState): void {
     if (state.inlineEdit !== edit) return;
     state.inlineEdit = null;
@@ -772,10 +856,14 @@ export function createSftpController(deps: SftpControllerDeps): SftpController {
     }
   }
 
-  function sftpHandleTransferProgressEvent(state: SftpModalState, event: SftpTransferProgressEvent): void {
+  function sftpHandleTransferProgressEvent(state: SftpModalState, event: SftpTransferEvent): void {
     if (getActive() !== state || state.closing) return;
+    if (event.direction === 'remoteCopy') return;
     const transfer = state.transferState;
     if (!transfer || transfer.direction !== event.direction) return;
+    if (event.phase === 'complete' && event.warning) {
+      sftpSetStatus(state, event.warning, 'error');
+    }
 
     const fileKey = `${event.localPath}::${event.remotePath}`;
 
@@ -970,17 +1058,20 @@ export function createSftpController(deps: SftpControllerDeps): SftpController {
   function sftpCreateEntryRow(entry: FileEntry, selected: boolean): HTMLButtonElement {
     const row = document.createElement('button');
     row.type = 'button';
-    row.className = `sftp-file-row${selected ? ' selected' : ''}`;
+    row.className = `sftp-file-row${selected ? ' selected' : ''}${entry.hidden ? ' dotfile' : ''}`;
 
     const icon = document.createElement('span');
     icon.className = 'sftp-file-icon';
     icon.innerHTML = deps.sftpEntryIcon(entry.kind);
     row.appendChild(icon);
 
-    sftpAppendRowCell(row, 'sftp-file-name', entry.name);
+    const nameCell = sftpAppendRowCell(row, 'sftp-file-name', entry.name);
+    if (entry.nameLossy) {
+      nameCell.title = 'Name is not valid UTF-8; shown with replacement characters';
+    }
     sftpAppendRowCell(row, 'sftp-file-owner', sftpFormatOwner(entry.owner));
 
-    const perms = sftpFormatPermissions(entry.permissions);
+    const perms = entry.permissionsStr ?? '-';
     const permsCell = sftpAppendRowCell(row, 'sftp-file-perms', perms);
     const octal = sftpFormatPermissionsOctal(entry.permissions);
     if (octal) {
@@ -1030,7 +1121,7 @@ export function createSftpController(deps: SftpControllerDeps): SftpController {
     const ownerText = entry ? sftpFormatOwner(entry.owner) : '-';
     sftpAppendRowCell(row, 'sftp-file-owner', ownerText);
 
-    const perms = entry ? sftpFormatPermissions(entry.permissions) : '-';
+    const perms = entry?.permissionsStr ?? '-';
     const permsCell = sftpAppendRowCell(row, 'sftp-file-perms', perms);
     if (entry) {
       const octal = sftpFormatPermissionsOctal(entry.permissions);
@@ -1148,20 +1239,29 @@ export function createSftpController(deps: SftpControllerDeps): SftpController {
     });
   }
 
-  function sftpSortEntriesForPane(entries: FileEntry[], sortKey: 'name' | 'size'): FileEntry[] {
+  /** Mirrors the backend's ordering so entries added by watches and edits land in place. */
+  function sftpSortEntriesForPane(entries: FileEntry[], sortKey: SftpPaneSortKey): FileEntry[] {
+    const { key, descending, dirsFirst } = SFTP_PANE_SORTS[sortKey].sort;
+    const byKey = (a: FileEntry, b: FileEntry): number => {
+      const byName = a.name.localeCompare(b.name, undefined, { sensitivity: 'base', numeric: true });
+      switch (key) {
+        case 'size':
+          return (a.size ?? -1) - (b.size ?? -1) || byName;
+        case 'modified':
+          return (a.modifiedAt ?? -1) - (b.modifiedAt ?? -1) || byName;
+        case 'kind':
+          return a.kind.localeCompare(b.kind) || byName;
+        default:
+          return byName;
+      }
+    };
+
     const sorted = [...entries];
     sorted.sort((a, b) => {
       const aDir = a.kind === 'dir';
       const bDir = b.kind === 'dir';
-      if (aDir !== bDir) return aDir ? -1 : 1;
-
-      if (sortKey === 'size' && !aDir && !bDir) {
-        const aSize = a.size ?? Number.NEGATIVE_INFINITY;
-        const bSize = b.size ?? Number.NEGATIVE_INFINITY;
-        if (aSize !== bSize) return bSize - aSize;
-      }
-
-      return a.name.localeCompare(b.name, undefined, { sensitivity: 'base', numeric: true });
+      if (dirsFirst && aDir !== bDir) return aDir ? -1 : 1;
+      return descending ? byKey(b, a) : byKey(a, b);
     });
     return sorted;
   }
@@ -1171,13 +1271,6 @@ export function createSftpController(deps: SftpControllerDeps): SftpController {
     return trimmed ? trimmed : '-';
   }
 
-  function sftpFormatPermissions(mode: number | null | undefined): string {
-    if (mode == null) return '-';
-    const bits = mode & 0o777;
-    const triplets = [0o400, 0o200, 0o100, 0o40, 0o20, 0o10, 0o4, 0o2, 0o1];
-    return triplets.map((flag, idx) => ((bits & flag) !== 0 ? 'rwx'[idx % 3] : '-')).join('');
-  }
-
   function sftpFormatPermissionsOctal(mode: number | null | undefined): string | null {
     if (mode == null) return null;
     const bits = mode & 0o7777;
@@ -1283,6 +1376,8 @@ export function createSftpController(deps: SftpControllerDeps): SftpController {
           sftpSelectPaneEntry(state, pane.side, entry);
           if (entry.kind === 'dir') {
             void sftpRunAfterInlineEditSettles(state, pane.side, () => sftpLoadPane(state, pane.side, entry.path));
+          } else if (pane.side === 'remote' && entry.kind === 'file') {
+            void sftpOpenRemoteExternal(state, entry);
           }
         });
 
@@ -1307,6 +1402,21 @@ export function createSftpController(deps: SftpControllerDeps): SftpController {
     await sftpLoadPane(state, side, pane.cwd || (side === 'remote' ? '.' : ''));
   }
 
+  /** Opens a path typed into the path bar, expanding `~` and variables first; clicks load paths as they are. */
+  async function sftpOpenTypedPath(state: SftpModalState, side: FilePaneSide, typed: string): Promise<void> {
+    let path: string;
+    try {
+      path =
+        side === 'local'
+          ? await deps.api.localFsExpandPath(typed)
+          : await deps.api.sftpExpandPath(state.sshSessionId, typed);
+    } catch (error) {
+      sftpSetStatus(state, deps.formatError(error), 'error');
+      return;
+    }
+    await sftpLoadPane(state, side, path);
+  }
+
   async function sftpLoadPane(state: SftpModalState, side: FilePaneSide, path: string): Promise<void> {
     if (getActive() !== state || state.closing) return;
     const pane = sftpGetPane(state, side);
@@ -1315,14 +1425,17 @@ export function createSftpController(deps: SftpControllerDeps): SftpController {
 
     try {
       let result: FileListResult;
+      const sort = SFTP_PANE_SORTS[pane.sortKey].sort;
       if (side === 'local') {
-        result = await deps.api.localFsList(path);
+        result = await deps.api.localFsList(path, sort, pane.showHidden);
       } else {
         if (!state.sftpSessionId) throw new Error('SFTP session is closed');
         result = await deps.api.listSftp({
           sshSessionId: state.sshSessionId,
           sftpSessionId: state.sftpSessionId,
           path,
+          sort,
+          showHidden: pane.showHidden,
         });
       }
 
@@ -1373,6 +1486,10 @@ export function createSftpController(deps: SftpControllerDeps): SftpController {
       sftpSetStatus(state, 'Select a file or folder to rename', 'error');
       return;
     }
+    if (entry.nameLossy) {
+      sftpSetStatus(state, `"${entry.name}" isn't valid UTF-8 and can't be renamed here`, 'error');
+      return;
+    }
     sftpBeginInlineEdit(state, {
       side,
       mode: 'rename',
@@ -1392,6 +1509,10 @@ export function createSftpController(deps: SftpControllerDeps): SftpController {
       sftpSetStatus(state, 'Select a file or folder to delete', 'error');
       return;
     }
+    if (entry.nameLossy) {
+      sftpSetStatus(state, `"${entry.name}" isn't valid UTF-8 and can't be deleted here`, 'error');
+      return;
+    }
 
     const confirmed = await sftpAskPaneConfirm(state, side, {
       message: `Delete ${entry.kind === 'dir' ? 'folder' : 'file'} "${entry.name}"?`,
@@ -1419,6 +1540,28 @@ export function createSftpController(deps: SftpControllerDeps): SftpController {
     }
   }
 
+  /** Asks before an upload the remote file system has no room for. Servers that can't report free space are not asked about. */
+  async function sftpConfirmRemoteSpace(
+    state: SftpModalState,
+    remoteDir: string,
+    fileName: string,
+    size: number | null | undefined,
+  ): Promise<boolean> {
+    if (size == null || !state.sftpSessionId) return true;
+    let stats: SftpFsStats;
+    try {
+      stats = await deps.api.sftpStatvfs(state.sshSessionId, state.sftpSessionId, remoteDir);
+    } catch {
+      return true;
+    }
+    if (stats.available >= size) return true;
+    return sftpAskPaneConfirm(state, 'remote', {
+      message: `"${fileName}" is ${sftpFormatBytes(size)} but only ${sftpFormatBytes(stats.available)} is free on the server. Upload anyway?`,
+      confirmLabel: 'Upload',
+      tone: 'default',
+    });
+  }
+
   async function sftpTransfer(state: SftpModalState, direction: 'upload' | 'download'): Promise<void> {
     if (!state.sftpSessionId) {
       sftpSetStatus(state, 'SFTP session is closed', 'error');
@@ -1446,6 +1589,9 @@ export function createSftpController(deps: SftpControllerDeps): SftpController {
     }
 
     const fileName = sftpBaseName(sourceEntry.path);
+    if (direction === 'upload' && !(await sftpConfirmRemoteSpace(state, targetPane.cwd, fileName, sourceEntry.size))) {
+      return;
+    }
     const localPath = direction === 'upload' ? sourceEntry.path : sftpLocalJoinPath(targetPane.cwd, fileName);
     const remotePath = direction === 'upload' ? sftpRemoteJoinPath(targetPane.cwd, fileName) : sourceEntry.path;
     const activeTransfer = sftpBeginTrackedTransfer(state, {
@@ -1510,6 +1656,84 @@ export function createSftpController(deps: SftpControllerDeps): SftpController {
     }
   }
 
+  async function sftpOpenRemoteExternal(state: SftpModalState, entry: FileEntry): Promise<void> {
+    if (!state.sftpSessionId) return;
+    const request = {
+      sshSessionId: state.sshSessionId,
+      sftpSessionId: state.sftpSessionId,
+      remotePath: entry.path,
+    };
+
+    try {
+      sftpSetStatus(state, `Opening ${entry.name}...`);
+      let result = await deps.api.sftpOpenExternal(request);
+      if (result.localPath == null && result.executable) {
+        const ok = await sftpAskPaneConfirm(state, 'remote', {
+          message: `"${entry.name}" may run as a program when opened. Open it anyway?`,
+          confirmLabel: 'Open',
+          tone: 'danger',
+        });
+        if (!ok) {
+          sftpSetStatus(state, '');
+          return;
+        }
+        result = await deps.api.sftpOpenExternal({ ...request, allowExecutable: true });
+      }
+      sftpSetStatus(state, `Opened ${entry.name}; saved changes can be uploaded while this window is open`);
+    } catch (error) {
+      sftpSetStatus(state, deps.formatError(error), 'error');
+    }
+  }
+
+  async function sftpAttachExternalSavedListener(state: SftpModalState): Promise<void> {
+    if (!state.sftpSessionId) return;
+    try {
+      const unlisten = await deps.api.listenSftpExternalSaved(state.sftpSessionId, (event) => {
+        void sftpHandleExternalSaved(state, event);
+      });
+
+      if (getActive() !== state || state.closing || state.sftpSessionId == null) {
+        unlisten();
+        return;
+      }
+
+      if (state.externalSavedUnlisten) {
+        unlisten();
+        return;
+      }
+
+      state.externalSavedUnlisten = unlisten;
+    } catch (error) {
+      sftpSetStatus(state, `Re-upload of opened files is unavailable: ${deps.formatError(error)}`, 'error');
+    }
+  }
+
+  async function sftpHandleExternalSaved(state: SftpModalState, event: SftpExternalSavedEvent): Promise<void> {
+    if (getActive() !== state || state.closing || !state.sftpSessionId) return;
+
+    const fileName = sftpBaseName(event.remotePath);
+    const ok = await sftpAskPaneConfirm(state, 'remote', {
+      message: `"${fileName}" was saved locally. Upload it to the server?`,
+      confirmLabel: 'Upload',
+      tone: 'default',
+    });
+    if (!ok || getActive() !== state || state.closing || !state.sftpSessionId) return;
+
+    try {
+      await deps.api.sftpUploadFile({
+        sshSessionId: state.sshSessionId,
+        sftpSessionId: state.sftpSessionId,
+        localPath: event.localPath,
+        remotePath: event.remotePath,
+        overwrite: true,
+      });
+      sftpSetStatus(state, `Uploaded ${fileName}`);
+      await sftpRefreshPane(state, 'remote');
+    } catch (error) {
+      sftpSetStatus(state, deps.formatError(error), 'error');
+    }
+  }
+
   async function sftpAttachDragDropListener(state: SftpModalState): Promise<void> {
     try {
       const unlisten = await getCurrentWebview().onDragDropEvent((event) => {
@@ -1779,8 +2003,12 @@ export function createSftpController(deps: SftpControllerDeps): SftpController {
       }
 
       const fileName = sftpBaseName(remotePath);
+      const conflict = (error as Partial<SftpError> | null)?.conflict;
+      const sizes = conflict
+        ? ` (remote ${sftpFormatFileSize(conflict.remote.size)}, local ${sftpFormatFileSize(conflict.local.size)})`
+        : '';
       const ok = await sftpAskPaneConfirm(state, 'remote', {
-        message: `Overwrite existing remote file "${fileName}"?`,
+        message: `Overwrite existing remote file "${fileName}"${sizes}?`,
         confirmLabel: 'Overwrite',
         tone: 'default',
       });
@@ -1882,5 +2110,6 @@ export function createSftpController(deps: SftpControllerDeps): SftpController {
 
   return {
     openSftpModalForTab,
+    formatFileSize: sftpFormatFileSize,
   };
 }
@@ -15,6 +15,7 @@ export type ShellControllerDeps = {
   showImportModal: () => void;
   showExportModal: () => void;
   showPreferencesModal: () => void;
+  showDiagnosticsModal: () => void;
   showAboutModal: () => void;
   hideContextMenu: () => void;
   hideModal: () => void;
@@ -94,6 +95,11 @@ export function createShellController(deps: ShellControllerDeps): ShellControlle
       deps.showPreferencesModal();
     });
 
+    deps.requireButton('#settings-diagnostics').addEventListener('click', () => {
+      setSettingsMenuOpen(false);
+      deps.showDiagnosticsModal();
+    });
+
     deps.requireButton('#settings-about').addEventListener('click', () => {
       setSettingsMenuOpen(false);
       deps.showAboutModal();
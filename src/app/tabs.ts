@@ -9,6 +9,7 @@ export type TabsControllerDeps = {
   getActiveSftpModalTabKey: () => string | null;
   hideModal: () => void;
   closeSsh: (sessionId: string) => Promise<unknown>;
+  cancelSshOpen: (sessionId: string) => Promise<unknown>;
   closeRdp: (sessionId: string) => Promise<unknown>;
   resizeSsh: (sessionId: string, cols: number, rows: number) => Promise<unknown>;
   setRdpBounds: (sessionId: string, viewport: RdpViewport) => Promise<unknown>;
@@ -57,12 +58,27 @@ export function createTabsController(deps: TabsControllerDeps): TabsController {
       el.className = `tab${deps.getActiveTab() === tabKey ? ' active' : ''}`;
 
       const label = document.createElement('span');
+      const title = tab.kind === 'ssh' && tab.remoteTitle ? `${tab.title}: ${tab.remoteTitle}` : tab.title;
       label.textContent =
         tab.kind === 'ssh' && tab.sshState === 'connecting'
-          ? `${tab.title} [connecting]`
+          ? `${title} [connecting]`
           : tab.kind === 'ssh' && tab.sshState === 'exited'
-            ? `${tab.title} [exited]`
-            : tab.title;
+            ? `${title} [exited]`
+            : tab.kind === 'ssh' && tab.lastActivitySecs !== null && tab.lastActivitySecs >= 60
+              ? `${title} [idle ${Math.floor(tab.lastActivitySecs / 60)}m]`
+              : title;
+      if (tab.kind === 'ssh' && (tab.systemInfo || tab.algorithms)) {
+        const lines: string[] = [];
+        if (tab.systemInfo) {
+          const { os, shell, hostname } = tab.systemInfo;
+          lines.push([hostname, os, shell].filter(Boolean).join(' · '));
+        }
+        if (tab.algorithms) {
+          const { kex, hostKeyAlg, cipher, mac } = tab.algorithms;
+          lines.push([cipher, mac, kex, hostKeyAlg].filter(Boolean).join(' · '));
+        }
+        label.title = lines.filter(Boolean).join('\n');
+      }
       el.appendChild(label);
 
       el.addEventListener('click', () => {
@@ -149,6 +165,9 @@ export function createTabsController(deps: TabsControllerDeps): TabsController {
     }
 
     if (tab.kind === 'ssh') {
+      if (tab.sessionId && tab.sshState === 'connecting') {
+        await deps.cancelSshOpen(tab.sessionId).catch(() => undefined);
+      }
       if (tab.sessionId) {
         await deps.closeSsh(tab.sessionId).catch(() => undefined);
       }
@@ -1,4 +1,10 @@
-import type { ConnectionNode, NodeKind, NodeMoveRequest } from '../types';
+import type {
+  ConnectionNode,
+  ConnectionStatus,
+  NodeKind,
+  NodeMoveRequest,
+  TreeChangedEvent,
+} from '../types';
 import type { MenuAction } from './context-menu';
 
 type DropZone = 'before' | 'after' | 'into';
@@ -46,6 +52,7 @@ type TreeRowOpts = {
 
 export type TreeControllerDeps = {
   listTree: () => Promise<ConnectionNode[]>;
+  getSubtree: (nodeId: string) => Promise<ConnectionNode[]>;
   moveNode: (request: NodeMoveRequest) => Promise<void>;
   getTreeEl: () => HTMLDivElement | null;
   getTreeSearchQuery: () => string;
@@ -58,6 +65,7 @@ export type TreeControllerDeps = {
   clearConnectionCheckStatus: () => void;
   checkSelectedConnection: (nodeId: string, connectionName: string) => Promise<void>;
   svgIcon: (kind: NodeKind) => string;
+  getConnectionStatus: (nodeId: string) => ConnectionStatus | undefined;
   openConnectionNode: (node: ConnectionNode) => void;
   showContextMenu: (x: number, y: number, actions: MenuAction[]) => void;
   buildFolderMenuActions: (node: ConnectionNode | null, isRoot: boolean) => MenuAction[];
@@ -68,6 +76,7 @@ export type TreeControllerDeps = {
 
 export type TreeController = {
   refreshTree: () => Promise<void>;
+  applyTreeChange: (change: TreeChangedEvent) => Promise<void>;
   renderTree: () => void;
 };
 
@@ -86,12 +95,50 @@ export function createTreeController(deps: TreeControllerDeps): TreeController {
   let pointerDragState: PointerDragState | null = null;
   let pointerDragListenersAttached = false;
   let suppressClickUntilMs = 0;
+  let pendingTreeChange: Promise<void> = Promise.resolve();
 
   async function refreshTree(): Promise<void> {
     deps.setNodes(await deps.listTree());
     renderTree();
   }
 
+  /** Changes are applied one at a time, so a slow refetch can't overwrite a newer one. */
+  function applyTreeChange(change: TreeChangedEvent): Promise<void> {
+    pendingTreeChange = pendingTreeChange
+      .then(() => patchTree(change))
+      .catch((error) => deps.writeStatus(deps.formatError(error)));
+    return pendingTreeChange;
+  }
+
+  /** Swaps the changed branches for fresh copies, falling back to a full refetch at the root. */
+  async function patchTree(change: TreeChangedEvent): Promise<void> {
+    if (change.kind === 'reset' || change.affectedIds.includes(null)) {
+      await refreshTree();
+      return;
+    }
+
+    const branchIds = change.affectedIds.filter((id): id is string => id !== null);
+    const branches = await Promise.all(branchIds.map((id) => deps.getSubtree(id)));
+
+    const nodes = deps.getNodes();
+    const { byParent } = getTreeIndexes(nodes);
+    const stale = new Set<string>();
+    const pending = [...branchIds];
+    while (pending.length > 0) {
+      const id = pending.pop()!;
+      if (stale.has(id)) continue;
+      stale.add(id);
+      for (const child of byParent.get(id) ?? []) pending.push(child.id);
+    }
+
+    // One affected folder can hold another (a move reports both parents), so the same
+    // node can come back in more than one branch.
+    const fetched = new Map<string, ConnectionNode>();
+    for (const node of branches.flat()) fetched.set(node.id, node);
+    deps.setNodes([...nodes.filter((node) => !stale.has(node.id)), ...fetched.values()]);
+    renderTree();
+  }
+
   function compareNodes(a: ConnectionNode, b: ConnectionNode): number {
     if (a.orderIndex !== b.orderIndex) return a.orderIndex - b.orderIndex;
     return a.name.localeCompare(b.name);
@@ -306,7 +353,6 @@ export function createTreeController(deps: TreeControllerDeps): TreeController {
       }
       selectNodeAndSyncConnectionCheck(draggedNodeId);
       await deps.moveNode(request);
-      await refreshTree();
     } catch (error) {
       deps.writeStatus(deps.formatError(error));
     } finally {
@@ -646,6 +692,18 @@ export function createTreeController(deps: TreeControllerDeps): TreeController {
     labelEl.textContent = label;
     row.appendChild(labelEl);
 
+    const status = !isFolder && id ? deps.getConnectionStatus(id) : undefined;
+    if (status) {
+      const dot = document.createElement('span');
+      dot.className = `tree-status-dot ${status.reachable ? 'reachable' : 'unreachable'}`;
+      dot.title = !status.reachable
+        ? 'Unreachable'
+        : status.latencyMs === null
+          ? 'Connected'
+          : `Reachable (${status.latencyMs} ms)`;
+      row.appendChild(dot);
+    }
+
     row.addEventListener('click', () => {
       if (shouldSuppressClick()) {
         return;
@@ -703,6 +761,7 @@ export function createTreeController(deps: TreeControllerDeps): TreeController {
 
   return {
     refreshTree,
+    applyTreeChange,
     renderTree,
   };
 }
@@ -1,6 +1,6 @@
 import type { FitAddon } from '@xterm/addon-fit';
 import type { Terminal } from '@xterm/xterm';
-import type { FileEntry, FileEntryKind } from '../types';
+import type { FileEntry, FileEntryKind, SshAlgorithms, SshSystemInfo } from '../types';
 
 export type SshSessionTab = {
   kind: 'ssh';
@@ -14,6 +14,13 @@ export type SshSessionTab = {
   fitAddon: FitAddon;
   sshState: 'connecting' | 'connected' | 'exited';
   exitCode: number | null;
+  systemInfo: SshSystemInfo | null;
+  algorithms: SshAlgorithms | null;
+  localEcho: boolean;
+  /** Window title set by the remote shell, if any. */
+  remoteTitle: string | null;
+  /** Seconds since the session last saw output or input, as of the latest heartbeat. */
+  lastActivitySecs: number | null;
   cleanup: Array<() => void>;
 };
 
@@ -42,11 +49,14 @@ export type PreferencesSectionDefinition = {
   placeholders: Array<{ label: string; hint: string }>;
 };
 
+export type SftpPaneSortKey = 'name' | 'size' | 'modified';
+
 export type SftpPaneState = {
   side: FilePaneSide;
   cwd: string;
   entries: FileEntry[];
-  sortKey: 'name' | 'size';
+  sortKey: SftpPaneSortKey;
+  showHidden: boolean;
   selectedPath: string | null;
   selectedKind: FileEntryKind | null;
   loading: boolean;
@@ -111,6 +121,7 @@ export type SftpModalState = {
   transferState: SftpTransferUiState | null;
   transferProgressUnlisten: (() => void) | null;
   dragDropUnlisten: (() => void) | null;
+  externalSavedUnlisten: (() => void) | null;
   remoteDropHover: boolean;
   localDropReject: boolean;
   dropTransferRunning: boolean;
@@ -24,6 +24,9 @@ export function parseCargoPackageVersion(toml: string): string | null {
 
 export function formatError(error: unknown): string {
   if (error instanceof Error) return error.message;
+  if (typeof error === 'object' && error !== null && typeof (error as { message?: unknown }).message === 'string') {
+    return (error as { message: string }).message;
+  }
   return String(error);
 }
 
@@ -6,16 +6,37 @@ export interface ConnectionNode {
   kind: NodeKind;
   name: string;
   orderIndex: number;
+  color: string | null;
+  icon: string | null;
   ssh: SshConfig | null;
   rdp: RdpConfig | null;
 }
 
+export type HostKeyMode = 'off' | 'tofu' | 'strict';
+
+export type SshAuthMethod = 'publickey' | 'password' | 'none';
+
 export interface SshConfig {
   host: string;
   port: number;
   username: string;
-  strictHostKey: boolean;
+  hostKeyMode: HostKeyMode;
   keyPath?: string | null;
+  certPath?: string | null;
+  /** Keep recent terminal output so Reconnect can replay it. */
+  retainScrollback?: boolean;
+  /** Terminal size used when the tab can't be measured yet. */
+  defaultCols?: number | null;
+  defaultRows?: number | null;
+  /** E.g. `cloudflared access ssh --hostname %h`; `%h`, `%p` and `%r` are substituted. */
+  proxyCommand?: string | null;
+  /** Local IP address to connect from, like `ssh -b`; unused with a proxy command. */
+  bindAddress?: string | null;
+  /** Open SFTP in the last directory listed instead of the home directory. */
+  rememberSftpPath?: boolean;
+  lastSftpPath?: string | null;
+  /** Tried in order when connecting; unlisted methods are never tried. */
+  authMethods?: SshAuthMethod[];
   authRef?: string | null;
   keyPassphraseRef?: string | null;
 }
@@ -28,22 +49,50 @@ export interface RdpConfig {
   screenMode: number;
   width?: number | null;
   height?: number | null;
+  /** Seconds to wait for the server per attempt (1-600); the client default when unset. */
+  connectTimeoutSecs?: number | null;
+  /** Extra attempts when the server refuses or doesn't answer, e.g. while it boots. */
+  connectRetries?: number;
+  /** Makes local smart cards available to the session, e.g. for smart-card logon. */
+  redirectSmartcards?: boolean;
+  /** Keeps the bitmap cache on disk between sessions; it holds pieces of the remote screen. */
+  bitmapCache?: boolean;
+  experience?: RdpExperience;
   credentialRef?: string | null;
 }
 
+/** Visual effects asked of the server, as in mstsc's Experience tab. */
+export interface RdpExperience {
+  wallpaper: boolean;
+  fontSmoothing: boolean;
+  desktopComposition: boolean;
+  menuAnimations: boolean;
+  themes: boolean;
+}
+
 export interface FolderUpsert {
   id: string;
   parentId: string | null;
   name: string;
   orderIndex: number;
+  color?: string | null;
+  icon?: string | null;
 }
 
 export interface SshConfigInput {
   host: string;
   port: number;
   username: string;
-  strictHostKey: boolean;
+  hostKeyMode: HostKeyMode;
   keyPath?: string | null;
+  certPath?: string | null;
+  retainScrollback?: boolean;
+  defaultCols?: number | null;
+  defaultRows?: number | null;
+  proxyCommand?: string | null;
+  bindAddress?: string | null;
+  rememberSftpPath?: boolean;
+  authMethods?: SshAuthMethod[];
   password?: string | null;
   keyPassphrase?: string | null;
 }
@@ -56,6 +105,11 @@ export interface RdpConfigInput {
   screenMode: number;
   width?: number | null;
   height?: number | null;
+  connectTimeoutSecs?: number | null;
+  connectRetries?: number;
+  redirectSmartcards?: boolean;
+  bitmapCache?: boolean;
+  experience?: RdpExperience;
   password?: string | null;
 }
 
@@ -65,6 +119,8 @@ export interface ConnectionUpsert {
   kind: 'ssh' | 'rdp';
   name: string;
   orderIndex: number;
+  color?: string | null;
+  icon?: string | null;
   ssh?: SshConfigInput | null;
   rdp?: RdpConfigInput | null;
 }
@@ -76,8 +132,19 @@ export interface NodeMoveRequest {
 }
 
 export interface ImportRequest {
+  /** Tags this import's progress events and lets `cancelImport` stop just this one. */
+  importId: string;
   path: string;
   mode: 'dry_run' | 'apply';
+  /** Folder to import into; the tree root when omitted. */
+  targetParentId?: string | null;
+  /** Put everything in a new "Imported <date>" folder under the target. */
+  newFolder?: boolean;
+}
+
+export interface NodeDeleteResult {
+  nodesRemoved: number;
+  secretsRemoved: number;
 }
 
 export interface ImportReport {
@@ -85,6 +152,23 @@ export interface ImportReport {
   updated: number;
   skipped: number;
   warnings: string[];
+  cancelled: boolean;
+}
+
+/**
+ * `tree://changed`: refetch the subtrees of `affectedIds`, where `null` is the root.
+ * `reset` lists no ids and means the whole tree.
+ */
+export interface TreeChangedEvent {
+  kind: 'upserted' | 'moved' | 'deleted' | 'reset';
+  affectedIds: (string | null)[];
+}
+
+export interface ImportProgressEvent {
+  importId: string;
+  phase: 'parsed' | 'applied';
+  done: number;
+  total: number;
 }
 
 export interface VaultStatus {
@@ -92,10 +176,112 @@ export interface VaultStatus {
   unlocked: boolean;
 }
 
+/** The default vault (id `default`) or a named one, e.g. for work credentials. */
+export interface VaultInfo {
+  id: string;
+  initialized: boolean;
+  unlocked: boolean;
+}
+
+export type SecretKind = 'password' | 'key_passphrase' | 'rdp_password';
+
+/** A stored vault secret, without its value. */
+export interface SecretRef {
+  id: string;
+  vaultId: string;
+  kind: SecretKind;
+  createdAt: string;
+}
+
 export interface SessionOptions {
   cols?: number;
   rows?: number;
   sessionId?: string;
+  probeSystem?: boolean;
+  /** Keep the decrypted private key in memory until the vault is locked. */
+  cachePrivateKey?: boolean;
+  keyPassphrase?: string;
+}
+
+/** Saved text to send to SSH sessions; `${name}` placeholders are filled in on send. */
+export interface Snippet {
+  id: string;
+  name: string;
+  body: string;
+}
+
+export interface SshSystemInfo {
+  os: string | null;
+  shell: string | null;
+  hostname: string | null;
+}
+
+/** Why an SSH session ended; see `ssh_session_last_error`. */
+export interface SshSessionExit {
+  exitCode: number | null;
+  reason: string;
+}
+
+export interface SshWriteQueueStats {
+  queued: number;
+  capacity: number;
+}
+
+/** What an SSH session's shell was started with; see `ssh_session_env`. */
+export interface SshSessionEnv {
+  term: string;
+  /** `[name, value]` pairs sent before the shell started; the server may have ignored some. */
+  env: [string, string][];
+}
+
+export type SshKeyType = 'ed25519' | 'ecdsaP256' | 'ecdsaP384' | 'ecdsaP521' | 'rsa';
+
+/** Input of `ssh_key_generate`; the public key is written to `path` + `.pub`. */
+export interface SshKeyGenerateRequest {
+  path: string;
+  /** Defaults to `ed25519`. */
+  keyType?: SshKeyType;
+  comment?: string;
+  /** Leave empty for an unencrypted key. */
+  passphrase?: string;
+}
+
+export interface SshKeyGenerated {
+  privateKeyPath: string;
+  publicKeyPath: string;
+  /** The line to add to the server's `authorized_keys`. */
+  publicKey: string;
+  fingerprint: string;
+}
+
+export interface SshRunCommandResult {
+  stdout: string;
+  stderr: string;
+  exitCode: number | null;
+  truncated: boolean;
+}
+
+export interface SshFilteredCommandResult {
+  /** Stdout lines matching the pattern. */
+  lines: string[];
+  totalLines: number;
+  stderr: string;
+  exitCode: number | null;
+  /** Too many lines matched; the rest of the output wasn't read. */
+  truncated: boolean;
+}
+
+export interface SshAlgorithms {
+  kex: string;
+  hostKeyAlg: string;
+  cipher: string;
+  mac: string | null;
+}
+
+export interface ConnectionStatus {
+  reachable: boolean;
+  /** Null when unreachable, or when the connection is open and was not probed. */
+  latencyMs: number | null;
 }
 
 export interface TcpProbeResult {
@@ -106,6 +292,20 @@ export interface TcpProbeResult {
 export interface SshSessionOpenedResult {
   type: 'opened';
   sessionId: string;
+  /** Set when a weak or deprecated host key was pinned on first sight. */
+  weakHostKey?: string;
+}
+
+/** One host key's fingerprint in the forms `ssh-keygen -l [-E md5]` prints. */
+export interface HostKeyFingerprints {
+  /** `SHA256:<unpadded base64>`, the OpenSSH default. */
+  sha256Base64: string;
+  /** `SHA256:aa:bb:...` */
+  sha256Hex: string;
+  /** `MD5:<unpadded base64>` */
+  md5Base64: string;
+  /** `MD5:aa:bb:...`, as older servers and docs show it. */
+  md5Hex: string;
 }
 
 export interface SshHostKeyMismatchResult {
@@ -115,12 +315,148 @@ export interface SshHostKeyMismatchResult {
   port: number;
   storedKeyType: string;
   storedFingerprint: string;
+  storedFingerprints: HostKeyFingerprints | null;
+  /** Why the saved key was weak or deprecated when it was pinned, when it was. */
+  storedWeakHostKey?: string;
   presentedKeyType: string;
   presentedFingerprint: string;
+  presentedFingerprints: HostKeyFingerprints;
   warning: string;
+  /** Why the presented key is weak or deprecated, when it is. */
+  weakHostKey?: string;
 }
 
-export type SshSessionOpenResult = SshSessionOpenedResult | SshHostKeyMismatchResult;
+export interface SshHostKeyUnknownResult {
+  type: 'hostKeyUnknown';
+  token: string;
+  host: string;
+  port: number;
+  keyType: string;
+  fingerprint: string;
+  fingerprints: HostKeyFingerprints;
+  warning: string;
+  weakHostKey?: string;
+}
+
+export interface SshKeyNeedsPassphraseResult {
+  type: 'keyNeedsPassphrase';
+  keyPath: string;
+  passphraseRejected: boolean;
+  message: string;
+}
+
+export type SshConnectFailure = 'refused' | 'reset' | 'timedOut' | 'unreachable' | 'dnsFailed' | 'other';
+
+export interface SshConnectFailedResult {
+  type: 'connectFailed';
+  reason: SshConnectFailure;
+  host: string;
+  port: number;
+  message: string;
+}
+
+export type SshSessionOpenResult =
+  | SshSessionOpenedResult
+  | SshHostKeyMismatchResult
+  | SshHostKeyUnknownResult
+  | SshKeyNeedsPassphraseResult
+  | SshConnectFailedResult;
+
+/** Result of `ssh_session_open_with_sftp`; `sftpFailed` leaves the shell open. */
+export type SshSftpOpenResult =
+  | { type: 'opened'; sessionId: string; weakHostKey?: string; sftp: SftpSessionOpenResult }
+  | { type: 'sftpFailed'; sessionId: string; weakHostKey?: string; error: SftpError }
+  | { type: 'blocked'; result: SshSessionOpenResult };
+
+/** Result of `ssh_test_connection`; `blocked` covers everything short of authenticating. */
+export type SshTestResult =
+  | { type: 'succeeded'; weakHostKey?: string }
+  | { type: 'authFailed'; username: string; message: string }
+  | { type: 'blocked'; result: SshSessionOpenResult };
+
+/** Result of `ssh_install_public_key`. */
+export type SshPublicKeyInstallResult =
+  | { type: 'installed'; authorizedKeys: string }
+  | { type: 'alreadyInstalled'; authorizedKeys: string }
+  | { type: 'authFailed'; username: string; message: string }
+  | { type: 'blocked'; result: SshSessionOpenResult }
+  | { type: 'failed'; code: SftpErrorCode; message: string };
+
+export type RdpSecurity = 'standard' | 'tls' | 'nla';
+
+/** Result of `rdp_test_connection`; only the X.224 security negotiation is run, so credentials aren't checked. */
+export type RdpTestResult =
+  | { type: 'securityNegotiated'; security: RdpSecurity }
+  | { type: 'negotiationFailed'; message: string }
+  | { type: 'unreachable'; host: string; port: number; message: string };
+
+export type SftpErrorCode =
+  | 'noSuchFile'
+  | 'permissionDenied'
+  | 'alreadyExists'
+  | 'failure'
+  | 'badMessage'
+  | 'noConnection'
+  | 'connectionLost'
+  | 'unsupported'
+  | 'timeout'
+  | 'io'
+  | 'channelLimit'
+  | 'other';
+
+export interface SshMetrics {
+  activeSessions: number;
+  sessionsOpened: number;
+  sessionsClosed: number;
+  /** Includes opens stopped for a host key or passphrase prompt. */
+  failedConnects: number;
+  bytesUploaded: number;
+  bytesDownloaded: number;
+}
+
+export interface RdpMetrics {
+  activeSessions: number;
+  sessionsOpened: number;
+  sessionsClosed: number;
+  failedConnects: number;
+}
+
+/** Counters since the app started, from `metrics`; kept in memory only. */
+export interface AppMetrics {
+  ssh: SshMetrics;
+  rdp: RdpMetrics;
+  /** Mean TCP connect time of the connection monitor's probes. */
+  averageLatencyMs: number | null;
+}
+
+/** A line from the in-app log buffer; see `log_list`. */
+export interface LogRecord {
+  timestampMs: number;
+  level: 'ERROR' | 'WARN' | 'INFO' | 'DEBUG' | 'TRACE';
+  target: string;
+  message: string;
+  fields: Record<string, string>;
+}
+
+/** Size and modification time (Unix seconds) of one side of an upload conflict. */
+export interface SftpFileStat {
+  size: number | null;
+  modifiedTime: number | null;
+}
+
+export interface SftpFileConflict {
+  remotePath: string;
+  local: SftpFileStat;
+  remote: SftpFileStat;
+}
+
+/** Rejection value of the `ssh_sftp_*` commands. */
+export interface SftpError {
+  code: SftpErrorCode;
+  message: string;
+  /** Set with `alreadyExists` when an upload's remote file exists. */
+  conflict?: SftpFileConflict;
+}
 
 export type FileEntryKind = 'file' | 'dir' | 'symlink' | 'other';
 
@@ -132,7 +468,11 @@ export interface FileEntry {
   modifiedAt?: number | null;
   owner?: string | null;
   permissions?: number | null;
+  /** `permissions` as `ls -l` shows them, e.g. `drwxr-xr-x`. */
+  permissionsStr?: string | null;
   hidden: boolean;
+  /** `name` wasn't valid UTF-8 and was decoded lossily; the entry can't be renamed or deleted. */
+  nameLossy: boolean;
 }
 
 export interface FileListResult {
@@ -140,21 +480,86 @@ export interface FileListResult {
   entries: FileEntry[];
 }
 
+export interface SftpRenameResult {
+  /** Where the entry was before the rename. */
+  oldPath: string;
+  /** `null` when the renamed entry couldn't be read back; reload the folder instead. */
+  entry: FileEntry | null;
+}
+
+export interface FileListPage extends FileListResult {
+  /** Entry count, once the directory has been read to the end. */
+  total: number | null;
+  /** False when the directory was too large to sort; entries are in server order. */
+  sorted: boolean;
+  hasMore: boolean;
+}
+
+export interface LocalFsChange {
+  path: string;
+  kind: 'created' | 'modified' | 'removed';
+}
+
 export interface SftpSessionOpenResult {
   sftpSessionId: string;
   remoteCwd: string;
 }
 
+/** Bytes on the remote file system; `available` excludes space reserved for root. */
+export interface SftpFsStats {
+  total: number;
+  free: number;
+  available: number;
+}
+
+export type FileSortKey = 'name' | 'size' | 'modified' | 'kind';
+
+/** Listing order; omitted fields default to directories first, then by name. */
+export interface FileSort {
+  key?: FileSortKey;
+  descending?: boolean;
+  dirsFirst?: boolean;
+}
+
 export interface SftpListRequest {
   sshSessionId: string;
   sftpSessionId: string;
   path: string;
+  sort?: FileSort;
+  /** `false` leaves dotfiles out of the listing. Defaults to `true`. */
+  showHidden?: boolean;
+}
+
+export interface SftpListPageRequest extends Omit<SftpListRequest, 'sort'> {
+  /** Offset 0 starts a fresh listing; request later pages in order. */
+  offset?: number;
+  limit?: number;
+  sort?: boolean;
+  /** How sorted pages are ordered. */
+  order?: FileSort;
+}
+
+export interface SftpWatchRequest {
+  sshSessionId: string;
+  sftpSessionId: string;
+  path: string;
+  intervalSecs?: number;
+}
+
+export interface SftpDirChangedEvent {
+  watchId: string;
+  cwd: string;
+  added: FileEntry[];
+  removed: FileEntry[];
+  modified: FileEntry[];
 }
 
 export interface SftpPathRequest {
   sshSessionId: string;
   sftpSessionId: string;
   path: string;
+  /** Create missing ancestor directories first. */
+  createParents?: boolean;
 }
 
 export interface SftpRenameRequest {
@@ -177,6 +582,47 @@ export interface SftpTransferRequest {
   localPath: string;
   remotePath: string;
   overwrite?: boolean;
+  /** Uploads only: what to do when the remote file exists; overrides `overwrite`. */
+  onConflict?: SftpConflictPolicy;
+  /** Downloads only: fetch large files as concurrent ranges (default true). */
+  parallel?: boolean;
+}
+
+/** `resume` sends only what's past the remote file's size; `rename` uploads as `name (1).ext`. */
+export type SftpConflictPolicy = 'error' | 'overwrite' | 'resume' | 'skip' | 'rename';
+
+export type SftpUploadOutcome =
+  | { type: 'uploaded'; remotePath: string }
+  | { type: 'resumed'; offset: number }
+  | { type: 'skipped' };
+
+export interface SftpOpenExternalRequest {
+  sshSessionId: string;
+  sftpSessionId: string;
+  remotePath: string;
+  /** Required to open files the OS would run rather than display. */
+  allowExecutable?: boolean;
+}
+
+export interface SftpOpenExternalResult {
+  /** Null when an executable was not opened because `allowExecutable` was unset. */
+  localPath: string | null;
+  executable: boolean;
+}
+
+export interface SftpExternalSavedEvent {
+  localPath: string;
+  remotePath: string;
+}
+
+export interface SftpTransferBetweenRequest {
+  srcSshSessionId: string;
+  srcSftpSessionId: string;
+  srcPath: string;
+  dstSshSessionId: string;
+  dstSftpSessionId: string;
+  dstPath: string;
+  overwrite?: boolean;
 }
 
 export interface SftpTransferProgressEvent {
@@ -184,6 +630,32 @@ export interface SftpTransferProgressEvent {
   phase: 'start' | 'progress' | 'complete';
   localPath: string;
   remotePath: string;
+  /** On `complete`, the destination's size once written. */
+  bytesTransferred: number;
+  totalBytes?: number | null;
+  /** Set on `complete` when the destination's size differs from the source's. */
+  warning?: string;
+}
+
+export interface SftpRemoteCopyProgressEvent {
+  direction: 'remoteCopy';
+  phase: 'start' | 'progress' | 'complete';
+  srcSftpSessionId: string;
+  srcPath: string;
+  dstSftpSessionId: string;
+  dstPath: string;
+  bytesTransferred: number;
+  totalBytes?: number | null;
+  warning?: string;
+}
+
+export type SftpTransferEvent = SftpTransferProgressEvent | SftpRemoteCopyProgressEvent;
+
+export interface LocalTransferProgressEvent {
+  operation: 'copy' | 'move';
+  phase: 'start' | 'progress' | 'complete';
+  srcPath: string;
+  dstPath: string;
   bytesTransferred: number;
   totalBytes?: number | null;
 }
@@ -195,11 +667,15 @@ export interface RdpViewport {
   height: number;
 }
 
+/** Set on `disconnected` when the discReason means the connection itself failed. */
+export type RdpConnectFailure = 'refused' | 'reset' | 'timedOut' | 'dnsFailed';
+
 export type RdpLifecycleEvent =
   | { type: 'connecting' }
   | { type: 'connected' }
   | { type: 'loginComplete' }
-  | { type: 'disconnected'; reason: number }
+  | { type: 'retrying'; attempt: number; retries: number; reason: number }
+  | { type: 'disconnected'; reason: number; extended: number; failure: RdpConnectFailure | null; message: string }
   | { type: 'fatalError'; errorCode: number }
   | { type: 'logonError'; errorCode: number }
   | { type: 'hostInitFailed'; stage: string; hresult: number | null; message: string };